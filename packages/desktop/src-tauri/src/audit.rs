@@ -0,0 +1,260 @@
+//! Opt-in audit log of smartcard operations for seQRets desktop.
+//!
+//! When enabled (`set_audit_logging`), every command that reads secrets from,
+//! writes to or changes the PIN state of a card appends one JSON line to
+//! `card-audit.log` in the app data directory: when, which command, which
+//! reader and how it ended. Only the error kind is recorded, never a PIN,
+//! share, label or error message.
+//!
+//! Entries form a SHA-256 hash chain: each records the previous entry's hash
+//! and its own over its fields, so `read_audit_log` can tell when a line has
+//! been edited, removed or reordered. An attacker who can rewrite the whole
+//! file can still recompute the chain; this is accountability, not a
+//! signature. Unlike the debug log it is written in release builds too.
+//!
+//! A failed append is logged and otherwise ignored, so a read-only data
+//! directory never blocks a card operation.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::error::SeqretsError;
+
+const AUDIT_LOG_FILENAME: &str = "card-audit.log";
+
+/// `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Recorded as `result` for commands that succeeded.
+const RESULT_OK: &str = "ok";
+
+/// One line of the audit log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AuditEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub command: String,
+    pub reader: String,
+    /// "ok", or the `SeqretsError` kind ("wrongPin", "cancelled", ...).
+    pub result: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// Returned by `read_audit_log`.
+#[derive(Serialize, Debug, Default)]
+pub struct AuditLog {
+    /// Entries in the order they were written; unparseable lines are skipped.
+    pub entries: Vec<AuditEntry>,
+    /// True when every line parses and the hash chain is unbroken.
+    pub intact: bool,
+    /// 0-based line of the first entry that breaks the chain, if any.
+    pub broken_at: Option<usize>,
+}
+
+/// Where the audit log lives: the app data directory, created if needed.
+pub(crate) fn log_path(app: &AppHandle) -> Result<PathBuf, SeqretsError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Could not resolve app data dir: {e}"))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create app data dir: {e}"))?;
+    Ok(dir.join(AUDIT_LOG_FILENAME))
+}
+
+fn entry_hash(
+    timestamp: u64,
+    command: &str,
+    reader: &str,
+    result: &str,
+    prev_hash: &str,
+) -> String {
+    let mut hasher = Sha256::new();
+    for field in [prev_hash, &timestamp.to_string(), command, reader, result] {
+        hasher.update((field.len() as u32).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn result_kind<T>(result: &Result<T, SeqretsError>) -> String {
+    match result {
+        Ok(_) => RESULT_OK.to_string(),
+        Err(e) => serde_json::to_value(e)
+            .ok()
+            .and_then(|v| v["kind"].as_str().map(str::to_string))
+            .unwrap_or_else(|| "other".to_string()),
+    }
+}
+
+/// The entry that would follow `prev_hash`.
+fn new_entry(
+    timestamp: u64,
+    command: &str,
+    reader: &str,
+    result: String,
+    prev_hash: String,
+) -> AuditEntry {
+    AuditEntry {
+        timestamp,
+        command: command.to_string(),
+        reader: reader.to_string(),
+        hash: entry_hash(timestamp, command, reader, &result, &prev_hash),
+        result,
+        prev_hash,
+    }
+}
+
+/// Checks the chain over the lines of a log file.
+fn verify(contents: &str) -> AuditLog {
+    let mut log = AuditLog::default();
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for (i, line) in contents.lines().enumerate() {
+        let entry = match serde_json::from_str::<AuditEntry>(line) {
+            Ok(entry) => entry,
+            Err(_) => {
+                log.broken_at.get_or_insert(i);
+                continue;
+            }
+        };
+        let expected = entry_hash(
+            entry.timestamp,
+            &entry.command,
+            &entry.reader,
+            &entry.result,
+            &prev_hash,
+        );
+        if entry.prev_hash != prev_hash || entry.hash != expected {
+            log.broken_at.get_or_insert(i);
+        }
+        prev_hash = entry.hash.clone();
+        log.entries.push(entry);
+    }
+    log.intact = log.broken_at.is_none();
+    log
+}
+
+/// The hash the next entry chains from: the last line's, or the genesis hash.
+fn last_hash(path: &Path) -> Result<String, SeqretsError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents
+            .lines()
+            .last()
+            .and_then(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .map_or_else(|| GENESIS_HASH.to_string(), |entry| entry.hash)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(GENESIS_HASH.to_string()),
+        Err(e) => Err(format!("Could not read audit log: {e}").into()),
+    }
+}
+
+fn append_entry(
+    path: &Path,
+    command: &str,
+    reader: &str,
+    result: String,
+) -> Result<(), SeqretsError> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let entry = new_entry(timestamp, command, reader, result, last_hash(path)?);
+    let mut line = serde_json::to_string(&entry)
+        .map_err(|e| format!("Could not serialize audit entry: {e}"))?;
+    line.push('\n');
+
+    let mut opts = fs::OpenOptions::new();
+    opts.append(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+        opts.custom_flags(libc::O_NOFOLLOW);
+    }
+    let mut f = opts
+        .open(path)
+        .map_err(|e| format!("Could not open audit log: {e}"))?;
+    f.write_all(line.as_bytes())
+        .map_err(|e| format!("Could not write audit log: {e}"))?;
+    f.sync_all()
+        .map_err(|e| format!("Could not fsync audit log: {e}"))?;
+    Ok(())
+}
+
+/// Appends an entry for `command` on `reader` to the log at `path`.
+pub(crate) fn record<T>(
+    path: &Path,
+    command: &str,
+    reader: &str,
+    result: &Result<T, SeqretsError>,
+) {
+    if let Err(e) = append_entry(path, command, reader, result_kind(result)) {
+        log::warn!("Audit log entry for {command} not written: {e}");
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Reads the card audit log and checks its hash chain. An empty log is
+/// returned when auditing has never been enabled.
+#[tauri::command]
+pub fn read_audit_log(app: AppHandle) -> Result<AuditLog, SeqretsError> {
+    let path = log_path(&app)?;
+    match fs::read_to_string(&path) {
+        Ok(contents) => Ok(verify(&contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AuditLog {
+            intact: true,
+            ..Default::default()
+        }),
+        Err(e) => Err(format!("Could not read audit log: {e}").into()),
+    }
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("seqrets-audit-test-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_chain_detects_edits_and_removals() {
+        let path = temp_path("chain");
+        let _ = fs::remove_file(&path);
+        record(
+            &path,
+            "verify_pin",
+            "Reader A",
+            &Err::<(), _>(SeqretsError::WrongPin("2 tries left".into())),
+        );
+        record(&path, "write_item_to_card", "Reader A", &Ok(()));
+        record(&path, "erase_card", "Reader B", &Ok(()));
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("tries left"));
+        let log = verify(&contents);
+        assert!(log.intact);
+        assert_eq!(log.entries.len(), 3);
+        assert_eq!(log.entries[0].result, "wrongPin");
+        assert_eq!(log.entries[0].prev_hash, GENESIS_HASH);
+        assert_eq!(log.entries[2].prev_hash, log.entries[1].hash);
+
+        let lines: Vec<&str> = contents.lines().collect();
+        let edited = contents.replace("Reader B", "Reader C");
+        assert_eq!(verify(&edited).broken_at, Some(2));
+        let removed = [lines[0], lines[2]].join("\n");
+        assert_eq!(verify(&removed).broken_at, Some(1));
+        fs::remove_file(&path).unwrap();
+    }
+}
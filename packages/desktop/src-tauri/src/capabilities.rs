@@ -0,0 +1,96 @@
+//! Backend introspection for seQRets desktop.
+//!
+//! `crypto_capabilities` reports what this build supports — ciphers, KDFs,
+//! wire-format versions, default Argon2 parameters — plus the smart card
+//! setup, so the UI's security details don't have to hardcode any of it.
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::crypto::{self, CryptoCapabilities};
+use crate::smartcard::{self, SmartcardState};
+use crate::stream::STREAM_VERSION;
+use crate::vault::CURRENT_SCHEMA_VERSION;
+
+/// Returned by `crypto_capabilities`.
+#[derive(Serialize, Debug)]
+pub struct Capabilities {
+    #[serde(flatten)]
+    pub crypto: CryptoCapabilities,
+    pub stream_format_version: u8,
+    pub payload_schema_version: u32,
+    /// AID used for SELECT, as space-separated hex.
+    pub applet_aid: String,
+    /// The PC/SC service is reachable (says nothing about readers).
+    pub pcsc_available: bool,
+}
+
+fn capabilities(applet_aid: String, pcsc_available: bool) -> Capabilities {
+    Capabilities {
+        crypto: crypto::crypto_capabilities(),
+        stream_format_version: STREAM_VERSION,
+        payload_schema_version: CURRENT_SCHEMA_VERSION,
+        applet_aid,
+        pcsc_available,
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Reports the supported ciphers, KDFs, format versions and default Argon2
+/// parameters, the applet AID in use and whether PC/SC is available.
+#[tauri::command]
+pub fn crypto_capabilities(state: State<'_, SmartcardState>) -> Capabilities {
+    capabilities(
+        smartcard::get_applet_aid(state),
+        smartcard::pcsc_available(),
+    )
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_serialize_flat() {
+        let value = serde_json::to_value(capabilities("F0 53".to_string(), false)).unwrap();
+        assert_eq!(value["default_cipher"], "xchacha20-poly1305");
+        assert_eq!(value["ciphers"][1]["name"], "aes-256-gcm");
+        assert_eq!(value["ciphers"][1]["nonce_length"], 12);
+        assert_eq!(
+            value["kdfs"],
+            serde_json::json!(["argon2id", "argon2i", "argon2d"])
+        );
+        assert_eq!(
+            value["encodings"],
+            serde_json::json!(["base64", "base64url"])
+        );
+        assert_eq!(value["readable_format_versions"][0], 0);
+        assert_eq!(value["default_params"]["m_cost"], 65536);
+        assert_eq!(value["applet_aid"], "F0 53");
+        assert_eq!(value["pcsc_available"], false);
+    }
+
+    // Every advertised cipher name must be accepted by the encryption commands.
+    #[test]
+    fn test_advertised_ciphers_encrypt() {
+        for cipher in crypto::crypto_capabilities().ciphers {
+            let result = crypto::crypto_seal(
+                "AA==".to_string(),
+                "pw".to_string(),
+                None,
+                Some(crypto::CryptoParams {
+                    m_cost: 8,
+                    t_cost: 1,
+                    p_cost: 1,
+                    ..Default::default()
+                }),
+                Some(cipher.name.to_string()),
+                None,
+            );
+            assert!(result.is_ok(), "{} rejected", cipher.name);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Clipboard copies of secrets that clear themselves, for seQRets desktop.
+//!
+//! `copy_secret_with_timeout` puts a revealed secret on the clipboard and
+//! clears it again after a timeout, the way password managers do, so a seed
+//! phrase pasted into a wallet doesn't linger there indefinitely. Only a
+//! SHA-256 digest of the value is kept while waiting: the clipboard is
+//! cleared only if it still hashes to that digest, so anything the user
+//! copied in the meantime is left alone.
+//!
+//! The command's copy of the value is zeroized once it is on the clipboard.
+//! The clipboard itself, and any clipboard history the OS keeps, are beyond
+//! this module's reach.
+
+use sha2::{Digest, Sha256};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use zeroize::Zeroizing;
+
+use crate::crypto;
+use crate::error::SeqretsError;
+
+/// Accepted range for `timeout_ms`.
+const CLEAR_TIMEOUT_MIN_MS: u64 = 1_000;
+const CLEAR_TIMEOUT_MAX_MS: u64 = 10 * 60 * 1_000;
+
+fn digest(text: &str) -> [u8; 32] {
+    Sha256::digest(text.as_bytes()).into()
+}
+
+/// True when the clipboard still holds the value behind `copied`.
+fn still_holds(current: Option<&str>, copied: &[u8; 32]) -> bool {
+    current.is_some_and(|text| crypto::ct_eq(&digest(text), copied))
+}
+
+fn clear_if_unchanged(app: &AppHandle, copied: &[u8; 32]) {
+    let current = app.clipboard().read_text().ok().map(Zeroizing::new);
+    if still_holds(current.as_deref().map(String::as_str), copied) {
+        if let Err(e) = app.clipboard().clear() {
+            log::warn!("Could not clear the clipboard: {e}");
+        }
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Copies `value` to the clipboard and clears it after `timeout_ms` (1 s to
+/// 10 min), unless the clipboard holds something else by then. Returns once
+/// the value is on the clipboard; the clear happens on a background thread.
+#[tauri::command]
+pub fn copy_secret_with_timeout(
+    app: AppHandle,
+    value: String,
+    timeout_ms: u64,
+) -> Result<(), SeqretsError> {
+    let value = Zeroizing::new(value);
+    if !(CLEAR_TIMEOUT_MIN_MS..=CLEAR_TIMEOUT_MAX_MS).contains(&timeout_ms) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Clipboard timeout must be {CLEAR_TIMEOUT_MIN_MS}-{CLEAR_TIMEOUT_MAX_MS} ms, got {timeout_ms}."
+        )));
+    }
+
+    app.clipboard()
+        .write_text(value.as_str())
+        .map_err(|e| format!("Could not write to the clipboard: {e}"))?;
+    let copied = digest(&value);
+    drop(value);
+
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(timeout_ms));
+        clear_if_unchanged(&app, &copied);
+    });
+    Ok(())
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clears_only_the_copied_value() {
+        let copied = digest("abandon ability able");
+        assert!(still_holds(Some("abandon ability able"), &copied));
+        assert!(!still_holds(
+            Some("something the user copied later"),
+            &copied
+        ));
+        assert!(!still_holds(Some(""), &copied));
+        assert!(!still_holds(None, &copied));
+    }
+}
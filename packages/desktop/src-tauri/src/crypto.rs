@@ -1,337 +1,4621 @@
 /// Native Rust cryptographic backend for seQRets desktop.
 ///
-/// Provides Argon2id key derivation and XChaCha20-Poly1305 authenticated
-/// encryption/decryption with gzip compression, called from the TypeScript
-/// frontend via Tauri IPC. All sensitive intermediate values are zeroed via
+/// Provides Argon2 key derivation (Argon2id by default) and XChaCha20-Poly1305
+/// (or AES-256-GCM) authenticated encryption/decryption with gzip compression,
+/// called from the TypeScript frontend via Tauri IPC. All sensitive intermediate values are zeroed via
 /// the `zeroize` crate when dropped.
 ///
-/// Wire format (identical to the @noble/* JS implementation):
-///   - Key derivation : Argon2id(m=65536, t=4, p=1, len=32) over (password ++ optional_keyfile)
-///   - Encryption     : XChaCha20-Poly1305 with a random 24-byte nonce
-///   - Payload format : base64( nonce[24] || xchacha20_ciphertext_with_tag )
-///   - Salt           : 16 random bytes, stored as base64 alongside the ciphertext
+/// Two blob layouts are read, told apart by their first bytes:
+///
+/// Version 0 (header-less) — the original layout, identical to the @noble/* JS
+/// implementation and seQRets Recover:
+///   - Key            : Argon2id (m=65536, t=4, p=1, len=32) over password,
+///     or password || keyfile
+///   - Encryption     : XChaCha20-Poly1305 under the Argon2 output, no associated data
+///   - Payload format : base64( nonce[24] || ciphertext_with_tag )
+///   - Plaintext      : a gzip stream (pako `ungzip` must accept it)
+///
+/// Version 1 (headered) — everything else this build writes:
+///   - Header[33]     : magic (0x53) || version (1) || cipher id
+///     || m, t, p (u32 BE each) || keyfile count
+///     || Argon2 variant id (0 = d, 1 = i, 2 = id) | plaintext flags
+///     || key commitment[16]
+///   - Plaintext flags: HEADER_FLAG_COMPRESSED (0x80) for `gzip_compress`
+///     output, HEADER_FLAG_OPAQUE (0x40) for a `store_opaque` blob, neither
+///     for raw bytes
+///   - Key            : Argon2 (variant and costs from the header) over
+///     password, password || keyfile, or for two or more keyfiles
+///     password || (keyfile_len (u32 BE) || keyfile)*, in the caller's order
+///   - Payload key    : HKDF-SHA256(Argon2 output, info="vault-encryption-v1")
+///   - Key commitment : first 16 bytes of HKDF-SHA256(Argon2 output,
+///     info="key-verifier-v1")
+///   - Encryption     : XChaCha20-Poly1305 with a random 24-byte nonce (cipher id 1),
+///     or AES-256-GCM with a random 12-byte nonce (cipher id 2)
+///   - Payload format : base64( header[33] || nonce || ciphertext_with_tag )
+///   - Associated data: header || salt || label_len (u32 BE) || label
+///   - Compressed plaintext: gzip stream, or 0x00 || raw bytes when gzip
+///     wouldn't shrink it, optionally padded as
+///     0x01 || length (u32 BE) || that payload || zeros
+///
+/// In both layouts the salt is 16 random bytes, stored as base64 alongside
+/// the ciphertext. `crypto_create` writes version 0 when its options allow
+/// (default params and cipher, no label, padding or stored compression, at
+/// most one keyfile) so shares stay readable by the web app and Recover.
+///
+/// The header is authenticated, so the cipher id, Argon2 fields and flags
+/// can't be rewritten to downgrade a blob; a version newer than this build is
+/// refused outright. Because the header is only authenticated after the key
+/// is derived, its costs are first checked against hard limits (2 GiB,
+/// 64 passes) and refused as `ParamsTooLarge` rather than handed to Argon2,
+/// and a keyfile count that doesn't match is refused as `InvalidInput`. When
+/// authentication fails but the key commitment matches, the associated data
+/// or ciphertext changed and the error is `HeaderTampered`, not `WrongPassword`.
+///
+/// Sensitive command arguments — `password`, `keyfiles_b64` and the plaintext
+/// `json_payload` / `json` — are moved into `Zeroizing` wrappers on entry, so
+/// the buffer Tauri deserialized is wiped (not a copy of it) and each one is
+/// dropped as soon as it has been consumed. Decrypted JSON is returned to the
+/// IPC layer as a plain `String`; that copy is outside this module's control.
+///
+/// Multi-recipient vaults (`encrypt_multi_recipient`) are envelope
+/// encrypted: the payload is sealed once under a random content key, and
+/// that key is sealed separately under each recipient's password (a blob in
+/// the usual format with its own salt), so any one recipient can open the
+/// vault without learning anyone else's password.
+use aes_gcm::Aes256Gcm;
 use argon2::{Algorithm, Argon2, Params, Version};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use chacha20poly1305::{
-    aead::Aead,
+    aead::{Aead, Payload},
     {KeyInit, XChaCha20Poly1305, XNonce},
 };
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
 use rand::RngCore;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::io::{Read, Write};
-use zeroize::{Zeroize, Zeroizing};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use tauri::{AppHandle, Emitter};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::encoding::{self, PayloadEncoding};
+use crate::error::SeqretsError;
+
+pub(crate) const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24; // XChaCha20-Poly1305
+const AES_GCM_NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16; // Poly1305 and GCM tags
+pub(crate) const KEY_LENGTH: usize = 32;
 
-const SALT_LENGTH: usize = 16;
-const NONCE_LENGTH: usize = 24;
-const KEY_LENGTH: usize = 32;
+// Versioned wire-format header.
+const HEADER_MAGIC: u8 = 0x53; // 'S'
+const HEADER_LENGTH: usize = 33;
+const FORMAT_VERSION: u8 = 1;
+/// Set in the variant byte when the plaintext is `gzip_compress` output
+/// rather than raw bytes.
+const HEADER_FLAG_COMPRESSED: u8 = 0x80;
+/// Set instead when the plaintext is an opaque blob from `store_opaque`.
+const HEADER_FLAG_OPAQUE: u8 = 0x40;
+const HEADER_FLAGS: u8 = HEADER_FLAG_COMPRESSED | HEADER_FLAG_OPAQUE;
+const ALG_XCHACHA20_POLY1305: u8 = 1;
+const ALG_AES_256_GCM: u8 = 2;
 
-// Argon2id parameters — must match the @noble/hashes JS implementation exactly.
+/// Version bytes below this are treated as "could be a header"; anything
+/// higher after the magic byte is assumed to be a legacy random nonce.
+const MAX_PLAUSIBLE_VERSION: u8 = 0x0F;
+
+/// HKDF `info` for the key that encrypts headered blobs.
+const VAULT_ENCRYPTION_CONTEXT: &str = "vault-encryption-v1";
+
+/// HKDF `info` for the key verifier returned in `CryptoResult::verifier`.
+const KEY_VERIFIER_CONTEXT: &str = "key-verifier-v1";
+/// Bytes of the key verifier stored in the header.
+const KEY_COMMITMENT_LENGTH: usize = 16;
+
+/// Leading plaintext byte marking data stored without compression. Gzip
+/// output always starts with 0x1f, so the two can't be confused.
+const STORED_FLAG: u8 = 0x00;
+/// Leading byte of a padded plaintext: `PADDED_FLAG || inner length (u32 BE)
+/// || inner || zero fill`, where `inner` is the gzip or stored payload.
+const PADDED_FLAG: u8 = 0x01;
+/// Smallest and largest accepted `pad_to`.
+const MIN_PAD_TO: usize = 16;
+const MAX_PAD_TO: usize = 1024 * 1024;
+
+// Default Argon2id parameters — must match the @noble/hashes JS implementation
+// exactly, since header-less (version 0) blobs are always derived with these.
 const ARGON2_M_COST: u32 = 65536; // 64 MiB
 const ARGON2_T_COST: u32 = 4; // iterations
 const ARGON2_P_COST: u32 = 1; // parallelism
 
-/// Returned by crypto_create and crypto_encrypt_blob.
-#[derive(Serialize)]
+// KDF benchmark bounds. The floor is OWASP's minimum Argon2id memory cost;
+// the ceiling keeps the benchmark from exhausting RAM on large machines.
+const BENCH_MIN_M_COST: u32 = 19 * 1024; // 19 MiB
+const BENCH_MAX_M_COST: u32 = 1024 * 1024; // 1 GiB
+const BENCH_MAX_T_COST: u32 = 16;
+
+// Hard ceilings on the Argon2 costs of any derivation, checked before Argon2
+// allocates anything, so a blob whose header declares absurd parameters can't
+// exhaust memory or hang the app.
+const MAX_M_COST: u32 = 2 * BENCH_MAX_M_COST; // 2 GiB
+const MAX_T_COST: u32 = 4 * BENCH_MAX_T_COST;
+const MAX_P_COST: u32 = 16;
+
+/// Tokens of the operations `cancel_kdf` can reach, by the `operation_id`
+/// the frontend started them with. See `KdfOperation`.
+static KDF_OPERATIONS: Mutex<BTreeMap<String, CancelToken>> = Mutex::new(BTreeMap::new());
+
+/// How often a waiting derivation checks for cancellation.
+const KDF_CANCEL_POLL: Duration = Duration::from_millis(20);
+
+/// Emitted while a derivation runs, once `set_kdf_progress` has enabled it
+const KDF_PROGRESS_EVENT: &str = "kdf-progress";
+const KDF_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// The estimate stops here until the derivation actually returns.
+const KDF_PROGRESS_CAP: f64 = 95.0;
+
+/// Where `kdf-progress` events go and how long a derivation is expected to
+/// take; `None` (the default) emits nothing.
+static KDF_PROGRESS: Mutex<Option<(AppHandle, Duration)>> = Mutex::new(None);
+
+/// Encrypting below this floor (OWASP's minimum Argon2id configuration)
+/// returns a `ParamsWarning`. `weak_params` also accepts less memory per
+/// pass made up by more memory overall: m × t must reach 19 MiB × 2.
+const MIN_SECURE_PARAMS: CryptoParams = CryptoParams {
+    m_cost: 19 * 1024,
+    t_cost: 2,
+    p_cost: 1,
+    argon2_variant: Argon2Variant::Argon2id,
+};
+
+/// `recommended_params` gives Argon2 at most this fraction (1/n) of the RAM
+/// currently available, leaving the rest to the app and the OS.
+const RECOMMENDED_MEMORY_DIVISOR: u64 = 8;
+
+/// Returned by crypto_create and crypto_encrypt_blob; `import_card` takes
+/// one back.
+#[derive(Serialize, Deserialize)]
 pub struct CryptoResult {
     pub salt: String, // base64-encoded 16-byte random salt
-    pub data: String, // base64-encoded (nonce[24] || xchacha20_ciphertext)
+    pub data: String, // base64-encoded (header[33] || nonce || ciphertext)
+    /// base64 `key_verifier` of the key `data` was encrypted under, for
+    /// `verify_key_material`. Safe to store next to the blob.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<String>,
+    /// Set when the blob was encrypted with parameters below
+    /// `MIN_SECURE_PARAMS`; the encryption itself succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params_warning: Option<ParamsWarning>,
 }
 
-// ── Private helpers ──────────────────────────────────────────────────────────
+/// Returned alongside an encryption whose Argon2 settings are weaker than
+/// recommended, so the UI can ask the user to confirm them. Decrypting such
+/// blobs is never refused.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ParamsWarning {
+    /// The parameters the data was encrypted with.
+    pub params: CryptoParams,
+    /// The floor they fall short of.
+    pub minimum: CryptoParams,
+    pub message: String,
+}
 
-/// Derives a 32-byte key from a password and optional base64-encoded keyfile
-/// using Argon2id. The input buffer is zeroized when it drops.
-fn derive_key(
-    password: &str,
-    salt: &[u8],
-    keyfile_b64: Option<&str>,
-) -> Result<Zeroizing<[u8; KEY_LENGTH]>, String> {
-    // Build the KDF input: password_bytes || optional_keyfile_bytes
-    let input: Zeroizing<Vec<u8>> = if let Some(kf_b64) = keyfile_b64 {
-        let kf_bytes = STANDARD
-            .decode(kf_b64)
-            .map_err(|e| format!("Keyfile base64 decode error: {e}"))?;
-        let mut combined = Vec::with_capacity(password.len() + kf_bytes.len());
-        combined.extend_from_slice(password.as_bytes());
-        combined.extend_from_slice(&kf_bytes);
-        Zeroizing::new(combined)
-    } else {
-        Zeroizing::new(password.as_bytes().to_vec())
-    };
+/// Returned by crypto_restore and crypto_decrypt_blob: the bare value, or
+/// with `with_timing` the value plus how long key derivation took, so the
+/// UI can warn that unlocking is slow on this machine.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum MaybeTimed<T> {
+    Plain(T),
+    Timed { value: T, kdf_ms: u64 },
+}
 
-    let params = Params::new(ARGON2_M_COST, ARGON2_T_COST, ARGON2_P_COST, Some(KEY_LENGTH))
-        .map_err(|e| format!("Argon2 params error: {e}"))?;
-    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+impl<T> MaybeTimed<T> {
+    fn new(value: T, kdf_time: Duration, with_timing: Option<bool>) -> Self {
+        if with_timing.unwrap_or(false) {
+            MaybeTimed::Timed {
+                value,
+                kdf_ms: kdf_time.as_millis() as u64,
+            }
+        } else {
+            MaybeTimed::Plain(value)
+        }
+    }
 
-    let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
-    argon2
-        .hash_password_into(input.as_slice(), salt, key.as_mut_slice())
-        .map_err(|e| format!("Argon2 hash error: {e}"))?;
+    #[cfg(test)]
+    fn into_value(self) -> T {
+        match self {
+            MaybeTimed::Plain(value) | MaybeTimed::Timed { value, .. } => value,
+        }
+    }
+}
 
-    // `input` is Zeroizing<Vec<u8>> — automatically zeroized on drop here.
-    Ok(key)
+/// AEAD cipher recorded in the header's cipher-id byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cipher {
+    XChaCha20Poly1305,
+    Aes256Gcm,
 }
 
-fn gzip_compress(data: &[u8]) -> Result<Vec<u8>, String> {
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    encoder
-        .write_all(data)
-        .map_err(|e| format!("Gzip write error: {e}"))?;
-    encoder
-        .finish()
-        .map_err(|e| format!("Gzip finish error: {e}"))
+impl Cipher {
+    fn id(self) -> u8 {
+        match self {
+            Cipher::XChaCha20Poly1305 => ALG_XCHACHA20_POLY1305,
+            Cipher::Aes256Gcm => ALG_AES_256_GCM,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Cipher, SeqretsError> {
+        match id {
+            ALG_XCHACHA20_POLY1305 => Ok(Cipher::XChaCha20Poly1305),
+            ALG_AES_256_GCM => Ok(Cipher::Aes256Gcm),
+            other => Err(SeqretsError::CorruptData(format!(
+                "Unsupported encryption algorithm id {other}"
+            ))),
+        }
+    }
+
+    /// The name accepted by `from_name`.
+    fn name(self) -> &'static str {
+        match self {
+            Cipher::XChaCha20Poly1305 => "xchacha20-poly1305",
+            Cipher::Aes256Gcm => "aes-256-gcm",
+        }
+    }
+
+    /// Parses the optional `cipher` command argument; `None` selects the default.
+    fn from_name(name: Option<&str>) -> Result<Cipher, SeqretsError> {
+        match name.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("xchacha20-poly1305") => Ok(Cipher::XChaCha20Poly1305),
+            Some("aes-256-gcm") => Ok(Cipher::Aes256Gcm),
+            Some(other) => Err(SeqretsError::InvalidInput(format!(
+                "Unknown cipher \"{other}\" (expected \"xchacha20-poly1305\" or \"aes-256-gcm\")"
+            ))),
+        }
+    }
+
+    fn nonce_length(self) -> usize {
+        match self {
+            Cipher::XChaCha20Poly1305 => NONCE_LENGTH,
+            Cipher::Aes256Gcm => AES_GCM_NONCE_LENGTH,
+        }
+    }
+
+    fn seal(
+        self,
+        key: &[u8; KEY_LENGTH],
+        nonce: &[u8],
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SeqretsError> {
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        let result = match self {
+            Cipher::XChaCha20Poly1305 => XChaCha20Poly1305::new_from_slice(key)
+                .map_err(|_| "Cipher init error (invalid key length)".to_string())?
+                .encrypt(XNonce::from_slice(nonce), payload),
+            Cipher::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+                .map_err(|_| "Cipher init error (invalid key length)".to_string())?
+                .encrypt(aes_gcm::Nonce::from_slice(nonce), payload),
+        };
+        result.map_err(|_| "Encryption error".into())
+    }
+
+    fn open(
+        self,
+        key: &[u8; KEY_LENGTH],
+        nonce: &[u8],
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, SeqretsError> {
+        let payload = Payload {
+            msg: ciphertext,
+            aad,
+        };
+        let result = match self {
+            Cipher::XChaCha20Poly1305 => XChaCha20Poly1305::new_from_slice(key)
+                .map_err(|_| "Cipher init error (invalid key length)".to_string())?
+                .decrypt(XNonce::from_slice(nonce), payload),
+            Cipher::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+                .map_err(|_| "Cipher init error (invalid key length)".to_string())?
+                .decrypt(aes_gcm::Nonce::from_slice(nonce), payload),
+        };
+        result.map_err(|_| {
+            SeqretsError::WrongPassword(
+                "Decryption failed — wrong password, keyfile, or corrupted data".to_string(),
+            )
+        })
+    }
 }
 
-fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
-    let mut decoder = GzDecoder::new(data);
-    let mut out = Vec::new();
-    decoder
-        .read_to_end(&mut out)
-        .map_err(|e| format!("Gzip decompress error: {e}"))?;
-    Ok(out)
+/// Argon2 flavour used for key derivation. The discriminant is the id stored
+/// in the blob header (and matches the RFC 9106 type numbers).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Argon2Variant {
+    Argon2d,
+    Argon2i,
+    #[default]
+    Argon2id,
 }
 
-/// Encrypts `plaintext` with XChaCha20-Poly1305 using `key`.
-/// Returns `base64(random_nonce[24] || ciphertext_with_tag)`.
-fn encrypt(plaintext: &[u8], key: &[u8; KEY_LENGTH]) -> Result<String, String> {
-    let mut nonce_bytes = [0u8; NONCE_LENGTH];
-    rand::rng().fill_bytes(&mut nonce_bytes);
+impl Argon2Variant {
+    fn id(self) -> u8 {
+        match self {
+            Argon2Variant::Argon2d => 0,
+            Argon2Variant::Argon2i => 1,
+            Argon2Variant::Argon2id => 2,
+        }
+    }
 
-    let cipher = XChaCha20Poly1305::new_from_slice(key)
-        .map_err(|_| "Cipher init error (invalid key length)".to_string())?;
-    let nonce = XNonce::from_slice(&nonce_bytes);
+    fn from_id(id: u8) -> Result<Self, SeqretsError> {
+        match id {
+            0 => Ok(Argon2Variant::Argon2d),
+            1 => Ok(Argon2Variant::Argon2i),
+            2 => Ok(Argon2Variant::Argon2id),
+            other => Err(SeqretsError::CorruptData(format!(
+                "Unknown Argon2 variant id {other}. Please update seQRets."
+            ))),
+        }
+    }
 
-    let ciphertext = cipher
-        .encrypt(nonce, plaintext)
-        .map_err(|_| "Encryption error".to_string())?;
+    fn algorithm(self) -> Algorithm {
+        match self {
+            Argon2Variant::Argon2d => Algorithm::Argon2d,
+            Argon2Variant::Argon2i => Algorithm::Argon2i,
+            Argon2Variant::Argon2id => Algorithm::Argon2id,
+        }
+    }
+}
 
-    let mut combined = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
-    combined.extend_from_slice(&nonce_bytes);
-    combined.extend_from_slice(&ciphertext);
+/// Argon2 cost parameters and variant. Chosen at encryption time and recorded
+/// in the blob header; decryption reads them back from the header.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CryptoParams {
+    pub m_cost: u32, // memory in KiB
+    pub t_cost: u32, // iterations
+    pub p_cost: u32, // parallelism
+    /// Omitted by older frontends; defaults to Argon2id.
+    #[serde(default)]
+    pub argon2_variant: Argon2Variant,
+}
 
-    Ok(STANDARD.encode(combined))
+impl Default for CryptoParams {
+    fn default() -> Self {
+        CryptoParams {
+            m_cost: ARGON2_M_COST,
+            t_cost: ARGON2_T_COST,
+            p_cost: ARGON2_P_COST,
+            argon2_variant: Argon2Variant::Argon2id,
+        }
+    }
 }
 
-/// Decrypts `data_b64` (base64 of nonce[24] || ciphertext) with XChaCha20-Poly1305.
-/// Returns the plaintext bytes. Sensitive intermediate bytes are zeroized on drop.
-fn decrypt(data_b64: &str, key: &[u8; KEY_LENGTH]) -> Result<Zeroizing<Vec<u8>>, String> {
-    let combined = STANDARD
-        .decode(data_b64)
-        .map_err(|e| format!("Base64 decode error: {e}"))?;
+/// Format version of a `MultiRecipientVault`.
+const MULTI_RECIPIENT_VERSION: u8 = 1;
+
+/// Most recipients one vault can be encrypted to; each costs an Argon2
+/// derivation when encrypting, and up to one when decrypting.
+const MAX_RECIPIENTS: usize = 16;
+
+/// Longest recipient name, in characters.
+const MAX_RECIPIENT_NAME_LENGTH: usize = 64;
+
+/// Label prefix bound into each wrapped content key, followed by the
+/// recipient's name.
+const RECIPIENT_KEY_LABEL: &str = "seqrets-recipient-key|";
+
+/// One recipient of `encrypt_multi_recipient`. Zeroized on drop.
+#[derive(Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct RecipientPassword {
+    /// Shown to whoever opens the vault (e.g. "Alice"); not secret.
+    #[serde(default)]
+    pub name: String,
+    pub password: String,
+    #[serde(default)]
+    pub keyfiles_b64: Option<Vec<String>>,
+}
 
-    if combined.len() < NONCE_LENGTH {
-        return Err("Encrypted data is too short to contain a nonce".to_string());
+/// The content key sealed for one recipient.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WrappedKey {
+    pub name: String,
+    pub salt: String, // base64 Argon2 salt for this recipient
+    pub data: String, // base64 blob holding the content key
+}
+
+/// Returned by `encrypt_multi_recipient` and passed back whole to
+/// `decrypt_multi_recipient`; safe to store as JSON.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MultiRecipientVault {
+    pub version: u8,
+    /// base64 (header || nonce || ciphertext) of the payload, sealed under
+    /// the content key.
+    pub data: String,
+    pub recipients: Vec<WrappedKey>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params_warning: Option<ParamsWarning>,
+}
+
+/// Parsed versioned header.
+struct Header {
+    algorithm: u8,
+    params: CryptoParams,
+    keyfile_count: u8,
+    kind: PlaintextKind,
+    /// See `key_commitment`.
+    key_commitment: [u8; KEY_COMMITMENT_LENGTH],
+}
+
+/// What a blob's plaintext holds, as recorded in the header flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PlaintextKind {
+    /// `gzip_compress` (and possibly `pad_plaintext`) output.
+    Compressed,
+    /// Raw bytes (`crypto_seal`, shares, wrapped keys).
+    Raw,
+    /// An already-protected blob from `store_opaque`, not vault JSON.
+    Opaque,
+}
+
+impl PlaintextKind {
+    fn flag(self) -> u8 {
+        match self {
+            PlaintextKind::Compressed => HEADER_FLAG_COMPRESSED,
+            PlaintextKind::Raw => 0,
+            PlaintextKind::Opaque => HEADER_FLAG_OPAQUE,
+        }
     }
 
-    let nonce_bytes = &combined[..NONCE_LENGTH];
-    let ciphertext = &combined[NONCE_LENGTH..];
+    fn from_flags(flags: u8) -> Result<Self, SeqretsError> {
+        match flags & HEADER_FLAGS {
+            HEADER_FLAG_COMPRESSED => Ok(PlaintextKind::Compressed),
+            0 => Ok(PlaintextKind::Raw),
+            HEADER_FLAG_OPAQUE => Ok(PlaintextKind::Opaque),
+            _ => Err(SeqretsError::CorruptData(
+                "Header marks the payload both compressed and opaque".to_string(),
+            )),
+        }
+    }
+}
 
-    let cipher = XChaCha20Poly1305::new_from_slice(key)
-        .map_err(|_| "Cipher init error (invalid key length)".to_string())?;
-    let nonce = XNonce::from_slice(nonce_bytes);
+impl Header {
+    fn encode(&self) -> [u8; HEADER_LENGTH] {
+        let mut out = [0u8; HEADER_LENGTH];
+        out[0] = HEADER_MAGIC;
+        out[1] = FORMAT_VERSION;
+        out[2] = self.algorithm;
+        out[3..7].copy_from_slice(&self.params.m_cost.to_be_bytes());
+        out[7..11].copy_from_slice(&self.params.t_cost.to_be_bytes());
+        out[11..15].copy_from_slice(&self.params.p_cost.to_be_bytes());
+        out[15] = self.keyfile_count;
+        out[16] = self.params.argon2_variant.id() | self.kind.flag();
+        out[17..].copy_from_slice(&self.key_commitment);
+        out
+    }
 
-    let plaintext = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|_| "Decryption failed — wrong password, keyfile, or corrupted data".to_string())?;
+    /// Caller has already checked the magic and version bytes and that
+    /// `bytes` holds a full header. Fails on an Argon2 variant id this build
+    /// doesn't know or contradictory plaintext flags.
+    fn decode(bytes: &[u8]) -> Result<Header, SeqretsError> {
+        let u32_at =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Ok(Header {
+            algorithm: bytes[2],
+            params: CryptoParams {
+                m_cost: u32_at(3),
+                t_cost: u32_at(7),
+                p_cost: u32_at(11),
+                argon2_variant: Argon2Variant::from_id(bytes[16] & !HEADER_FLAGS)?,
+            },
+            keyfile_count: bytes[15],
+            kind: PlaintextKind::from_flags(bytes[16])?,
+            key_commitment: bytes[17..HEADER_LENGTH].try_into().expect("full header"),
+        })
+    }
+}
 
-    Ok(Zeroizing::new(plaintext))
+// ── Private helpers ──────────────────────────────────────────────────────────
+
+/// Takes ownership of the optional `keyfiles_b64` command argument so each
+/// keyfile is zeroized when dropped. `None` means no keyfiles.
+pub(crate) fn secret_keyfiles(keyfiles_b64: Option<Vec<String>>) -> Vec<Zeroizing<String>> {
+    keyfiles_b64
+        .unwrap_or_default()
+        .into_iter()
+        .map(Zeroizing::new)
+        .collect()
 }
 
-// ── Tauri commands ────────────────────────────────────────────────────────────
+/// Builds the Argon2 input from the password and base64 keyfiles. No keyfile
+/// and a single keyfile keep the original `password || keyfile` layout; two
+/// or more are length-prefixed so the boundaries (and order) are unambiguous.
+fn kdf_input(
+    password: &str,
+    keyfiles_b64: &[Zeroizing<String>],
+) -> Result<Zeroizing<Vec<u8>>, SeqretsError> {
+    let mut input = Zeroizing::new(password.as_bytes().to_vec());
+    for (i, kf_b64) in keyfiles_b64.iter().enumerate() {
+        let kf_bytes = Zeroizing::new(STANDARD.decode(kf_b64.as_bytes()).map_err(|e| {
+            SeqretsError::InvalidInput(format!("Keyfile {} base64 decode error: {e}", i + 1))
+        })?);
+        if keyfiles_b64.len() > 1 {
+            input.extend_from_slice(&(kf_bytes.len() as u32).to_be_bytes());
+        }
+        input.extend_from_slice(&kf_bytes);
+    }
+    Ok(input)
+}
+
+/// `ParamsTooLarge` when any Argon2 cost exceeds the hard limits.
+fn check_params_limit(params: &CryptoParams) -> Result<(), SeqretsError> {
+    if params.m_cost > MAX_M_COST || params.t_cost > MAX_T_COST || params.p_cost > MAX_P_COST {
+        return Err(SeqretsError::ParamsTooLarge(format!(
+            "Argon2 parameters (m={} KiB, t={}, p={}) exceed this build's limits (m={MAX_M_COST} KiB, t={MAX_T_COST}, p={MAX_P_COST}).",
+            params.m_cost, params.t_cost, params.p_cost
+        )));
+    }
+    Ok(())
+}
 
-/// Gzip-compresses `json_payload`, derives a key with Argon2id, then encrypts
-/// with XChaCha20-Poly1305. Returns a random base64 salt and the encrypted blob.
+/// Derives a 32-byte key from a password and base64-encoded keyfiles (in
+/// order) using the Argon2 variant and cost parameters in `params`. The decoded
+/// keyfiles and the input buffer are zeroized when they drop.
 ///
-/// Used by `createShares` in desktop-crypto.ts: the caller performs the Shamir
-/// split on the decoded `data` bytes in JavaScript.
-#[tauri::command]
-pub fn crypto_create(
-    json_payload: String,
-    password: String,
-    keyfile_b64: Option<String>,
-) -> Result<CryptoResult, String> {
-    let password = Zeroizing::new(password);
-    let compressed = Zeroizing::new(gzip_compress(json_payload.as_bytes())?);
+/// Parameters beyond the hard limits are refused with `ParamsTooLarge`.
+/// Nothing can cancel it; see `derive_key_cancellable`.
+///
+/// While `set_kdf_progress` is on, `kdf-progress` events report an estimate
+/// of how far along it is.
+pub(crate) fn derive_key(
+    password: &str,
+    salt: &[u8],
+    keyfiles_b64: &[Zeroizing<String>],
+    params: &CryptoParams,
+) -> Result<Zeroizing<[u8; KEY_LENGTH]>, SeqretsError> {
+    derive_key_cancellable(
+        password,
+        salt,
+        keyfiles_b64,
+        params,
+        &CancelToken::default(),
+    )
+}
 
-    let mut salt = [0u8; SALT_LENGTH];
-    rand::rng().fill_bytes(&mut salt);
+/// `derive_key`, returning `Cancelled` if `cancel` is cancelled while it
+/// runs.
+pub(crate) fn derive_key_cancellable(
+    password: &str,
+    salt: &[u8],
+    keyfiles_b64: &[Zeroizing<String>],
+    params: &CryptoParams,
+    cancel: &CancelToken,
+) -> Result<Zeroizing<[u8; KEY_LENGTH]>, SeqretsError> {
+    let ticker = ProgressTicker::start();
+    let key = run_argon2(password, salt, keyfiles_b64, params, cancel);
+    if let Some(ticker) = ticker {
+        ticker.finish(key.is_ok());
+    }
+    key
+}
 
-    let key = derive_key(password.as_str(), &salt, keyfile_b64.as_deref())?;
-    let data = encrypt(&compressed, &key)?;
+/// Lets one operation's key derivations be abandoned. Clones share the
+/// same state.
+#[derive(Clone, Default)]
+pub(crate) struct CancelToken(Arc<AtomicBool>);
 
-    Ok(CryptoResult {
-        salt: STANDARD.encode(salt),
-        data,
-    })
+impl CancelToken {
+    fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
 }
 
-/// Derives a key with Argon2id, decrypts `encrypted_b64` (base64 of the
-/// Shamir-combined nonce||ciphertext), then gzip-decompresses. Returns the
-/// JSON payload string.
-///
-/// Used by `restoreSecret` in desktop-crypto.ts: the caller performs the
-/// Shamir combine in JavaScript before calling this command.
-#[tauri::command]
-pub fn crypto_restore(
-    salt_b64: String,
-    encrypted_b64: String,
-    password: String,
-    keyfile_b64: Option<String>,
-) -> Result<String, String> {
-    let password = Zeroizing::new(password);
-    let salt = STANDARD
-        .decode(&salt_b64)
-        .map_err(|e| format!("Salt base64 decode error: {e}"))?;
+/// A `CancelToken` that `cancel_kdf` can reach under the frontend's
+/// `operation_id` for as long as this is alive.
+pub(crate) struct KdfOperation {
+    id: Option<String>,
+    token: CancelToken,
+}
 
-    let key = derive_key(password.as_str(), &salt, keyfile_b64.as_deref())?;
-    let mut plaintext = decrypt(&encrypted_b64, &key)?;
+impl KdfOperation {
+    /// Registers `id`; `None` gives a token nothing can cancel. An id
+    /// already in use by a running operation is refused, so one
+    /// `cancel_kdf` can't reach two operations.
+    pub(crate) fn register(id: Option<String>) -> Result<Self, SeqretsError> {
+        let token = CancelToken::default();
+        if let Some(id) = &id {
+            let mut operations = KDF_OPERATIONS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if operations.contains_key(id) {
+                return Err(SeqretsError::InvalidInput(format!(
+                    "Operation id \"{id}\" is already in use."
+                )));
+            }
+            operations.insert(id.clone(), token.clone());
+        }
+        Ok(KdfOperation { id, token })
+    }
 
-    let decompressed = gzip_decompress(&plaintext)?;
-    plaintext.zeroize(); // zero the compressed-but-decrypted bytes
+    pub(crate) fn token(&self) -> &CancelToken {
+        &self.token
+    }
+}
 
-    // Convert to String; on failure, zeroize the invalid bytes before propagating.
-    match String::from_utf8(decompressed) {
-        Ok(s) => Ok(s),
-        Err(e) => {
-            let mut bytes = e.into_bytes();
-            bytes.zeroize();
-            Err("UTF-8 decode error".to_string())
+impl Drop for KdfOperation {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            KDF_OPERATIONS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(id);
         }
     }
 }
 
-/// Gzip-compresses and encrypts a JSON string for vault/instructions storage.
-/// Returns a base64 salt and encrypted blob (nonce||ciphertext).
-///
-/// Used by `encryptVault` and `encryptInstructions` in desktop-crypto.ts.
-#[tauri::command]
-pub fn crypto_encrypt_blob(
-    json: String,
-    password: String,
-    keyfile_b64: Option<String>,
-) -> Result<CryptoResult, String> {
-    let password = Zeroizing::new(password);
-    let compressed = Zeroizing::new(gzip_compress(json.as_bytes())?);
+/// Payload of the `kdf-progress` event.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct KdfProgress {
+    /// Estimated from the elapsed time, not measured: Argon2 reports no
+    /// progress. Rises steadily to `KDF_PROGRESS_CAP`, and reaches 100 only
+    /// when the derivation succeeds.
+    pub percent: f64,
+    /// The derivation has returned (successfully or not).
+    pub done: bool,
+}
 
-    let mut salt = [0u8; SALT_LENGTH];
-    rand::rng().fill_bytes(&mut salt);
+/// Share of `expected` that `elapsed` covers, as a percentage capped at
+/// `KDF_PROGRESS_CAP`.
+fn estimate_progress(elapsed: Duration, expected: Duration) -> f64 {
+    let fraction = elapsed.as_secs_f64() / expected.as_secs_f64().max(f64::EPSILON);
+    (fraction * 100.0).min(KDF_PROGRESS_CAP)
+}
 
-    let key = derive_key(password.as_str(), &salt, keyfile_b64.as_deref())?;
-    let data = encrypt(&compressed, &key)?;
+/// Emits `kdf-progress` on its own thread every `KDF_PROGRESS_INTERVAL`
+/// until `finish`.
+struct ProgressTicker {
+    app: AppHandle,
+    started: Instant,
+    expected: Duration,
+    stop: Arc<AtomicBool>,
+}
 
-    Ok(CryptoResult {
-        salt: STANDARD.encode(salt),
-        data,
-    })
+impl ProgressTicker {
+    /// `None` unless `set_kdf_progress` has enabled progress events.
+    fn start() -> Option<Self> {
+        let (app, expected) = KDF_PROGRESS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()?;
+        let ticker = ProgressTicker {
+            app,
+            started: Instant::now(),
+            expected,
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        let (app, started, stop) = (ticker.app.clone(), ticker.started, ticker.stop.clone());
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                let percent = estimate_progress(started.elapsed(), expected);
+                let _ = app.emit(
+                    KDF_PROGRESS_EVENT,
+                    KdfProgress {
+                        percent,
+                        done: false,
+                    },
+                );
+                thread::sleep(KDF_PROGRESS_INTERVAL);
+            }
+        });
+        Some(ticker)
+    }
+
+    fn finish(self, succeeded: bool) {
+        self.stop.store(true, Ordering::SeqCst);
+        let percent = if succeeded {
+            100.0
+        } else {
+            estimate_progress(self.started.elapsed(), self.expected)
+        };
+        let _ = self.app.emit(
+            KDF_PROGRESS_EVENT,
+            KdfProgress {
+                percent,
+                done: true,
+            },
+        );
+    }
 }
 
-/// Derives a key with Argon2id, decrypts `data_b64` (base64 of nonce||ciphertext),
-/// then gzip-decompresses. Returns the JSON string.
-///
-/// Used by `decryptVault` and `decryptInstructions` in desktop-crypto.ts.
-#[tauri::command]
-pub fn crypto_decrypt_blob(
-    salt_b64: String,
-    data_b64: String,
-    password: String,
-    keyfile_b64: Option<String>,
-) -> Result<String, String> {
-    let password = Zeroizing::new(password);
-    let salt = STANDARD
-        .decode(&salt_b64)
-        .map_err(|e| format!("Salt base64 decode error: {e}"))?;
+/// The Argon2 derivation behind `derive_key_cancellable`, without progress
+/// events. Argon2 runs on its own thread, which can't be interrupted: on
+/// cancellation it finishes in the background and its key is zeroized and
+/// dropped.
+fn run_argon2(
+    password: &str,
+    salt: &[u8],
+    keyfiles_b64: &[Zeroizing<String>],
+    params: &CryptoParams,
+    cancel: &CancelToken,
+) -> Result<Zeroizing<[u8; KEY_LENGTH]>, SeqretsError> {
+    check_params_limit(params)?;
+    let cancelled = || SeqretsError::Cancelled("Key derivation cancelled.".to_string());
+    if cancel.is_cancelled() {
+        return Err(cancelled());
+    }
+    let input = kdf_input(password, keyfiles_b64)?;
 
-    let key = derive_key(password.as_str(), &salt, keyfile_b64.as_deref())?;
-    let mut plaintext = decrypt(&data_b64, &key)?;
+    let algorithm = params.argon2_variant.algorithm();
+    let params = Params::new(
+        params.m_cost,
+        params.t_cost,
+        params.p_cost,
+        Some(KEY_LENGTH),
+    )
+    .map_err(|e| SeqretsError::InvalidInput(format!("Argon2 params error: {e}")))?;
+    let argon2 = Argon2::new(algorithm, Version::V0x13, params);
 
-    let decompressed = gzip_decompress(&plaintext)?;
-    plaintext.zeroize();
+    let salt = salt.to_vec();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+        let result = argon2
+            .hash_password_into(input.as_slice(), &salt, key.as_mut_slice())
+            .map(|_| key);
+        // `input` is Zeroizing<Vec<u8>> — automatically zeroized on drop here.
+        let _ = sender.send(result);
+    });
 
-    // Convert to String; on failure, zeroize the invalid bytes before propagating.
-    match String::from_utf8(decompressed) {
-        Ok(s) => Ok(s),
-        Err(e) => {
-            let mut bytes = e.into_bytes();
-            bytes.zeroize();
-            Err("UTF-8 decode error".to_string())
+    loop {
+        match receiver.recv_timeout(KDF_CANCEL_POLL) {
+            Ok(result) => return result.map_err(|e| format!("Argon2 hash error: {e}").into()),
+            Err(RecvTimeoutError::Timeout) if cancel.is_cancelled() => return Err(cancelled()),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err("Argon2 worker thread exited.".into())
+            }
         }
     }
 }
 
-// ── Unit tests ────────────────────────────────────────────────────────────────
+/// `derive_key_cancellable`, adding the time it took to `spent`.
+fn derive_key_timed(
+    password: &str,
+    salt: &[u8],
+    keyfiles_b64: &[Zeroizing<String>],
+    params: &CryptoParams,
+    cancel: &CancelToken,
+    spent: &mut Duration,
+) -> Result<Zeroizing<[u8; KEY_LENGTH]>, SeqretsError> {
+    let start = Instant::now();
+    let key = derive_key_cancellable(password, salt, keyfiles_b64, params, cancel);
+    *spent += start.elapsed();
+    key
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Argon2 keys for one password, salt and keyfile set, derived on first use
+/// and reused by every blob with the same parameters. The keys are zeroized
+/// when the cache drops. Derivations give up once `cancel` is cancelled.
+struct KeyCache<'a> {
+    password: &'a str,
+    salt: &'a [u8],
+    keyfiles_b64: &'a [Zeroizing<String>],
+    cancel: &'a CancelToken,
+    keys: Vec<(CryptoParams, Zeroizing<[u8; KEY_LENGTH]>)>,
+}
 
-    // Round-trip: encrypt then decrypt must return the original plaintext.
-    #[test]
-    fn test_blob_roundtrip_no_keyfile() {
-        let payload = r#"{"secret":"hello world","label":"test","isMnemonic":false}"#.to_string();
-        let password = "s3cur3P@ssw0rd!".to_string();
+impl<'a> KeyCache<'a> {
+    fn new(
+        password: &'a str,
+        salt: &'a [u8],
+        keyfiles_b64: &'a [Zeroizing<String>],
+        cancel: &'a CancelToken,
+    ) -> Self {
+        KeyCache {
+            password,
+            salt,
+            keyfiles_b64,
+            cancel,
+            keys: Vec::new(),
+        }
+    }
 
-        let result = crypto_encrypt_blob(payload.clone(), password.clone(), None)
-            .expect("encrypt_blob should not fail");
+    /// The key for `params`, deriving it if needed. Argon2 time is added to
+    /// `spent`; failed derivations are not cached.
+    fn key(
+        &mut self,
+        params: &CryptoParams,
+        spent: &mut Duration,
+    ) -> Result<&[u8; KEY_LENGTH], SeqretsError> {
+        let index = match self.keys.iter().position(|(cached, _)| cached == params) {
+            Some(index) => index,
+            None => {
+                let key = derive_key_timed(
+                    self.password,
+                    self.salt,
+                    self.keyfiles_b64,
+                    params,
+                    self.cancel,
+                    spent,
+                )?;
+                self.keys.push((*params, key));
+                self.keys.len() - 1
+            }
+        };
+        Ok(&self.keys[index].1)
+    }
+}
 
-        let decrypted = crypto_decrypt_blob(result.salt, result.data, password, None)
-            .expect("decrypt_blob should not fail");
+/// Derives an independent 32-byte subkey from the Argon2 `master` key with
+/// HKDF-SHA256, using `context` as the `info` string. Different contexts give
+/// unrelated keys, so each use of the master key gets its own.
+pub(crate) fn derive_subkey(
+    master: &[u8; KEY_LENGTH],
+    context: &str,
+) -> Zeroizing<[u8; KEY_LENGTH]> {
+    let mut subkey = Zeroizing::new([0u8; KEY_LENGTH]);
+    Hkdf::<Sha256>::new(None, master)
+        .expand(context.as_bytes(), subkey.as_mut_slice())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    subkey
+}
 
-        assert_eq!(decrypted, payload, "decrypted payload must match original");
+/// A `ParamsWarning` when `params` fall below `MIN_SECURE_PARAMS`: less
+/// than 19 MiB, or too few passes over the memory used.
+pub(crate) fn weak_params(params: &CryptoParams) -> Option<ParamsWarning> {
+    let floor = MIN_SECURE_PARAMS;
+    let total = u64::from(params.m_cost) * u64::from(params.t_cost);
+    let floor_total = u64::from(floor.m_cost) * u64::from(floor.t_cost);
+    if params.m_cost >= floor.m_cost && total >= floor_total {
+        return None;
     }
+    Some(ParamsWarning {
+        params: *params,
+        minimum: floor,
+        message: format!(
+            "These Argon2 settings ({}, {} pass{}) are weaker than the recommended minimum of {} MiB with {} passes, making the password easier to guess.",
+            if params.m_cost >= 1024 { format!("{} MiB", params.m_cost / 1024) } else { format!("{} KiB", params.m_cost) },
+            params.t_cost,
+            if params.t_cost == 1 { "" } else { "es" },
+            floor.m_cost / 1024,
+            floor.t_cost
+        ),
+    })
+}
 
-    #[test]
-    fn test_blob_roundtrip_with_keyfile() {
-        let payload = r#"{"secret":"seed phrase here","label":"wallet","isMnemonic":true}"#.to_string();
-        let password = "another-password".to_string();
-        // 32 random bytes encoded as base64
-        let keyfile_b64 = Some(STANDARD.encode(b"0123456789abcdef0123456789abcdef"));
+/// A 32-byte value that confirms a password and keyfiles reproduce the
+/// Argon2 output `key` without revealing it: HKDF-SHA256 under `key` with
+/// `KEY_VERIFIER_CONTEXT` as `info` (an HMAC of that fixed string under the
+/// extracted key), unrelated to the subkey that encrypts the data.
+fn key_verifier(key: &[u8; KEY_LENGTH]) -> Zeroizing<[u8; KEY_LENGTH]> {
+    derive_subkey(key, KEY_VERIFIER_CONTEXT)
+}
+
+/// The prefix of `key_verifier` that the header carries, so a blob that
+/// fails to authenticate can tell a wrong key from changed associated data.
+fn key_commitment(key: &[u8; KEY_LENGTH]) -> [u8; KEY_COMMITMENT_LENGTH] {
+    key_verifier(key)[..KEY_COMMITMENT_LENGTH]
+        .try_into()
+        .expect("verifier is longer than the commitment")
+}
 
-        let result = crypto_encrypt_blob(payload.clone(), password.clone(), keyfile_b64.clone())
-            .expect("encrypt_blob with keyfile should not fail");
+/// Compares secret bytes (read-back data, digests, MACs) in time that
+/// doesn't depend on where they differ. Only the lengths leak.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
 
-        let decrypted = crypto_decrypt_blob(result.salt, result.data, password, keyfile_b64)
-            .expect("decrypt_blob with keyfile should not fail");
+/// Builds the caller-side associated data: `salt || label_len (u32 BE) || label`.
+/// `encrypt` and `decrypt` prepend the header bytes to this.
+fn metadata_aad(salt: &[u8], label: Option<&str>) -> Vec<u8> {
+    let label = label.unwrap_or("").as_bytes();
+    let mut aad = Vec::with_capacity(salt.len() + 4 + label.len());
+    aad.extend_from_slice(salt);
+    aad.extend_from_slice(&(label.len() as u32).to_be_bytes());
+    aad.extend_from_slice(label);
+    aad
+}
 
-        assert_eq!(decrypted, payload);
+/// Gzip-compresses `data` at `level` (0–9, default best), falling back to
+/// `STORED_FLAG || data` when gzip doesn't make it smaller (already-compressed
+/// or random input). Level 0 always stores.
+pub(crate) fn gzip_compress(data: &[u8], level: Option<u32>) -> Result<Vec<u8>, SeqretsError> {
+    if level == Some(0) {
+        return Ok(stored(data));
+    }
+    let mut compressed = gzip(data, level)?;
+    if compressed.len() < data.len() + 1 {
+        return Ok(compressed);
     }
+    compressed.zeroize();
+    Ok(stored(data))
+}
 
-    #[test]
-    fn test_wrong_password_fails() {
-        let payload = r#"{"secret":"my secret","isMnemonic":false}"#.to_string();
-        let result = crypto_encrypt_blob(payload, "correct-password".to_string(), None)
-            .expect("encrypt should succeed");
+/// A gzip stream of `data` at `level` (default best), even when it comes
+/// out larger than `data`. Version 0 shares need this: pako's `ungzip` in
+/// packages/crypto and seQRets Recover doesn't know the stored flag.
+fn gzip(data: &[u8], level: Option<u32>) -> Result<Vec<u8>, SeqretsError> {
+    let level = match level {
+        None => Compression::best(),
+        Some(level @ 0..=9) => Compression::new(level),
+        Some(level) => {
+            return Err(SeqretsError::InvalidInput(format!(
+                "Compression level must be between 0 and 9, got {level}."
+            )))
+        }
+    };
+    let mut encoder = GzEncoder::new(Vec::new(), level);
+    encoder
+        .write_all(data)
+        .map_err(|e| format!("Gzip write error: {e}"))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| format!("Gzip finish error: {e}"))?;
+    Ok(compressed)
+}
 
-        let err = crypto_decrypt_blob(result.salt, result.data, "wrong-password".to_string(), None);
-        assert!(err.is_err(), "decryption with wrong password must fail");
+fn stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 1);
+    out.push(STORED_FLAG);
+    out.extend_from_slice(data);
+    out
+}
+
+/// Pads a `gzip_compress` output to the next multiple of `pad_to` bytes, so
+/// every payload in a size class encrypts to the same length.
+pub(crate) fn pad_plaintext(inner: &[u8], pad_to: usize) -> Result<Vec<u8>, SeqretsError> {
+    if !(MIN_PAD_TO..=MAX_PAD_TO).contains(&pad_to) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Padding size must be {MIN_PAD_TO}-{MAX_PAD_TO} bytes, got {pad_to}."
+        )));
     }
+    let inner_len = u32::try_from(inner.len()).map_err(|_| "Payload too large to pad")?;
+    let unpadded = 1 + 4 + inner.len();
+    let mut out = Vec::with_capacity(unpadded.div_ceil(pad_to) * pad_to);
+    out.push(PADDED_FLAG);
+    out.extend_from_slice(&inner_len.to_be_bytes());
+    out.extend_from_slice(inner);
+    out.resize(unpadded.div_ceil(pad_to) * pad_to, 0);
+    Ok(out)
+}
 
-    #[test]
-    fn test_create_restore_roundtrip() {
-        let payload = r#"{"secret":"wallet seed","label":"cold storage","isMnemonic":false}"#.to_string();
-        let password = "test-password-123".to_string();
+/// `gzip_compress`, then `pad_plaintext` when `pad_to` is set.
+fn compress_and_pad(
+    data: &[u8],
+    level: Option<u32>,
+    pad_to: Option<usize>,
+) -> Result<Vec<u8>, SeqretsError> {
+    let compressed = Zeroizing::new(gzip_compress(data, level)?);
+    match pad_to {
+        Some(pad_to) => pad_plaintext(&compressed, pad_to),
+        None => Ok(compressed.to_vec()),
+    }
+}
+
+/// Strips `pad_plaintext` padding. The fill must be all zeros.
+fn unpad_plaintext(data: &[u8]) -> Result<&[u8], SeqretsError> {
+    let malformed = || SeqretsError::CorruptData("Padded payload is malformed.".to_string());
+    let len_bytes: [u8; 4] = data
+        .get(1..5)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(malformed)?;
+    let end = 5usize
+        .checked_add(u32::from_be_bytes(len_bytes) as usize)
+        .ok_or_else(malformed)?;
+    let (inner, fill) = (data.get(5..end).ok_or_else(malformed)?, &data[end..]);
+    if fill.iter().any(|&b| b != 0) {
+        return Err(malformed());
+    }
+    Ok(inner)
+}
 
-        let created = crypto_create(payload.clone(), password.clone(), None)
-            .expect("crypto_create should succeed");
+/// Returns the raw bytes of a `gzip_compress` output that was stored
+/// uncompressed, or `None` if it is a gzip stream.
+pub(crate) fn stored_payload(data: &[u8]) -> Option<&[u8]> {
+    match data.split_first() {
+        Some((&STORED_FLAG, raw)) => Some(raw),
+        _ => None,
+    }
+}
 
-        let restored = crypto_restore(created.salt, created.data, password, None)
-            .expect("crypto_restore should succeed");
+/// Decrypted bytes, and what the header says they are. Zeroized on drop.
+pub(crate) struct Plaintext {
+    bytes: Zeroizing<Vec<u8>>,
+    /// `None` for version 0 blobs, which don't say.
+    kind: Option<PlaintextKind>,
+}
 
-        assert_eq!(restored, payload);
+impl Plaintext {
+    fn new(bytes: Zeroizing<Vec<u8>>, kind: Option<PlaintextKind>) -> Self {
+        Plaintext { bytes, kind }
     }
+}
 
-    #[test]
-    fn test_different_encryptions_produce_different_ciphertext() {
-        // Same plaintext + password should produce different (salt, data) each time
-        // due to random salt and nonce.
-        let payload = r#"{"secret":"test","isMnemonic":false}"#.to_string();
-        let password = "pw".to_string();
+/// Shows the length only, never the bytes.
+impl std::fmt::Debug for Plaintext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Plaintext")
+            .field("len", &self.bytes.len())
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
 
-        let r1 = crypto_encrypt_blob(payload.clone(), password.clone(), None).unwrap();
-        let r2 = crypto_encrypt_blob(payload, password, None).unwrap();
+impl std::ops::Deref for Plaintext {
+    type Target = Vec<u8>;
 
-        // Different salts means different keys means different ciphertext
-        assert_ne!(r1.salt, r2.salt);
-        assert_ne!(r1.data, r2.data);
+    fn deref(&self) -> &Vec<u8> {
+        &self.bytes
+    }
+}
+
+/// The payload of a decrypted blob: unpacked with `gzip_decompress` when
+/// the header marks it compressed or there is no header, as is otherwise.
+pub(crate) fn decompress(plaintext: &Plaintext) -> Result<Vec<u8>, SeqretsError> {
+    match plaintext.kind {
+        Some(PlaintextKind::Raw | PlaintextKind::Opaque) => Ok(plaintext.bytes.to_vec()),
+        Some(PlaintextKind::Compressed) | None => gzip_decompress(&plaintext.bytes),
+    }
+}
+
+pub(crate) fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, SeqretsError> {
+    if data.first() == Some(&PADDED_FLAG) {
+        return gzip_decompress(unpad_plaintext(data)?);
+    }
+    if let Some(raw) = stored_payload(data) {
+        return Ok(raw.to_vec());
+    }
+    let mut decoder = GzDecoder::new(data);
+    let mut out = Vec::new();
+    decoder
+        .read_to_end(&mut out)
+        .map_err(|e| SeqretsError::CorruptData(format!("Gzip decompress error: {e}")))?;
+    Ok(out)
+}
+
+/// What the first bytes of a decoded blob say about its format.
+enum BlobFormat {
+    /// Header-less original layout: nonce || ciphertext.
+    Legacy,
+    /// Current header.
+    Versioned(Header),
+    /// Header that can't be used: an unknown cipher or Argon2 variant id,
+    /// contradictory flags, or too few bytes after it for a nonce and tag.
+    Malformed(SeqretsError),
+    /// Magic byte followed by a version this build doesn't know.
+    Unsupported { version: u8 },
+}
+
+fn detect_format(combined: &[u8]) -> BlobFormat {
+    if combined.len() < 2 || combined[0] != HEADER_MAGIC {
+        return BlobFormat::Legacy;
+    }
+    match combined[1] {
+        FORMAT_VERSION if combined.len() >= HEADER_LENGTH => {
+            match Header::decode(combined).and_then(|header| check_payload_length(combined, header))
+            {
+                Ok(header) => BlobFormat::Versioned(header),
+                Err(e) => BlobFormat::Malformed(e),
+            }
+        }
+        v if v > FORMAT_VERSION && v <= MAX_PLAUSIBLE_VERSION => {
+            BlobFormat::Unsupported { version: v }
+        }
+        _ => BlobFormat::Legacy,
+    }
+}
+
+/// `header` when its cipher is known and `combined` holds a nonce and tag
+/// for it after the header.
+fn check_payload_length(combined: &[u8], header: Header) -> Result<Header, SeqretsError> {
+    let cipher = Cipher::from_id(header.algorithm)?;
+    if combined.len() < HEADER_LENGTH + cipher.nonce_length() + TAG_LENGTH {
+        return Err(SeqretsError::CorruptData(
+            "Encrypted data is too short to contain a nonce and tag".to_string(),
+        ));
+    }
+    Ok(header)
+}
+
+/// Encrypts `plaintext` with `cipher` under the `vault-encryption-v1` subkey
+/// of the Argon2 output `key`, recording the cipher id,
+/// `params`, the number of keyfiles, the `kind` of `plaintext` and the
+/// `key_commitment` in the header. The header followed by
+/// `aad` is authenticated as associated data.
+/// Returns `header[33] || random_nonce || ciphertext_with_tag` as base64 text
+/// in `encoding`.
+#[allow(clippy::too_many_arguments)]
+fn encrypt(
+    plaintext: &[u8],
+    kind: PlaintextKind,
+    key: &[u8; KEY_LENGTH],
+    params: &CryptoParams,
+    cipher: Cipher,
+    keyfile_count: u8,
+    aad: &[u8],
+    encoding: PayloadEncoding,
+) -> Result<String, SeqretsError> {
+    let mut nonce_bytes = vec![0u8; cipher.nonce_length()];
+    rand::rng().fill_bytes(&mut nonce_bytes);
+
+    let header = Header {
+        algorithm: cipher.id(),
+        params: *params,
+        keyfile_count,
+        kind,
+        key_commitment: key_commitment(key),
+    }
+    .encode();
+    let subkey = derive_subkey(key, VAULT_ENCRYPTION_CONTEXT);
+    let ciphertext = cipher.seal(
+        &subkey,
+        &nonce_bytes,
+        plaintext,
+        &[&header[..], aad].concat(),
+    )?;
+
+    let mut combined = Vec::with_capacity(HEADER_LENGTH + nonce_bytes.len() + ciphertext.len());
+    combined.extend_from_slice(&header);
+    combined.extend_from_slice(&nonce_bytes);
+    combined.extend_from_slice(&ciphertext);
+
+    Ok(encoding.encode(&combined))
+}
+
+/// Seals `plaintext` in the header-less version 0 layout that packages/crypto
+/// and seQRets Recover read: `base64(nonce || ciphertext_with_tag)`,
+/// XChaCha20-Poly1305 under the Argon2 output `key` with no associated data,
+/// as text in `encoding`. `plaintext` must be a `gzip` stream, never the
+/// stored fallback, or those readers can't open it.
+fn encrypt_legacy(plaintext: &[u8], key: &[u8; KEY_LENGTH], encoding: PayloadEncoding) -> String {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rand::rng().fill_bytes(&mut nonce);
+    encrypt_with_nonce(plaintext, key, &nonce, encoding)
+}
+
+/// `encrypt_legacy` with the nonce taken as an argument, which makes the
+/// output deterministic so known-answer vectors can be compared byte for byte.
+fn encrypt_with_nonce(
+    plaintext: &[u8],
+    key: &[u8; KEY_LENGTH],
+    nonce: &[u8; NONCE_LENGTH],
+    encoding: PayloadEncoding,
+) -> String {
+    let ciphertext = Cipher::XChaCha20Poly1305
+        .seal(key, nonce, plaintext, &[])
+        .expect("XChaCha20-Poly1305 seal with a valid key");
+    encoding.encode(&[&nonce[..], &ciphertext].concat())
+}
+
+/// True when `crypto_create` options can be written in the version 0 layout.
+fn legacy_share_options(
+    params: &CryptoParams,
+    cipher: Cipher,
+    label: Option<&str>,
+    compression: Option<u32>,
+    pad_to: Option<usize>,
+    keyfile_count: u8,
+) -> bool {
+    *params == CryptoParams::default()
+        && cipher == Cipher::XChaCha20Poly1305
+        && label.is_none()
+        && compression != Some(0)
+        && pad_to.is_none()
+        && keyfile_count <= 1
+}
+
+/// Derives the key and decrypts `data_b64`, dispatching on the header: the
+/// cipher and Argon2 parameters come from the header (or XChaCha20-Poly1305
+/// with the defaults for version 0).
+/// Returns the plaintext bytes. Sensitive intermediate bytes are zeroized on drop.
+///
+/// Headered blobs authenticate `header || aad`; version 0 blobs predate
+/// associated data and are decrypted without it.
+///
+/// A legacy nonce can begin with the magic byte by chance, so whenever the
+/// header path fails — a malformed or unsupported header, `ParamsTooLarge`,
+/// a keyfile count mismatch or `WrongPassword` — the blob is retried as
+/// version 0, and the header error is reported only if that fails too. The
+/// exceptions are `Cancelled` and `HeaderTampered` (the key commitment
+/// matched, so the header is real), which are returned without a retry.
+/// The header's Argon2 costs are still checked against the limits before
+/// they are used; the retry only ever derives with the defaults.
+///
+/// Blobs too short to hold a nonce and tag are `CorruptData`, so only a real
+/// authentication failure reads as `WrongPassword`.
+///
+/// When a headered blob fails to authenticate but its key commitment
+/// matches, the key was right and the associated data (header, salt, label)
+/// or the ciphertext is what changed, reported as `HeaderTampered` instead
+/// of `WrongPassword`.
+///
+/// The header records how many keyfiles were used; presenting a different
+/// number is reported as `InvalidInput` rather than a wrong password.
+///
+/// Time spent in Argon2 (every derivation attempted) is added to `kdf_time`.
+/// Derivations give up with `Cancelled` once `cancel` is cancelled.
+fn decrypt(
+    data_b64: &str,
+    password: &str,
+    salt: &[u8],
+    keyfiles_b64: &[Zeroizing<String>],
+    aad: &[u8],
+    cancel: &CancelToken,
+    kdf_time: &mut Duration,
+) -> Result<Plaintext, SeqretsError> {
+    decrypt_with_keys(
+        data_b64,
+        &mut KeyCache::new(password, salt, keyfiles_b64, cancel),
+        aad,
+        kdf_time,
+    )
+}
+
+/// `decrypt`, taking keys from `keys` so blobs sharing a salt and parameters
+/// pay for one Argon2 derivation between them.
+fn decrypt_with_keys(
+    data_b64: &str,
+    keys: &mut KeyCache<'_>,
+    aad: &[u8],
+    kdf_time: &mut Duration,
+) -> Result<Plaintext, SeqretsError> {
+    let combined = encoding::decode(data_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Base64 decode error: {e}")))?;
+    // No format fits in fewer bytes than a legacy nonce and tag; reject
+    // truncated input before spending an Argon2 derivation on it.
+    if combined.len() < NONCE_LENGTH + TAG_LENGTH {
+        return Err(SeqretsError::CorruptData(format!(
+            "Encrypted data is truncated ({} bytes, need at least {}).",
+            combined.len(),
+            NONCE_LENGTH + TAG_LENGTH
+        )));
+    }
+
+    let legacy_params = CryptoParams::default();
+
+    let header_err = match detect_format(&combined) {
+        BlobFormat::Legacy => {
+            let key = keys.key(&legacy_params, kdf_time)?;
+            return decrypt_payload(&combined, key, Cipher::XChaCha20Poly1305, &[])
+                .map(|bytes| Plaintext::new(bytes, None));
+        }
+        BlobFormat::Versioned(header) => {
+            match decrypt_versioned(&combined, &header, keys, aad, kdf_time) {
+                Err(e @ (SeqretsError::Cancelled(_) | SeqretsError::HeaderTampered(_))) => {
+                    return Err(e)
+                }
+                Err(e) => e,
+                ok => return ok,
+            }
+        }
+        BlobFormat::Malformed(e) => e,
+        BlobFormat::Unsupported { version } => SeqretsError::CorruptData(format!(
+            "Unsupported encrypted data format version {version}. Please update seQRets."
+        )),
+    };
+
+    // The cache already holds this key when the header asked for the
+    // default params.
+    let key = keys.key(&legacy_params, kdf_time)?;
+    decrypt_payload(&combined, key, Cipher::XChaCha20Poly1305, &[])
+        .map(|bytes| Plaintext::new(bytes, None))
+        .map_err(|_| header_err)
+}
+
+/// The header path of `decrypt_with_keys`.
+fn decrypt_versioned(
+    combined: &[u8],
+    header: &Header,
+    keys: &mut KeyCache<'_>,
+    aad: &[u8],
+    kdf_time: &mut Duration,
+) -> Result<Plaintext, SeqretsError> {
+    check_keyfile_count(header, keys.keyfiles_b64.len())?;
+    check_params_limit(&header.params)?;
+    let cipher = Cipher::from_id(header.algorithm)?;
+    let key = keys.key(&header.params, kdf_time)?;
+    let (header_bytes, payload) = combined.split_at(HEADER_LENGTH);
+    let committed = ct_eq(&header.key_commitment, &key_commitment(key));
+    let subkey = derive_subkey(key, VAULT_ENCRYPTION_CONTEXT);
+    let opened = decrypt_payload(payload, &subkey, cipher, &[header_bytes, aad].concat());
+    opened
+        .map(|bytes| Plaintext::new(bytes, Some(header.kind)))
+        .map_err(|e| {
+            match e {
+        SeqretsError::WrongPassword(_) if committed => SeqretsError::HeaderTampered(
+            "The password is correct, but the label or salt given doesn't match what this data was \
+             encrypted with, or the data has been altered."
+                .to_string(),
+        ),
+        e => e,
+    }
+        })
+}
+
+/// Opens a headered blob sealed by `encrypt` directly under `key` rather
+/// than an Argon2 output, as the payload of a `MultiRecipientVault` is.
+fn decrypt_with_key(
+    data_b64: &str,
+    key: &[u8; KEY_LENGTH],
+    aad: &[u8],
+) -> Result<Plaintext, SeqretsError> {
+    let combined = encoding::decode(data_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Base64 decode error: {e}")))?;
+    let header = match detect_format(&combined) {
+        BlobFormat::Versioned(header) => header,
+        BlobFormat::Malformed(e) => return Err(e),
+        _ => {
+            return Err(SeqretsError::CorruptData(
+                "Vault payload has no valid header.".to_string(),
+            ))
+        }
+    };
+    let cipher = Cipher::from_id(header.algorithm)?;
+    let (header_bytes, payload) = combined.split_at(HEADER_LENGTH);
+    let subkey = derive_subkey(key, VAULT_ENCRYPTION_CONTEXT);
+    decrypt_payload(payload, &subkey, cipher, &[header_bytes, aad].concat())
+        .map(|bytes| Plaintext::new(bytes, Some(header.kind)))
+}
+
+/// One supported AEAD, as reported by `crypto_capabilities`.
+#[derive(Serialize, Debug)]
+pub struct CipherInfo {
+    pub id: u8,
+    pub name: &'static str,
+    pub nonce_length: usize,
+}
+
+/// Returned by `check_nonce_uniqueness`. Indices refer to the caller's list.
+#[derive(Serialize, Debug, Default)]
+pub struct NonceReport {
+    /// Blobs whose nonce could be read.
+    pub checked: usize,
+    /// Blobs that aren't base64 or are too short to hold a nonce.
+    pub unreadable: Vec<usize>,
+    /// Each group lists distinct blobs that share one nonce.
+    pub collisions: Vec<Vec<usize>>,
+    /// True when no collision was found.
+    pub unique: bool,
+}
+
+/// What this build's vault/blob format supports.
+#[derive(Serialize, Debug)]
+pub struct CryptoCapabilities {
+    pub ciphers: Vec<CipherInfo>,
+    pub default_cipher: &'static str,
+    pub kdfs: Vec<&'static str>,
+    /// Text encodings the share/QR commands accept for `encoding`.
+    pub encodings: Vec<&'static str>,
+    /// Header version written by the encryption commands.
+    pub format_version: u8,
+    /// Every version decryption accepts, 0 being the header-less JS layout.
+    pub readable_format_versions: Vec<u8>,
+    pub default_params: CryptoParams,
+    pub max_keyfiles: usize,
+}
+
+pub(crate) fn crypto_capabilities() -> CryptoCapabilities {
+    let default_cipher = Cipher::from_name(None).expect("default cipher");
+    CryptoCapabilities {
+        ciphers: [Cipher::XChaCha20Poly1305, Cipher::Aes256Gcm]
+            .into_iter()
+            .map(|c| CipherInfo {
+                id: c.id(),
+                name: c.name(),
+                nonce_length: c.nonce_length(),
+            })
+            .collect(),
+        default_cipher: default_cipher.name(),
+        kdfs: vec!["argon2id", "argon2i", "argon2d"],
+        encodings: encoding::ENCODING_NAMES.to_vec(),
+        format_version: FORMAT_VERSION,
+        readable_format_versions: vec![0, FORMAT_VERSION],
+        default_params: CryptoParams::default(),
+        max_keyfiles: u8::MAX as usize,
+    }
+}
+
+/// The nonce of a decoded blob: right after the header for headered blobs,
+/// the first 24 bytes for version 0. `None` when the blob can't hold one.
+fn blob_nonce(combined: &[u8]) -> Option<&[u8]> {
+    match detect_format(combined) {
+        BlobFormat::Legacy => combined.get(..NONCE_LENGTH),
+        BlobFormat::Versioned(header) => {
+            let cipher = Cipher::from_id(header.algorithm).ok()?;
+            combined.get(HEADER_LENGTH..HEADER_LENGTH + cipher.nonce_length())
+        }
+        BlobFormat::Malformed(_) | BlobFormat::Unsupported { .. } => None,
+    }
+}
+
+/// Groups `blobs` by nonce. The same blob listed twice is one encryption,
+/// not a reuse, so only groups holding different blobs count as collisions.
+fn nonce_report(blobs: &[String]) -> NonceReport {
+    let mut report = NonceReport::default();
+    let mut by_nonce: HashMap<Vec<u8>, Vec<(usize, Vec<u8>)>> = HashMap::new();
+    for (i, blob) in blobs.iter().enumerate() {
+        let Ok(combined) = encoding::decode(blob) else {
+            report.unreadable.push(i);
+            continue;
+        };
+        match blob_nonce(&combined) {
+            Some(nonce) => {
+                report.checked += 1;
+                by_nonce
+                    .entry(nonce.to_vec())
+                    .or_default()
+                    .push((i, combined));
+            }
+            None => report.unreadable.push(i),
+        }
+    }
+
+    report.collisions = by_nonce
+        .into_values()
+        .filter(|group| group.iter().any(|(_, bytes)| *bytes != group[0].1))
+        .map(|group| group.into_iter().map(|(i, _)| i).collect())
+        .collect();
+    report.collisions.sort();
+    report.unique = report.collisions.is_empty();
+    report
+}
+
+/// Fails when the header records a different number of keyfiles than were
+/// supplied.
+fn check_keyfile_count(header: &Header, supplied: usize) -> Result<(), SeqretsError> {
+    let expected = header.keyfile_count;
+    if expected as usize != supplied {
+        return Err(SeqretsError::InvalidInput(format!(
+            "This data was encrypted with {expected} keyfile(s), but {supplied} were provided."
+        )));
+    }
+    Ok(())
+}
+
+/// The keyfile count as stored in the header.
+fn keyfile_count(keyfiles_b64: &[Zeroizing<String>]) -> Result<u8, SeqretsError> {
+    u8::try_from(keyfiles_b64.len()).map_err(|_| {
+        SeqretsError::InvalidInput(format!(
+            "At most {} keyfiles are supported, got {}.",
+            u8::MAX,
+            keyfiles_b64.len()
+        ))
+    })
+}
+
+/// Decrypts `nonce || ciphertext` with `cipher`, authenticating `aad`.
+fn decrypt_payload(
+    payload: &[u8],
+    key: &[u8; KEY_LENGTH],
+    cipher: Cipher,
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, SeqretsError> {
+    let nonce_length = cipher.nonce_length();
+    if payload.len() < nonce_length + TAG_LENGTH {
+        return Err(SeqretsError::CorruptData(
+            "Encrypted data is too short to contain a nonce and tag".to_string(),
+        ));
+    }
+
+    let (nonce_bytes, ciphertext) = payload.split_at(nonce_length);
+    let plaintext = cipher.open(key, nonce_bytes, ciphertext, aad)?;
+
+    Ok(Zeroizing::new(plaintext))
+}
+
+/// Gunzips decrypted vault bytes and returns them as a string. The decrypted
+/// bytes, and the decompressed ones if they aren't UTF-8, are zeroized.
+/// Opaque blobs are refused rather than read as text.
+pub(crate) fn decompress_json(plaintext: Plaintext) -> Result<String, SeqretsError> {
+    if plaintext.kind == Some(PlaintextKind::Opaque) {
+        return Err(SeqretsError::InvalidInput(
+            "This blob holds opaque data stored with store_opaque, not a vault; open it with open_opaque.".to_string(),
+        ));
+    }
+    let decompressed = decompress(&plaintext)?;
+    drop(plaintext); // zero the compressed-but-decrypted bytes
+
+    // Convert to String; on failure, zeroize the invalid bytes before propagating.
+    String::from_utf8(decompressed).map_err(|e| {
+        let mut bytes = e.into_bytes();
+        bytes.zeroize();
+        SeqretsError::CorruptData("UTF-8 decode error".to_string())
+    })
+}
+
+/// Times one Argon2id derivation with throwaway inputs.
+fn time_kdf(params: &CryptoParams) -> Result<Duration, SeqretsError> {
+    let salt = [0u8; SALT_LENGTH];
+    let start = Instant::now();
+    // Benchmark runs would only confuse a progress bar, and nothing cancels them.
+    run_argon2(
+        "seQRets-kdf-benchmark",
+        &salt,
+        &[],
+        params,
+        &CancelToken::default(),
+    )?;
+    Ok(start.elapsed())
+}
+
+/// Finds the largest Argon2 cost that fits within `target`: memory is
+/// doubled from the floor while a single pass stays under half the target,
+/// then iterations are added to fill the remaining time.
+fn run_kdf_benchmark(target: Duration) -> Result<CryptoParams, SeqretsError> {
+    let mut params = CryptoParams {
+        m_cost: BENCH_MIN_M_COST,
+        t_cost: 1,
+        p_cost: ARGON2_P_COST,
+        argon2_variant: Argon2Variant::Argon2id,
+    };
+
+    let mut single_pass = time_kdf(&params)?;
+    while single_pass * 2 <= target && params.m_cost * 2 <= BENCH_MAX_M_COST {
+        params.m_cost *= 2;
+        single_pass = time_kdf(&params)?;
+    }
+
+    params.t_cost = iterations_for(target, single_pass);
+    Ok(params)
+}
+
+/// How many passes of `single_pass` fit in `target`, within the benchmark bounds.
+fn iterations_for(target: Duration, single_pass: Duration) -> u32 {
+    let passes = target.as_nanos() / single_pass.as_nanos().max(1);
+    passes.min(BENCH_MAX_T_COST as u128).max(1) as u32
+}
+
+/// RAM the KDF can draw on, in bytes: `MemAvailable` on Linux, physical
+/// memory on macOS. `None` on other platforms or when it can't be read.
+fn available_memory() -> Option<u64> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        std::fs::read_to_string("/proc/meminfo")
+            .ok()
+            .and_then(|meminfo| parse_mem_available(&meminfo))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut bytes: u64 = 0;
+        let mut len = std::mem::size_of::<u64>();
+        // SAFETY: hw.memsize is a u64 and `len` gives the buffer size.
+        let rc = unsafe {
+            libc::sysctlbyname(
+                c"hw.memsize".as_ptr(),
+                (&mut bytes as *mut u64).cast(),
+                &mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (rc == 0).then_some(bytes)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// The `MemAvailable` line of /proc/meminfo, in bytes.
+#[cfg(any(target_os = "linux", target_os = "android", test))]
+fn parse_mem_available(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Argon2 memory cost (KiB) for `available` bytes of RAM: a fraction of it,
+/// rounded down to whole MiB and clamped to the benchmark bounds. The
+/// default cost when the amount is unknown.
+fn memory_cost_for(available: Option<u64>) -> u32 {
+    match available {
+        Some(bytes) => {
+            let kib =
+                (bytes / 1024 / RECOMMENDED_MEMORY_DIVISOR).min(BENCH_MAX_M_COST as u64) as u32;
+            (kib / 1024 * 1024).max(BENCH_MIN_M_COST)
+        }
+        None => ARGON2_M_COST,
+    }
+}
+
+/// Scales memory to this machine's free RAM, then fits iterations to
+/// `target`. Memory is halved (down to the floor) while a single pass
+/// alone overruns the target.
+fn run_recommended_params(target: Duration) -> Result<CryptoParams, SeqretsError> {
+    let mut params = CryptoParams {
+        m_cost: memory_cost_for(available_memory()),
+        t_cost: 1,
+        p_cost: ARGON2_P_COST,
+        argon2_variant: Argon2Variant::Argon2id,
+    };
+
+    let mut single_pass = time_kdf(&params)?;
+    while single_pass > target && params.m_cost / 2 >= BENCH_MIN_M_COST {
+        params.m_cost /= 2;
+        single_pass = time_kdf(&params)?;
+    }
+
+    params.t_cost = iterations_for(target, single_pass);
+    Ok(params)
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Gzip-compresses `json_payload`, derives a key with Argon2, then encrypts
+/// with the chosen cipher. Returns a random base64 salt and the encrypted blob.
+/// `params` defaults to Argon2id with the standard cost factors and `cipher`
+/// ("xchacha20-poly1305" or "aes-256-gcm") to XChaCha20-Poly1305 when omitted.
+/// `label` is bound into the ciphertext as associated data and must be passed
+/// unchanged to `crypto_restore`. `compression` is the gzip level (0–9, where
+/// 0 stores the payload uncompressed); it defaults to 9. `encoding`
+/// ("base64" or "base64url") selects how `salt` and `data` are encoded for
+/// share strings and defaults to base64. `pad_to` pads the compressed
+/// payload to a multiple of that many bytes (16 B to 1 MiB) so a seed phrase
+/// and a larger secret can't be told apart by length; decryption strips it.
+///
+/// Used by `createShares` in desktop-crypto.ts: the caller performs the Shamir
+/// split on the decoded `data` bytes in JavaScript.
+///
+/// Qard shares must stay readable by the web app and seQRets Recover, which
+/// only know the header-less version 0 layout. With the default params and
+/// cipher, no label or padding, a gzip level above 0 and at most one keyfile
+/// that layout is written (see `encrypt_legacy`), always as a real gzip
+/// stream even when that is larger than the payload. Any of the other
+/// options needs the header and produces shares only this app can restore.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn crypto_create(
+    json_payload: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    params: Option<CryptoParams>,
+    cipher: Option<String>,
+    label: Option<String>,
+    compression: Option<u32>,
+    encoding: Option<String>,
+    pad_to: Option<usize>,
+) -> Result<CryptoResult, SeqretsError> {
+    let json_payload = Zeroizing::new(json_payload);
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let params = params.unwrap_or_default();
+    let cipher = Cipher::from_name(cipher.as_deref())?;
+    let encoding = PayloadEncoding::from_name(encoding.as_deref())?;
+    let count = keyfile_count(&keyfiles_b64)?;
+    let legacy = legacy_share_options(
+        &params,
+        cipher,
+        label.as_deref(),
+        compression,
+        pad_to,
+        count,
+    );
+    let compressed = Zeroizing::new(if legacy {
+        gzip(json_payload.as_bytes(), compression)?
+    } else {
+        compress_and_pad(json_payload.as_bytes(), compression, pad_to)?
+    });
+    drop(json_payload);
+
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::rng().fill_bytes(&mut salt);
+
+    let key = derive_key(password.as_str(), &salt, &keyfiles_b64, &params)?;
+    drop(password);
+    drop(keyfiles_b64);
+    let data = if legacy {
+        encrypt_legacy(&compressed, &key, encoding)
+    } else {
+        let aad = metadata_aad(&salt, label.as_deref());
+        encrypt(
+            &compressed,
+            PlaintextKind::Compressed,
+            &key,
+            &params,
+            cipher,
+            count,
+            &aad,
+            encoding,
+        )?
+    };
+
+    Ok(CryptoResult {
+        salt: encoding.encode(&salt),
+        data,
+        verifier: Some(encoding.encode(key_verifier(&key).as_slice())),
+        params_warning: weak_params(&params),
+    })
+}
+
+/// Derives a key with Argon2 (using the variant and parameters stored in the header),
+/// decrypts `encrypted_b64` (base64 or base64url of the Shamir-combined blob), then
+/// gzip-decompresses. Returns the JSON payload string. `label` must match
+/// the one given at creation. With `with_timing` the Argon2 time is
+/// returned too (see `MaybeTimed`).
+///
+/// Used by `restoreSecret` in desktop-crypto.ts: the caller performs the
+/// Shamir combine in JavaScript before calling this command. Runs off the
+/// main thread, so when started with an `operation_id` a slow derivation can
+/// be aborted with `cancel_kdf`.
+#[tauri::command(async)]
+pub fn crypto_restore(
+    salt_b64: String,
+    encrypted_b64: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    label: Option<String>,
+    with_timing: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<MaybeTimed<String>, SeqretsError> {
+    let operation = KdfOperation::register(operation_id)?;
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let salt = encoding::decode(&salt_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Salt base64 decode error: {e}")))?;
+
+    let aad = metadata_aad(&salt, label.as_deref());
+    let mut kdf_time = Duration::ZERO;
+    let plaintext = decrypt(
+        &encrypted_b64,
+        password.as_str(),
+        &salt,
+        &keyfiles_b64,
+        &aad,
+        operation.token(),
+        &mut kdf_time,
+    )?;
+    drop(password);
+    drop(keyfiles_b64);
+
+    let json = decompress_json(plaintext)?;
+    Ok(MaybeTimed::new(json, kdf_time, with_timing))
+}
+
+/// Gzip-compresses and encrypts a JSON string for vault/instructions storage.
+/// Returns a base64 salt and encrypted blob (header||nonce||ciphertext).
+/// `params`, `cipher`, `label`, `compression` and `pad_to` behave as in
+/// `crypto_create`.
+///
+/// Used by `encryptVault` and `encryptInstructions` in desktop-crypto.ts.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn crypto_encrypt_blob(
+    json: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    params: Option<CryptoParams>,
+    cipher: Option<String>,
+    label: Option<String>,
+    compression: Option<u32>,
+    pad_to: Option<usize>,
+) -> Result<CryptoResult, SeqretsError> {
+    let json = Zeroizing::new(json);
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let params = params.unwrap_or_default();
+    let cipher = Cipher::from_name(cipher.as_deref())?;
+    let compressed = Zeroizing::new(compress_and_pad(json.as_bytes(), compression, pad_to)?);
+    drop(json);
+    seal_with_new_salt(
+        &compressed,
+        PlaintextKind::Compressed,
+        password.as_str(),
+        &keyfiles_b64,
+        &params,
+        cipher,
+        label.as_deref(),
+        &CancelToken::default(),
+    )
+}
+
+/// Encrypts `plaintext` under a fresh random salt, as `crypto_encrypt_blob`
+/// (compressed), `crypto_seal` (raw) and `store_opaque` return it. The
+/// derivation gives up once `cancel` is cancelled.
+#[allow(clippy::too_many_arguments)]
+fn seal_with_new_salt(
+    plaintext: &[u8],
+    kind: PlaintextKind,
+    password: &str,
+    keyfiles_b64: &[Zeroizing<String>],
+    params: &CryptoParams,
+    cipher: Cipher,
+    label: Option<&str>,
+    cancel: &CancelToken,
+) -> Result<CryptoResult, SeqretsError> {
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::rng().fill_bytes(&mut salt);
+
+    let count = keyfile_count(keyfiles_b64)?;
+    let key = derive_key_cancellable(password, &salt, keyfiles_b64, params, cancel)?;
+    let aad = metadata_aad(&salt, label);
+    let data = encrypt(
+        plaintext,
+        kind,
+        &key,
+        params,
+        cipher,
+        count,
+        &aad,
+        PayloadEncoding::Base64,
+    )?;
+
+    Ok(CryptoResult {
+        salt: STANDARD.encode(salt),
+        data,
+        verifier: Some(STANDARD.encode(key_verifier(&key).as_slice())),
+        params_warning: weak_params(params),
+    })
+}
+
+/// Derives a key with Argon2 (using the variant and parameters stored in the header),
+/// decrypts `data_b64` (base64 of header||nonce||ciphertext), then
+/// gzip-decompresses. Returns the JSON string. `label` must match the one
+/// given at encryption. `with_timing` behaves as in `crypto_restore`.
+///
+/// Used by `decryptVault` and `decryptInstructions` in desktop-crypto.ts.
+/// Cancellable with `cancel_kdf` given an `operation_id`, like `crypto_restore`.
+#[tauri::command(async)]
+pub fn crypto_decrypt_blob(
+    salt_b64: String,
+    data_b64: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    label: Option<String>,
+    with_timing: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<MaybeTimed<String>, SeqretsError> {
+    let operation = KdfOperation::register(operation_id)?;
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let mut kdf_time = Duration::ZERO;
+    let plaintext = decrypt_blob(
+        &salt_b64,
+        &data_b64,
+        password.as_str(),
+        &keyfiles_b64,
+        label.as_deref(),
+        operation.token(),
+        &mut kdf_time,
+    )?;
+    drop(password);
+    drop(keyfiles_b64);
+
+    let json = decompress_json(plaintext)?;
+    Ok(MaybeTimed::new(json, kdf_time, with_timing))
+}
+
+/// Decodes the salt and decrypts a blob from `crypto_encrypt_blob`, returning
+/// the still-compressed plaintext. Argon2 time is added to `kdf_time`, and
+/// gives up once `cancel` is cancelled.
+pub(crate) fn decrypt_blob(
+    salt_b64: &str,
+    data_b64: &str,
+    password: &str,
+    keyfiles_b64: &[Zeroizing<String>],
+    label: Option<&str>,
+    cancel: &CancelToken,
+    kdf_time: &mut Duration,
+) -> Result<Plaintext, SeqretsError> {
+    let salt = STANDARD
+        .decode(salt_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Salt base64 decode error: {e}")))?;
+    let aad = metadata_aad(&salt, label);
+    decrypt(
+        data_b64,
+        password,
+        &salt,
+        keyfiles_b64,
+        &aad,
+        cancel,
+        kdf_time,
+    )
+}
+
+/// Decrypts several `crypto_encrypt_blob` blobs that share one salt and
+/// password, such as the entries of a vault, in a single call. The Argon2 key
+/// is derived once per distinct set of header parameters instead of once per
+/// item, and zeroized when the call returns.
+///
+/// Each item gets its own result, so one corrupt entry doesn't hide the
+/// others; only an undecodable salt fails the whole call. `label` applies to
+/// every item.
+#[tauri::command]
+pub fn crypto_decrypt_batch(
+    salt_b64: String,
+    items: Vec<String>,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    label: Option<String>,
+) -> Result<Vec<Result<String, SeqretsError>>, SeqretsError> {
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let salt = STANDARD
+        .decode(&salt_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Salt base64 decode error: {e}")))?;
+
+    let aad = metadata_aad(&salt, label.as_deref());
+    let cancel = CancelToken::default();
+    let mut keys = KeyCache::new(password.as_str(), &salt, &keyfiles_b64, &cancel);
+    let mut kdf_time = Duration::ZERO;
+    Ok(items
+        .iter()
+        .map(|item| {
+            decrypt_with_keys(item, &mut keys, &aad, &mut kdf_time).and_then(decompress_json)
+        })
+        .collect())
+}
+
+/// Domain separator for shares wrapped to one smart card.
+const CARD_WRAP_DOMAIN: &[u8] = b"seQRets-card-share-v1";
+
+/// The Argon2 salt and associated data that tie a wrapped share to the card
+/// identified by `card_id`.
+fn card_binding(card_id: &[u8]) -> ([u8; SALT_LENGTH], Vec<u8>) {
+    let digest = Sha256::new_with_prefix(CARD_WRAP_DOMAIN)
+        .chain_update(card_id)
+        .finalize();
+    let mut salt = [0u8; SALT_LENGTH];
+    salt.copy_from_slice(&digest[..SALT_LENGTH]);
+    (salt, [CARD_WRAP_DOMAIN, card_id].concat())
+}
+
+/// Seals `share` for storage on one card. The key is the HKDF subkey of an
+/// Argon2id derivation over `passphrase`, salted with a hash of `card_id`,
+/// and `card_id` is also associated data, so the result opens only with both
+/// the passphrase and the same card. Returns a blob in the usual format.
+pub(crate) fn wrap_card_share(
+    share: &str,
+    passphrase: &str,
+    card_id: &[u8],
+) -> Result<String, SeqretsError> {
+    let (salt, aad) = card_binding(card_id);
+    let params = CryptoParams::default();
+    let key = derive_key(passphrase, &salt, &[], &params)?;
+    encrypt(
+        share.as_bytes(),
+        PlaintextKind::Raw,
+        &key,
+        &params,
+        Cipher::XChaCha20Poly1305,
+        0,
+        &aad,
+        PayloadEncoding::Base64,
+    )
+}
+
+/// Reverses `wrap_card_share`. A wrong passphrase or a share copied from
+/// another card fails as `WrongPassword`.
+pub(crate) fn unwrap_card_share(
+    data: &str,
+    passphrase: &str,
+    card_id: &[u8],
+) -> Result<Zeroizing<String>, SeqretsError> {
+    let (salt, aad) = card_binding(card_id);
+    let plaintext = decrypt(
+        data,
+        passphrase,
+        &salt,
+        &[],
+        &aad,
+        &CancelToken::default(),
+        &mut Duration::default(),
+    )?;
+    std::str::from_utf8(&plaintext)
+        .map(|share| Zeroizing::new(share.to_string()))
+        .map_err(|_| SeqretsError::CorruptData("Wrapped share is not valid UTF-8".to_string()))
+}
+
+/// Domain separator for the key that signs a card's metadata.
+const CARD_SIGNATURE_DOMAIN: &[u8] = b"seQRets-card-signature-v1";
+
+/// HKDF context for the card signing key.
+const CARD_SIGNATURE_KEY_CONTEXT: &str = "seqrets-card-signature-key";
+
+/// The HMAC key `sign_card` uses for the card identified by `card_id`: the
+/// HKDF subkey of an Argon2id derivation over `password`, salted with a hash
+/// of `card_id`, so a signature can't be recomputed without the password or
+/// carried over to another card.
+pub(crate) fn card_signing_key(
+    password: &str,
+    card_id: &[u8],
+) -> Result<Zeroizing<[u8; KEY_LENGTH]>, SeqretsError> {
+    let digest = Sha256::new_with_prefix(CARD_SIGNATURE_DOMAIN)
+        .chain_update(card_id)
+        .finalize();
+    let master = derive_key(
+        password,
+        &digest[..SALT_LENGTH],
+        &[],
+        &CryptoParams::default(),
+    )?;
+    Ok(derive_subkey(&master, CARD_SIGNATURE_KEY_CONTEXT))
+}
+
+/// HMAC-SHA256 of `message` under `key`.
+pub(crate) fn hmac_sha256(key: &[u8; KEY_LENGTH], message: &[u8]) -> [u8; 32] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// Encrypts raw bytes without the gzip stage, for data that is already
+/// compressed or binary (Shamir shares, keyfiles). `data_b64` is the base64
+/// plaintext; `params`, `cipher` and `label` behave as in `crypto_create`.
+/// Returns a random base64 salt and the encrypted blob (header||nonce||ciphertext).
+#[tauri::command]
+pub fn crypto_seal(
+    data_b64: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    params: Option<CryptoParams>,
+    cipher: Option<String>,
+    label: Option<String>,
+) -> Result<CryptoResult, SeqretsError> {
+    let data_b64 = Zeroizing::new(data_b64);
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let params = params.unwrap_or_default();
+    let cipher = Cipher::from_name(cipher.as_deref())?;
+    let plaintext = Zeroizing::new(
+        STANDARD
+            .decode(data_b64.as_bytes())
+            .map_err(|e| SeqretsError::InvalidInput(format!("Data base64 decode error: {e}")))?,
+    );
+    drop(data_b64);
+    seal_with_new_salt(
+        &plaintext,
+        PlaintextKind::Raw,
+        password.as_str(),
+        &keyfiles_b64,
+        &params,
+        cipher,
+        label.as_deref(),
+        &CancelToken::default(),
+    )
+}
+
+/// Re-encrypts a `crypto_encrypt_blob` vault under new key material: the
+/// password change flow. The blob is decrypted with `old_password` and
+/// `old_keyfiles_b64`, then sealed again with `new_password`,
+/// `new_keyfiles_b64` and `new_params` (the defaults when omitted, so this
+/// also moves a vault onto stronger Argon2 settings) under a fresh salt.
+/// The compressed plaintext never leaves the backend and is zeroized as
+/// soon as it has been re-encrypted. `label` and `cipher` apply to the new
+/// blob; `label` must also match the old one.
+///
+/// Cancellable with `cancel_kdf` given an `operation_id`. A wrong old
+/// password fails with `WrongPassword` before the new key is derived.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn rekey_vault(
+    salt_b64: String,
+    data_b64: String,
+    old_password: String,
+    old_keyfiles_b64: Option<Vec<String>>,
+    new_password: String,
+    new_keyfiles_b64: Option<Vec<String>>,
+    new_params: Option<CryptoParams>,
+    cipher: Option<String>,
+    label: Option<String>,
+    operation_id: Option<String>,
+) -> Result<CryptoResult, SeqretsError> {
+    let operation = KdfOperation::register(operation_id)?;
+    let old_password = Zeroizing::new(old_password);
+    let old_keyfiles_b64 = secret_keyfiles(old_keyfiles_b64);
+    let new_password = Zeroizing::new(new_password);
+    let new_keyfiles_b64 = secret_keyfiles(new_keyfiles_b64);
+    let params = new_params.unwrap_or_default();
+    let cipher = Cipher::from_name(cipher.as_deref())?;
+
+    let plaintext = decrypt_blob(
+        &salt_b64,
+        &data_b64,
+        old_password.as_str(),
+        &old_keyfiles_b64,
+        label.as_deref(),
+        operation.token(),
+        &mut Duration::default(),
+    )?;
+    drop(old_password);
+    drop(old_keyfiles_b64);
+    let kind = plaintext.kind.unwrap_or(PlaintextKind::Compressed);
+    seal_with_new_salt(
+        &plaintext,
+        kind,
+        new_password.as_str(),
+        &new_keyfiles_b64,
+        &params,
+        cipher,
+        label.as_deref(),
+        operation.token(),
+    )
+}
+
+/// Encrypts `json` once under a random content key, then seals that key
+/// separately for each of `recipients` with their own password, keyfiles and
+/// a fresh salt, so each can open the vault with `decrypt_multi_recipient`
+/// without knowing the others' passwords. `params`, `cipher`, `label`,
+/// `compression` and `pad_to` behave as in `crypto_encrypt_blob`; `label`
+/// binds the payload and must be passed unchanged to decrypt.
+///
+/// Takes one Argon2 derivation per recipient, at most `MAX_RECIPIENTS`.
+/// Recipient names must be distinct; they are stored in the clear.
+/// Cancellable with `cancel_kdf` given an `operation_id`.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_multi_recipient(
+    json: String,
+    recipients: Vec<RecipientPassword>,
+    params: Option<CryptoParams>,
+    cipher: Option<String>,
+    label: Option<String>,
+    compression: Option<u32>,
+    pad_to: Option<usize>,
+    operation_id: Option<String>,
+) -> Result<MultiRecipientVault, SeqretsError> {
+    let operation = KdfOperation::register(operation_id)?;
+    let json = Zeroizing::new(json);
+    if recipients.is_empty() || recipients.len() > MAX_RECIPIENTS {
+        return Err(SeqretsError::InvalidInput(format!(
+            "A shared vault needs 1 to {MAX_RECIPIENTS} recipients, got {}.",
+            recipients.len()
+        )));
+    }
+    for (i, recipient) in recipients.iter().enumerate() {
+        if recipient.name.chars().count() > MAX_RECIPIENT_NAME_LENGTH
+            || recipient.name.chars().any(char::is_control)
+        {
+            return Err(SeqretsError::InvalidInput(format!(
+                "Recipient {} needs a name of at most {MAX_RECIPIENT_NAME_LENGTH} printable characters.",
+                i + 1
+            )));
+        }
+        if recipients[..i].iter().any(|r| r.name == recipient.name) {
+            return Err(SeqretsError::InvalidInput(format!(
+                "Recipient name '{}' is used twice.",
+                recipient.name
+            )));
+        }
+    }
+    let params = params.unwrap_or_default();
+    let cipher = Cipher::from_name(cipher.as_deref())?;
+    let compressed = Zeroizing::new(compress_and_pad(json.as_bytes(), compression, pad_to)?);
+    drop(json);
+
+    let mut content_key = Zeroizing::new([0u8; KEY_LENGTH]);
+    rand::rng().fill_bytes(content_key.as_mut_slice());
+    let aad = metadata_aad(&[], label.as_deref());
+    let data = encrypt(
+        &compressed,
+        PlaintextKind::Compressed,
+        &content_key,
+        &params,
+        cipher,
+        0,
+        &aad,
+        PayloadEncoding::Base64,
+    )?;
+
+    let wrapped = recipients
+        .iter()
+        .map(|recipient| {
+            let keyfiles_b64 = secret_keyfiles(recipient.keyfiles_b64.clone());
+            let sealed = seal_with_new_salt(
+                content_key.as_slice(),
+                PlaintextKind::Raw,
+                &recipient.password,
+                &keyfiles_b64,
+                &params,
+                cipher,
+                Some(&format!("{RECIPIENT_KEY_LABEL}{}", recipient.name)),
+                operation.token(),
+            )?;
+            Ok(WrappedKey {
+                name: recipient.name.clone(),
+                salt: sealed.salt,
+                data: sealed.data,
+            })
+        })
+        .collect::<Result<Vec<_>, SeqretsError>>()?;
+
+    Ok(MultiRecipientVault {
+        version: MULTI_RECIPIENT_VERSION,
+        data,
+        recipients: wrapped,
+        params_warning: weak_params(&params),
+    })
+}
+
+/// Opens a vault from `encrypt_multi_recipient` with any one recipient's
+/// `password` and `keyfiles_b64`, trying each wrapped key in turn (one
+/// Argon2 derivation apiece until one opens). Fails with `WrongPassword`
+/// when none does. `label` must match the one given at encryption.
+/// Cancellable with `cancel_kdf` given an `operation_id`.
+#[tauri::command(async)]
+pub fn decrypt_multi_recipient(
+    blob: MultiRecipientVault,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    label: Option<String>,
+    operation_id: Option<String>,
+) -> Result<String, SeqretsError> {
+    let operation = KdfOperation::register(operation_id)?;
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    if blob.version != MULTI_RECIPIENT_VERSION {
+        return Err(SeqretsError::CorruptData(format!(
+            "Unsupported shared vault version {}. Please update seQRets.",
+            blob.version
+        )));
+    }
+
+    let mut content_key = None;
+    for recipient in &blob.recipients {
+        let key_label = format!("{RECIPIENT_KEY_LABEL}{}", recipient.name);
+        match decrypt_blob(
+            &recipient.salt,
+            &recipient.data,
+            password.as_str(),
+            &keyfiles_b64,
+            Some(&key_label),
+            operation.token(),
+            &mut Duration::default(),
+        ) {
+            Ok(key) => {
+                content_key = Some(key);
+                break;
+            }
+            // Another recipient's key, or one sealed with a different keyfile count
+            Err(SeqretsError::WrongPassword(_) | SeqretsError::InvalidInput(_)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    drop(password);
+    drop(keyfiles_b64);
+
+    let content_key = content_key.ok_or_else(|| {
+        SeqretsError::WrongPassword(
+            "The password doesn't open any of this vault's recipient keys.".to_string(),
+        )
+    })?;
+    let content_key: Zeroizing<[u8; KEY_LENGTH]> =
+        Zeroizing::new(content_key.as_slice().try_into().map_err(|_| {
+            SeqretsError::CorruptData("Wrapped content key has the wrong length.".to_string())
+        })?);
+    let aad = metadata_aad(&[], label.as_deref());
+    decompress_json(decrypt_with_key(&blob.data, &content_key, &aad)?)
+}
+
+/// Checks that `password` and `keyfiles_b64` derive the key behind
+/// `verifier_b64` (a `CryptoResult::verifier`) for `salt_b64`, e.g. for a
+/// "confirm your password" step or to fail fast on a wrong password before
+/// decrypting a large vault. `params` must be the ones the blob was created
+/// with (the defaults when omitted). The verifiers are compared in constant
+/// time; a malformed salt or verifier is `CorruptData`, a mismatch `false`.
+#[tauri::command]
+pub fn verify_key_material(
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    salt_b64: String,
+    verifier_b64: String,
+    params: Option<CryptoParams>,
+) -> Result<bool, SeqretsError> {
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let salt = encoding::decode(&salt_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Salt base64 decode error: {e}")))?;
+    let expected = encoding::decode(&verifier_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Verifier base64 decode error: {e}")))?;
+    if expected.len() != KEY_LENGTH {
+        return Err(SeqretsError::CorruptData(format!(
+            "Verifier must be {KEY_LENGTH} bytes, got {}.",
+            expected.len()
+        )));
+    }
+
+    let key = derive_key(
+        password.as_str(),
+        &salt,
+        &keyfiles_b64,
+        &params.unwrap_or_default(),
+    )?;
+    Ok(ct_eq(key_verifier(&key).as_slice(), &expected))
+}
+
+/// True when `a` and `b` are the same secret, for confirmation steps such
+/// as re-typing a seed phrase that was written down. Both are moved into
+/// `Zeroizing` buffers on entry and compared through their SHA-256 digests
+/// with `ct_eq`, so neither the position of a difference nor a difference
+/// in length shows in the timing. Everything is zeroized before returning.
+/// The strings are compared exactly; normalize them (case, whitespace)
+/// before calling if that shouldn't matter.
+#[tauri::command]
+pub fn secrets_match(a: String, b: String) -> bool {
+    let (a, b) = (Zeroizing::new(a), Zeroizing::new(b));
+    let digest = |s: &str| Zeroizing::new(<[u8; 32]>::from(Sha256::digest(s.as_bytes())));
+    ct_eq(digest(&a).as_slice(), digest(&b).as_slice())
+}
+
+/// Decrypts a blob written by `crypto_seal` and returns the raw plaintext as
+/// base64, without gunzipping. `label` must match the one given to
+/// `crypto_seal`.
+#[tauri::command]
+pub fn crypto_open(
+    salt_b64: String,
+    data_b64: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    label: Option<String>,
+) -> Result<String, SeqretsError> {
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let salt = STANDARD
+        .decode(&salt_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Salt base64 decode error: {e}")))?;
+
+    let aad = metadata_aad(&salt, label.as_deref());
+    let cancel = CancelToken::default();
+    let plaintext = decrypt(
+        &data_b64,
+        password.as_str(),
+        &salt,
+        &keyfiles_b64,
+        &aad,
+        &cancel,
+        &mut Duration::default(),
+    )?;
+    Ok(STANDARD.encode(plaintext.as_slice()))
+}
+
+/// Stores `data_b64`, a blob that is already protected (typically a vault
+/// encrypted elsewhere), under one XChaCha20-Poly1305 layer for at-rest
+/// protection. Unlike `crypto_encrypt_blob` nothing is compressed, and the
+/// header is flagged opaque so the blob is never mistaken for vault JSON.
+/// `keyfiles_b64`, `params` and `label` behave as in `crypto_seal`.
+/// Cancellable with `cancel_kdf` given an `operation_id`.
+#[tauri::command(async)]
+pub fn store_opaque(
+    data_b64: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    params: Option<CryptoParams>,
+    label: Option<String>,
+    operation_id: Option<String>,
+) -> Result<CryptoResult, SeqretsError> {
+    let operation = KdfOperation::register(operation_id)?;
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let data = STANDARD
+        .decode(data_b64.as_bytes())
+        .map_err(|e| SeqretsError::InvalidInput(format!("Data base64 decode error: {e}")))?;
+    if data.is_empty() {
+        return Err(SeqretsError::InvalidInput(
+            "Nothing to store: the data is empty.".to_string(),
+        ));
+    }
+    let params = params.unwrap_or_default();
+    seal_with_new_salt(
+        &data,
+        PlaintextKind::Opaque,
+        password.as_str(),
+        &keyfiles_b64,
+        &params,
+        Cipher::XChaCha20Poly1305,
+        label.as_deref(),
+        operation.token(),
+    )
+}
+
+/// Opens a blob written by `store_opaque` and returns the stored data as
+/// base64. Blobs not flagged opaque are refused with `InvalidInput`, so a
+/// vault can't be opened through this path by mistake. Cancellable with
+/// `cancel_kdf` given an `operation_id`.
+#[tauri::command(async)]
+pub fn open_opaque(
+    salt_b64: String,
+    data_b64: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    label: Option<String>,
+    operation_id: Option<String>,
+) -> Result<String, SeqretsError> {
+    let operation = KdfOperation::register(operation_id)?;
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let salt = STANDARD
+        .decode(&salt_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Salt base64 decode error: {e}")))?;
+
+    let aad = metadata_aad(&salt, label.as_deref());
+    let plaintext = decrypt(
+        &data_b64,
+        password.as_str(),
+        &salt,
+        &keyfiles_b64,
+        &aad,
+        operation.token(),
+        &mut Duration::default(),
+    )?;
+    if plaintext.kind != Some(PlaintextKind::Opaque) {
+        return Err(SeqretsError::InvalidInput(
+            "This blob wasn't stored with store_opaque.".to_string(),
+        ));
+    }
+    Ok(STANDARD.encode(plaintext.as_slice()))
+}
+
+/// True when the header of `data_b64` flags it opaque (`store_opaque`), so
+/// the UI can tell before unlocking that it won't hold vault JSON. The
+/// header isn't authenticated until the blob is opened.
+#[tauri::command]
+pub fn blob_is_opaque(data_b64: String) -> Result<bool, SeqretsError> {
+    let combined = encoding::decode(&data_b64)
+        .map_err(|e| SeqretsError::CorruptData(format!("Data base64 decode error: {e}")))?;
+    match detect_format(&combined) {
+        BlobFormat::Versioned(header) => Ok(header.kind == PlaintextKind::Opaque),
+        BlobFormat::Legacy => Ok(false),
+        BlobFormat::Malformed(e) => Err(e),
+        BlobFormat::Unsupported { version } => Err(SeqretsError::CorruptData(format!(
+            "Unsupported format version {version}; please update seQRets."
+        ))),
+    }
+}
+
+/// Measures Argon2id on this machine and returns the strongest parameters
+/// whose derivation fits in roughly `target_ms` milliseconds (memory capped at
+/// 1 GiB). Runs on a blocking thread so the IPC loop stays responsive.
+///
+/// The frontend calls this during setup and passes the result as `params`
+/// to the encryption commands, which store it in the blob header.
+#[tauri::command]
+pub async fn benchmark_kdf(target_ms: u32) -> Result<CryptoParams, SeqretsError> {
+    let target = Duration::from_millis(target_ms as u64);
+    tauri::async_runtime::spawn_blocking(move || run_kdf_benchmark(target))
+        .await
+        .map_err(|e| format!("KDF benchmark task failed: {e}"))?
+}
+
+/// Recommends Argon2id parameters for this machine: memory is an eighth of
+/// the RAM currently available (19 MiB to 1 GiB, or 64 MiB when it can't
+/// be read), then iterations are added until a derivation takes roughly
+/// `target_ms`. Runs on a blocking thread like `benchmark_kdf`.
+///
+/// The setup flow passes the result as `params` when creating a vault; the
+/// header records it, so later unlocks reproduce it on any machine.
+#[tauri::command]
+pub async fn recommended_params(target_ms: u32) -> Result<CryptoParams, SeqretsError> {
+    let target = Duration::from_millis(target_ms as u64);
+    tauri::async_runtime::spawn_blocking(move || run_recommended_params(target))
+        .await
+        .map_err(|e| format!("KDF recommendation task failed: {e}"))?
+}
+
+/// Aborts the key derivations of the operation started with `operation_id`
+/// (`crypto_restore`, `crypto_decrypt_blob`, `rekey_vault`, the
+/// multi-recipient and opaque commands), which then fails with `Cancelled`.
+/// Other operations, including ones started without an id, carry on.
+/// Argon2 itself can't be interrupted, so its memory is released once the
+/// abandoned pass finishes in the background. Returns false when no
+/// operation with that id is running.
+#[tauri::command]
+pub fn cancel_kdf(operation_id: String) -> bool {
+    let operations = KDF_OPERATIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    match operations.get(&operation_id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Turns `kdf-progress` events on for every later key derivation, reckoned
+/// against `expected_ms` (the target duration the parameters were
+/// benchmarked for), or off with `None`. Off by default. The percentage is
+/// an estimate from elapsed time, held at 95 until the derivation returns;
+/// it only keeps a progress bar moving.
+#[tauri::command]
+pub fn set_kdf_progress(app: AppHandle, expected_ms: Option<u32>) -> Result<(), SeqretsError> {
+    let setting = match expected_ms {
+        Some(0) => {
+            return Err(SeqretsError::InvalidInput(
+                "The expected duration must be positive.".to_string(),
+            ))
+        }
+        Some(ms) => Some((app, Duration::from_millis(ms as u64))),
+        None => None,
+    };
+    *KDF_PROGRESS.lock().unwrap_or_else(PoisonError::into_inner) = setting;
+    Ok(())
+}
+
+/// Audits a batch of base64 (or base64url) blobs for repeated nonces. Nonces
+/// are random, so any collision points at a broken RNG; for
+/// XChaCha20-Poly1305 a reused nonce under one key leaks the XOR of the
+/// plaintexts. Purely local: nothing is decrypted and no password is needed.
+///
+/// Blobs that can't be parsed are listed in `unreadable` rather than failing
+/// the whole check.
+#[tauri::command]
+pub fn check_nonce_uniqueness(blobs: Vec<String>) -> NonceReport {
+    nonce_report(&blobs)
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Round-trip: encrypt then decrypt must return the original plaintext.
+    #[test]
+    fn test_blob_roundtrip_no_keyfile() {
+        let payload = r#"{"secret":"hello world","label":"test","isMnemonic":false}"#.to_string();
+        let password = "s3cur3P@ssw0rd!".to_string();
+
+        let result = crypto_encrypt_blob(
+            payload.clone(),
+            password.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .expect("encrypt_blob should not fail");
+
+        let decrypted =
+            crypto_decrypt_blob(result.salt, result.data, password, None, None, None, None)
+                .expect("decrypt_blob should not fail")
+                .into_value();
+
+        assert_eq!(decrypted, payload, "decrypted payload must match original");
+    }
+
+    #[test]
+    fn test_blob_roundtrip_with_keyfile() {
+        let payload =
+            r#"{"secret":"seed phrase here","label":"wallet","isMnemonic":true}"#.to_string();
+        let password = "another-password".to_string();
+        // 32 random bytes encoded as base64
+        let keyfile_b64 = Some(vec![STANDARD.encode(b"0123456789abcdef0123456789abcdef")]);
+
+        let result = crypto_encrypt_blob(
+            payload.clone(),
+            password.clone(),
+            keyfile_b64.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .expect("encrypt_blob with keyfile should not fail");
+
+        let decrypted = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            password,
+            keyfile_b64,
+            None,
+            None,
+            None,
+        )
+        .expect("decrypt_blob with keyfile should not fail")
+        .into_value();
+
+        assert_eq!(decrypted, payload);
+    }
+
+    fn two_keyfiles() -> Vec<String> {
+        vec![
+            STANDARD.encode(b"first keyfile, sixteen+ bytes"),
+            STANDARD.encode(b"second keyfile, also 16+ bytes"),
+        ]
+    }
+
+    #[test]
+    fn test_blob_roundtrip_with_multiple_keyfiles() {
+        let payload = r#"{"secret":"two factors","isMnemonic":false}"#.to_string();
+        let result = crypto_encrypt_blob(
+            payload.clone(),
+            "pw".to_string(),
+            Some(two_keyfiles()),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(STANDARD.decode(&result.data).unwrap()[15], 2);
+
+        let decrypted = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            Some(two_keyfiles()),
+            None,
+            None,
+            None,
+        )
+        .expect("decrypt with both keyfiles in order should succeed")
+        .into_value();
+        assert_eq!(decrypted, payload);
+    }
+
+    #[test]
+    fn test_changed_label_with_right_key_is_header_tampered() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..Default::default()
+        };
+        let label = || Some("Vault 1".to_string());
+        let sealed = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            Some(params),
+            None,
+            label(),
+            None,
+            None,
+        )
+        .unwrap();
+        let open = |password: &str, label: Option<String>| {
+            crypto_decrypt_blob(
+                sealed.salt.clone(),
+                sealed.data.clone(),
+                password.to_string(),
+                None,
+                label,
+                None,
+                None,
+            )
+        };
+
+        let err = open("pw", Some("Vault 2".to_string()));
+        assert!(
+            matches!(err, Err(SeqretsError::HeaderTampered(_))),
+            "got: {err:?}"
+        );
+        let err = open("pw", None);
+        assert!(
+            matches!(err, Err(SeqretsError::HeaderTampered(_))),
+            "got: {err:?}"
+        );
+        let err = open("wrong", Some("Vault 2".to_string()));
+        assert!(
+            matches!(err, Err(SeqretsError::WrongPassword(_))),
+            "got: {err:?}"
+        );
+        assert_eq!(open("pw", label()).unwrap().into_value(), "{}");
+
+        // The commitment is authenticated too: flipping it is tampering, and
+        // can't turn a wrong password into HeaderTampered.
+        let mut bytes = STANDARD.decode(&sealed.data).unwrap();
+        bytes[HEADER_LENGTH - 1] ^= 1;
+        let flipped = STANDARD.encode(&bytes);
+        let err = crypto_decrypt_blob(
+            sealed.salt.clone(),
+            flipped,
+            "pw".to_string(),
+            None,
+            label(),
+            None,
+            None,
+        );
+        assert!(
+            matches!(err, Err(SeqretsError::WrongPassword(_))),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_keyfiles_in_wrong_order_fail() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            Some(two_keyfiles()),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut reversed = two_keyfiles();
+        reversed.reverse();
+
+        let err = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            Some(reversed),
+            None,
+            None,
+            None,
+        );
+        assert!(
+            matches!(err, Err(SeqretsError::WrongPassword(_))),
+            "got: {err:?}"
+        );
+    }
+
+    // Length prefixes keep the keyfile boundaries part of the key, so moving
+    // bytes from one keyfile to the next doesn't derive the same key.
+    #[test]
+    fn test_keyfile_boundaries_are_bound() {
+        let salt = [1u8; SALT_LENGTH];
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let split = |a: &[u8], b: &[u8]| {
+            vec![
+                Zeroizing::new(STANDARD.encode(a)),
+                Zeroizing::new(STANDARD.encode(b)),
+            ]
+        };
+        let a = derive_key("pw", &salt, &split(b"abcd", b"efgh"), &params).unwrap();
+        let b = derive_key("pw", &salt, &split(b"abc", b"defgh"), &params).unwrap();
+        assert_ne!(a.as_slice(), b.as_slice());
+    }
+
+    #[test]
+    fn test_keyfile_count_mismatch_rejected() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            Some(two_keyfiles()),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let one = Some(two_keyfiles()[..1].to_vec());
+
+        let err = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            one,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, SeqretsError::InvalidInput(_)), "got: {err:?}");
+        assert!(
+            err.to_string().contains("2 keyfile(s), but 1"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn test_with_timing_reports_kdf_duration() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let timed = crypto_decrypt_blob(
+            result.salt.clone(),
+            result.data.clone(),
+            "pw".to_string(),
+            None,
+            None,
+            Some(true),
+            None,
+        )
+        .unwrap();
+        let json = serde_json::to_value(&timed).unwrap();
+        assert_eq!(json["value"], "{}");
+        assert!(json["kdf_ms"].is_u64(), "got: {json}");
+
+        // Without the flag the command still returns the bare string
+        let plain = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&plain).unwrap(),
+            serde_json::json!("{}")
+        );
+    }
+
+    #[test]
+    fn test_secrets_match() {
+        let phrase = "abandon ability able about above absent";
+        assert!(secrets_match(phrase.into(), phrase.into()));
+        assert!(!secrets_match(
+            phrase.into(),
+            "abandon ability able about above absorb".into()
+        ));
+        assert!(!secrets_match(phrase.into(), format!("{phrase} ")));
+        assert!(secrets_match(String::new(), String::new()));
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq(b"seQRets share", b"seQRets share"));
+        assert!(ct_eq(b"", b""));
+        assert!(!ct_eq(b"seQRets share", b"seQRets shard"));
+        assert!(!ct_eq(b"seQRets", b"seQRets share"));
+    }
+
+    #[test]
+    fn test_subkeys_are_separated_by_context() {
+        let master = [0x42u8; KEY_LENGTH];
+        let a = derive_subkey(&master, VAULT_ENCRYPTION_CONTEXT);
+        assert_eq!(
+            a.as_slice(),
+            derive_subkey(&master, VAULT_ENCRYPTION_CONTEXT).as_slice()
+        );
+        assert_ne!(
+            a.as_slice(),
+            derive_subkey(&master, "card-slot-1").as_slice()
+        );
+        assert_ne!(a.as_slice(), &master[..]);
+    }
+
+    // Headered payloads are sealed under the subkey, never the Argon2 output.
+    #[test]
+    fn test_payload_key_is_not_the_master_key() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let salt = STANDARD.decode(&result.salt).unwrap();
+        let bytes = STANDARD.decode(&result.data).unwrap();
+        let (header, payload) = bytes.split_at(HEADER_LENGTH);
+        let aad = [header, &metadata_aad(&salt, None)].concat();
+
+        let master = derive_key("pw", &salt, &[], &CryptoParams::default()).unwrap();
+        assert!(decrypt_payload(payload, &master, Cipher::XChaCha20Poly1305, &aad).is_err());
+        let subkey = derive_subkey(&master, VAULT_ENCRYPTION_CONTEXT);
+        assert!(decrypt_payload(payload, &subkey, Cipher::XChaCha20Poly1305, &aad).is_ok());
+    }
+
+    #[test]
+    fn test_every_argon2_variant_roundtrips() {
+        let payload = r#"{"secret":"variant","isMnemonic":false}"#.to_string();
+        let salt = [9u8; SALT_LENGTH];
+        let mut keys = Vec::new();
+        for variant in [
+            Argon2Variant::Argon2d,
+            Argon2Variant::Argon2i,
+            Argon2Variant::Argon2id,
+        ] {
+            let params = CryptoParams {
+                m_cost: 8,
+                t_cost: 1,
+                p_cost: 1,
+                argon2_variant: variant,
+            };
+            let result = crypto_encrypt_blob(
+                payload.clone(),
+                "pw".to_string(),
+                None,
+                Some(params),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let bytes = STANDARD.decode(&result.data).unwrap();
+            assert_eq!(bytes[16], variant.id() | HEADER_FLAG_COMPRESSED);
+            assert_eq!(
+                Header::decode(&bytes).unwrap().params.argon2_variant,
+                variant
+            );
+
+            let decrypted = crypto_decrypt_blob(
+                result.salt,
+                result.data,
+                "pw".to_string(),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+            .into_value();
+            assert_eq!(decrypted, payload);
+            keys.push(derive_key("pw", &salt, &[], &params).unwrap());
+        }
+        // The variant is part of the derivation, not just a label.
+        assert_ne!(keys[0].as_slice(), keys[1].as_slice());
+        assert_ne!(keys[1].as_slice(), keys[2].as_slice());
+        assert_ne!(keys[0].as_slice(), keys[2].as_slice());
+    }
+
+    #[test]
+    fn test_unknown_argon2_variant_rejected() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut bytes = STANDARD.decode(&result.data).unwrap();
+        bytes[16] = 7;
+
+        let err = crypto_decrypt_blob(
+            result.salt,
+            STANDARD.encode(bytes),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, SeqretsError::CorruptData(_)), "got: {err:?}");
+        assert!(
+            err.to_string().contains("Unknown Argon2 variant id 7"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn test_params_without_variant_deserialize_as_argon2id() {
+        let params: CryptoParams =
+            serde_json::from_str(r#"{"m_cost":8,"t_cost":1,"p_cost":1}"#).unwrap();
+        assert_eq!(params.argon2_variant, Argon2Variant::Argon2id);
+        let params: CryptoParams = serde_json::from_str(
+            r#"{"m_cost":8,"t_cost":1,"p_cost":1,"argon2_variant":"argon2i"}"#,
+        )
+        .unwrap();
+        assert_eq!(params.argon2_variant, Argon2Variant::Argon2i);
+    }
+
+    #[test]
+    fn test_truncated_blob_is_corrupt_not_wrong_password() {
+        let salt = STANDARD.encode([0u8; SALT_LENGTH]);
+        let short = STANDARD.encode([0u8; NONCE_LENGTH + TAG_LENGTH - 1]);
+        let err = crypto_decrypt_blob(
+            salt.clone(),
+            short,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(
+            matches!(err, Err(SeqretsError::CorruptData(_))),
+            "got: {err:?}"
+        );
+
+        // Long enough to be well-formed, so the failure is authentication.
+        let garbage = STANDARD.encode([0u8; NONCE_LENGTH + TAG_LENGTH]);
+        let err = crypto_decrypt_blob(salt, garbage, "pw".to_string(), None, None, None, None);
+        assert!(
+            matches!(err, Err(SeqretsError::WrongPassword(_))),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_truncated_headered_blob_is_corrupt() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let bytes = STANDARD.decode(&result.data).unwrap();
+        let cut = STANDARD.encode(&bytes[..HEADER_LENGTH + NONCE_LENGTH + TAG_LENGTH - 1]);
+
+        let err = crypto_decrypt_blob(result.salt, cut, "pw".to_string(), None, None, None, None);
+        assert!(
+            matches!(err, Err(SeqretsError::CorruptData(_))),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_wrong_password_fails() {
+        let payload = r#"{"secret":"my secret","isMnemonic":false}"#.to_string();
+        let result = crypto_encrypt_blob(
+            payload,
+            "correct-password".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .expect("encrypt should succeed");
+
+        let err = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "wrong-password".to_string(),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(err.is_err(), "decryption with wrong password must fail");
+        assert!(
+            matches!(err, Err(SeqretsError::WrongPassword(_))),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_create_restore_roundtrip() {
+        let payload =
+            r#"{"secret":"wallet seed","label":"cold storage","isMnemonic":false}"#.to_string();
+        let password = "test-password-123".to_string();
+
+        let created = crypto_create(
+            payload.clone(),
+            password.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .expect("crypto_create should succeed");
+
+        let restored = crypto_restore(created.salt, created.data, password, None, None, None, None)
+            .expect("crypto_restore should succeed")
+            .into_value();
+
+        assert_eq!(restored, payload);
+    }
+
+    // Default Qard shares must open the way packages/crypto and seQRets
+    // Recover open them: the raw Argon2 output as key, the first 24 bytes
+    // as nonce, no associated data, then a plain gunzip (pako's `ungzip`,
+    // which knows no stored flag). The payload is short enough that gzip
+    // doesn't shrink it, as with most single secrets.
+    #[test]
+    fn test_create_writes_js_readable_shares() {
+        let payload = r#"{"secret":"hunter2"}"#.to_string();
+        assert_eq!(
+            gzip_compress(payload.as_bytes(), None).unwrap()[0],
+            STORED_FLAG
+        );
+        let keyfile = Some(vec![STANDARD.encode(b"keyfile bytes")]);
+        let created = crypto_create(
+            payload.clone(),
+            "pw".to_string(),
+            keyfile.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let salt = STANDARD.decode(&created.salt).unwrap();
+        let combined = STANDARD.decode(&created.data).unwrap();
+        let key = derive_key(
+            "pw",
+            &salt,
+            &secret_keyfiles(keyfile),
+            &CryptoParams::default(),
+        )
+        .unwrap();
+        let (nonce, ciphertext) = combined.split_at(NONCE_LENGTH);
+        let gzipped = Cipher::XChaCha20Poly1305
+            .open(&key, nonce, ciphertext, &[])
+            .expect("opens as version 0");
+        let mut ungzipped = Vec::new();
+        GzDecoder::new(gzipped.as_slice())
+            .read_to_end(&mut ungzipped)
+            .expect("a plain gzip stream");
+        assert_eq!(ungzipped, payload.as_bytes());
+
+        let labeled = crypto_create(
+            payload,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            Some("x".to_string()),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            STANDARD.decode(&labeled.data).unwrap()[..2],
+            [HEADER_MAGIC, FORMAT_VERSION]
+        );
+    }
+
+    #[test]
+    fn test_create_base64url_roundtrip() {
+        let payload = r#"{"secret":"qr friendly","isMnemonic":false}"#.to_string();
+        let url = Some("base64url".to_string());
+        let created = crypto_create(
+            payload.clone(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            url,
+            None,
+        )
+        .unwrap();
+        assert!(PayloadEncoding::Base64Url.matches(&created.salt) && !created.salt.ends_with('='));
+        assert!(PayloadEncoding::Base64Url.matches(&created.data));
+
+        let restored = crypto_restore(
+            created.salt,
+            created.data,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .into_value();
+        assert_eq!(restored, payload);
+    }
+
+    #[test]
+    fn test_nonce_report_flags_reuse() {
+        let blob = |cipher: &str| {
+            crypto_encrypt_blob(
+                "{}".to_string(),
+                "pw".to_string(),
+                None,
+                None,
+                Some(cipher.to_string()),
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+            .data
+        };
+        let a = blob("xchacha20-poly1305");
+        let b = blob("xchacha20-poly1305");
+        let gcm = blob("aes-256-gcm");
+
+        let report = check_nonce_uniqueness(vec![
+            a.clone(),
+            b.clone(),
+            gcm,
+            a.clone(),
+            "not base64!".to_string(),
+        ]);
+        assert_eq!(report.checked, 4);
+        assert_eq!(report.unreadable, vec![4]);
+        assert!(report.unique, "a listed twice is not a reuse: {report:?}");
+
+        // Give `b` the nonce of `a`.
+        let mut forged = STANDARD.decode(&b).unwrap();
+        let nonce = HEADER_LENGTH..HEADER_LENGTH + NONCE_LENGTH;
+        forged[nonce.clone()].copy_from_slice(&STANDARD.decode(&a).unwrap()[nonce]);
+        let report = check_nonce_uniqueness(vec![b, a, STANDARD.encode(forged)]);
+        assert!(!report.unique);
+        assert_eq!(report.collisions, vec![vec![1, 2]]);
+    }
+
+    #[test]
+    fn test_headered_blob_format() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let bytes = STANDARD.decode(&result.data).unwrap();
+        assert_eq!(
+            &bytes[..3],
+            &[HEADER_MAGIC, FORMAT_VERSION, ALG_XCHACHA20_POLY1305]
+        );
+    }
+
+    // Version 0 (header-less) blobs, as written by older builds and the JS
+    // implementation, must still decrypt.
+    #[test]
+    fn test_legacy_headerless_blob_decrypts() {
+        let payload = r#"{"secret":"legacy","isMnemonic":false}"#;
+        let password = "legacy-password";
+        let salt = [7u8; SALT_LENGTH];
+
+        let params = CryptoParams::default();
+        let key = derive_key(password, &salt, &[], &params).unwrap();
+        let compressed = gzip_compress(payload.as_bytes(), None).unwrap();
+        let legacy_b64 = encrypt_with_nonce(
+            &compressed,
+            &key,
+            &[9u8; NONCE_LENGTH],
+            PayloadEncoding::Base64,
+        );
+
+        let decrypted = crypto_decrypt_blob(
+            STANDARD.encode(salt),
+            legacy_b64,
+            password.to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .expect("legacy blob should decrypt")
+        .into_value();
+        assert_eq!(decrypted, payload);
+    }
+
+    // Known-answer vector for the header-less format shared with
+    // packages/crypto (`createShares`), with its random salt and nonce fixed.
+    // The gzip stream is pinned as input because pako and flate2 don't emit
+    // identical bytes. A change here means the two implementations no longer
+    // agree on the wire format.
+    #[test]
+    fn test_js_wire_format_known_answer() {
+        let password = "correct horse battery staple";
+        let salt: Vec<u8> = (0..SALT_LENGTH as u8).collect();
+        let nonce: [u8; NONCE_LENGTH] = core::array::from_fn(|i| 0x80 + i as u8);
+        let gzipped = STANDARD
+            .decode("H4sIAAAAAAAC/wE6AMX/eyJzZWNyZXQiOiJrbm93biBhbnN3ZXIiLCJsYWJlbCI6ImthdCIsImlzTW5lbW9uaWMiOmZhbHNlfT7ewZE6AAAA")
+            .unwrap();
+
+        let key = derive_key(password, &salt, &[], &CryptoParams::default()).unwrap();
+        assert_eq!(
+            key.as_slice(),
+            hex_bytes("a54a52ccd93916709c4bea2aa2eadc694de81986bb77038453b6aba55a765d9d")
+                .as_slice()
+        );
+
+        let blob = encrypt_with_nonce(&gzipped, &key, &nonce, PayloadEncoding::Base64);
+        assert_eq!(
+            blob,
+            "gIGCg4SFhoeIiYqLjI2Oj5CRkpOUlZaXPQDfKvjZRzdej/pZuHUZMZG7x2j+2hDLCnE/UiqZErOm9Yyl67Ov8//llQmK0oCtGjRVL2Qv1xYURUyugSngtN9S1AfV7os85b+RqyCKN3YMdBej/H0Ffcl5ymiKaKohmA=="
+        );
+
+        let restored = crypto_decrypt_blob(
+            STANDARD.encode(&salt),
+            blob,
+            password.to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .into_value();
+        assert_eq!(
+            restored,
+            r#"{"secret":"known answer","label":"kat","isMnemonic":false}"#
+        );
+    }
+
+    fn hex_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Non-default parameters are recorded in the header and used on decrypt.
+    #[test]
+    fn test_custom_params_roundtrip() {
+        let payload = r#"{"secret":"custom params","isMnemonic":false}"#.to_string();
+        let params = CryptoParams {
+            m_cost: 8192,
+            t_cost: 2,
+            p_cost: 1,
+            argon2_variant: Argon2Variant::Argon2id,
+        };
+
+        let result = crypto_encrypt_blob(
+            payload.clone(),
+            "pw".to_string(),
+            None,
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let bytes = STANDARD.decode(&result.data).unwrap();
+        assert_eq!(Header::decode(&bytes).unwrap().params, params);
+
+        let decrypted = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .into_value();
+        assert_eq!(decrypted, payload);
+    }
+
+    #[test]
+    fn test_aes_gcm_roundtrip() {
+        let payload = r#"{"secret":"aes","isMnemonic":false}"#.to_string();
+        let result = crypto_encrypt_blob(
+            payload.clone(),
+            "pw".to_string(),
+            None,
+            None,
+            Some("aes-256-gcm".to_string()),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let bytes = STANDARD.decode(&result.data).unwrap();
+        assert_eq!(bytes[2], ALG_AES_256_GCM);
+
+        let decrypted = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .into_value();
+        assert_eq!(decrypted, payload);
+    }
+
+    #[test]
+    fn test_xchacha_roundtrip_explicit() {
+        let payload = r#"{"secret":"xchacha","isMnemonic":false}"#.to_string();
+        let result = crypto_encrypt_blob(
+            payload.clone(),
+            "pw".to_string(),
+            None,
+            None,
+            Some("xchacha20-poly1305".to_string()),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            STANDARD.decode(&result.data).unwrap()[2],
+            ALG_XCHACHA20_POLY1305
+        );
+
+        let decrypted = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .into_value();
+        assert_eq!(decrypted, payload);
+    }
+
+    #[test]
+    fn test_memory_cost_scales_with_available_ram() {
+        let meminfo = "MemTotal:       16318412 kB\nMemFree:         1203400 kB\nMemAvailable:    8159206 kB\n";
+        assert_eq!(parse_mem_available(meminfo), Some(8_159_206 * 1024));
+        assert_eq!(parse_mem_available("MemTotal: 1 kB\n"), None);
+
+        const GIB: u64 = 1024 * 1024 * 1024;
+        assert_eq!(memory_cost_for(Some(2 * GIB)), 256 * 1024);
+        assert_eq!(memory_cost_for(Some(64 * GIB)), BENCH_MAX_M_COST);
+        assert_eq!(memory_cost_for(Some(64 * 1024 * 1024)), BENCH_MIN_M_COST);
+        assert_eq!(memory_cost_for(Some(1_000_000_000)), 119 * 1024);
+        assert_eq!(memory_cost_for(None), ARGON2_M_COST);
+
+        assert_eq!(
+            iterations_for(Duration::from_millis(1000), Duration::from_millis(300)),
+            3
+        );
+        assert_eq!(
+            iterations_for(Duration::from_millis(100), Duration::from_millis(300)),
+            1
+        );
+        assert_eq!(
+            iterations_for(Duration::from_secs(60), Duration::from_millis(10)),
+            BENCH_MAX_T_COST
+        );
+    }
+
+    #[test]
+    fn test_card_wrapped_share_needs_passphrase_and_card() {
+        let wrapped = wrap_card_share("share-text", "correct horse", b"card-a").unwrap();
+        assert_eq!(
+            *unwrap_card_share(&wrapped, "correct horse", b"card-a").unwrap(),
+            "share-text"
+        );
+
+        for (passphrase, card) in [("wrong", &b"card-a"[..]), ("correct horse", &b"card-b"[..])] {
+            let err = unwrap_card_share(&wrapped, passphrase, card);
+            assert!(
+                matches!(err, Err(SeqretsError::WrongPassword(_))),
+                "{passphrase}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_key_material() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let keyfile = STANDARD.encode(b"keyfile bytes");
+        let result = crypto_encrypt_blob(
+            "{}".into(),
+            "right".into(),
+            Some(vec![keyfile.clone()]),
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let verifier = result.verifier.clone().unwrap();
+        let check = |password: &str, keyfiles: Option<Vec<String>>| {
+            verify_key_material(
+                password.into(),
+                keyfiles,
+                result.salt.clone(),
+                verifier.clone(),
+                Some(params),
+            )
+        };
+
+        assert!(check("right", Some(vec![keyfile.clone()])).unwrap());
+        assert!(!check("wrong", Some(vec![keyfile])).unwrap());
+        assert!(!check("right", None).unwrap());
+        assert!(matches!(
+            verify_key_material(
+                "right".into(),
+                None,
+                result.salt.clone(),
+                "AAAA".into(),
+                Some(params)
+            ),
+            Err(SeqretsError::CorruptData(_))
+        ));
+    }
+
+    #[test]
+    fn test_weak_params_warn_without_failing() {
+        assert_eq!(weak_params(&CryptoParams::default()), None);
+        assert_eq!(weak_params(&MIN_SECURE_PARAMS), None);
+        let more_memory = CryptoParams {
+            m_cost: 38 * 1024,
+            t_cost: 1,
+            ..MIN_SECURE_PARAMS
+        };
+        assert_eq!(weak_params(&more_memory), None);
+        let one_pass = CryptoParams {
+            t_cost: 1,
+            ..MIN_SECURE_PARAMS
+        };
+        assert_eq!(weak_params(&one_pass).unwrap().minimum, MIN_SECURE_PARAMS);
+
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let result = crypto_encrypt_blob(
+            "{}".into(),
+            "pw".into(),
+            None,
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let warning = result.params_warning.unwrap();
+        assert_eq!(warning.params, params);
+        assert!(
+            warning.message.contains("(8 KiB, 1 pass)"),
+            "{}",
+            warning.message
+        );
+    }
+
+    #[test]
+    fn test_multi_recipient_opens_for_each_password() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let recipient = |name: &str, password: &str| RecipientPassword {
+            name: name.into(),
+            password: password.into(),
+            keyfiles_b64: None,
+        };
+        let payload = r#"{"secret":"shared with family"}"#;
+        let vault = encrypt_multi_recipient(
+            payload.into(),
+            vec![recipient("Alice", "alice-pw"), recipient("Bob", "bob-pw")],
+            Some(params),
+            None,
+            Some("family".into()),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(vault.recipients.len(), 2);
+        assert_ne!(vault.recipients[0].salt, vault.recipients[1].salt);
+
+        let open = |vault: &MultiRecipientVault, password: &str, label: &str| {
+            decrypt_multi_recipient(
+                vault.clone(),
+                password.into(),
+                None,
+                Some(label.into()),
+                None,
+            )
+        };
+        assert_eq!(open(&vault, "alice-pw", "family").unwrap(), payload);
+        assert_eq!(open(&vault, "bob-pw", "family").unwrap(), payload);
+        assert!(matches!(
+            open(&vault, "eve-pw", "family"),
+            Err(SeqretsError::WrongPassword(_))
+        ));
+        assert!(open(&vault, "bob-pw", "other").is_err());
+
+        // Renaming a recipient breaks its wrapped key.
+        let mut renamed = vault.clone();
+        renamed.recipients[1].name = "Mallory".into();
+        assert!(matches!(
+            open(&renamed, "bob-pw", "family"),
+            Err(SeqretsError::HeaderTampered(_))
+        ));
+
+        let duplicate = encrypt_multi_recipient(
+            payload.into(),
+            vec![recipient("Alice", "a"), recipient("Alice", "b")],
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(matches!(duplicate, Err(SeqretsError::InvalidInput(_))));
+        assert!(encrypt_multi_recipient(
+            payload.into(),
+            vec![],
+            None,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_rekey_vault_changes_key_material() {
+        let old_params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let new_params = CryptoParams {
+            m_cost: 16,
+            t_cost: 2,
+            ..old_params
+        };
+        let keyfile = STANDARD.encode(b"new keyfile");
+        let payload = r#"{"secret":"rotate me"}"#;
+        let old = crypto_encrypt_blob(
+            payload.into(),
+            "old".into(),
+            None,
+            Some(old_params),
+            None,
+            Some("v".into()),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let rekey = |password: &str| {
+            rekey_vault(
+                old.salt.clone(),
+                old.data.clone(),
+                password.into(),
+                None,
+                "new".into(),
+                Some(vec![keyfile.clone()]),
+                Some(new_params),
+                Some("aes-256-gcm".into()),
+                Some("v".into()),
+                None,
+            )
+        };
+        assert!(matches!(
+            rekey("wrong"),
+            Err(SeqretsError::WrongPassword(_))
+        ));
+        let new = rekey("old").unwrap();
+        assert_ne!(new.salt, old.salt);
+
+        let combined = STANDARD.decode(&new.data).unwrap();
+        let header = Header::decode(&combined).unwrap();
+        assert_eq!(header.params, new_params);
+        assert_eq!(header.algorithm, Cipher::Aes256Gcm.id());
+        assert!(crypto_decrypt_blob(
+            new.salt.clone(),
+            new.data.clone(),
+            "old".into(),
+            None,
+            Some("v".into()),
+            None,
+            None
+        )
+        .is_err());
+        let opened = crypto_decrypt_blob(
+            new.salt,
+            new.data,
+            "new".into(),
+            Some(vec![keyfile.clone()]),
+            Some("v".into()),
+            None,
+            None,
+        )
+        .unwrap()
+        .into_value();
+        assert_eq!(opened, payload);
+    }
+
+    #[test]
+    fn test_oversized_header_params_are_refused() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let result = crypto_encrypt_blob(
+            "{}".into(),
+            "pw".into(),
+            None,
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut combined = STANDARD.decode(&result.data).unwrap();
+        combined[3..7].copy_from_slice(&u32::MAX.to_be_bytes());
+        let tampered = STANDARD.encode(&combined);
+        assert!(matches!(
+            crypto_decrypt_blob(result.salt, tampered, "pw".into(), None, None, None, None),
+            Err(SeqretsError::ParamsTooLarge(_))
+        ));
+
+        let huge = CryptoParams {
+            t_cost: MAX_T_COST + 1,
+            ..params
+        };
+        assert!(matches!(
+            derive_key("pw", &[0u8; SALT_LENGTH], &[], &huge),
+            Err(SeqretsError::ParamsTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_derivation_is_cancellable() {
+        let operation = KdfOperation::register(Some("test-derivation-cancel".to_string())).unwrap();
+        assert!(matches!(
+            KdfOperation::register(Some("test-derivation-cancel".to_string())),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        let bystander = KdfOperation::register(None).unwrap();
+        let started = Instant::now();
+        let slow = CryptoParams {
+            t_cost: 64,
+            ..MIN_SECURE_PARAMS
+        };
+        let result = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                assert!(cancel_kdf("test-derivation-cancel".to_string()));
+            });
+            derive_key_cancellable("pw", &[0u8; SALT_LENGTH], &[], &slow, operation.token())
+        });
+        assert!(matches!(result, Err(SeqretsError::Cancelled(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Only that operation's token was cancelled.
+        let fast = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        assert_eq!(
+            derive_key_cancellable("pw", &[0u8; SALT_LENGTH], &[], &fast, bystander.token())
+                .unwrap(),
+            derive_key("pw", &[0u8; SALT_LENGTH], &[], &fast).unwrap()
+        );
+        drop(operation);
+        assert!(!cancel_kdf("test-derivation-cancel".to_string()));
+    }
+
+    // Any header failure but cancellation is retried as version 0, since the
+    // "header" may be the start of a legacy nonce; the retry only derives
+    // with the default params, and the header error is what gets reported.
+    #[test]
+    fn test_header_errors_fall_back_to_the_legacy_retry() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let result = crypto_encrypt_blob(
+            "{}".into(),
+            "pw".into(),
+            None,
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let salt = STANDARD.decode(&result.salt).unwrap();
+        let aad = metadata_aad(&salt, None);
+        let attempt =
+            |data: &str, password: &str, keyfiles: &[Zeroizing<String>], cancel: &CancelToken| {
+                let mut keys = KeyCache::new(password, &salt, keyfiles, cancel);
+                let outcome = decrypt_with_keys(data, &mut keys, &aad, &mut Duration::default());
+                (
+                    outcome,
+                    keys.keys
+                        .iter()
+                        .map(|(params, _)| *params)
+                        .collect::<Vec<_>>(),
+                )
+            };
+
+        let cancelled = CancelToken::default();
+        cancelled.cancel();
+        let (outcome, derived) = attempt(&result.data, "pw", &[], &cancelled);
+        assert!(
+            matches!(outcome, Err(SeqretsError::Cancelled(_))),
+            "got: {outcome:?}"
+        );
+        assert!(derived.is_empty());
+
+        let keyfile = secret_keyfiles(Some(vec![STANDARD.encode(b"unexpected")]));
+        let (outcome, derived) = attempt(&result.data, "pw", &keyfile, &CancelToken::default());
+        assert!(
+            matches!(outcome, Err(SeqretsError::InvalidInput(_))),
+            "got: {outcome:?}"
+        );
+        assert_eq!(derived, [CryptoParams::default()]);
+
+        let mut bytes = STANDARD.decode(&result.data).unwrap();
+        bytes[3..7].copy_from_slice(&u32::MAX.to_be_bytes());
+        let (outcome, derived) =
+            attempt(&STANDARD.encode(&bytes), "pw", &[], &CancelToken::default());
+        assert!(
+            matches!(outcome, Err(SeqretsError::ParamsTooLarge(_))),
+            "got: {outcome:?}"
+        );
+        assert_eq!(derived, [CryptoParams::default()]);
+
+        let mut bytes = STANDARD.decode(&result.data).unwrap();
+        bytes[1] = FORMAT_VERSION + 1;
+        let (outcome, derived) =
+            attempt(&STANDARD.encode(&bytes), "pw", &[], &CancelToken::default());
+        assert!(
+            matches!(outcome, Err(SeqretsError::CorruptData(_))),
+            "got: {outcome:?}"
+        );
+        assert_eq!(derived, [CryptoParams::default()]);
+
+        let (outcome, derived) = attempt(&result.data, "wrong", &[], &CancelToken::default());
+        assert!(
+            matches!(outcome, Err(SeqretsError::WrongPassword(_))),
+            "got: {outcome:?}"
+        );
+        assert_eq!(derived, [params, CryptoParams::default()]);
+    }
+
+    // A legacy nonce that happens to start with the magic byte and read as
+    // an unsupported version, or as a header with absurd costs, is still a
+    // legacy blob and must decrypt.
+    #[test]
+    fn test_legacy_nonce_that_looks_like_a_header_decrypts() {
+        let payload = r#"{"secret":"a legacy share whose nonce starts with the magic byte","isMnemonic":false}"#;
+        let salt = [6u8; SALT_LENGTH];
+        let key = derive_key("pw", &salt, &[], &CryptoParams::default()).unwrap();
+        let gzipped = gzip(payload.as_bytes(), None).unwrap();
+
+        let mut unsupported = [0x11u8; NONCE_LENGTH];
+        unsupported[..2].copy_from_slice(&[HEADER_MAGIC, 0x02]);
+        let mut too_large = [0u8; NONCE_LENGTH];
+        too_large[..3].copy_from_slice(&[HEADER_MAGIC, FORMAT_VERSION, ALG_XCHACHA20_POLY1305]);
+        too_large[3..7].copy_from_slice(&u32::MAX.to_be_bytes());
+        too_large[16] = Argon2Variant::Argon2id.id() | HEADER_FLAG_COMPRESSED;
+
+        for nonce in [unsupported, too_large] {
+            let blob = encrypt_with_nonce(&gzipped, &key, &nonce, PayloadEncoding::Base64);
+            let decrypted = crypto_decrypt_blob(
+                STANDARD.encode(salt),
+                blob,
+                "pw".to_string(),
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("legacy blob should decrypt")
+            .into_value();
+            assert_eq!(decrypted, payload);
+        }
+        let blob = STANDARD
+            .decode(encrypt_with_nonce(
+                &gzipped,
+                &key,
+                &too_large,
+                PayloadEncoding::Base64,
+            ))
+            .unwrap();
+        assert!(
+            matches!(detect_format(&blob), BlobFormat::Versioned(_)),
+            "nonce should parse as a header"
+        );
+    }
+
+    #[test]
+    fn test_decrypt_batch_derives_once_and_reports_per_item() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            argon2_variant: Argon2Variant::Argon2id,
+        };
+        let salt = [9u8; SALT_LENGTH];
+        let key = derive_key("pw", &salt, &[], &params).unwrap();
+        let aad = metadata_aad(&salt, None);
+        let seal = |json: &str| {
+            let compressed = gzip_compress(json.as_bytes(), None).unwrap();
+            encrypt(
+                &compressed,
+                PlaintextKind::Compressed,
+                &key,
+                &params,
+                Cipher::XChaCha20Poly1305,
+                0,
+                &aad,
+                PayloadEncoding::Base64,
+            )
+            .unwrap()
+        };
+        let items = vec![seal(r#"{"a":1}"#), "!!".to_string(), seal(r#"{"b":2}"#)];
+
+        let cancel = CancelToken::default();
+        let mut keys = KeyCache::new("pw", &salt, &[], &cancel);
+        let mut kdf_time = Duration::ZERO;
+        decrypt_with_keys(&items[0], &mut keys, &aad, &mut kdf_time).unwrap();
+        decrypt_with_keys(&items[2], &mut keys, &aad, &mut kdf_time).unwrap();
+        assert_eq!(keys.keys.len(), 1);
+
+        let results =
+            crypto_decrypt_batch(STANDARD.encode(salt), items, "pw".to_string(), None, None)
+                .unwrap();
+        assert_eq!(results[0].as_deref(), Ok(r#"{"a":1}"#));
+        assert!(matches!(results[1], Err(SeqretsError::CorruptData(_))));
+        assert_eq!(results[2].as_deref(), Ok(r#"{"b":2}"#));
+    }
+
+    // Rewriting the cipher id must not let a blob decrypt under the other cipher.
+    #[test]
+    fn test_cipher_id_swap_fails() {
+        for (from, to) in [
+            ("xchacha20-poly1305", ALG_AES_256_GCM),
+            ("aes-256-gcm", ALG_XCHACHA20_POLY1305),
+        ] {
+            // Long enough that either nonce length leaves a tag to check.
+            let result = crypto_encrypt_blob(
+                r#"{"secret":"swap"}"#.to_string(),
+                "pw".to_string(),
+                None,
+                None,
+                Some(from.to_string()),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let mut bytes = STANDARD.decode(&result.data).unwrap();
+            bytes[2] = to;
+
+            let err = crypto_decrypt_blob(
+                result.salt,
+                STANDARD.encode(bytes),
+                "pw".to_string(),
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(
+                matches!(err, Err(SeqretsError::HeaderTampered(_))),
+                "{from} blob with cipher id {to} must fail authentication, got: {:?}",
+                err.map(MaybeTimed::into_value)
+            );
+        }
+    }
+
+    // Every header field is associated data: a rewrite that still parses
+    // fails authentication instead of decrypting under the new settings.
+    // Rewrites that leave the key alone are caught by the key commitment as
+    // tampering; the rest derive a different key.
+    #[test]
+    fn test_header_field_rewrites_fail_authentication() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            argon2_variant: Argon2Variant::Argon2id,
+        };
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let original = STANDARD.decode(&result.data).unwrap();
+
+        let rewrites: [(&str, usize, u8); 5] = [
+            ("cipher id", 2, ALG_AES_256_GCM),
+            ("m_cost", 6, 16),
+            ("t_cost", 10, 2),
+            (
+                "argon2 variant",
+                16,
+                Argon2Variant::Argon2i.id() | HEADER_FLAG_COMPRESSED,
+            ),
+            ("compressed flag", 16, Argon2Variant::Argon2id.id()),
+        ];
+        for (field, index, value) in rewrites {
+            let mut bytes = original.clone();
+            assert_ne!(bytes[index], value, "{field} rewrite is a no-op");
+            bytes[index] = value;
+            let err = crypto_decrypt_blob(
+                result.salt.clone(),
+                STANDARD.encode(bytes),
+                "pw".to_string(),
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(
+                matches!(
+                    err,
+                    Err(SeqretsError::WrongPassword(_) | SeqretsError::HeaderTampered(_))
+                ),
+                "rewriting {field} must fail authentication, got: {:?}",
+                err.map(MaybeTimed::into_value)
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_cipher_name_rejected() {
+        let err = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            Some("rot13".to_string()),
+            None,
+            None,
+            None,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, SeqretsError::InvalidInput(_)), "got: {err:?}");
+        assert!(err.to_string().contains("Unknown cipher"), "got: {err}");
+    }
+
+    // The label is authenticated: flipping one byte must make decryption fail.
+    #[test]
+    fn test_tampered_label_fails() {
+        let label = "cold storage".to_string();
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            Some(label.clone()),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut tampered = label.into_bytes();
+        tampered[0] ^= 0x01;
+        let tampered = String::from_utf8(tampered).unwrap();
+
+        let err = crypto_decrypt_blob(
+            result.salt.clone(),
+            result.data.clone(),
+            "pw".to_string(),
+            None,
+            Some(tampered),
+            None,
+            None,
+        );
+        assert!(err.is_err(), "decryption with a tampered label must fail");
+
+        let label = Some("cold storage".to_string());
+        let ok = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            None,
+            label,
+            None,
+            None,
+        );
+        assert_eq!(ok.unwrap().into_value(), "{}");
+    }
+
+    #[test]
+    fn test_swapped_salt_fails() {
+        let r1 = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let r2 = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let err = crypto_decrypt_blob(r2.salt, r1.data, "pw".to_string(), None, None, None, None);
+        assert!(
+            err.is_err(),
+            "ciphertext re-paired with another salt must fail"
+        );
+    }
+
+    #[test]
+    fn test_benchmark_respects_floor() {
+        // A zero target can't fit anything, so the floor is returned.
+        let params = run_kdf_benchmark(Duration::ZERO).unwrap();
+        assert_eq!(params.m_cost, BENCH_MIN_M_COST);
+        assert_eq!(params.t_cost, 1);
+    }
+
+    #[test]
+    fn test_unknown_version_rejected() {
+        let result = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut bytes = STANDARD.decode(&result.data).unwrap();
+        bytes[1] = FORMAT_VERSION + 1;
+
+        let err = crypto_decrypt_blob(
+            result.salt,
+            STANDARD.encode(bytes),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, SeqretsError::CorruptData(_)), "got: {err:?}");
+        assert!(
+            err.to_string()
+                .contains("Unsupported encrypted data format version"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn test_different_encryptions_produce_different_ciphertext() {
+        // Same plaintext + password should produce different (salt, data) each time
+        // due to random salt and nonce.
+        let payload = r#"{"secret":"test","isMnemonic":false}"#.to_string();
+        let password = "pw".to_string();
+
+        let r1 = crypto_encrypt_blob(
+            payload.clone(),
+            password.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let r2 =
+            crypto_encrypt_blob(payload, password, None, None, None, None, None, None).unwrap();
+
+        // Different salts means different keys means different ciphertext
+        assert_ne!(r1.salt, r2.salt);
+        assert_ne!(r1.data, r2.data);
+    }
+
+    #[test]
+    fn test_incompressible_data_is_stored() {
+        let mut random = vec![0u8; 4096];
+        rand::rng().fill_bytes(&mut random);
+        let packed = gzip_compress(&random, None).unwrap();
+        assert_eq!(packed.len(), random.len() + 1);
+        assert_eq!(packed[0], STORED_FLAG);
+        assert_eq!(gzip_decompress(&packed).unwrap(), random);
+
+        let empty = gzip_compress(&[], None).unwrap();
+        assert_eq!(empty, vec![STORED_FLAG]);
+        assert!(gzip_decompress(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compressible_data_is_gzipped() {
+        let text = "seed ".repeat(200);
+        let packed = gzip_compress(text.as_bytes(), None).unwrap();
+        assert_eq!(&packed[..2], &[0x1f, 0x8b]);
+        assert!(packed.len() < text.len());
+        assert_eq!(gzip_decompress(&packed).unwrap(), text.as_bytes());
+    }
+
+    #[test]
+    fn test_stored_payload_roundtrip_through_blob() {
+        let mut random = [0u8; 64];
+        rand::rng().fill_bytes(&mut random);
+        let json = STANDARD.encode(random);
+        let result = crypto_encrypt_blob(
+            json.clone(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let decrypted = crypto_decrypt_blob(
+            result.salt,
+            result.data,
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .into_value();
+        assert_eq!(decrypted, json);
+    }
+
+    #[test]
+    fn test_header_records_whether_the_payload_is_compressed() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let flag = |data: &str| STANDARD.decode(data).unwrap()[16] & HEADER_FLAG_COMPRESSED != 0;
+        let json = r#"{"secret":"flagged"}"#;
+
+        // Gzip and stored payloads are both marked compressed and round-trip.
+        for level in [None, Some(0)] {
+            let blob = crypto_encrypt_blob(
+                json.into(),
+                "pw".into(),
+                None,
+                Some(params),
+                None,
+                None,
+                level,
+                None,
+            )
+            .unwrap();
+            assert!(flag(&blob.data));
+            let opened =
+                crypto_decrypt_blob(blob.salt, blob.data, "pw".into(), None, None, None, None)
+                    .unwrap();
+            assert_eq!(opened.into_value(), json);
+        }
+
+        // Raw UTF-8 from crypto_seal is returned as is, never gunzipped.
+        let raw = crypto_seal(
+            STANDARD.encode(json),
+            "pw".into(),
+            None,
+            Some(params),
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(!flag(&raw.data));
+        let opened =
+            crypto_decrypt_blob(raw.salt, raw.data, "pw".into(), None, None, None, None).unwrap();
+        assert_eq!(opened.into_value(), json);
+    }
+
+    #[test]
+    fn test_opaque_blob_is_stored_uncompressed_and_flagged() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let inner = crypto_encrypt_blob(
+            "{}".into(),
+            "inner".into(),
+            None,
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let stored = store_opaque(
+            STANDARD.encode(&inner.data),
+            "outer".into(),
+            None,
+            Some(params),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let bytes = STANDARD.decode(&stored.data).unwrap();
+        assert_eq!(bytes[16], Argon2Variant::Argon2id.id() | HEADER_FLAG_OPAQUE);
+        assert_eq!(
+            bytes.len(),
+            HEADER_LENGTH + NONCE_LENGTH + inner.data.len() + TAG_LENGTH
+        );
+        assert!(blob_is_opaque(stored.data.clone()).unwrap());
+        assert!(!blob_is_opaque(inner.data.clone()).unwrap());
+
+        let opened = open_opaque(
+            stored.salt.clone(),
+            stored.data.clone(),
+            "outer".into(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(STANDARD.decode(opened).unwrap(), inner.data.as_bytes());
+        let err = crypto_decrypt_blob(
+            stored.salt,
+            stored.data,
+            "outer".into(),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(
+            matches!(err, Err(SeqretsError::InvalidInput(_))),
+            "got: {:?}",
+            err.map(MaybeTimed::into_value)
+        );
+        let err = open_opaque(inner.salt, inner.data, "inner".into(), None, None, None);
+        assert!(
+            matches!(err, Err(SeqretsError::InvalidInput(_))),
+            "got: {err:?}"
+        );
+
+        let mut both = bytes.clone();
+        both[16] |= HEADER_FLAG_COMPRESSED;
+        assert!(matches!(
+            Header::decode(&both),
+            Err(SeqretsError::CorruptData(_))
+        ));
+    }
+
+    #[test]
+    fn test_kdf_progress_estimate_is_capped() {
+        let expected = Duration::from_secs(4);
+        assert_eq!(estimate_progress(Duration::ZERO, expected), 0.0);
+        assert_eq!(estimate_progress(Duration::from_secs(1), expected), 25.0);
+        assert_eq!(
+            estimate_progress(Duration::from_secs(30), expected),
+            KDF_PROGRESS_CAP
+        );
+        assert!(estimate_progress(Duration::from_millis(1), Duration::ZERO) <= KDF_PROGRESS_CAP);
+    }
+
+    #[test]
+    fn test_compression_level_zero_roundtrip() {
+        let payload =
+            r#"{"secret":"quick seed phrase","label":"fast","isMnemonic":false}"#.to_string();
+        let password = "pw".to_string();
+        let created = crypto_create(
+            payload.clone(),
+            password.clone(),
+            None,
+            None,
+            None,
+            None,
+            Some(0),
+            None,
+            None,
+        )
+        .unwrap();
+        let restored = crypto_restore(created.salt, created.data, password, None, None, None, None)
+            .unwrap()
+            .into_value();
+        assert_eq!(restored, payload);
+
+        assert_eq!(
+            gzip_compress(payload.as_bytes(), Some(0)).unwrap()[0],
+            STORED_FLAG
+        );
+    }
+
+    #[test]
+    fn test_padding_hides_payload_size() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let seal = |json: &str, pad_to: Option<usize>| {
+            crypto_encrypt_blob(
+                json.into(),
+                "pw".into(),
+                None,
+                Some(params),
+                None,
+                None,
+                None,
+                pad_to,
+            )
+            .unwrap()
+        };
+        let short = r#"{"secret":"abandon ability able"}"#;
+        let long = format!(r#"{{"secret":"{}"}}"#, "x".repeat(300));
+
+        let (a, b) = (seal(short, Some(512)), seal(&long, Some(512)));
+        assert_eq!(a.data.len(), b.data.len());
+        assert_ne!(seal(short, None).data.len(), a.data.len());
+        for (result, json) in [(a, short), (b, long.as_str())] {
+            let opened = crypto_decrypt_blob(
+                result.salt,
+                result.data,
+                "pw".into(),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(opened.into_value(), json);
+        }
+
+        // Larger payloads round up to the next multiple
+        let padded = pad_plaintext(&[7u8; 40], 32).unwrap();
+        assert_eq!(padded.len(), 64);
+        assert_eq!(
+            gzip_decompress(&pad_plaintext(&stored(b"hi"), 16).unwrap()).unwrap(),
+            b"hi"
+        );
+        let mut bad_fill = padded.clone();
+        *bad_fill.last_mut().unwrap() = 1;
+        assert!(matches!(
+            unpad_plaintext(&bad_fill),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        assert!(matches!(
+            unpad_plaintext(&padded[..30]),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        assert!(matches!(
+            pad_plaintext(b"", 8),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_compression_levels() {
+        let text = "seed ".repeat(200);
+        for level in 1..=9 {
+            let packed = gzip_compress(text.as_bytes(), Some(level)).unwrap();
+            assert_eq!(&packed[..2], &[0x1f, 0x8b]);
+            assert_eq!(gzip_decompress(&packed).unwrap(), text.as_bytes());
+        }
+        let err = crypto_encrypt_blob(
+            "{}".to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            Some(10),
+            None,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err,
+            SeqretsError::InvalidInput(
+                "Compression level must be between 0 and 9, got 10.".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip_raw_bytes() {
+        let raw: Vec<u8> = (0..=255u8).collect();
+        let sealed = crypto_seal(
+            STANDARD.encode(&raw),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // The raw path must not gzip: the decrypted blob is the input itself.
+        let salt = STANDARD.decode(&sealed.salt).unwrap();
+        let aad = metadata_aad(&salt, None);
+        let inner = decrypt(
+            &sealed.data,
+            "pw",
+            &salt,
+            &[],
+            &aad,
+            &CancelToken::default(),
+            &mut Duration::default(),
+        )
+        .unwrap();
+        assert_eq!(inner.as_slice(), raw.as_slice());
+
+        let opened = crypto_open(sealed.salt, sealed.data, "pw".to_string(), None, None).unwrap();
+        assert_eq!(STANDARD.decode(opened).unwrap(), raw);
+    }
+
+    #[test]
+    fn test_open_wrong_password_fails() {
+        let sealed = crypto_seal(
+            STANDARD.encode(b"share"),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let err = crypto_open(sealed.salt, sealed.data, "nope".to_string(), None, None);
+        assert!(
+            matches!(err, Err(SeqretsError::WrongPassword(_))),
+            "got: {err:?}"
+        );
     }
 }
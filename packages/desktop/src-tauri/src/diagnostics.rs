@@ -0,0 +1,169 @@
+//! Offline self-test for seQRets desktop.
+//!
+//! `run_diagnostics` exercises the encryption path with a throwaway password,
+//! checks the PC/SC service and lists readers, and reports the app, Tauri and
+//! wire-format versions. The report holds nothing secret and no user data, so
+//! it can be pasted into a bug report as-is.
+
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::crypto::{self, Argon2Variant, CryptoParams};
+use crate::error::SeqretsError;
+use crate::smartcard::{self, PcscStatus};
+use crate::stream::STREAM_VERSION;
+use crate::vault::CURRENT_SCHEMA_VERSION;
+
+/// Argon2 cost for the self-test: the benchmark floor, so the round trip
+/// takes well under a second and still runs the real KDF.
+const SELF_TEST_PARAMS: CryptoParams = CryptoParams {
+    m_cost: 19 * 1024,
+    t_cost: 1,
+    p_cost: 1,
+    argon2_variant: Argon2Variant::Argon2id,
+};
+
+const SELF_TEST_PASSWORD: &str = "seQRets-diagnostics";
+const SELF_TEST_PAYLOAD: &str = r#"{"diagnostics":true}"#;
+
+/// Outcome of one self-test step.
+#[derive(Serialize, Debug)]
+pub struct DiagnosticCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub duration_ms: u64,
+    pub error: Option<SeqretsError>,
+}
+
+/// Returned by `run_diagnostics`.
+#[derive(Serialize, Debug)]
+pub struct DiagnosticsReport {
+    pub app_version: &'static str,
+    pub tauri_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub crypto_format_version: u8,
+    pub stream_format_version: u8,
+    pub payload_schema_version: u32,
+    pub checks: Vec<DiagnosticCheck>,
+    /// PC/SC service state, including the reader names when it is ready.
+    pub pcsc: PcscStatus,
+}
+
+fn timed_check(
+    name: &'static str,
+    check: impl FnOnce() -> Result<(), SeqretsError>,
+) -> DiagnosticCheck {
+    let start = Instant::now();
+    let result = check();
+    DiagnosticCheck {
+        name,
+        passed: result.is_ok(),
+        duration_ms: start.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+/// Encrypt then decrypt a fixed payload with each supported cipher.
+fn crypto_round_trip() -> Result<(), SeqretsError> {
+    for cipher in crypto::crypto_capabilities().ciphers {
+        let sealed = crypto::crypto_encrypt_blob(
+            SELF_TEST_PAYLOAD.to_string(),
+            SELF_TEST_PASSWORD.to_string(),
+            None,
+            Some(SELF_TEST_PARAMS),
+            Some(cipher.name.to_string()),
+            Some("diagnostics".to_string()),
+            None,
+            None,
+        )?;
+        let opened = crypto::decrypt_blob(
+            &sealed.salt,
+            &sealed.data,
+            SELF_TEST_PASSWORD,
+            &[],
+            Some("diagnostics"),
+            &Default::default(),
+            &mut Default::default(),
+        )?;
+        if crypto::decompress(&opened)? != SELF_TEST_PAYLOAD.as_bytes() {
+            return Err(format!("{} round trip returned different data", cipher.name).into());
+        }
+    }
+    Ok(())
+}
+
+/// A wrong password must fail authentication, not decrypt to garbage.
+fn wrong_password_rejected() -> Result<(), SeqretsError> {
+    let sealed = crypto::crypto_encrypt_blob(
+        SELF_TEST_PAYLOAD.to_string(),
+        SELF_TEST_PASSWORD.to_string(),
+        None,
+        Some(SELF_TEST_PARAMS),
+        None,
+        None,
+        None,
+        None,
+    )?;
+    match crypto::decrypt_blob(
+        &sealed.salt,
+        &sealed.data,
+        "not-the-password",
+        &[],
+        None,
+        &Default::default(),
+        &mut Default::default(),
+    ) {
+        Err(SeqretsError::WrongPassword(_)) => Ok(()),
+        Err(e) => Err(format!("wrong password gave an unexpected error: {e}").into()),
+        Ok(_) => Err("wrong password decrypted successfully".into()),
+    }
+}
+
+fn diagnostics() -> DiagnosticsReport {
+    let checks = vec![
+        timed_check("crypto_round_trip", crypto_round_trip),
+        timed_check("wrong_password_rejected", wrong_password_rejected),
+    ];
+    DiagnosticsReport {
+        app_version: env!("CARGO_PKG_VERSION"),
+        tauri_version: tauri::VERSION,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        crypto_format_version: crypto::crypto_capabilities().format_version,
+        stream_format_version: STREAM_VERSION,
+        payload_schema_version: CURRENT_SCHEMA_VERSION,
+        checks,
+        pcsc: smartcard::pcsc_status(),
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Runs the offline self-test and returns a report safe to share. Runs on a
+/// blocking thread because of the Argon2 derivations.
+#[tauri::command]
+pub async fn run_diagnostics() -> Result<DiagnosticsReport, SeqretsError> {
+    tauri::async_runtime::spawn_blocking(diagnostics)
+        .await
+        .map_err(|e| format!("Diagnostics task failed: {e}").into())
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        let report = diagnostics();
+        for check in &report.checks {
+            assert!(check.passed, "{} failed: {:?}", check.name, check.error);
+        }
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["app_version"], env!("CARGO_PKG_VERSION"));
+        assert!(json["pcsc"]["state"].is_string());
+        assert!(!json.to_string().contains(SELF_TEST_PASSWORD));
+    }
+}
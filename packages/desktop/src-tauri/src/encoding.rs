@@ -0,0 +1,124 @@
+//! Text encodings for binary payloads in seQRets desktop.
+//!
+//! Standard padded base64 is the default and what every existing blob and
+//! share uses. The share/QR-oriented commands can instead emit URL-safe
+//! base64 without padding: no `=` padding to carry in the QR code, and no
+//! `+`/`/` to escape when a share travels in a URL.
+//!
+//! Decoding accepts either form. Text without `-`, `_` or a missing pad
+//! decodes to the same bytes under both alphabets, so detection never
+//! changes the result; shares additionally record their encoding in the
+//! header (see `shamir`), which is checked against the text.
+
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+
+use crate::error::SeqretsError;
+
+/// Names accepted by the `encoding` command arguments.
+pub(crate) const ENCODING_NAMES: [&str; 2] = ["base64", "base64url"];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum PayloadEncoding {
+    /// RFC 4648 base64, padded.
+    #[default]
+    Base64,
+    /// RFC 4648 base64url, unpadded.
+    Base64Url,
+}
+
+impl PayloadEncoding {
+    /// `None` selects standard base64.
+    pub(crate) fn from_name(name: Option<&str>) -> Result<Self, SeqretsError> {
+        match name {
+            None | Some("base64") => Ok(PayloadEncoding::Base64),
+            Some("base64url") => Ok(PayloadEncoding::Base64Url),
+            Some(other) => Err(SeqretsError::InvalidInput(format!(
+                "Unknown encoding '{other}'. Expected one of: {}.",
+                ENCODING_NAMES.join(", ")
+            ))),
+        }
+    }
+
+    pub(crate) fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            PayloadEncoding::Base64 => STANDARD.encode(bytes),
+            PayloadEncoding::Base64Url => URL_SAFE_NO_PAD.encode(bytes),
+        }
+    }
+
+    /// Which encoding `text` is in. Standard base64 is always a multiple of
+    /// four characters; anything with the URL-safe alphabet or an unpadded
+    /// length is base64url.
+    pub(crate) fn detect(text: &str) -> Self {
+        if text.contains(['-', '_']) || text.len() % 4 != 0 {
+            PayloadEncoding::Base64Url
+        } else {
+            PayloadEncoding::Base64
+        }
+    }
+
+    /// False when `text` contains characters this encoding never produces.
+    pub(crate) fn matches(self, text: &str) -> bool {
+        match self {
+            PayloadEncoding::Base64 => PayloadEncoding::detect(text) == PayloadEncoding::Base64,
+            PayloadEncoding::Base64Url => !text.contains(['+', '/', '=']),
+        }
+    }
+}
+
+/// Decodes standard base64 or base64url, whichever `text` is in.
+pub(crate) fn decode(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    match PayloadEncoding::detect(text) {
+        PayloadEncoding::Base64 => STANDARD.decode(text),
+        PayloadEncoding::Base64Url => URL_SAFE_NO_PAD.decode(text),
+    }
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_both_encodings_roundtrip() {
+        for len in 0..64usize {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 73 + 250) as u8).collect();
+            for encoding in [PayloadEncoding::Base64, PayloadEncoding::Base64Url] {
+                let text = encoding.encode(&bytes);
+                assert!(encoding.matches(&text), "{encoding:?} {text}");
+                assert_eq!(decode(&text).unwrap(), bytes, "{encoding:?} {text}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_url_safe_is_shorter_and_has_no_escapes() {
+        let bytes = [0xFBu8, 0xFF, 0xBF, 0x01];
+        let standard = PayloadEncoding::Base64.encode(&bytes);
+        let url = PayloadEncoding::Base64Url.encode(&bytes);
+        assert_eq!(standard, "+/+/AQ==");
+        assert_eq!(url, "-_-_AQ");
+        assert!(!PayloadEncoding::Base64Url.matches(&standard));
+        assert!(!PayloadEncoding::Base64.matches(&url));
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(
+            PayloadEncoding::from_name(None).unwrap(),
+            PayloadEncoding::Base64
+        );
+        assert_eq!(
+            PayloadEncoding::from_name(Some("base64url")).unwrap(),
+            PayloadEncoding::Base64Url
+        );
+        assert!(matches!(
+            PayloadEncoding::from_name(Some("hex")),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+}
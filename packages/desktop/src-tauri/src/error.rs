@@ -0,0 +1,124 @@
+//! Typed error returned by the Tauri commands.
+//!
+//! Serializes to `{ "kind": "wrongPassword", "message": "..." }` so the
+//! frontend can branch on (and translate) `kind` while still showing the
+//! English `message` as a fallback. Helpers that have no better category
+//! keep returning `String`, which converts to `Other` through `?`.
+
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum SeqretsError {
+    /// Authenticated decryption failed: wrong password, keyfile or label.
+    WrongPassword(String),
+    /// The key is right (the blob's key commitment matches), but the blob or
+    /// the associated data bound to it (label or salt) doesn't match what it
+    /// was sealed with: the file was altered.
+    HeaderTampered(String),
+    /// The card's issuer has locked the applet (GlobalPlatform LOCKED); only
+    /// the issuer can unlock it.
+    AppletLocked(String),
+    /// The card rejected the PIN or PUK; the message says how many tries remain.
+    WrongPin(String),
+    /// The PIN (or PUK) is blocked after too many failed attempts.
+    CardLocked(String),
+    /// The card needs a verified PIN for this operation.
+    PinRequired(String),
+    /// The data doesn't fit in the card's storage.
+    StorageFull(String),
+    /// No PC/SC readers are connected.
+    NoReaders(String),
+    /// Another application holds the reader or card exclusively.
+    ReaderBusy(String),
+    /// A command asked to wait for a card, and none was tapped or inserted
+    /// in time.
+    NoCardPresented(String),
+    /// Stored or scanned data is malformed: bad base64, truncated blobs,
+    /// failed decompression, unreadable card contents.
+    CorruptData(String),
+    /// A caller-supplied argument is out of range or malformed.
+    InvalidInput(String),
+    /// Encrypted data declares Argon2 costs above this build's hard limits;
+    /// refused before any memory is allocated.
+    ParamsTooLarge(String),
+    /// The user cancelled a card operation or key derivation.
+    Cancelled(String),
+    /// The card or reader stopped responding mid-command.
+    Timeout(String),
+    /// Anything else: I/O, PC/SC transport, internal failures.
+    Other(String),
+}
+
+impl SeqretsError {
+    pub fn message(&self) -> &str {
+        match self {
+            SeqretsError::WrongPassword(m)
+            | SeqretsError::HeaderTampered(m)
+            | SeqretsError::AppletLocked(m)
+            | SeqretsError::WrongPin(m)
+            | SeqretsError::CardLocked(m)
+            | SeqretsError::PinRequired(m)
+            | SeqretsError::StorageFull(m)
+            | SeqretsError::NoReaders(m)
+            | SeqretsError::ReaderBusy(m)
+            | SeqretsError::NoCardPresented(m)
+            | SeqretsError::CorruptData(m)
+            | SeqretsError::InvalidInput(m)
+            | SeqretsError::ParamsTooLarge(m)
+            | SeqretsError::Cancelled(m)
+            | SeqretsError::Timeout(m)
+            | SeqretsError::Other(m) => m,
+        }
+    }
+}
+
+impl fmt::Display for SeqretsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for SeqretsError {}
+
+impl From<String> for SeqretsError {
+    fn from(message: String) -> Self {
+        SeqretsError::Other(message)
+    }
+}
+
+impl From<&str> for SeqretsError {
+    fn from(message: &str) -> Self {
+        SeqretsError::Other(message.to_string())
+    }
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_kind_and_message() {
+        let err = SeqretsError::WrongPassword("Decryption failed".to_string());
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({ "kind": "wrongPassword", "message": "Decryption failed" })
+        );
+
+        let err = SeqretsError::NoReaders("No smartcard readers found".to_string());
+        assert_eq!(serde_json::to_value(&err).unwrap()["kind"], "noReaders");
+    }
+
+    #[test]
+    fn test_string_errors_become_other() {
+        let err: SeqretsError = "Write error: disk full".to_string().into();
+        assert_eq!(
+            err,
+            SeqretsError::Other("Write error: disk full".to_string())
+        );
+        assert_eq!(err.to_string(), "Write error: disk full");
+    }
+}
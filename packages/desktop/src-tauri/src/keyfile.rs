@@ -0,0 +1,125 @@
+//! Keyfile generation for seQRets desktop.
+//!
+//! A keyfile is a block of random bytes mixed into the Argon2id input
+//! alongside the password (see `crypto::derive_key`), giving vaults a
+//! second factor. Keyfiles are stored as raw binary (`.bin`) and passed to
+//! the crypto commands as base64.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+use std::fs;
+use std::io::Write;
+use zeroize::Zeroizing;
+
+use crate::error::SeqretsError;
+
+const KEYFILE_MIN_BYTES: usize = 16;
+const KEYFILE_MAX_BYTES: usize = 1024;
+
+fn random_keyfile(size_bytes: usize) -> Result<Zeroizing<Vec<u8>>, SeqretsError> {
+    if !(KEYFILE_MIN_BYTES..=KEYFILE_MAX_BYTES).contains(&size_bytes) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Keyfile size must be {}-{} bytes, got {}.",
+            KEYFILE_MIN_BYTES, KEYFILE_MAX_BYTES, size_bytes
+        )));
+    }
+    let mut bytes = Zeroizing::new(vec![0u8; size_bytes]);
+    rand::rng().fill_bytes(&mut bytes);
+    Ok(bytes)
+}
+
+/// Writes the keyfile as raw bytes. On unix the file is owner-only (0600),
+/// including when an existing file is overwritten, and a symlink at `path`
+/// is refused rather than followed.
+fn write_keyfile(path: &str, bytes: &[u8]) -> Result<(), SeqretsError> {
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+        opts.custom_flags(libc::O_NOFOLLOW);
+    }
+
+    let mut f = opts
+        .open(path)
+        .map_err(|e| format!("Could not create keyfile {path}: {e}"))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        f.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Could not restrict keyfile permissions: {e}"))?;
+    }
+
+    f.write_all(bytes)
+        .map_err(|e| format!("Could not write keyfile: {e}"))?;
+    f.sync_all()
+        .map_err(|e| format!("Could not fsync keyfile: {e}"))?;
+    Ok(())
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Generates a `size_bytes` keyfile (16–1024) from the OS CSPRNG and returns
+/// it as base64. When `output_path` is set the raw bytes are also written
+/// there (0600 on unix). The generated buffer is zeroized after encoding.
+#[tauri::command]
+pub fn generate_keyfile(
+    size_bytes: usize,
+    output_path: Option<String>,
+) -> Result<String, SeqretsError> {
+    let bytes = random_keyfile(size_bytes)?;
+    if let Some(path) = output_path.as_deref() {
+        write_keyfile(path, &bytes)?;
+    }
+    Ok(STANDARD.encode(bytes.as_slice()))
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generates_requested_size() {
+        for size in [KEYFILE_MIN_BYTES, 32, KEYFILE_MAX_BYTES] {
+            let b64 = generate_keyfile(size, None).unwrap();
+            assert_eq!(STANDARD.decode(b64).unwrap().len(), size);
+        }
+        assert_ne!(
+            generate_keyfile(32, None).unwrap(),
+            generate_keyfile(32, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_sizes() {
+        for size in [0, KEYFILE_MIN_BYTES - 1, KEYFILE_MAX_BYTES + 1] {
+            assert!(matches!(
+                generate_keyfile(size, None),
+                Err(SeqretsError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_writes_raw_bytes_to_path() {
+        let path =
+            std::env::temp_dir().join(format!("seqrets-keyfile-test-{}.bin", std::process::id()));
+        let path_str = path.to_str().unwrap().to_string();
+
+        let b64 = generate_keyfile(64, Some(path_str)).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), STANDARD.decode(b64).unwrap());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
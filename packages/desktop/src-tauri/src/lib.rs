@@ -1,55 +1,170 @@
+mod audit;
+mod capabilities;
+mod clipboard;
 mod crypto;
+mod diagnostics;
+mod encoding;
+mod error;
 mod keychain;
+mod keyfile;
+mod mnemonic;
+mod password;
+mod pdf;
+mod qr;
+mod redact;
 mod review_reminder;
+mod shamir;
 mod smartcard;
+mod stream;
+mod vault;
+mod wipe;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-  tauri::Builder::default()
-    .plugin(tauri_plugin_dialog::init())
-    .plugin(tauri_plugin_fs::init())
-    .plugin(tauri_plugin_opener::init())
-    .plugin(tauri_plugin_updater::Builder::new().build())
-    .plugin(tauri_plugin_process::init())
-    .setup(|app| {
-      if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
-      }
-      Ok(())
-    })
-    .invoke_handler(tauri::generate_handler![
-      // Smartcard commands
-      smartcard::list_readers,
-      smartcard::get_card_status,
-      smartcard::write_item_to_card,
-      smartcard::read_card_items,
-      smartcard::read_card_item,
-      smartcard::delete_card_item,
-      smartcard::write_all_items,
-      smartcard::erase_card,
-      smartcard::force_erase_card,
-      smartcard::verify_pin,
-      smartcard::set_pin,
-      smartcard::change_pin,
-      smartcard::set_wipe_protect,
-      // Native crypto commands (Argon2id + XChaCha20-Poly1305)
-      crypto::crypto_create,
-      crypto::crypto_restore,
-      crypto::crypto_encrypt_blob,
-      crypto::crypto_decrypt_blob,
-      // OS keychain commands
-      keychain::keychain_get,
-      keychain::keychain_set,
-      keychain::keychain_delete,
-      // Inheritance plan review-reminder sidecar
-      review_reminder::reminder_read,
-      review_reminder::reminder_write,
-      review_reminder::reminder_delete,
-    ])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    tauri::Builder::default()
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(smartcard::SmartcardState::default())
+        .setup(|app| {
+            if cfg!(debug_assertions) {
+                app.handle().plugin(
+                    tauri_plugin_log::Builder::default()
+                        .level(log::LevelFilter::Info)
+                        // Mask anything secret-looking before it reaches the log file.
+                        .format(|out, message, record| {
+                            out.finish(format_args!(
+                                "{}[{}][{}] {}",
+                                tauri_plugin_log::TimezoneStrategy::UseUtc.get_now(),
+                                record.target(),
+                                record.level(),
+                                redact::redact_log(record.target(), &message.to_string())
+                            ))
+                        })
+                        .build(),
+                )?;
+            }
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            // Smartcard commands
+            smartcard::pcsc_status,
+            smartcard::list_readers,
+            smartcard::wait_for_reader,
+            smartcard::set_reader_alias,
+            smartcard::get_reader_aliases,
+            smartcard::enumerate_cards,
+            smartcard::applet_info,
+            smartcard::reader_capabilities,
+            smartcard::get_card_status,
+            smartcard::preflight_write,
+            smartcard::write_item_to_card,
+            smartcard::read_card_items,
+            smartcard::card_matches_vault,
+            smartcard::sign_card,
+            smartcard::verify_card_authenticity,
+            smartcard::recover_from_cards,
+            smartcard::read_card_item,
+            smartcard::keyfile_from_card,
+            smartcard::delete_card_item,
+            smartcard::write_all_items,
+            smartcard::export_card,
+            smartcard::import_card,
+            smartcard::write_shares_to_cards,
+            smartcard::distribute_share,
+            smartcard::begin_enrollment,
+            smartcard::enroll_next_card,
+            smartcard::enrollment_status,
+            smartcard::end_enrollment,
+            smartcard::erase_card,
+            smartcard::force_erase_card,
+            smartcard::cancel_card_operation,
+            smartcard::verify_pin,
+            smartcard::verify_pin_on_reader,
+            smartcard::set_pin,
+            smartcard::change_pin,
+            smartcard::set_wipe_protect,
+            smartcard::set_puk,
+            smartcard::unblock_pin,
+            smartcard::factory_reset,
+            smartcard::set_applet_aid,
+            smartcard::set_transmit_timeout,
+            smartcard::set_write_chunk_size,
+            smartcard::set_apdu_trace,
+            smartcard::set_require_contact,
+            smartcard::set_read_only,
+            smartcard::set_audit_logging,
+            audit::read_audit_log,
+            smartcard::get_applet_aid,
+            // Native crypto commands (Argon2id + XChaCha20-Poly1305)
+            crypto::crypto_create,
+            crypto::crypto_restore,
+            crypto::crypto_encrypt_blob,
+            crypto::crypto_decrypt_blob,
+            crypto::rekey_vault,
+            crypto::encrypt_multi_recipient,
+            crypto::decrypt_multi_recipient,
+            crypto::crypto_decrypt_batch,
+            crypto::verify_key_material,
+            crypto::secrets_match,
+            crypto::crypto_seal,
+            crypto::crypto_open,
+            crypto::store_opaque,
+            crypto::open_opaque,
+            crypto::blob_is_opaque,
+            crypto::check_nonce_uniqueness,
+            vault::verify_vault,
+            vault::vault_fingerprint,
+            vault::encrypt_vault_section,
+            vault::decrypt_vault_section,
+            vault::diff_vaults,
+            vault::parse_vault,
+            vault::migrate_vault,
+            vault::save_vault_atomic,
+            vault::list_vault_backups,
+            vault::restore_vault_backup,
+            keyfile::generate_keyfile,
+            wipe::secure_delete,
+            clipboard::copy_secret_with_timeout,
+            crypto::benchmark_kdf,
+            crypto::recommended_params,
+            crypto::cancel_kdf,
+            crypto::set_kdf_progress,
+            capabilities::crypto_capabilities,
+            diagnostics::run_diagnostics,
+            stream::crypto_encrypt_stream,
+            stream::crypto_decrypt_stream,
+            password::estimate_password_strength,
+            password::estimate_bruteforce_cost,
+            mnemonic::validate_mnemonic,
+            mnemonic::mnemonic_to_entropy,
+            mnemonic::entropy_to_mnemonic,
+            mnemonic::generate_secret,
+            // Native Shamir secret sharing (GF(256))
+            shamir::shamir_split,
+            shamir::shamir_combine,
+            shamir::split_keyfile,
+            shamir::combine_keyfile,
+            shamir::import_shares_from_text,
+            shamir::preview_reconstruction,
+            // QR codes
+            qr::share_to_qr_png,
+            qr::encode_ur,
+            qr::decode_ur,
+            qr::decode_qr,
+            pdf::generate_recovery_pdf,
+            // OS keychain commands
+            keychain::keychain_get,
+            keychain::keychain_set,
+            keychain::keychain_delete,
+            // Inheritance plan review-reminder sidecar
+            review_reminder::reminder_read,
+            review_reminder::reminder_write,
+            review_reminder::reminder_delete,
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
 }
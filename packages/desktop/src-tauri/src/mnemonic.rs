@@ -0,0 +1,423 @@
+//! BIP39 mnemonic validation for seQRets secrets.
+//!
+//! Seed phrases are a common secret type (`isMnemonic` in the vault
+//! payload), and a single mistyped word makes a backup worthless. This module
+//! checks a phrase against the BIP39 English wordlist and its checksum before
+//! it is encrypted, so the UI can point at the exact word that is wrong.
+//!
+//! `generate_secret` creates a fresh secret from the operating system's
+//! CSPRNG, as hex or as a mnemonic, for users who want seQRets to be where
+//! the secret originates rather than only where it is kept.
+
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+use crate::crypto;
+use crate::error::SeqretsError;
+
+/// The canonical BIP39 English wordlist (2048 words, sorted).
+const ENGLISH_WORDLIST: &str = include_str!("bip39-english.txt");
+
+const VALID_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+const VALID_ENTROPY_BITS: [usize; 5] = [128, 160, 192, 224, 256];
+const BITS_PER_WORD: usize = 11;
+
+/// Accepted `entropy_bits` for hex output of `generate_secret` (whole bytes).
+const MIN_SECRET_BITS: u32 = 128;
+const MAX_SECRET_BITS: u32 = 512;
+
+/// Returned by `validate_mnemonic`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct MnemonicInfo {
+    /// Word count, wordlist and checksum all check out.
+    pub valid: bool,
+    pub word_count: usize,
+    /// Zero-based positions of words not in the BIP39 English wordlist.
+    pub invalid_words: Vec<usize>,
+}
+
+pub(crate) fn english_wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| ENGLISH_WORDLIST.lines().collect())
+}
+
+/// Lowercases and collapses whitespace: "  Abandon\tABILITY " -> ["abandon", "ability"].
+fn normalize(phrase: &str) -> Zeroizing<Vec<String>> {
+    Zeroizing::new(phrase.split_whitespace().map(str::to_lowercase).collect())
+}
+
+fn bit_at(bytes: &[u8], i: usize) -> bool {
+    (bytes[i / 8] >> (7 - i % 8)) & 1 == 1
+}
+
+/// Splits word indices back into entropy, returning `None` if the trailing
+/// checksum bits don't match SHA-256 of the entropy.
+fn indices_to_entropy(indices: &[u16]) -> Option<Zeroizing<Vec<u8>>> {
+    let total_bits = indices.len() * BITS_PER_WORD;
+    let checksum_bits = total_bits / 33;
+    let entropy_bits = total_bits - checksum_bits;
+
+    let bit =
+        |i: usize| (indices[i / BITS_PER_WORD] >> (BITS_PER_WORD - 1 - i % BITS_PER_WORD)) & 1 == 1;
+
+    let mut entropy = Zeroizing::new(vec![0u8; entropy_bits / 8]);
+    for i in 0..entropy_bits {
+        if bit(i) {
+            entropy[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+
+    let hash = Sha256::digest(entropy.as_slice());
+    let stored: Vec<u8> = (0..checksum_bits)
+        .map(|i| bit(entropy_bits + i) as u8)
+        .collect();
+    let computed: Vec<u8> = (0..checksum_bits).map(|i| bit_at(&hash, i) as u8).collect();
+    crypto::ct_eq(&stored, &computed).then_some(entropy)
+}
+
+/// Appends the SHA-256 checksum to the entropy and cuts it into 11-bit word indices.
+fn entropy_to_indices(entropy: &[u8]) -> Result<Zeroizing<Vec<u16>>, String> {
+    let entropy_bits = entropy.len() * 8;
+    if !VALID_ENTROPY_BITS.contains(&entropy_bits) {
+        return Err(format!(
+            "Invalid entropy: expected 128, 160, 192, 224 or 256 bits, got {entropy_bits}."
+        ));
+    }
+    let checksum_bits = entropy_bits / 32;
+    let hash = Sha256::digest(entropy);
+
+    let bit = |i: usize| {
+        if i < entropy_bits {
+            bit_at(entropy, i)
+        } else {
+            bit_at(&hash, i - entropy_bits)
+        }
+    };
+
+    let word_count = (entropy_bits + checksum_bits) / BITS_PER_WORD;
+    let indices = (0..word_count)
+        .map(|w| {
+            (0..BITS_PER_WORD).fold(0u16, |acc, b| {
+                (acc << 1) | bit(w * BITS_PER_WORD + b) as u16
+            })
+        })
+        .collect();
+    Ok(Zeroizing::new(indices))
+}
+
+/// Looks up each word, returning the indices or the positions of unknown words.
+fn word_indices(words: &[String]) -> Result<Zeroizing<Vec<u16>>, Vec<usize>> {
+    let wordlist = english_wordlist();
+    let mut indices = Zeroizing::new(Vec::with_capacity(words.len()));
+    let mut invalid_words = Vec::new();
+    for (position, word) in words.iter().enumerate() {
+        match wordlist.binary_search(&word.as_str()) {
+            Ok(index) => indices.push(index as u16),
+            Err(_) => invalid_words.push(position),
+        }
+    }
+    if invalid_words.is_empty() {
+        Ok(indices)
+    } else {
+        Err(invalid_words)
+    }
+}
+
+fn inspect(phrase: &str) -> MnemonicInfo {
+    let words = normalize(phrase);
+    let count_ok = VALID_WORD_COUNTS.contains(&words.len());
+
+    let (valid, invalid_words) = match word_indices(&words) {
+        Ok(indices) => (
+            count_ok && indices_to_entropy(&indices).is_some(),
+            Vec::new(),
+        ),
+        Err(invalid_words) => (false, invalid_words),
+    };
+
+    MnemonicInfo {
+        valid,
+        word_count: words.len(),
+        invalid_words,
+    }
+}
+
+fn phrase_to_entropy(phrase: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let words = normalize(phrase);
+    if !VALID_WORD_COUNTS.contains(&words.len()) {
+        return Err(format!(
+            "Invalid mnemonic: expected 12, 15, 18, 21 or 24 words, got {}.",
+            words.len()
+        ));
+    }
+    let indices = word_indices(&words).map_err(|invalid| {
+        format!(
+            "Invalid mnemonic: word {} is not in the BIP39 English wordlist.",
+            invalid[0] + 1
+        )
+    })?;
+    indices_to_entropy(&indices).ok_or_else(|| {
+        "Invalid mnemonic: checksum mismatch. Check the words and their order.".to_string()
+    })
+}
+
+fn entropy_to_phrase(entropy: &[u8]) -> Result<Zeroizing<String>, String> {
+    let wordlist = english_wordlist();
+    let indices = entropy_to_indices(entropy)?;
+    let words: Vec<&str> = indices.iter().map(|&i| wordlist[i as usize]).collect();
+    Ok(Zeroizing::new(words.join(" ")))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let hex = hex.trim();
+    if hex.len() % 2 != 0 {
+        return Err("Invalid entropy: hex string has an odd number of digits.".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .map(Zeroizing::new)
+        .ok_or_else(|| "Invalid entropy: not a valid hex string.".to_string())
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Checks a BIP39 English mnemonic: word count (12/15/18/21/24), each word
+/// against the wordlist, and the checksum. Whitespace and case are
+/// normalized first.
+#[tauri::command]
+pub fn validate_mnemonic(phrase: String) -> MnemonicInfo {
+    let phrase = Zeroizing::new(phrase);
+    inspect(phrase.as_str())
+}
+
+/// Converts a BIP39 English mnemonic to its raw entropy as lowercase hex.
+/// The phrase must be complete and pass the checksum.
+#[tauri::command]
+pub fn mnemonic_to_entropy(phrase: String) -> Result<String, SeqretsError> {
+    let phrase = Zeroizing::new(phrase);
+    let entropy = phrase_to_entropy(&phrase).map_err(SeqretsError::InvalidInput)?;
+    Ok(encode_hex(&entropy))
+}
+
+/// Regenerates the BIP39 English mnemonic for 128–256 bits of hex entropy.
+#[tauri::command]
+pub fn entropy_to_mnemonic(entropy_hex: String) -> Result<String, SeqretsError> {
+    let entropy_hex = Zeroizing::new(entropy_hex);
+    let entropy = decode_hex(&entropy_hex).map_err(SeqretsError::InvalidInput)?;
+    let phrase = entropy_to_phrase(&entropy).map_err(SeqretsError::InvalidInput)?;
+    Ok(phrase.to_string())
+}
+
+/// Generates a new random secret of `entropy_bits` bits: a BIP39 English
+/// mnemonic when `as_mnemonic` (128, 160, 192, 224 or 256 bits, i.e. 12 to
+/// 24 words), lowercase hex otherwise (128 to 512 bits, in whole bytes).
+/// The entropy comes from the OS CSPRNG and, like the intermediate word
+/// list, is zeroized before returning; only the returned string remains.
+#[tauri::command]
+pub fn generate_secret(entropy_bits: u32, as_mnemonic: bool) -> Result<String, SeqretsError> {
+    let valid = if as_mnemonic {
+        VALID_ENTROPY_BITS.contains(&(entropy_bits as usize))
+    } else {
+        (MIN_SECRET_BITS..=MAX_SECRET_BITS).contains(&entropy_bits) && entropy_bits % 8 == 0
+    };
+    if !valid {
+        return Err(SeqretsError::InvalidInput(if as_mnemonic {
+            format!(
+                "A mnemonic needs 128, 160, 192, 224 or 256 bits of entropy, got {entropy_bits}."
+            )
+        } else {
+            format!("A secret needs {MIN_SECRET_BITS}-{MAX_SECRET_BITS} bits in whole bytes, got {entropy_bits}.")
+        }));
+    }
+
+    let mut entropy = Zeroizing::new(vec![0u8; entropy_bits as usize / 8]);
+    rand::rng().fill_bytes(&mut entropy);
+    if as_mnemonic {
+        let phrase = entropy_to_phrase(&entropy).map_err(SeqretsError::InvalidInput)?;
+        Ok(phrase.to_string())
+    } else {
+        Ok(encode_hex(&entropy))
+    }
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Official BIP39 test vectors (all-zero and all-0x7f entropy).
+    const VALID_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const VALID_24: &str = "legal winner thank year wave sausage worth useful legal winner thank year \
+                            wave sausage worth useful legal winner thank year wave sausage worth title";
+
+    #[test]
+    fn test_wordlist_is_sorted_and_complete() {
+        let words = english_wordlist();
+        assert_eq!(words.len(), 2048);
+        assert!(words.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_valid_mnemonics() {
+        for (phrase, count) in [(VALID_12, 12), (VALID_24, 24)] {
+            let info = inspect(phrase);
+            assert!(info.valid, "{phrase}");
+            assert_eq!(info.word_count, count);
+            assert!(info.invalid_words.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_normalizes_case_and_whitespace() {
+        let messy = format!("  {}\t\n", VALID_12.to_uppercase().replace(' ', "   "));
+        assert!(inspect(&messy).valid);
+    }
+
+    #[test]
+    fn test_bad_checksum() {
+        let info = inspect(&"abandon ".repeat(12));
+        assert!(!info.valid);
+        assert!(info.invalid_words.is_empty());
+    }
+
+    #[test]
+    fn test_reports_invalid_word_positions() {
+        let phrase = VALID_12
+            .replacen("abandon", "abandonn", 1)
+            .replace("about", "abuot");
+        let info = inspect(&phrase);
+        assert!(!info.valid);
+        assert_eq!(info.invalid_words, vec![0, 11]);
+    }
+
+    #[test]
+    fn test_wrong_word_count() {
+        let info = inspect("abandon abandon abandon");
+        assert!(!info.valid);
+        assert_eq!(info.word_count, 3);
+        assert!(info.invalid_words.is_empty());
+    }
+
+    /// Trezor reference vectors from the BIP39 spec (entropy, mnemonic).
+    const BIP39_VECTORS: &[(&str, &str)] = &[
+        ("00000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+        ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", "legal winner thank year wave sausage worth useful legal winner thank yellow"),
+        ("80808080808080808080808080808080", "letter advice cage absurd amount doctor acoustic avoid letter advice cage above"),
+        ("ffffffffffffffffffffffffffffffff", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong"),
+        ("000000000000000000000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon agent"),
+        ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", "legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal will"),
+        ("808080808080808080808080808080808080808080808080", "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter always"),
+        ("ffffffffffffffffffffffffffffffffffffffffffffffff", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo when"),
+        ("0000000000000000000000000000000000000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art"),
+        ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", "legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth title"),
+        ("8080808080808080808080808080808080808080808080808080808080808080", "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless"),
+        ("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote"),
+        ("9e885d952ad362caeb4efe34a8e91bd2", "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic"),
+        ("6610b25967cdcca9d59875f5cb50b0ea75433311869e930b", "gravity machine north sort system female filter attitude volume fold club stay feature office ecology stable narrow fog"),
+        ("68a79eaca2324873eacc50cb9c6eca8cc68ea5d936f98787c60c7ebc74e6ce7c", "hamster diagram private dutch cause delay private meat slide toddler razor book happy fancy gospel tennis maple dilemma loan word shrug inflict delay length"),
+        ("c0ba5a8e914111210f2bd131f3d5e08d", "scheme spot photo card baby mountain device kick cradle pact join borrow"),
+        ("6d9be1ee6ebd27a258115aad99b7317b9c8d28b6d76431c3", "horn tenant knee talent sponsor spell gate clip pulse soap slush warm silver nephew swap uncle crack brave"),
+        ("9f6a2878b2520799a44ef18bc7df394e7061a224d2c33cd015b157d746869863", "panda eyebrow bullet gorilla call smoke muffin taste mesh discover soft ostrich alcohol speed nation flash devote level hobby quick inner drive ghost inside"),
+        ("23db8160a31d3e0dca3688ed941adbf3", "cat swing flag economy stadium alone churn speed unique patch report train"),
+        ("8197a4a47f0425faeaa69deebc05ca29c0a5b5cc76ceacc0", "light rule cinnamon wrap drastic word pride squirrel upgrade then income fatal apart sustain crack supply proud access"),
+        ("066dca1a2bb7e8a1db2832148ce9933eea0f3ac9548d793112d9a95c9407efad", "all hour make first leader extend hole alien behind guard gospel lava path output census museum junior mass reopen famous sing advance salt reform"),
+        ("f30f8c1da665478f49b001d94c5fc452", "vessel ladder alter error federal sibling chat ability sun glass valve picture"),
+        ("c10ec20dc3cd9f652c7fac2f1230f7a3c828389a14392f05", "scissors invite lock maple supreme raw rapid void congress muscle digital elegant little brisk hair mango congress clump"),
+        ("f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f", "void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold"),
+    ];
+
+    #[test]
+    fn test_entropy_to_mnemonic_vectors() {
+        for (entropy, phrase) in BIP39_VECTORS {
+            assert_eq!(entropy_to_mnemonic(entropy.to_string()).unwrap(), *phrase);
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_to_entropy_vectors() {
+        for (entropy, phrase) in BIP39_VECTORS {
+            assert_eq!(mnemonic_to_entropy(phrase.to_string()).unwrap(), *entropy);
+            assert!(inspect(phrase).valid);
+        }
+    }
+
+    #[test]
+    fn test_generate_secret() {
+        for bits in VALID_ENTROPY_BITS {
+            let phrase = generate_secret(bits as u32, true).unwrap();
+            let info = inspect(&phrase);
+            assert!(info.valid, "{bits}-bit mnemonic must be valid");
+            assert_eq!(info.word_count, (bits + bits / 32) / BITS_PER_WORD);
+            assert_ne!(generate_secret(bits as u32, true).unwrap(), phrase);
+        }
+
+        let hex = generate_secret(256, false).unwrap();
+        assert_eq!(hex.len(), 64);
+        assert!(hex
+            .bytes()
+            .all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase()));
+        assert_ne!(generate_secret(256, false).unwrap(), hex);
+        assert_eq!(generate_secret(512, false).unwrap().len(), 128);
+
+        for (bits, as_mnemonic) in [
+            (100, true),
+            (512, true),
+            (0, false),
+            (120, false),
+            (130, false),
+            (520, false),
+        ] {
+            assert!(
+                matches!(
+                    generate_secret(bits, as_mnemonic),
+                    Err(SeqretsError::InvalidInput(_))
+                ),
+                "{bits}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_entropy_length_rejected() {
+        let err = entropy_to_mnemonic("00".repeat(15)).unwrap_err();
+        assert!(matches!(err, SeqretsError::InvalidInput(_)), "{err:?}");
+        assert!(err.to_string().contains("got 120"), "{err}");
+        assert!(entropy_to_mnemonic("000".to_string())
+            .unwrap_err()
+            .to_string()
+            .contains("odd"));
+        assert!(entropy_to_mnemonic("zz".repeat(16))
+            .unwrap_err()
+            .to_string()
+            .contains("hex"));
+    }
+
+    #[test]
+    fn test_mnemonic_to_entropy_rejects_bad_phrases() {
+        assert!(mnemonic_to_entropy("abandon abandon".to_string())
+            .unwrap_err()
+            .to_string()
+            .contains("got 2"));
+        assert!(mnemonic_to_entropy(VALID_12.replace("about", "abuot"))
+            .unwrap_err()
+            .to_string()
+            .contains("word 12"));
+        assert!(mnemonic_to_entropy("abandon ".repeat(12))
+            .unwrap_err()
+            .to_string()
+            .contains("checksum"));
+    }
+}
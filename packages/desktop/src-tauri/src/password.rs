@@ -0,0 +1,351 @@
+//! Password strength estimation for seQRets desktop.
+//!
+//! Wraps the `zxcvbn` crate (a Rust port of Dropbox's estimator) so the
+//! setup screen can gate vault creation on a minimum score without the
+//! password ever leaving the backend. The incoming password is zeroized once
+//! the estimate is computed; `zxcvbn` makes short-lived internal copies that
+//! are freed (but not zeroized) before this command returns.
+//!
+//! `estimate_bruteforce_cost` turns an entropy estimate and the Argon2
+//! parameters into an order-of-magnitude offline cracking estimate. Argon2
+//! is memory-hard, so the model charges each guess the memory traffic of
+//! its passes (`BYTES_TOUCHED_PER_BLOCK` per KiB block per pass) and divides
+//! an attacker's memory bandwidth by that. Real crackers fall short of peak
+//! bandwidth, hence the ranges; the figures are for education, not a
+//! guarantee.
+
+use serde::Serialize;
+use zeroize::Zeroizing;
+
+use crate::crypto::CryptoParams;
+use crate::error::SeqretsError;
+
+/// Each Argon2 pass reads two 1 KiB blocks and writes one per block filled.
+const BYTES_TOUCHED_PER_BLOCK: f64 = 3.0 * 1024.0;
+
+/// Fraction of peak memory bandwidth a cracker is assumed to reach, low to high.
+const BANDWIDTH_EFFICIENCY: (f64, f64) = (0.1, 1.0);
+
+/// Assumed rental price of one GPU, in US dollars per hour, low to high.
+const GPU_HOUR_USD: (f64, f64) = (0.5, 2.0);
+
+/// Largest entropy estimate accepted, in bits.
+const MAX_ENTROPY_BITS: f64 = 1024.0;
+
+/// One assumed attacker, from the single GPU to a large cluster.
+struct Attacker {
+    name: &'static str,
+    gpus: f64,
+    /// Peak memory bandwidth of one GPU, bytes per second.
+    gpu_bandwidth: f64,
+}
+
+const ATTACKERS: [Attacker; 3] = [
+    Attacker {
+        name: "One high-end GPU",
+        gpus: 1.0,
+        gpu_bandwidth: 1.0e12,
+    },
+    Attacker {
+        name: "A rented cluster of 100 GPUs",
+        gpus: 100.0,
+        gpu_bandwidth: 1.0e12,
+    },
+    Attacker {
+        name: "A well-funded attacker with 100,000 GPUs",
+        gpus: 100_000.0,
+        gpu_bandwidth: 1.0e12,
+    },
+];
+
+/// Returned by `estimate_password_strength`.
+#[derive(Serialize)]
+pub struct PasswordStrength {
+    /// log2 of the estimated number of guesses an attacker needs.
+    pub entropy_bits: f64,
+    /// 0 (trivially guessable) to 4 (very unguessable), zxcvbn scale.
+    pub score: u8,
+    /// The main weakness found, if any (e.g. "This is a very common password.").
+    pub warning: Option<String>,
+    /// Concrete suggestions for a stronger password.
+    pub suggestions: Vec<String>,
+}
+
+fn estimate(password: &str) -> PasswordStrength {
+    let entropy = zxcvbn::zxcvbn(password, &[]);
+    let entropy_bits = if password.is_empty() {
+        0.0
+    } else {
+        entropy.guesses_log10() * std::f64::consts::LOG2_10
+    };
+
+    let (warning, suggestions) = match entropy.feedback() {
+        Some(feedback) => (
+            feedback.warning().map(|w| w.to_string()),
+            feedback
+                .suggestions()
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        ),
+        None => (None, Vec::new()),
+    };
+
+    PasswordStrength {
+        entropy_bits,
+        score: u8::from(entropy.score()),
+        warning,
+        suggestions,
+    }
+}
+
+/// A low-to-high range; the two ends can be orders of magnitude apart.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub low: f64,
+    pub high: f64,
+}
+
+/// The estimate for one assumed attacker.
+#[derive(Serialize, Debug)]
+pub struct CrackScenario {
+    pub attacker: String,
+    pub guesses_per_second: Range,
+    /// Expected time to find the password (half the search space).
+    pub seconds: Range,
+    /// GPU rental cost of that time, in US dollars.
+    pub cost_usd: Range,
+    /// `seconds` in words, e.g. "~3 days to ~1 month".
+    pub time_label: String,
+}
+
+/// Returned by `estimate_bruteforce_cost`.
+#[derive(Serialize, Debug)]
+pub struct CrackEstimate {
+    pub params: CryptoParams,
+    pub password_entropy_bits: f64,
+    /// Memory traffic an attacker spends on each guess.
+    pub bytes_per_guess: f64,
+    /// From the weakest attacker to the strongest.
+    pub scenarios: Vec<CrackScenario>,
+    /// The assumptions behind every figure, to show alongside them.
+    pub assumptions: Vec<String>,
+}
+
+/// `value` rounded to one significant figure, for labels.
+fn round_label(value: f64) -> String {
+    if value < 1.0 {
+        return "<1".to_string();
+    }
+    let magnitude = 10f64.powi(value.log10().floor() as i32);
+    format!("{}", (value / magnitude).round() * magnitude)
+}
+
+/// A duration in the largest unit that fits, to one significant figure.
+fn describe_seconds(seconds: f64) -> String {
+    const UNITS: [(&str, f64); 5] = [
+        ("year", 365.25 * 86_400.0),
+        ("day", 86_400.0),
+        ("hour", 3_600.0),
+        ("minute", 60.0),
+        ("second", 1.0),
+    ];
+    if seconds < 1.0 {
+        return "under a second".to_string();
+    }
+    let years = seconds / UNITS[0].1;
+    if years >= 1.0e9 {
+        return format!("~10^{} years", years.log10().floor());
+    }
+    let (unit, size) = UNITS
+        .iter()
+        .find(|(_, size)| seconds >= *size)
+        .copied()
+        .unwrap_or(UNITS[4]);
+    let count = round_label(seconds / size);
+    format!("~{count} {unit}{}", if count == "1" { "" } else { "s" })
+}
+
+fn bruteforce_estimate(
+    params: &CryptoParams,
+    entropy_bits: f64,
+) -> Result<CrackEstimate, SeqretsError> {
+    if params.m_cost == 0 || params.t_cost == 0 || params.p_cost == 0 {
+        return Err(SeqretsError::InvalidInput(
+            "Argon2 costs must be at least 1.".to_string(),
+        ));
+    }
+    if !entropy_bits.is_finite() || !(0.0..=MAX_ENTROPY_BITS).contains(&entropy_bits) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Password entropy must be between 0 and {MAX_ENTROPY_BITS} bits."
+        )));
+    }
+
+    let bytes_per_guess =
+        f64::from(params.m_cost) * f64::from(params.t_cost) * BYTES_TOUCHED_PER_BLOCK;
+    // On average the password turns up halfway through the search space.
+    let expected_guesses = 2f64.powf(entropy_bits - 1.0).max(1.0);
+
+    let scenarios = ATTACKERS
+        .iter()
+        .map(|attacker| {
+            let peak = attacker.gpus * attacker.gpu_bandwidth / bytes_per_guess;
+            let rate = Range {
+                low: peak * BANDWIDTH_EFFICIENCY.0,
+                high: peak * BANDWIDTH_EFFICIENCY.1,
+            };
+            let seconds = Range {
+                low: expected_guesses / rate.high,
+                high: expected_guesses / rate.low,
+            };
+            let gpu_hours = |secs: f64| secs / 3_600.0 * attacker.gpus;
+            CrackScenario {
+                attacker: attacker.name.to_string(),
+                guesses_per_second: rate,
+                cost_usd: Range {
+                    low: gpu_hours(seconds.low) * GPU_HOUR_USD.0,
+                    high: gpu_hours(seconds.high) * GPU_HOUR_USD.1,
+                },
+                time_label: format!(
+                    "{} to {}",
+                    describe_seconds(seconds.low),
+                    describe_seconds(seconds.high)
+                ),
+                seconds,
+            }
+        })
+        .collect();
+
+    Ok(CrackEstimate {
+        params: *params,
+        password_entropy_bits: entropy_bits,
+        bytes_per_guess,
+        scenarios,
+        assumptions: vec![
+            "Offline attack: the attacker has the encrypted vault and its salt, and no keyfile is used.".to_string(),
+            format!(
+                "Each guess costs {} pass{} over {} of Argon2 memory; parallelism and variant don't change the total work.",
+                params.t_cost,
+                if params.t_cost == 1 { "" } else { "es" },
+                if params.m_cost >= 1024 { format!("{} MiB", params.m_cost / 1024) } else { format!("{} KiB", params.m_cost) }
+            ),
+            "Each GPU has about 1 TB/s of memory bandwidth, of which a cracker reaches 10% to 100%.".to_string(),
+            format!("GPUs rent for ${:.2} to ${:.2} per hour.", GPU_HOUR_USD.0, GPU_HOUR_USD.1),
+            "The password is found after searching half of the space its entropy estimate implies.".to_string(),
+            "The entropy estimate is itself approximate; a weaker-than-estimated password falls much sooner.".to_string(),
+        ],
+    })
+}
+
+/// Estimates password strength (entropy bits, 0–4 score, and feedback).
+#[tauri::command]
+pub fn estimate_password_strength(password: String) -> PasswordStrength {
+    let password = Zeroizing::new(password);
+    estimate(password.as_str())
+}
+
+/// Order-of-magnitude offline cracking time and cost for a password of
+/// `password_entropy_bits` (e.g. `estimate_password_strength`'s) under the
+/// Argon2 `params`, for a few assumed attackers. Every figure is a range;
+/// `assumptions` lists what it rests on.
+#[tauri::command]
+pub fn estimate_bruteforce_cost(
+    params: CryptoParams,
+    password_entropy_bits: f64,
+) -> Result<CrackEstimate, SeqretsError> {
+    bruteforce_estimate(&params, password_entropy_bits)
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dictionary_word_is_weak() {
+        let result = estimate("password");
+        assert_eq!(result.score, 0);
+        assert!(result.warning.is_some());
+    }
+
+    #[test]
+    fn test_keyboard_walk_is_weak() {
+        let result = estimate("qwertyuiop");
+        assert!(result.score <= 1, "keyboard walk scored {}", result.score);
+    }
+
+    #[test]
+    fn test_repeats_are_weak() {
+        let result = estimate("aaaaaaaaaaaa");
+        assert!(
+            result.score <= 1,
+            "repeated characters scored {}",
+            result.score
+        );
+        assert!(!result.suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_random_passphrase_is_strong() {
+        let result = estimate("correct-Horse-battery-staple-97-vortex");
+        assert_eq!(result.score, 4);
+        assert!(result.entropy_bits > 40.0);
+    }
+
+    #[test]
+    fn test_empty_password() {
+        let result = estimate("");
+        assert_eq!(result.score, 0);
+        assert_eq!(result.entropy_bits, 0.0);
+    }
+
+    #[test]
+    fn test_bruteforce_estimate_scales_with_params_and_entropy() {
+        let params = CryptoParams::default();
+        let weak = bruteforce_estimate(&params, 20.0).unwrap();
+        assert_eq!(weak.scenarios.len(), ATTACKERS.len());
+        assert!(weak
+            .scenarios
+            .iter()
+            .all(|s| s.seconds.low <= s.seconds.high && s.cost_usd.low <= s.cost_usd.high));
+        assert!(weak.scenarios[0].seconds.high > weak.scenarios[2].seconds.high);
+        assert!(!weak.assumptions.is_empty());
+
+        // 10 more bits is ~1000x the time; doubling memory doubles it.
+        let strong = bruteforce_estimate(&params, 30.0).unwrap();
+        let ratio = strong.scenarios[0].seconds.low / weak.scenarios[0].seconds.low;
+        assert!((ratio - 1024.0).abs() < 1e-6);
+        let heavier = bruteforce_estimate(
+            &CryptoParams {
+                m_cost: params.m_cost * 2,
+                ..params
+            },
+            20.0,
+        )
+        .unwrap();
+        assert!(
+            (heavier.scenarios[0].seconds.low / weak.scenarios[0].seconds.low - 2.0).abs() < 1e-9
+        );
+
+        assert!(bruteforce_estimate(&params, f64::NAN).is_err());
+        assert!(bruteforce_estimate(&params, -1.0).is_err());
+        assert!(bruteforce_estimate(
+            &CryptoParams {
+                t_cost: 0,
+                ..params
+            },
+            20.0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_describe_seconds() {
+        assert_eq!(describe_seconds(0.2), "under a second");
+        assert_eq!(describe_seconds(1.0), "~1 second");
+        assert_eq!(describe_seconds(150.0), "~3 minutes");
+        assert_eq!(describe_seconds(3.0 * 86_400.0), "~3 days");
+        assert_eq!(describe_seconds(1.0e20), "~10^12 years");
+    }
+}
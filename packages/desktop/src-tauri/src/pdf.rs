@@ -0,0 +1,369 @@
+//! Printable PDF recovery sheets ("paper backups") for seQRets shares.
+//!
+//! One A4 page per share: the vault label, the share's position and the
+//! threshold needed to restore, the share as a QR code, and the share text
+//! for manual transcription. Written with `pdf-writer` using the built-in
+//! Helvetica/Courier fonts, so nothing is embedded, fetched or shelled out.
+//!
+//! Text is limited to printable ASCII (the standard fonts' safe subset);
+//! other characters in labels are printed as '?'. Share strings are ASCII.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use flate2::{write::ZlibEncoder, Compression};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use serde::Deserialize;
+use std::io::Write;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::error::SeqretsError;
+use crate::qr::{encode_qr, EccLevel, QrModules, DARK, LIGHT, QUIET_ZONE_MODULES};
+
+// A4 portrait, in PDF points (1/72 in).
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+
+const QR_SIZE: f32 = 300.0;
+const SHARE_FONT_SIZE: f32 = 9.0;
+const SHARE_LINE_HEIGHT: f32 = 12.0;
+/// Courier glyphs are 0.6 em wide, so this many fit between the margins.
+const SHARE_CHARS_PER_LINE: usize =
+    ((PAGE_WIDTH - 2.0 * MARGIN) / (SHARE_FONT_SIZE * 0.6)) as usize;
+
+const FONT_REGULAR: Name = Name(b"F1");
+const FONT_BOLD: Name = Name(b"F2");
+const FONT_MONO: Name = Name(b"F3");
+const QR_IMAGE: Name = Name(b"Im1");
+
+/// QR levels tried from most to least robust; paper gets the most
+/// redundancy the share's length allows.
+const QR_LEVELS: [EccLevel; 4] = [
+    EccLevel::High,
+    EccLevel::Quartile,
+    EccLevel::Medium,
+    EccLevel::Low,
+];
+
+/// One share to print.
+#[derive(Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct ShareInfo {
+    pub index: u32, // 1-based share number
+    pub share: String,
+}
+
+/// Vault-level fields printed on every page.
+#[derive(Deserialize)]
+pub struct RecoveryMetadata {
+    pub label: String,
+    pub threshold: u32,
+    pub total_shares: u32,
+    pub created_at: Option<String>, // free-form date, e.g. "2026-10-14"
+}
+
+/// Printable-ASCII bytes for the standard PDF fonts.
+fn pdf_text(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| {
+            if (' '..='~').contains(&c) {
+                c as u8
+            } else {
+                b'?'
+            }
+        })
+        .collect()
+}
+
+/// QR for `share` at the most robust level that fits.
+fn share_qr(share: &str) -> Result<QrModules, String> {
+    let mut last_error = String::new();
+    for level in QR_LEVELS {
+        match encode_qr(share.as_bytes(), level) {
+            Ok(modules) => return Ok(modules),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// 1 pixel per module (plus quiet zone), 8-bit gray, zlib-compressed.
+fn qr_samples(modules: &QrModules) -> Result<(i32, Vec<u8>), String> {
+    let side = modules.width + 2 * QUIET_ZONE_MODULES;
+    let mut pixels = Zeroizing::new(vec![LIGHT; side * side]);
+    for (i, _) in modules.dark.iter().enumerate().filter(|(_, &dark)| dark) {
+        let x = i % modules.width + QUIET_ZONE_MODULES;
+        let y = i / modules.width + QUIET_ZONE_MODULES;
+        pixels[y * side + x] = DARK;
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(&pixels)
+        .and_then(|_| encoder.finish())
+        .map(|compressed| (side as i32, compressed))
+        .map_err(|e| format!("PDF image compression error: {e}"))
+}
+
+/// Page content stream for one share.
+fn page_content(
+    share: &ShareInfo,
+    metadata: &RecoveryMetadata,
+    cut_marks: bool,
+) -> Zeroizing<Vec<u8>> {
+    let mut content = Content::new();
+    let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+
+    let line = |content: &mut Content, font: Name, size: f32, y: f32, text: &str| {
+        content
+            .begin_text()
+            .set_font(font, size)
+            .next_line(MARGIN, y)
+            .show(Str(&pdf_text(text)))
+            .end_text();
+    };
+
+    line(&mut content, FONT_BOLD, 20.0, y, "seQRets Recovery Sheet");
+    y -= 30.0;
+    line(
+        &mut content,
+        FONT_REGULAR,
+        12.0,
+        y,
+        &format!("Vault: {}", metadata.label),
+    );
+    y -= 18.0;
+    line(
+        &mut content,
+        FONT_REGULAR,
+        12.0,
+        y,
+        &format!(
+            "Share {} of {} - any {} shares restore the secret.",
+            share.index, metadata.total_shares, metadata.threshold
+        ),
+    );
+    if let Some(created_at) = &metadata.created_at {
+        y -= 18.0;
+        line(
+            &mut content,
+            FONT_REGULAR,
+            12.0,
+            y,
+            &format!("Created: {created_at}"),
+        );
+    }
+
+    // Share block: QR code followed by the share text.
+    let block_top = y - 24.0;
+    let qr_x = (PAGE_WIDTH - QR_SIZE) / 2.0;
+    let qr_y = block_top - QR_SIZE;
+    content
+        .save_state()
+        .transform([QR_SIZE, 0.0, 0.0, QR_SIZE, qr_x, qr_y])
+        .x_object(QR_IMAGE)
+        .restore_state();
+
+    let share_text = Zeroizing::new(pdf_text(&share.share));
+    let mut text_y = qr_y - 24.0;
+    for chunk in share_text.chunks(SHARE_CHARS_PER_LINE) {
+        content
+            .begin_text()
+            .set_font(FONT_MONO, SHARE_FONT_SIZE)
+            .next_line(MARGIN, text_y)
+            .show(Str(chunk))
+            .end_text();
+        text_y -= SHARE_LINE_HEIGHT;
+    }
+    let block_bottom = text_y;
+
+    if cut_marks {
+        // Dashed frame around the share block.
+        let pad = 12.0;
+        content
+            .save_state()
+            .set_line_width(0.5)
+            .set_dash_pattern([4.0, 3.0], 0.0)
+            .rect(
+                MARGIN - pad,
+                block_bottom - pad,
+                PAGE_WIDTH - 2.0 * (MARGIN - pad),
+                block_top - block_bottom + 2.0 * pad,
+            )
+            .stroke()
+            .restore_state();
+        line(
+            &mut content,
+            FONT_REGULAR,
+            8.0,
+            block_bottom - pad - 12.0,
+            "Cut along the dashed line to separate this share.",
+        );
+    }
+
+    line(
+        &mut content,
+        FONT_REGULAR,
+        9.0,
+        MARGIN,
+        "Keep this sheet private. Restore with the seQRets app using the password and any keyfile.",
+    );
+
+    Zeroizing::new(content.finish().to_vec())
+}
+
+/// Builds the recovery PDF, one page per share.
+fn build_recovery_pdf(
+    shares: &[ShareInfo],
+    metadata: &RecoveryMetadata,
+    cut_marks: bool,
+) -> Result<Vec<u8>, String> {
+    if shares.is_empty() {
+        return Err("No shares to print.".to_string());
+    }
+
+    let catalog_id = Ref::new(1);
+    let pages_id = Ref::new(2);
+    let font_ids = [Ref::new(3), Ref::new(4), Ref::new(5)];
+    // Per share: page, content stream, QR image.
+    let page_ref = |i: usize, offset: i32| Ref::new(6 + 3 * i as i32 + offset);
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(pages_id);
+    pdf.pages(pages_id)
+        .kids((0..shares.len()).map(|i| page_ref(i, 0)))
+        .count(shares.len() as i32);
+    for (id, font) in font_ids
+        .iter()
+        .zip([b"Helvetica".as_slice(), b"Helvetica-Bold", b"Courier"])
+    {
+        pdf.type1_font(*id)
+            .base_font(Name(font))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+    }
+
+    for (i, share) in shares.iter().enumerate() {
+        let (page_id, content_id, image_id) = (page_ref(i, 0), page_ref(i, 1), page_ref(i, 2));
+
+        let modules = share_qr(&share.share).map_err(|e| format!("Share {}: {e}", share.index))?;
+        let (qr_side, samples) = qr_samples(&modules)?;
+        let mut image = pdf.image_xobject(image_id, &samples);
+        image.filter(Filter::FlateDecode);
+        image.width(qr_side);
+        image.height(qr_side);
+        image.color_space().device_gray();
+        image.bits_per_component(8);
+        image.interpolate(false); // keep module edges sharp when scaled
+        image.finish();
+
+        let content = page_content(share, metadata, cut_marks);
+        pdf.stream(content_id, &content);
+
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(pages_id);
+        page.contents(content_id);
+        let mut resources = page.resources();
+        resources
+            .fonts()
+            .pair(FONT_REGULAR, font_ids[0])
+            .pair(FONT_BOLD, font_ids[1])
+            .pair(FONT_MONO, font_ids[2]);
+        resources.x_objects().pair(QR_IMAGE, image_id);
+        resources.finish();
+        page.finish();
+    }
+
+    Ok(pdf.finish())
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Generates a printable recovery sheet with one page per share (QR code,
+/// share text, threshold info and vault label). `cut_marks` frames each
+/// share block with a dashed cut line. Returns the PDF as base64.
+#[tauri::command]
+pub fn generate_recovery_pdf(
+    shares: Vec<ShareInfo>,
+    metadata: RecoveryMetadata,
+    cut_marks: bool,
+) -> Result<String, SeqretsError> {
+    let pdf = Zeroizing::new(
+        build_recovery_pdf(&shares, &metadata, cut_marks).map_err(SeqretsError::InvalidInput)?,
+    );
+    Ok(STANDARD.encode(pdf.as_slice()))
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> RecoveryMetadata {
+        RecoveryMetadata {
+            label: "Family vault".to_string(),
+            threshold: 2,
+            total_shares: 3,
+            created_at: Some("2026-10-14".to_string()),
+        }
+    }
+
+    fn shares(n: u32) -> Vec<ShareInfo> {
+        (1..=n)
+            .map(|index| ShareInfo {
+                index,
+                share: format!("seQRets|{index}|{}", "A1b2C3d4".repeat(40)),
+            })
+            .collect()
+    }
+
+    fn count(haystack: &[u8], needle: &[u8]) -> usize {
+        haystack
+            .windows(needle.len())
+            .filter(|w| *w == needle)
+            .count()
+    }
+
+    #[test]
+    fn test_one_page_per_share() {
+        let pdf = build_recovery_pdf(&shares(3), &metadata(), false).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert_eq!(count(&pdf, b"/Type /Page\n"), 3);
+        assert_eq!(count(&pdf, b"/Subtype /Image"), 3);
+    }
+
+    #[test]
+    fn test_cut_marks_option() {
+        let plain = build_recovery_pdf(&shares(1), &metadata(), false).unwrap();
+        let cut = build_recovery_pdf(&shares(1), &metadata(), true).unwrap();
+        assert_eq!(count(&plain, b"Cut along the dashed line"), 0);
+        assert_eq!(count(&cut, b"Cut along the dashed line"), 1);
+    }
+
+    #[test]
+    fn test_wrapped_share_text_is_printed() {
+        let shares = shares(1);
+        let pdf = build_recovery_pdf(&shares, &metadata(), false).unwrap();
+        let first_line = &shares[0].share.as_bytes()[..SHARE_CHARS_PER_LINE];
+        assert_eq!(count(&pdf, first_line), 1);
+    }
+
+    #[test]
+    fn test_rejects_empty_and_oversized() {
+        assert!(build_recovery_pdf(&[], &metadata(), false).is_err());
+
+        let huge = vec![ShareInfo {
+            index: 1,
+            share: "x".repeat(5000),
+        }];
+        let err = build_recovery_pdf(&huge, &metadata(), false).unwrap_err();
+        assert!(
+            err.starts_with("Share 1:") && err.contains("too long"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn test_non_ascii_label_is_replaced() {
+        assert_eq!(pdf_text("Café"), b"Caf?".to_vec());
+    }
+}
@@ -0,0 +1,396 @@
+//! Native QR code generation for seQRets shares.
+//!
+//! Rendering in Rust keeps the share text out of the JS heap until the
+//! finished image is displayed: the frontend only ever receives PNG bytes.
+//!
+//! Payloads too large for one symbol (e.g. a full encrypted vault) are sent
+//! as an animated sequence of BC-UR `ur:bytes/...` parts. The parts are
+//! fountain-coded, so a scanner can reassemble the data from any sufficient
+//! subset, in any order, with duplicates.
+//!
+//! Decoding (`decode_qr`) accepts PNG or JPEG bytes, e.g. a photo or scan of
+//! a printed share, and returns every code found in the image.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use qrcode::{types::QrError, Color, EcLevel, QrCode};
+use serde::Deserialize;
+use zeroize::Zeroizing;
+
+use crate::encoding::{self, PayloadEncoding};
+use crate::error::SeqretsError;
+
+/// Extra fountain-mixed parts appended to an animated sequence, as a fraction
+/// of the fragment count. They let a scanner that missed frames finish
+/// before the loop comes round again.
+const UR_REDUNDANCY_DIVISOR: usize = 2;
+
+/// Pixels per QR module in the rendered PNG.
+const MODULE_PIXELS: usize = 8;
+/// Light border around the symbol, in modules (4 is the spec minimum).
+pub(crate) const QUIET_ZONE_MODULES: usize = 4;
+
+pub(crate) const DARK: u8 = 0x00;
+pub(crate) const LIGHT: u8 = 0xFF;
+
+/// QR error-correction level, as sent by the frontend.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EccLevel {
+    Low,      // ~7% recoverable
+    Medium,   // ~15%
+    Quartile, // ~25%
+    High,     // ~30%
+}
+
+impl EccLevel {
+    fn to_qrcode(self) -> EcLevel {
+        match self {
+            EccLevel::Low => EcLevel::L,
+            EccLevel::Medium => EcLevel::M,
+            EccLevel::Quartile => EcLevel::Q,
+            EccLevel::High => EcLevel::H,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            EccLevel::Low => "low",
+            EccLevel::Medium => "medium",
+            EccLevel::Quartile => "quartile",
+            EccLevel::High => "high",
+        }
+    }
+}
+
+/// QR module matrix: `width` × `width` cells, row-major, `true` = dark.
+pub(crate) struct QrModules {
+    pub width: usize,
+    pub dark: Zeroizing<Vec<bool>>,
+}
+
+/// Encodes `data` as a QR symbol at `ecc_level`.
+pub(crate) fn encode_qr(data: &[u8], ecc_level: EccLevel) -> Result<QrModules, String> {
+    let code =
+        QrCode::with_error_correction_level(data, ecc_level.to_qrcode()).map_err(|e| match e {
+            QrError::DataTooLong => format!(
+                "Data too long for a QR code at {} error correction ({} bytes). \
+             Choose a lower level or split the data.",
+                ecc_level.name(),
+                data.len()
+            ),
+            other => format!("QR encode error: {other}"),
+        })?;
+
+    let width = code.width();
+    let dark = code
+        .into_colors()
+        .into_iter()
+        .map(|c| c == Color::Dark)
+        .collect();
+    Ok(QrModules {
+        width,
+        dark: Zeroizing::new(dark),
+    })
+}
+
+/// Encodes `data` as a QR symbol at `ecc_level` and renders it to an 8-bit
+/// grayscale PNG with a quiet zone.
+pub(crate) fn render_qr_png(data: &[u8], ecc_level: EccLevel) -> Result<Vec<u8>, String> {
+    let modules = encode_qr(data, ecc_level)?;
+    let width = modules.width;
+    let side = (width + 2 * QUIET_ZONE_MODULES) * MODULE_PIXELS;
+
+    let mut pixels = Zeroizing::new(vec![LIGHT; side * side]);
+    for (i, _) in modules.dark.iter().enumerate().filter(|(_, &dark)| dark) {
+        let x0 = (i % width + QUIET_ZONE_MODULES) * MODULE_PIXELS;
+        let y0 = (i / width + QUIET_ZONE_MODULES) * MODULE_PIXELS;
+        for y in y0..y0 + MODULE_PIXELS {
+            pixels[y * side + x0..y * side + x0 + MODULE_PIXELS].fill(DARK);
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, side as u32, side as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("PNG encode error: {e}"))?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| format!("PNG encode error: {e}"))?;
+    writer
+        .finish()
+        .map_err(|e| format!("PNG encode error: {e}"))?;
+    Ok(out)
+}
+
+/// Splits `data` into `ur:bytes` parts: every plain fragment once, followed
+/// by fountain-mixed parts for redundancy. Data that fits in one fragment
+/// yields a single-part UR.
+fn ur_encode_parts(data: &[u8], max_fragment_len: usize) -> Result<Vec<String>, String> {
+    if data.is_empty() {
+        return Err("Nothing to encode.".to_string());
+    }
+    if max_fragment_len == 0 {
+        return Err("Fragment length must be at least 1 byte.".to_string());
+    }
+    let mut encoder =
+        ur::Encoder::bytes(data, max_fragment_len).map_err(|e| format!("UR encode error: {e}"))?;
+    let fragments = encoder.fragment_count();
+    let total = if fragments == 1 {
+        1
+    } else {
+        fragments + fragments.div_ceil(UR_REDUNDANCY_DIVISOR)
+    };
+    (0..total)
+        .map(|_| {
+            encoder
+                .next_part()
+                .map_err(|e| format!("UR encode error: {e}"))
+        })
+        .collect()
+}
+
+/// Reassembles the message from any sufficient set of UR parts.
+fn ur_decode_parts(parts: &[String]) -> Result<Vec<u8>, String> {
+    let mut decoder = ur::Decoder::default();
+    for part in parts {
+        let part = part.trim();
+        match ur::decode(part).map_err(|e| format!("Invalid UR part: {e}"))? {
+            (ur::ur::Kind::SinglePart, data) => return Ok(data),
+            (ur::ur::Kind::MultiPart, _) => decoder
+                .receive(part)
+                .map_err(|e| format!("Invalid UR part: {e}"))?,
+        }
+        if decoder.complete() {
+            break;
+        }
+    }
+    match decoder
+        .message()
+        .map_err(|e| format!("UR decode error: {e}"))?
+    {
+        Some(message) => Ok(message),
+        None => Err(format!(
+            "Not enough parts yet ({} scanned). Keep scanning the animated QR code.",
+            parts.len()
+        )),
+    }
+}
+
+/// Detects and decodes every QR code in a PNG/JPEG image.
+fn decode_qr_image(image_bytes: &[u8]) -> Result<Vec<String>, String> {
+    let luma = image::load_from_memory(image_bytes)
+        .map_err(|e| format!("Cannot read image (expected PNG or JPEG): {e}"))?
+        .into_luma8();
+    let (width, height) = luma.dimensions();
+    let mut prepared =
+        rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+            luma.get_pixel(x as u32, y as u32)[0]
+        });
+
+    let grids = prepared.detect_grids();
+    if grids.is_empty() {
+        return Err("No QR code found in the image.".to_string());
+    }
+
+    let mut payloads = Vec::with_capacity(grids.len());
+    let mut last_error = None;
+    for grid in &grids {
+        match grid.decode() {
+            Ok((_, content)) => payloads.push(content),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    match last_error {
+        Some(e) if payloads.is_empty() => {
+            Err(format!("QR code found but could not be decoded: {e}"))
+        }
+        _ => Ok(payloads),
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Renders `data` (typically a share string) as a QR code PNG at the given
+/// error-correction level ("low", "medium", "quartile" or "high").
+/// Returns the PNG as base64.
+#[tauri::command]
+pub fn share_to_qr_png(data: String, ecc_level: EccLevel) -> Result<String, SeqretsError> {
+    let data = Zeroizing::new(data);
+    let png = render_qr_png(data.as_bytes(), ecc_level).map_err(SeqretsError::InvalidInput)?;
+    Ok(STANDARD.encode(png))
+}
+
+/// Encodes base64 or base64url `data_b64` as an animated QR sequence of BC-UR
+/// parts, each carrying at most `max_fragment_len` payload bytes. Render each
+/// part with `share_to_qr_png` and loop the frames.
+#[tauri::command]
+pub fn encode_ur(data_b64: String, max_fragment_len: usize) -> Result<Vec<String>, SeqretsError> {
+    let data = Zeroizing::new(
+        encoding::decode(&data_b64)
+            .map_err(|e| SeqretsError::InvalidInput(format!("Base64 decode error: {e}")))?,
+    );
+    ur_encode_parts(&data, max_fragment_len).map_err(SeqretsError::InvalidInput)
+}
+
+/// Reassembles the payload from scanned UR parts (any order, duplicates
+/// allowed) and returns it as base64, or base64url when `encoding` is
+/// "base64url". Fails with a "not enough parts" error until the set is
+/// sufficient, so the caller can keep scanning and retry.
+#[tauri::command]
+pub fn decode_ur(parts: Vec<String>, encoding: Option<String>) -> Result<String, SeqretsError> {
+    let encoding = PayloadEncoding::from_name(encoding.as_deref())?;
+    let message = Zeroizing::new(ur_decode_parts(&parts).map_err(SeqretsError::CorruptData)?);
+    Ok(encoding.encode(message.as_slice()))
+}
+
+/// Decodes the QR code(s) in a base64 PNG/JPEG image and returns each
+/// payload string, in detection order. Fails with "No QR code found" when
+/// the image contains none.
+#[tauri::command]
+pub fn decode_qr(image_b64: String) -> Result<Vec<String>, SeqretsError> {
+    let image_bytes = STANDARD
+        .decode(&image_b64)
+        .map_err(|e| SeqretsError::InvalidInput(format!("Base64 decode error: {e}")))?;
+    decode_qr_image(&image_bytes).map_err(SeqretsError::CorruptData)
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+    fn png_side(png: &[u8]) -> u32 {
+        // IHDR width: bytes 16..20
+        u32::from_be_bytes(png[16..20].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_renders_png() {
+        let png = render_qr_png(b"seQRets share", EccLevel::Medium).unwrap();
+        assert_eq!(&png[..8], PNG_SIGNATURE);
+        assert_eq!(png_side(&png) as usize % MODULE_PIXELS, 0);
+    }
+
+    #[test]
+    fn test_higher_level_needs_larger_symbol() {
+        let data = [b'x'; 200];
+        let low = render_qr_png(&data, EccLevel::Low).unwrap();
+        let high = render_qr_png(&data, EccLevel::High).unwrap();
+        assert!(png_side(&high) > png_side(&low));
+    }
+
+    #[test]
+    fn test_data_too_long() {
+        // Byte-mode capacity at level H tops out at 1273 bytes.
+        let data = vec![0xA5u8; 1500];
+        let err = render_qr_png(&data, EccLevel::High).unwrap_err();
+        assert!(
+            err.contains("Data too long") && err.contains("high"),
+            "got: {err}"
+        );
+        assert!(render_qr_png(&data, EccLevel::Low).is_ok());
+    }
+
+    #[test]
+    fn test_ecc_level_deserializes_lowercase() {
+        let level: EccLevel = serde_json::from_str("\"quartile\"").unwrap();
+        assert_eq!(level, EccLevel::Quartile);
+    }
+
+    #[test]
+    fn test_ur_roundtrip_out_of_order_with_duplicates() {
+        let payload: Vec<u8> = (0..2048u32).map(|i| (i * 7 + 3) as u8).collect();
+        let parts = ur_encode_parts(&payload, 200).unwrap();
+        assert!(
+            parts.len() >= 11,
+            "2 KB at 200 bytes/fragment: got {} parts",
+            parts.len()
+        );
+        assert!(parts.iter().all(|p| p.starts_with("ur:bytes/")));
+
+        // Reverse the order and repeat every part, as a looping scan would.
+        let scanned: Vec<String> = parts
+            .iter()
+            .rev()
+            .flat_map(|p| [p.clone(), p.clone()])
+            .collect();
+        assert_eq!(ur_decode_parts(&scanned).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_ur_incomplete_set() {
+        let payload = vec![0x42u8; 2048];
+        let parts = ur_encode_parts(&payload, 200).unwrap();
+        let err = ur_decode_parts(&parts[..3]).unwrap_err();
+        assert!(err.contains("Not enough parts"), "got: {err}");
+    }
+
+    #[test]
+    fn test_ur_commands_accept_and_emit_base64url() {
+        let data: Vec<u8> = (0u8..=200).collect();
+        let parts = encode_ur(PayloadEncoding::Base64Url.encode(&data), 64).unwrap();
+        assert_eq!(
+            decode_ur(parts.clone(), Some("base64url".to_string())).unwrap(),
+            PayloadEncoding::Base64Url.encode(&data)
+        );
+        assert_eq!(decode_ur(parts, None).unwrap(), STANDARD.encode(&data));
+    }
+
+    #[test]
+    fn test_ur_single_part() {
+        let parts = ur_encode_parts(b"tiny", 200).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(ur_decode_parts(&parts).unwrap(), b"tiny");
+    }
+
+    fn to_png(image: image::GrayImage) -> Vec<u8> {
+        let mut out = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageLuma8(image)
+            .write_to(&mut out, image::ImageOutputFormat::Png)
+            .unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let png = render_qr_png(b"seQRets|share|1-of-3", EccLevel::Medium).unwrap();
+        assert_eq!(
+            decode_qr_image(&png).unwrap(),
+            vec!["seQRets|share|1-of-3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_decode_multiple_codes() {
+        let a = image::load_from_memory(&render_qr_png(b"share-A", EccLevel::Low).unwrap())
+            .unwrap()
+            .into_luma8();
+        let b = image::load_from_memory(&render_qr_png(b"share-B", EccLevel::Low).unwrap())
+            .unwrap()
+            .into_luma8();
+        let mut canvas = image::GrayImage::from_pixel(
+            a.width() + b.width(),
+            a.height().max(b.height()),
+            image::Luma([LIGHT]),
+        );
+        image::imageops::replace(&mut canvas, &a, 0, 0);
+        image::imageops::replace(&mut canvas, &b, a.width() as i64, 0);
+
+        let mut found = decode_qr_image(&to_png(canvas)).unwrap();
+        found.sort();
+        assert_eq!(found, vec!["share-A".to_string(), "share-B".to_string()]);
+    }
+
+    #[test]
+    fn test_decode_no_code() {
+        let blank = image::GrayImage::from_pixel(64, 64, image::Luma([LIGHT]));
+        let err = decode_qr_image(&to_png(blank)).unwrap_err();
+        assert!(err.contains("No QR code found"), "got: {err}");
+    }
+}
@@ -0,0 +1,154 @@
+//! Log redaction for seQRets desktop.
+//!
+//! Debug builds persist `log` output through `tauri_plugin_log`, so a stray
+//! `log::info!` with a password, PIN or share would end up on disk. Every
+//! record passes through `redact_log` before it is written: records from the
+//! modules that handle secrets are replaced outright, and anything else has
+//! long base64-looking runs masked. `Secret<T>` is for values that must not
+//! show up in `Debug`/`Display` output at all.
+//!
+//! No module logs sensitive arguments today; this is the backstop if one
+//! ever does.
+
+use std::borrow::Cow;
+use std::fmt;
+
+/// Wraps a sensitive value so `Debug` and `Display` print `***`. The value
+/// is still reachable as `.0` for code that means to use it.
+pub struct Secret<T>(pub T);
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+/// Modules whose log records are never written, whatever they contain.
+const SENSITIVE_MODULES: [&str; 8] = [
+    "crypto",
+    "keychain",
+    "keyfile",
+    "mnemonic",
+    "shamir",
+    "smartcard",
+    "stream",
+    "vault",
+];
+
+/// Base64/base64url/hex runs at least this long are masked. Shorter runs are
+/// ordinary words; a 16-byte salt is already 24 base64 characters.
+const MIN_BLOB_LENGTH: usize = 24;
+
+fn is_blob_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '-' | '_')
+}
+
+/// True for `app_lib::crypto`, `app_lib::smartcard::apdu` and so on.
+fn is_sensitive_target(target: &str) -> bool {
+    let mut parts = target.split("::");
+    parts.next() == Some(env!("CARGO_CRATE_NAME"))
+        && parts
+            .next()
+            .is_some_and(|module| SENSITIVE_MODULES.contains(&module))
+}
+
+/// Replaces runs of base64-alphabet characters of `MIN_BLOB_LENGTH` or more
+/// with `***`. Errs on the side of masking: a long path segment without
+/// dots is masked too.
+pub(crate) fn redact_blobs(message: &str) -> Cow<'_, str> {
+    let mut out = String::new();
+    let mut last = 0;
+    let mut run_start = None;
+    for (i, c) in message
+        .char_indices()
+        .chain(std::iter::once((message.len(), ' ')))
+    {
+        match (is_blob_char(c), run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                if i - start >= MIN_BLOB_LENGTH {
+                    out.push_str(&message[last..start]);
+                    out.push_str("***");
+                    last = i;
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if last == 0 {
+        return Cow::Borrowed(message);
+    }
+    out.push_str(&message[last..]);
+    Cow::Owned(out)
+}
+
+/// The text actually written for a log record from `target`.
+pub(crate) fn redact_log(target: &str, message: &str) -> String {
+    if is_sensitive_target(target) {
+        "*** (redacted)".to_string()
+    } else {
+        redact_blobs(message).into_owned()
+    }
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_never_formats_its_value() {
+        let pin = Secret("123456".to_string());
+        assert_eq!(format!("{pin}"), "***");
+        assert_eq!(format!("{pin:?}"), "***");
+        assert_eq!(format!("{:?}", Some(&pin)), "Some(***)");
+        assert_eq!(pin.0, "123456");
+    }
+
+    #[test]
+    fn test_long_base64_runs_are_masked() {
+        let share =
+            "seQRets|c2FsdHNhbHRzYWx0c2FsdA==|AbCdEfGhIjKlMnOpQrStUvWxYz0123456789+/|sha256:ab";
+        assert_eq!(redact_blobs(share), "seQRets|***|***|sha256:ab");
+        assert_eq!(
+            redact_blobs("key ab12cd34ef56ab12cd34ef56ab12cd34 end"),
+            "key *** end"
+        );
+    }
+
+    #[test]
+    fn test_ordinary_text_is_untouched() {
+        let message = "Card connected in reader 'ACS ACR39U 00 00' (122 bytes free)";
+        assert!(matches!(redact_blobs(message), Cow::Borrowed(_)));
+        assert_eq!(redact_blobs(message), message);
+    }
+
+    #[test]
+    fn test_sensitive_modules_are_fully_redacted() {
+        let target = |module: &str| format!("{}::{module}", env!("CARGO_CRATE_NAME"));
+        assert_eq!(
+            redact_log(&target("crypto"), "derived key for pw"),
+            "*** (redacted)"
+        );
+        assert_eq!(
+            redact_log(&target("smartcard::apdu"), "PIN 1234 accepted"),
+            "*** (redacted)"
+        );
+        assert_eq!(
+            redact_log(&target("qr"), "rendered 3 codes"),
+            "rendered 3 codes"
+        );
+        assert_eq!(
+            redact_log("other_crate::crypto", "window created"),
+            "window created"
+        );
+    }
+}
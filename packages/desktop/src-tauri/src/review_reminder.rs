@@ -23,6 +23,8 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 
+use crate::error::SeqretsError;
+
 const SIDECAR_FILENAME: &str = "review-reminder.json";
 /// Hard cap on sidecar size. A healthy record is well under 512 bytes;
 /// 4 KB leaves comfortable headroom without enabling JSON bombs.
@@ -47,8 +49,7 @@ fn sidecar_path(app: &AppHandle) -> Result<PathBuf, String> {
         .path()
         .app_data_dir()
         .map_err(|e| format!("Could not resolve app data dir: {e}"))?;
-    fs::create_dir_all(&dir)
-        .map_err(|e| format!("Could not create app data dir: {e}"))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create app data dir: {e}"))?;
     Ok(dir.join(SIDECAR_FILENAME))
 }
 
@@ -85,28 +86,26 @@ fn ensure_regular_file(path: &Path) -> Result<bool, String> {
 ///   unknown fields). The caller MUST surface the error rather than
 ///   silently recreating the file.
 #[tauri::command]
-pub fn reminder_read(app: AppHandle) -> Result<Option<ReminderSidecar>, String> {
+pub fn reminder_read(app: AppHandle) -> Result<Option<ReminderSidecar>, SeqretsError> {
     let path = sidecar_path(&app)?;
 
     if !ensure_regular_file(&path)? {
         return Ok(None);
     }
 
-    let meta = fs::metadata(&path)
-        .map_err(|e| format!("Could not stat sidecar: {e}"))?;
+    let meta = fs::metadata(&path).map_err(|e| format!("Could not stat sidecar: {e}"))?;
     if meta.len() > MAX_SIDECAR_BYTES {
-        return Err(format!(
+        return Err(SeqretsError::CorruptData(format!(
             "Sidecar is {} bytes; refusing to parse anything larger than {} bytes",
             meta.len(),
             MAX_SIDECAR_BYTES
-        ));
+        )));
     }
 
-    let bytes = fs::read(&path)
-        .map_err(|e| format!("Could not read sidecar: {e}"))?;
+    let bytes = fs::read(&path).map_err(|e| format!("Could not read sidecar: {e}"))?;
 
     let sidecar: ReminderSidecar = serde_json::from_slice(&bytes)
-        .map_err(|e| format!("Sidecar parse error: {e}"))?;
+        .map_err(|e| SeqretsError::CorruptData(format!("Sidecar parse error: {e}")))?;
 
     Ok(Some(sidecar))
 }
@@ -115,7 +114,7 @@ pub fn reminder_read(app: AppHandle) -> Result<Option<ReminderSidecar>, String>
 /// → rename over the target. On unix the temp file is created with mode
 /// 0600 and `O_NOFOLLOW` semantics so symlinks can't redirect the write.
 #[tauri::command]
-pub fn reminder_write(app: AppHandle, sidecar: ReminderSidecar) -> Result<(), String> {
+pub fn reminder_write(app: AppHandle, sidecar: ReminderSidecar) -> Result<(), SeqretsError> {
     let path = sidecar_path(&app)?;
 
     // Reject pre-existing non-regular targets. If nothing is there yet, fine.
@@ -125,11 +124,11 @@ pub fn reminder_write(app: AppHandle, sidecar: ReminderSidecar) -> Result<(), St
         .map_err(|e| format!("Could not serialize sidecar: {e}"))?;
 
     if serialized.len() as u64 > MAX_SIDECAR_BYTES {
-        return Err(format!(
+        return Err(SeqretsError::InvalidInput(format!(
             "Refusing to write {}-byte sidecar (cap is {} bytes)",
             serialized.len(),
             MAX_SIDECAR_BYTES
-        ));
+        )));
     }
 
     let tmp_path = path.with_extension("json.tmp");
@@ -160,8 +159,7 @@ pub fn reminder_write(app: AppHandle, sidecar: ReminderSidecar) -> Result<(), St
             .map_err(|e| format!("Could not fsync sidecar temp file: {e}"))?;
     }
 
-    fs::rename(&tmp_path, &path)
-        .map_err(|e| format!("Could not finalize sidecar write: {e}"))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Could not finalize sidecar write: {e}"))?;
 
     Ok(())
 }
@@ -170,13 +168,12 @@ pub fn reminder_write(app: AppHandle, sidecar: ReminderSidecar) -> Result<(), St
 /// Refuses to delete a path that has been replaced by a symlink or
 /// directory.
 #[tauri::command]
-pub fn reminder_delete(app: AppHandle) -> Result<(), String> {
+pub fn reminder_delete(app: AppHandle) -> Result<(), SeqretsError> {
     let path = sidecar_path(&app)?;
 
     if !ensure_regular_file(&path)? {
         return Ok(());
     }
 
-    fs::remove_file(&path)
-        .map_err(|e| format!("Could not delete sidecar: {e}"))
+    fs::remove_file(&path).map_err(|e| format!("Could not delete sidecar: {e}").into())
 }
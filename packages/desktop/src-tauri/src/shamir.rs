@@ -0,0 +1,909 @@
+//! Native Shamir's Secret Sharing over GF(256) for seQRets desktop.
+//!
+//! Splitting in Rust keeps the raw secret in the same memory space as the
+//! decryption step, so it never has to cross the IPC boundary in plaintext.
+//!
+//! Field arithmetic uses the AES polynomial x^8 + x^4 + x^3 + x + 1 (0x11B),
+//! the same field as the `shamir-secret-sharing` JS library. Multiplication is
+//! branch-free (no log/exp lookup tables) to avoid cache-timing leaks.
+//!
+//! Share wire format (base64, or unpadded base64url on request):
+//!   [0]    format version (SHARE_VERSION), with SHARE_FLAG_BASE64URL set
+//!          when the share text is base64url
+//!   [1]    share index x (1..=255)
+//!   [2]    threshold
+//!   [3..7] secret-group id — first 4 bytes of SHA-256(GROUP_ID_DOMAIN || secret)
+//!   [7..]  y values — each secret byte's polynomial evaluated at x
+//!
+//! The metadata lets `shamir_combine` reject shares from different secrets or
+//! below-threshold share sets with a precise error, instead of silently
+//! producing a wrong reconstruction.
+//!
+//! Keyfile shares (`split_keyfile`) are the same share in standard base64
+//! behind `KEYFILE_SHARE_PREFIX`, so a card item of type "keyfile" holding
+//! one is never mistaken for the keyfile itself.
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::crypto;
+use crate::encoding::{self, PayloadEncoding};
+use crate::error::SeqretsError;
+
+/// Maximum number of shares — x must be a distinct non-zero field element.
+const MAX_SHARES: usize = 255;
+
+const SHARE_VERSION: u8 = 1;
+/// Set on the version byte of shares emitted as base64url, so the text
+/// encoding is recorded in the share itself.
+const SHARE_FLAG_BASE64URL: u8 = 0x80;
+const GROUP_ID_LENGTH: usize = 4;
+const SHARE_HEADER_LENGTH: usize = 3 + GROUP_ID_LENGTH;
+
+/// Domain separator so the group id is never a bare hash of the secret.
+const GROUP_ID_DOMAIN: &[u8] = b"seQRets-shamir-group-v1";
+
+/// Leads the text of a keyfile share. `|` is outside both base64 alphabets.
+const KEYFILE_SHARE_PREFIX: &str = "seQRets-keyfile|";
+
+/// A decoded share: metadata plus the raw y values.
+struct Share {
+    index: u8,
+    threshold: u8,
+    group_id: [u8; GROUP_ID_LENGTH],
+    ys: Vec<u8>,
+}
+
+impl Share {
+    fn encode(&self, encoding: PayloadEncoding) -> Vec<u8> {
+        let mut out = Vec::with_capacity(SHARE_HEADER_LENGTH + self.ys.len());
+        out.push(match encoding {
+            PayloadEncoding::Base64 => SHARE_VERSION,
+            PayloadEncoding::Base64Url => SHARE_VERSION | SHARE_FLAG_BASE64URL,
+        });
+        out.push(self.index);
+        out.push(self.threshold);
+        out.extend_from_slice(&self.group_id);
+        out.extend_from_slice(&self.ys);
+        out
+    }
+
+    /// `position` is the 1-based position in the caller's list, for error messages.
+    /// Also returns the text encoding recorded in the version byte.
+    fn decode(bytes: &[u8], position: usize) -> Result<(Share, PayloadEncoding), String> {
+        if bytes.len() <= SHARE_HEADER_LENGTH {
+            return Err(format!(
+                "Share {} is too short to be a seQRets share",
+                position
+            ));
+        }
+        let version = bytes[0] & !SHARE_FLAG_BASE64URL;
+        if version != SHARE_VERSION {
+            return Err(format!(
+                "Share {} has unsupported format version {}",
+                position, version
+            ));
+        }
+        let encoding = if bytes[0] & SHARE_FLAG_BASE64URL != 0 {
+            PayloadEncoding::Base64Url
+        } else {
+            PayloadEncoding::Base64
+        };
+        let mut group_id = [0u8; GROUP_ID_LENGTH];
+        group_id.copy_from_slice(&bytes[3..SHARE_HEADER_LENGTH]);
+        let share = Share {
+            index: bytes[1],
+            threshold: bytes[2],
+            group_id,
+            ys: bytes[SHARE_HEADER_LENGTH..].to_vec(),
+        };
+        Ok((share, encoding))
+    }
+}
+
+/// Returned by `preview_reconstruction`. Says whether a set of shares would
+/// reconstruct, never what it reconstructs to. Positions are 0-based indices
+/// into the caller's list.
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct ReconstructionPreview {
+    /// Shares needed, as recorded in the first readable share.
+    pub threshold: Option<u8>,
+    /// Distinct share indices present, ascending.
+    pub indices: Vec<u8>,
+    /// Shares that aren't valid seQRets share text.
+    pub unreadable: Vec<usize>,
+    /// Shares whose group id, threshold or length differs from the first
+    /// readable share.
+    pub foreign: Vec<usize>,
+    /// Shares repeating an index already seen earlier in the list.
+    pub duplicates: Vec<usize>,
+    /// True when every readable share belongs to the same secret group.
+    pub same_group: bool,
+    /// True when enough distinct shares of one group are present.
+    pub threshold_met: bool,
+    /// True when the shares reconstruct a secret that passes the group-id
+    /// integrity check, i.e. `shamir_combine` would succeed.
+    pub ready: bool,
+}
+
+/// One share found by `import_shares_from_text`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ParsedShare {
+    /// 1-based line of the input the share was found on.
+    pub line: usize,
+    /// The share text with whitespace and quotes removed, as `shamir_combine`
+    /// takes it.
+    pub share: String,
+    /// Header fields, when the share decodes.
+    pub index: Option<u8>,
+    pub threshold: Option<u8>,
+    /// Secret-group id in hex.
+    pub group_id: Option<String>,
+    /// Why the share can't be combined, or `None` when it can.
+    pub error: Option<String>,
+}
+
+/// Derive the 4-byte secret-group id that ties shares to their secret.
+fn group_id(secret: &[u8]) -> [u8; GROUP_ID_LENGTH] {
+    let digest = Sha256::new()
+        .chain_update(GROUP_ID_DOMAIN)
+        .chain_update(secret)
+        .finalize();
+    let mut id = [0u8; GROUP_ID_LENGTH];
+    id.copy_from_slice(&digest[..GROUP_ID_LENGTH]);
+    id
+}
+
+// ── GF(256) arithmetic ───────────────────────────────────────────────────────
+
+/// Multiply two field elements (Russian-peasant, reduced modulo 0x11B).
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        // Add `a` when the low bit of `b` is set, without branching on secret data.
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1B & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse via a^254 (a^255 = 1 for all non-zero a).
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Evaluate a polynomial (coefficients lowest-degree first) at x (Horner's method).
+fn eval_poly(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, &c| gf_mul(acc, x) ^ c)
+}
+
+/// Lagrange-interpolate the points `(xs[i], ys[i])` at x = 0.
+fn interpolate_at_zero(xs: &[u8], ys: &[u8]) -> u8 {
+    let mut result = 0u8;
+    for (i, (&xi, &yi)) in xs.iter().zip(ys).enumerate() {
+        let mut basis = 1u8;
+        for (j, &xj) in xs.iter().enumerate() {
+            if i != j {
+                // In GF(2^n) subtraction is XOR: (0 - xj) / (xi - xj) = xj / (xi ^ xj)
+                basis = gf_mul(basis, gf_mul(xj, gf_inv(xi ^ xj)));
+            }
+        }
+        result ^= gf_mul(yi, basis);
+    }
+    result
+}
+
+// ── Split / combine ──────────────────────────────────────────────────────────
+
+/// Split `secret` into `shares` shares, any `threshold` of which recover it.
+fn split(secret: &[u8], threshold: usize, shares: usize) -> Result<Vec<Share>, String> {
+    if secret.is_empty() {
+        return Err("Secret must not be empty".to_string());
+    }
+    if threshold < 2 {
+        return Err("Threshold must be at least 2".to_string());
+    }
+    if shares < threshold {
+        return Err(format!(
+            "Share count ({}) must be at least the threshold ({})",
+            shares, threshold
+        ));
+    }
+    if shares > MAX_SHARES {
+        return Err(format!("Share count must be at most {}", MAX_SHARES));
+    }
+
+    let group_id = group_id(secret);
+    let mut outputs: Vec<Share> = (1..=shares)
+        .map(|x| Share {
+            index: x as u8,
+            threshold: threshold as u8,
+            group_id,
+            ys: vec![0u8; secret.len()],
+        })
+        .collect();
+
+    // One random polynomial per secret byte; coefficient 0 is the secret byte.
+    let mut coefficients = Zeroizing::new(vec![0u8; threshold]);
+    for (byte_index, &secret_byte) in secret.iter().enumerate() {
+        coefficients[0] = secret_byte;
+        rand::rng().fill_bytes(&mut coefficients[1..]);
+        for share in outputs.iter_mut() {
+            share.ys[byte_index] = eval_poly(&coefficients, share.index);
+        }
+    }
+
+    Ok(outputs)
+}
+
+/// Check that shares belong to one secret group and meet its threshold.
+/// Error messages use 1-based share positions.
+fn validate_share_set(shares: &[Share]) -> Result<(), SeqretsError> {
+    let first = shares
+        .first()
+        .ok_or_else(|| SeqretsError::InvalidInput("No shares provided".to_string()))?;
+
+    for (i, share) in shares.iter().enumerate() {
+        if share.group_id != first.group_id
+            || share.threshold != first.threshold
+            || share.ys.len() != first.ys.len()
+        {
+            return Err(SeqretsError::InvalidInput(format!(
+                "Share {} belongs to a different secret",
+                i + 1
+            )));
+        }
+        if share.index == 0 {
+            return Err(SeqretsError::CorruptData(format!(
+                "Share {} has an invalid index (0)",
+                i + 1
+            )));
+        }
+        if let Some(j) = shares[..i].iter().position(|s| s.index == share.index) {
+            return Err(SeqretsError::InvalidInput(format!(
+                "Shares {} and {} are the same share (index {})",
+                j + 1,
+                i + 1,
+                share.index
+            )));
+        }
+    }
+
+    let threshold = first.threshold as usize;
+    if shares.len() < threshold {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Need {} shares, got {}",
+            threshold,
+            shares.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Recombine shares into the secret after validating their metadata, then
+/// confirm the result hashes back to the shares' group id.
+fn combine(shares: &[Share]) -> Result<Zeroizing<Vec<u8>>, SeqretsError> {
+    validate_share_set(shares)?;
+
+    let xs: Vec<u8> = shares.iter().map(|s| s.index).collect();
+    let secret_len = shares[0].ys.len();
+
+    let mut secret = Zeroizing::new(vec![0u8; secret_len]);
+    let mut ys = Zeroizing::new(vec![0u8; shares.len()]);
+    for (byte_index, out) in secret.iter_mut().enumerate() {
+        for (y, share) in ys.iter_mut().zip(shares) {
+            *y = share.ys[byte_index];
+        }
+        *out = interpolate_at_zero(&xs, &ys);
+    }
+
+    if !crypto::ct_eq(&group_id(&secret), &shares[0].group_id) {
+        return Err(SeqretsError::CorruptData(
+            "Reconstructed secret failed its integrity check — a share may be corrupted"
+                .to_string(),
+        ));
+    }
+
+    Ok(secret)
+}
+
+/// Decode one share's text, checking it against the encoding recorded in its
+/// header. `position` is 1-based, for error messages.
+fn decode_share(text: &str, position: usize) -> Result<(Share, PayloadEncoding), String> {
+    let bytes = encoding::decode(text)
+        .map_err(|e| format!("Share {} base64 decode error: {e}", position))?;
+    let (share, encoding) = Share::decode(&bytes, position)?;
+    if !encoding.matches(text) {
+        return Err(format!(
+            "Share {} text doesn't match the encoding recorded in its header",
+            position
+        ));
+    }
+    Ok((share, encoding))
+}
+
+/// Build the preview for `shares`. The trial reconstruction stays in this
+/// function and is zeroized on return.
+fn preview(shares: &[String]) -> ReconstructionPreview {
+    let mut report = ReconstructionPreview::default();
+    let mut group: Vec<Share> = Vec::new();
+
+    for (i, text) in shares.iter().enumerate() {
+        let share = match decode_share(text, i + 1) {
+            Ok((share, _)) if share.index != 0 => share,
+            _ => {
+                report.unreadable.push(i);
+                continue;
+            }
+        };
+        if let Some(first) = group.first() {
+            if share.group_id != first.group_id
+                || share.threshold != first.threshold
+                || share.ys.len() != first.ys.len()
+            {
+                report.foreign.push(i);
+                continue;
+            }
+        }
+        if group.iter().any(|s| s.index == share.index) {
+            report.duplicates.push(i);
+            continue;
+        }
+        group.push(share);
+    }
+
+    report.threshold = group.first().map(|s| s.threshold);
+    report.indices = group.iter().map(|s| s.index).collect();
+    report.indices.sort_unstable();
+    report.same_group = report.foreign.is_empty();
+    report.threshold_met = report.threshold.is_some_and(|t| group.len() >= t as usize);
+    report.ready = report.threshold_met && combine(&group).is_ok();
+    report
+}
+
+/// Split pasted text into share candidates: one per comma-separated piece of
+/// each line, with whitespace (e.g. transcription grouping) and surrounding
+/// quotes or brackets removed. Blank pieces are skipped.
+fn share_candidates(text: &str) -> Vec<(usize, String)> {
+    let mut candidates = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        for piece in line.split(',') {
+            let share: String = piece
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+                .trim_matches(|c| matches!(c, '"' | '\'' | '[' | ']'))
+                .to_string();
+            if !share.is_empty() {
+                candidates.push((i + 1, share));
+            }
+        }
+    }
+    candidates
+}
+
+/// Parse and validate every share in `text`. Shares are checked against the
+/// first valid one the way `shamir_combine` would, so a line that decodes but
+/// belongs to another secret or repeats an index is still reported.
+fn parse_shares(text: &str) -> Vec<ParsedShare> {
+    let mut parsed: Vec<ParsedShare> = Vec::new();
+    let mut group: Vec<(usize, Share)> = Vec::new();
+
+    for (position, (line, share_text)) in share_candidates(text).into_iter().enumerate() {
+        let mut entry = ParsedShare {
+            line,
+            share: share_text,
+            index: None,
+            threshold: None,
+            group_id: None,
+            error: None,
+        };
+        let decoded = if is_keyfile_share(&entry.share) {
+            Err("This is a keyfile share; combine it with combine_keyfile".to_string())
+        } else {
+            decode_share(&entry.share, position + 1).map(|(share, _)| share)
+        };
+        match decoded {
+            Ok(share) => {
+                entry.index = Some(share.index);
+                entry.threshold = Some(share.threshold);
+                entry.group_id = Some(share.group_id.iter().map(|b| format!("{b:02x}")).collect());
+                let foreign = group.first().filter(|(_, first)| {
+                    share.group_id != first.group_id
+                        || share.threshold != first.threshold
+                        || share.ys.len() != first.ys.len()
+                });
+                entry.error = if share.index == 0 {
+                    Some("Invalid share index (0)".to_string())
+                } else if let Some((first_line, _)) = foreign {
+                    Some(format!(
+                        "Belongs to a different secret than the share on line {first_line}"
+                    ))
+                } else if let Some((dup_line, _)) =
+                    group.iter().find(|(_, s)| s.index == share.index)
+                {
+                    Some(format!(
+                        "Repeats share {} from line {dup_line}",
+                        share.index
+                    ))
+                } else {
+                    group.push((line, share));
+                    None
+                };
+            }
+            Err(e) => entry.error = Some(e),
+        }
+        parsed.push(entry);
+    }
+    parsed
+}
+
+/// Outcome of `recover`. `shares` follows the caller's list.
+pub(crate) struct Recovery {
+    /// The secret in the encoding of the first share used, or why there is none.
+    pub(crate) secret: Result<String, SeqretsError>,
+    /// As recorded in the shares used.
+    pub(crate) threshold: Option<u8>,
+    /// Per share, its index when it went into the reconstruction, or why not.
+    pub(crate) shares: Vec<Result<u8, String>>,
+}
+
+/// Recombine whichever of `shares` can be: unreadable shares, shares of a
+/// different secret and repeated indices are set aside with a reason
+/// instead of failing the whole set. When shares of several secrets are
+/// mixed, the one with the most distinct shares is recovered.
+pub(crate) fn recover(shares: &[&str]) -> Recovery {
+    let decoded: Vec<Result<(Share, PayloadEncoding), String>> = shares
+        .iter()
+        .enumerate()
+        .map(|(i, text)| match decode_share(text, i + 1) {
+            Ok((share, _)) if share.index == 0 => {
+                Err(format!("Share {} has an invalid index (0)", i + 1))
+            }
+            other => other,
+        })
+        .collect();
+
+    let same_secret = |a: &Share, b: &Share| {
+        a.group_id == b.group_id && a.threshold == b.threshold && a.ys.len() == b.ys.len()
+    };
+    let distinct_in_group = |share: &Share| {
+        let mut indices: Vec<u8> = decoded
+            .iter()
+            .filter_map(|d| d.as_ref().ok())
+            .filter(|(other, _)| same_secret(share, other))
+            .map(|(other, _)| other.index)
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.len()
+    };
+    // max_by_key keeps the last of equal elements; reverse so the first wins.
+    let chosen = decoded
+        .iter()
+        .filter_map(|d| d.as_ref().ok())
+        .rev()
+        .max_by_key(|(share, _)| distinct_in_group(share))
+        .map(|(share, encoding)| (share.group_id, share.threshold, share.ys.len(), *encoding));
+
+    let mut group: Vec<Share> = Vec::new();
+    let mut reports = Vec::with_capacity(decoded.len());
+    for d in decoded {
+        let share = match d {
+            Ok((share, _)) => share,
+            Err(e) => {
+                reports.push(Err(e));
+                continue;
+            }
+        };
+        if chosen.map(|(id, t, len, _)| (id, t, len))
+            != Some((share.group_id, share.threshold, share.ys.len()))
+        {
+            reports.push(Err(
+                "Belongs to a different secret than the other shares".to_string()
+            ));
+        } else if group.iter().any(|s| s.index == share.index) {
+            reports.push(Err(format!("Repeats share {}", share.index)));
+        } else {
+            reports.push(Ok(share.index));
+            group.push(share);
+        }
+    }
+
+    let secret = match chosen {
+        None => Err(SeqretsError::InvalidInput(
+            "None of the shares could be read".to_string(),
+        )),
+        Some((_, _, _, encoding)) => {
+            combine(&group).map(|secret| encoding.encode(secret.as_slice()))
+        }
+    };
+    Recovery {
+        secret,
+        threshold: chosen.map(|(_, t, _, _)| t),
+        shares: reports,
+    }
+}
+
+/// True when `text` is share text `shamir_combine` can read.
+pub(crate) fn is_share(text: &str) -> bool {
+    decode_share(text, 1).is_ok()
+}
+
+/// True when `text` is a keyfile share from `split_keyfile` rather than a keyfile.
+pub(crate) fn is_keyfile_share(text: &str) -> bool {
+    text.starts_with(KEYFILE_SHARE_PREFIX)
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Splits a base64 or base64url secret into `shares` shares, any
+/// `threshold` of which reconstruct it. `encoding` ("base64" or
+/// "base64url") selects the share text encoding and defaults to base64.
+#[tauri::command]
+pub fn shamir_split(
+    secret_b64: String,
+    threshold: u8,
+    shares: u8,
+    encoding: Option<String>,
+) -> Result<Vec<String>, SeqretsError> {
+    let encoding = PayloadEncoding::from_name(encoding.as_deref())?;
+    let secret = Zeroizing::new(
+        encoding::decode(&secret_b64)
+            .map_err(|e| SeqretsError::InvalidInput(format!("Secret base64 decode error: {e}")))?,
+    );
+
+    let split_shares =
+        split(&secret, threshold as usize, shares as usize).map_err(SeqretsError::InvalidInput)?;
+    Ok(split_shares
+        .iter()
+        .map(|s| encoding.encode(&s.encode(encoding)))
+        .collect())
+}
+
+/// Reconstructs the secret from base64 or base64url shares, returning it in
+/// the first share's encoding. Fails with a precise error if the shares come
+/// from different secrets, fewer than the threshold are supplied, or a
+/// share's text doesn't match the encoding recorded in its header.
+#[tauri::command]
+pub fn shamir_combine(shares: Vec<String>) -> Result<String, SeqretsError> {
+    let decoded = shares
+        .iter()
+        .enumerate()
+        .map(|(i, s)| decode_share(s, i + 1))
+        .collect::<Result<Vec<(Share, PayloadEncoding)>, String>>()
+        .map_err(SeqretsError::CorruptData)?;
+
+    let output_encoding = decoded.first().map(|(_, e)| *e).unwrap_or_default();
+    let decoded: Vec<Share> = decoded.into_iter().map(|(share, _)| share).collect();
+    let secret = combine(&decoded)?;
+    Ok(output_encoding.encode(secret.as_slice()))
+}
+
+/// Splits a base64 keyfile into `shares` keyfile shares, any `threshold` of
+/// which reconstruct it with `combine_keyfile`. Each is
+/// `seQRets-keyfile|<share base64>`, to be stored on a card as a "keyfile"
+/// item, so the keyfile is no more a single point of failure than the secret.
+#[tauri::command]
+pub fn split_keyfile(
+    keyfile_b64: String,
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<String>, SeqretsError> {
+    let keyfile =
+        Zeroizing::new(encoding::decode(keyfile_b64.trim()).map_err(|e| {
+            SeqretsError::InvalidInput(format!("Keyfile base64 decode error: {e}"))
+        })?);
+    if keyfile.is_empty() {
+        return Err(SeqretsError::InvalidInput(
+            "The keyfile is empty.".to_string(),
+        ));
+    }
+
+    let split_shares =
+        split(&keyfile, threshold as usize, shares as usize).map_err(SeqretsError::InvalidInput)?;
+    Ok(split_shares
+        .iter()
+        .map(|s| {
+            format!(
+                "{KEYFILE_SHARE_PREFIX}{}",
+                PayloadEncoding::Base64.encode(&s.encode(PayloadEncoding::Base64))
+            )
+        })
+        .collect())
+}
+
+/// Reconstructs a keyfile from `split_keyfile` shares, returned as the
+/// standard base64 the crypto commands take in `keyfiles_b64`. Fails like
+/// `shamir_combine`, and with `CorruptData` for text that isn't a keyfile share.
+#[tauri::command]
+pub fn combine_keyfile(shares: Vec<String>) -> Result<String, SeqretsError> {
+    let decoded = shares
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match s.trim().strip_prefix(KEYFILE_SHARE_PREFIX) {
+            Some(share) => decode_share(share, i + 1).map(|(share, _)| share),
+            None => Err(format!("Share {} is not a seQRets keyfile share", i + 1)),
+        })
+        .collect::<Result<Vec<Share>, String>>()
+        .map_err(SeqretsError::CorruptData)?;
+
+    let keyfile = combine(&decoded)?;
+    Ok(PayloadEncoding::Base64.encode(keyfile.as_slice()))
+}
+
+/// Reports whether `shares` are enough to recover their secret (the
+/// threshold, whether they share one secret group, which indices are present)
+/// without returning the secret itself; `shamir_combine` is the explicit
+/// reveal step. Shares that can't be read or belong to another group are
+/// listed rather than failing the preview.
+#[tauri::command]
+pub fn preview_reconstruction(shares: Vec<String>) -> ReconstructionPreview {
+    preview(&shares)
+}
+
+/// Parses shares pasted or loaded as text — newline- or comma-separated,
+/// with any whitespace, quotes or brackets around or inside them — and
+/// validates each. Every candidate is returned with its line number; those
+/// with an `error` (unreadable, another secret, a repeated index) should be
+/// left out, and the `share` text of the rest passed to `shamir_combine`.
+#[tauri::command]
+pub fn import_shares_from_text(text: String) -> Vec<ParsedShare> {
+    parse_shares(&text)
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    #[test]
+    fn test_gf_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1, "a * a^-1 must be 1 for a = {a}");
+        }
+    }
+
+    // Split a 32-byte seed 3-of-5 and reconstruct from every subset of size >= 3.
+    #[test]
+    fn test_split_combine_every_valid_subset() {
+        let seed: Vec<u8> = (0u8..32)
+            .map(|i| i.wrapping_mul(37).wrapping_add(11))
+            .collect();
+        let secret_b64 = STANDARD.encode(&seed);
+
+        let shares = shamir_split(secret_b64.clone(), 3, 5, None).expect("split should succeed");
+        assert_eq!(shares.len(), 5);
+
+        let mut subsets_checked = 0;
+        for mask in 0u32..(1 << 5) {
+            if mask.count_ones() < 3 {
+                continue;
+            }
+            let subset: Vec<String> = (0..5)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| shares[i].clone())
+                .collect();
+            let recovered = shamir_combine(subset).expect("combine should succeed");
+            assert_eq!(
+                recovered, secret_b64,
+                "subset mask {mask:05b} must reconstruct the seed"
+            );
+            subsets_checked += 1;
+        }
+        assert_eq!(subsets_checked, 16);
+    }
+
+    #[test]
+    fn test_keyfile_shares_roundtrip_and_are_marked() {
+        let keyfile = STANDARD.encode((0u8..64).collect::<Vec<u8>>());
+        let shares = split_keyfile(keyfile.clone(), 2, 3).unwrap();
+        assert!(shares.iter().all(|share| is_keyfile_share(share)));
+        assert!(!is_keyfile_share(&keyfile));
+        assert_eq!(combine_keyfile(shares[1..].to_vec()).unwrap(), keyfile);
+        assert_eq!(
+            combine_keyfile(vec![shares[2].clone(), shares[0].clone()]).unwrap(),
+            keyfile
+        );
+
+        // Plain secret shares aren't keyfile shares, and one share is below threshold
+        let plain = shamir_split(keyfile.clone(), 2, 2, None).unwrap();
+        assert!(matches!(
+            combine_keyfile(plain),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        assert!(combine_keyfile(shares[..1].to_vec()).is_err());
+        assert!(matches!(
+            split_keyfile(String::new(), 2, 3),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_base64url_shares_record_their_encoding() {
+        let seed = [0xFBu8; 48];
+        let url_secret = PayloadEncoding::Base64Url.encode(&seed);
+        let shares = shamir_split(url_secret.clone(), 2, 3, Some("base64url".to_string())).unwrap();
+        for share in &shares {
+            assert!(PayloadEncoding::Base64Url.matches(share), "{share}");
+            assert_eq!(
+                encoding::decode(share).unwrap()[0],
+                SHARE_VERSION | SHARE_FLAG_BASE64URL
+            );
+        }
+        assert_eq!(shamir_combine(shares[1..].to_vec()).unwrap(), url_secret);
+
+        // Standard shares of the same secret combine back to standard base64.
+        let standard = shamir_split(url_secret, 2, 3, None).unwrap();
+        assert_eq!(
+            shamir_combine(standard[..2].to_vec()).unwrap(),
+            STANDARD.encode(seed)
+        );
+    }
+
+    #[test]
+    fn test_share_text_must_match_recorded_encoding() {
+        let shares = shamir_split(
+            STANDARD.encode([7u8; 33]),
+            2,
+            2,
+            Some("base64url".to_string()),
+        )
+        .unwrap();
+        let bytes = encoding::decode(&shares[0]).unwrap();
+        let relabelled = vec![STANDARD.encode(&bytes), shares[1].clone()];
+        let err = shamir_combine(relabelled).unwrap_err();
+        assert!(matches!(err, SeqretsError::CorruptData(_)), "got: {err:?}");
+    }
+
+    #[test]
+    fn test_below_threshold_rejected() {
+        let seed = [0x42u8; 32];
+        let shares = shamir_split(STANDARD.encode(seed), 3, 5, None).unwrap();
+
+        let err = shamir_combine(shares[..2].to_vec()).unwrap_err();
+        assert_eq!(
+            err,
+            SeqretsError::InvalidInput("Need 3 shares, got 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_mixed_secrets_rejected() {
+        let shares_a = shamir_split(STANDARD.encode([0xAAu8; 32]), 2, 3, None).unwrap();
+        let shares_b = shamir_split(STANDARD.encode([0xBBu8; 32]), 2, 3, None).unwrap();
+
+        let mixed = vec![
+            shares_a[0].clone(),
+            shares_a[1].clone(),
+            shares_b[2].clone(),
+        ];
+        let err = shamir_combine(mixed).unwrap_err();
+        assert_eq!(
+            err,
+            SeqretsError::InvalidInput("Share 3 belongs to a different secret".to_string())
+        );
+    }
+
+    #[test]
+    fn test_preview_reports_without_reconstructing() {
+        let shares = shamir_split(STANDARD.encode([0x5Cu8; 32]), 3, 5, None).unwrap();
+        let other = shamir_split(STANDARD.encode([0x5Du8; 32]), 3, 5, None).unwrap();
+
+        let short = preview_reconstruction(vec![shares[4].clone(), shares[1].clone()]);
+        assert_eq!(short.threshold, Some(3));
+        assert_eq!(short.indices, vec![2, 5]);
+        assert!(short.same_group && !short.threshold_met && !short.ready);
+
+        let messy = vec![
+            shares[0].clone(),
+            "not a share".to_string(),
+            other[1].clone(),
+            shares[0].clone(),
+            shares[2].clone(),
+            shares[3].clone(),
+        ];
+        let report = preview_reconstruction(messy);
+        assert_eq!(report.unreadable, vec![1]);
+        assert_eq!(report.foreign, vec![2]);
+        assert_eq!(report.duplicates, vec![3]);
+        assert_eq!(report.indices, vec![1, 3, 4]);
+        assert!(!report.same_group);
+        assert!(report.threshold_met && report.ready);
+
+        // A corrupted y value still meets the threshold but fails the integrity check.
+        let mut bytes = STANDARD.decode(&shares[0]).unwrap();
+        bytes[SHARE_HEADER_LENGTH] ^= 1;
+        let tampered = vec![STANDARD.encode(bytes), shares[1].clone(), shares[2].clone()];
+        let report = preview_reconstruction(tampered);
+        assert!(report.threshold_met && !report.ready);
+    }
+
+    #[test]
+    fn test_import_shares_from_text() {
+        let shares = shamir_split(STANDARD.encode([0x6Au8; 16]), 2, 3, None).unwrap();
+        let other = shamir_split(STANDARD.encode([0x6Bu8; 16]), 2, 3, None).unwrap();
+        let (head, tail) = shares[2].split_at(8);
+        let text = format!(
+            "  {}  \n\n\"{head} {tail}\", nonsense\n{}\n{},{}",
+            shares[0], other[1], shares[0], KEYFILE_SHARE_PREFIX
+        );
+
+        let parsed = import_shares_from_text(text);
+        let lines: Vec<usize> = parsed.iter().map(|p| p.line).collect();
+        assert_eq!(lines, vec![1, 3, 3, 4, 5, 5]);
+        assert_eq!(parsed[0].index, Some(1));
+        assert_eq!(parsed[0].threshold, Some(2));
+        assert_eq!(parsed[1].share, shares[2]);
+        assert!(parsed[0].error.is_none() && parsed[1].error.is_none());
+        assert!(parsed[2].error.is_some() && parsed[2].index.is_none());
+        assert!(parsed[3]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("different secret"));
+        assert!(parsed[4].error.as_deref().unwrap().contains("line 1"));
+        assert!(parsed[5].error.as_deref().unwrap().contains("keyfile"));
+
+        let valid: Vec<String> = parsed
+            .into_iter()
+            .filter(|p| p.error.is_none())
+            .map(|p| p.share)
+            .collect();
+        assert_eq!(
+            shamir_combine(valid).unwrap(),
+            STANDARD.encode([0x6Au8; 16])
+        );
+    }
+
+    #[test]
+    fn test_recover_sets_aside_bad_shares() {
+        let shares = shamir_split(STANDARD.encode([0x5Cu8; 32]), 2, 3, None).unwrap();
+        let other = shamir_split(STANDARD.encode([0x5Du8; 32]), 2, 3, None).unwrap();
+        let recovery = recover(&[&other[0], "not a share", &shares[1], &shares[1], &shares[2]]);
+        assert_eq!(recovery.secret.unwrap(), STANDARD.encode([0x5Cu8; 32]));
+        assert_eq!(recovery.threshold, Some(2));
+        assert!(recovery.shares[0]
+            .as_ref()
+            .unwrap_err()
+            .contains("different secret"));
+        assert!(recovery.shares[1].is_err());
+        assert_eq!(recovery.shares[2], Ok(2));
+        assert!(recovery.shares[3].as_ref().unwrap_err().contains("Repeats"));
+        assert_eq!(recovery.shares[4], Ok(3));
+
+        let short = recover(&[&shares[0], &other[1]]);
+        assert!(matches!(short.secret, Err(SeqretsError::InvalidInput(_))));
+        assert!(recover(&["garbage"]).threshold.is_none());
+    }
+
+    #[test]
+    fn test_invalid_parameters_rejected() {
+        let secret = STANDARD.encode([1u8, 2, 3]);
+        assert!(shamir_split(secret.clone(), 1, 3, None).is_err());
+        assert!(shamir_split(secret.clone(), 4, 3, None).is_err());
+        assert!(shamir_split(String::new(), 2, 3, None).is_err());
+
+        let shares = shamir_split(secret, 2, 3, None).unwrap();
+        let duplicated = vec![shares[0].clone(), shares[0].clone()];
+        assert!(shamir_combine(duplicated).is_err());
+    }
+}
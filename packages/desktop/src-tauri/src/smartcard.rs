@@ -2,18 +2,41 @@
 //!
 //! Provides Tauri commands for reading/writing Shamir shares and vault data
 //! to/from JavaCard smartcards via the seQRets applet (AID: F0 53 51 52 54 53 01 00 00).
+//! The AID can be overridden at runtime (see `set_applet_aid`) for custom or
+//! pre-release applet builds installed under a different instance AID.
 //!
 //! Supports multi-item storage: multiple items (shares, vaults, instructions)
 //! are serialized as a JSON array and stored in the card's single data slot.
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use pcsc::*;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::audit;
+use crate::crypto;
+use crate::error::SeqretsError;
+use crate::qr::{self, EccLevel};
+use crate::shamir;
 
 // ── Constants ───────────────────────────────────────────────────────────
 
-/// seQRets applet AID (Application Identifier)
+/// seQRets applet AID (Application Identifier) — the default when no override is set
 const SEQRETS_AID: &[u8] = &[0xF0, 0x53, 0x51, 0x52, 0x54, 0x53, 0x01, 0x00, 0x00];
 
+/// ISO 7816-5 AID length bounds (RID is 5 bytes, PIX up to 11 bytes)
+const AID_MIN_LENGTH: usize = 5;
+const AID_MAX_LENGTH: usize = 16;
+
 /// Proprietary CLA byte
 const CLA: u8 = 0x80;
 
@@ -22,24 +45,145 @@ const INS_STORE_DATA: u8 = 0x01;
 const INS_READ_DATA: u8 = 0x02;
 const INS_GET_STATUS: u8 = 0x03;
 const INS_ERASE_DATA: u8 = 0x04;
+const INS_GET_VERSION: u8 = 0x05;
+const INS_GET_WRITE_COUNT: u8 = 0x06;
 const INS_SET_TYPE: u8 = 0x10;
 const INS_SET_LABEL: u8 = 0x11;
 const INS_VERIFY_PIN: u8 = 0x20;
 const INS_CHANGE_PIN: u8 = 0x21;
 const INS_SET_PIN: u8 = 0x22;
 const INS_SET_WIPE_PROTECT: u8 = 0x23;
+const INS_SET_PUK: u8 = 0x24;
+const INS_UNBLOCK_PIN: u8 = 0x25;
+const INS_FACTORY_RESET: u8 = 0x26;
+
+/// Version assumed for applets that predate GET_VERSION (they answer 6D00).
+const OLDEST_APPLET_VERSION: AppletVersion = AppletVersion { major: 1, minor: 0 };
+
+/// Oldest applet whose APDU semantics this build understands. Raise it when
+/// a change here relies on newer applet behaviour.
+const MIN_SUPPORTED_APPLET_VERSION: AppletVersion = AppletVersion { major: 1, minor: 0 };
 
-/// Maximum bytes per APDU data field
+/// PIN / PUK length bounds — must match MIN_PIN_SIZE / MAX_PIN_SIZE in the applet
+const PIN_MIN_LENGTH: usize = 8;
+const PIN_MAX_LENGTH: usize = 16;
+
+/// Maximum bytes per APDU data field, and the fixed chunk size of applets
+/// without `CAP_VARIABLE_WRITE_CHUNK`
 const CHUNK_SIZE: usize = 240;
 
+/// Largest data field of a short APDU
+const MAX_SHORT_APDU_DATA: usize = 255;
+
+/// Command bytes ahead of the data field: CLA INS P1 P2 Lc
+const APDU_HEADER_LENGTH: usize = 5;
+
+/// Readers that can't take STORE_DATA chunks this large are refused
+const MIN_WRITE_CHUNK_SIZE: usize = 16;
+
+/// FCI capability bit (applet 1.3+): chunk 0 of STORE_DATA sets the chunk size
+const CAP_VARIABLE_WRITE_CHUNK: u16 = 0x0020;
+
+/// FCI capability bit (applet 1.4+): READ_DATA with P2 = `READ_SEQUENCED`
+/// puts the chunk index and the generation of the write that stored the
+/// chunk ahead of its data
+const CAP_SEQUENCED_READ: u16 = 0x0040;
+const READ_SEQUENCED: u8 = 0x01;
+
+/// FCI capability bit (applet 1.5+): GET_WRITE_COUNT reports how many times
+/// the data area has been written or erased
+const CAP_WRITE_COUNT: u16 = 0x0080;
+
+/// PC/SC part 10 feature tag for verifying a PIN on the reader's PIN pad
+const FEATURE_VERIFY_PIN_DIRECT: u8 = 0x06;
+
+/// Seconds the PIN pad waits for entry (first key, then between keys)
+const PINPAD_TIMEOUT_SECS: u8 = 30;
+
+/// CM_IOCTL_GET_FEATURE_REQUEST, before `ctl_code`
+const IOCTL_GET_FEATURE_REQUEST: u32 = 3400;
+
+/// PC/SC GET DATA for the card UID, answered by the reader (contactless cards)
+const GET_UID_APDU: [u8; 5] = [0xFF, 0xCA, 0x00, 0x00, 0x00];
+
+/// SELECT with no AID, which picks the card manager on GlobalPlatform cards
+const SELECT_CARD_MANAGER_APDU: [u8; 5] = [0x00, 0xA4, 0x04, 0x00, 0x00];
+
+/// GlobalPlatform GET DATA for the Card Production Life Cycle data (tag 9F7F)
+const GET_CPLC_APDU: [u8; 5] = [0x80, 0xCA, 0x9F, 0x7F, 0x00];
+
+/// Item types the frontend writes (`CardItem::item_type`)
+const ITEM_TYPES: [&str; 4] = ["share", "vault", "instructions", ITEM_TYPE_KEYFILE];
+
+/// Item type of a keyfile, or of a keyfile share from `shamir::split_keyfile`
+const ITEM_TYPE_KEYFILE: &str = "keyfile";
+
+/// Item type of the metadata signature `sign_card` stores, always first
+const ITEM_TYPE_SIGNATURE: &str = "signature";
+
+/// Leads the decoded data of a signature item
+const CARD_SIGNATURE_VERSION: u8 = 1;
+
+/// Names of the `preflight_write` checks, in the order they run
+const PREFLIGHT_PAYLOAD: &str = "payload";
+const PREFLIGHT_READER: &str = "reader";
+const PREFLIGHT_APPLET: &str = "applet";
+const PREFLIGHT_PIN: &str = "pin";
+const PREFLIGHT_CAPACITY: &str = "capacity";
+
+/// Maximum card label length in bytes — must match MAX_LABEL_SIZE in the applet
+const LABEL_MAX_LENGTH: usize = 64;
+
 /// Data type constants (applet-level; multi-item is detected by JSON parsing)
 const TYPE_SHARE: u8 = 0x01;
 const TYPE_VAULT: u8 = 0x02;
 
+/// Events emitted after each STORE_DATA / READ_DATA chunk
+const CARD_WRITE_PROGRESS_EVENT: &str = "card-write-progress";
+const CARD_READ_PROGRESS_EVENT: &str = "card-read-progress";
+
+/// Event emitted for every APDU exchanged while tracing is on
+const APDU_TRACE_EVENT: &str = "apdu-trace";
+
 /// Default card capacity — used as a fallback when the card's GET_STATUS
 /// response does not include the capacity field (older applet versions).
 const DEFAULT_CARD_CAPACITY: usize = 8192;
 
+/// How long a single APDU may take before the card is treated as dead.
+/// A wedged card or reader otherwise blocks the command forever.
+const DEFAULT_TRANSMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Lower bound for the ERASE_DATA and FACTORY_RESET APDUs (see
+/// `apdu_timeout`), which rewrite the whole EEPROM in one APDU and can
+/// legitimately take several seconds on slow cards.
+const ERASE_TRANSMIT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How often `wait_for_reader` retries a reader held by another application,
+/// and the longest it may be asked to wait.
+const READER_BUSY_POLL: Duration = Duration::from_millis(250);
+const WAIT_FOR_READER_MAX_MS: u64 = 60_000;
+
+/// Longest a command may be asked to wait for a card (`wait_for_card`)
+const WAIT_FOR_CARD_MAX_MS: u32 = 120_000;
+
+/// Reader-name words that mark a contactless (PICC) interface, lowercase.
+const CONTACTLESS_READER_WORDS: [&str; 5] = ["contactless", "picc", "nfc", "rfid", "cl"];
+
+/// Format version of the JSON sealed inside an `export_card` backup.
+const CARD_BACKUP_VERSION: u8 = 1;
+
+/// Label bound into a card backup's associated data, so a vault blob can't
+/// be passed off as a backup (or the reverse).
+const CARD_BACKUP_LABEL: &str = "seqrets-card-backup";
+
+/// Accepted range for `set_transmit_timeout`, in milliseconds.
+const TRANSMIT_TIMEOUT_MIN_MS: u64 = 100;
+const TRANSMIT_TIMEOUT_MAX_MS: u64 = 120_000;
+
+/// Accepted range for `set_write_chunk_size`, in bytes.
+const WRITE_CHUNK_OVERRIDE_MIN: usize = MIN_WRITE_CHUNK_SIZE;
+const WRITE_CHUNK_OVERRIDE_MAX: usize = MAX_SHORT_APDU_DATA;
+
 // ── Serde types for frontend ────────────────────────────────────────────
 
 /// A single item stored on the card.
@@ -48,6 +192,48 @@ pub struct CardItem {
     pub item_type: String,
     pub label: String,
     pub data: String,
+    /// `data` is a share sealed to this card with a passphrase (see
+    /// `crypto::wrap_card_share`). Omitted from the stored JSON when false.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub wrapped: bool,
+    /// Groups the items written for one vault (its `vault_fingerprint`), so
+    /// `card_matches_vault` can tell which cards belong to it. Omitted from
+    /// the stored JSON when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault_id: Option<String>,
+}
+
+/// Longest accepted vault-group id, in bytes.
+const MAX_VAULT_ID_LENGTH: usize = 64;
+
+/// Trims and lowercases a vault-group id so ids compare regardless of how
+/// they were typed; empty means none.
+fn normalize_vault_id(vault_id: Option<&str>) -> Result<Option<String>, SeqretsError> {
+    let vault_id = match vault_id.map(str::trim) {
+        None | Some("") => return Ok(None),
+        Some(id) => id.to_ascii_lowercase(),
+    };
+    if vault_id.len() > MAX_VAULT_ID_LENGTH || !vault_id.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Vault ids must be at most {MAX_VAULT_ID_LENGTH} printable ASCII characters."
+        )));
+    }
+    Ok(Some(vault_id))
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// What `export_card` seals: every item on the card, plus the card's
+/// identifier when an item is wrapped to it (see `import_card`).
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CardBackup {
+    version: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    card_id: Option<Vec<u8>>,
+    items: Vec<CardItem>,
 }
 
 /// Summary of an item (without full data) for status display.
@@ -57,6 +243,8 @@ pub struct CardItemSummary {
     pub item_type: String,
     pub label: String,
     pub data_size: usize,
+    pub wrapped: bool,
+    pub vault_id: Option<String>,
 }
 
 /// Multi-item card status returned to the frontend.
@@ -72,659 +260,5472 @@ pub struct CardStatus {
     pub pin_retries_remaining: u8,
     pub free_bytes_estimate: i32,
     pub wipe_protected: bool,
+    pub puk_set: bool,
+    pub puk_retries_remaining: u8,
+    /// "major.minor" as reported by GET_VERSION ("1.0" for older applets).
+    pub applet_version: String,
+    /// The card refused the status or item read with 6982 because no PIN
+    /// was verified. When the status itself was refused the other fields
+    /// are unknown and left at their defaults.
+    pub pin_required: bool,
+    /// How the card is connected to the reader.
+    pub interface: CardInterface,
+    /// GlobalPlatform lifecycle of the applet; see `gp_lifecycle`.
+    pub lifecycle: String,
+    /// Completed writes and erases of the card's data area, from
+    /// GET_WRITE_COUNT. `None` when the applet has no counter (before 1.5).
+    pub write_count: Option<u32>,
 }
 
-// ── Helper functions ────────────────────────────────────────────────────
+impl CardStatus {
+    /// Status of a card whose applet won't report anything without a PIN.
+    fn pin_required(applet_version: AppletVersion, interface: CardInterface) -> Self {
+        CardStatus {
+            has_data: false,
+            data_length: 0,
+            card_capacity: DEFAULT_CARD_CAPACITY as u16,
+            total_items: 0,
+            items: Vec::new(),
+            pin_set: true,
+            pin_verified: false,
+            pin_retries_remaining: 0,
+            free_bytes_estimate: 0,
+            wipe_protected: false,
+            puk_set: false,
+            puk_retries_remaining: 0,
+            applet_version: applet_version.to_string(),
+            pin_required: true,
+            interface,
+            lifecycle: gp_lifecycle(None).to_string(),
+            write_count: None,
+        }
+    }
+}
 
-/// Send a raw APDU and return the response data (without SW1/SW2).
-/// Returns an error if SW != 0x9000.
-fn send_apdu(card: &Card, cla: u8, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>, String> {
-    // Build command APDU
-    let mut cmd = vec![cla, ins, p1, p2];
+/// Whether the card sits in a contact slot or talks to the reader over the
+/// air. `Unknown` when neither the ATR nor the reader name says.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CardInterface {
+    Contact,
+    Contactless,
+    Unknown,
+}
 
-    if !data.is_empty() {
-        cmd.push(data.len() as u8); // Lc
-        cmd.extend_from_slice(data);
+/// Applet lifecycle state from the SELECT FCI. `Unknown` for applets
+/// that return no FCI (before 1.2) or a state this build doesn't know.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AppletLifecycle {
+    /// No data, PIN or PUK.
+    Fresh,
+    InUse,
+    PinBlocked,
+    Unknown,
+}
+
+impl AppletLifecycle {
+    fn from_fci(state: Option<u8>) -> Self {
+        match state {
+            Some(0x01) => AppletLifecycle::Fresh,
+            Some(0x02) => AppletLifecycle::InUse,
+            Some(0x03) => AppletLifecycle::PinBlocked,
+            _ => AppletLifecycle::Unknown,
+        }
     }
+}
 
-    let mut resp_buf = [0u8; 258]; // max short APDU response
-    let resp = card
-        .transmit(&cmd, &mut resp_buf)
-        .map_err(|e| format!("APDU transmit failed: {}", e))?;
+/// What the selected applet reports about itself, from `applet_info`.
+/// `capabilities` is the FCI bitmap (0x0001 PIN, 0x0002 wipe protect,
+/// 0x0004 PUK, 0x0008 factory reset, 0x0010 GET_VERSION, 0x0020 variable
+/// write chunk, 0x0040 sequenced read, 0x0080 write counter), or `None` when
+/// the applet doesn't report one and the frontend should assume all but the
+/// last three.
+#[derive(Serialize, Clone)]
+pub struct AppletInfo {
+    pub aid: String,
+    pub version: String,
+    pub lifecycle: AppletLifecycle,
+    pub capabilities: Option<u16>,
+}
 
-    if resp.len() < 2 {
-        return Err("Response too short".to_string());
-    }
+/// One reader as listed by `enumerate_cards`. `has_data` and `label` are
+/// `None` when there is no seQRets applet or the card won't report its
+/// status without a PIN; `label` is also `None` when none is set.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ReaderCardInfo {
+    pub reader: String,
+    /// Name set with `set_reader_alias`, if any.
+    pub alias: Option<String>,
+    pub card_present: bool,
+    pub applet_present: bool,
+    pub has_data: Option<bool>,
+    pub label: Option<String>,
+}
 
-    let sw1 = resp[resp.len() - 2];
-    let sw2 = resp[resp.len() - 1];
-    let data_resp = &resp[..resp.len() - 2];
+/// A reader from `list_readers`: the PC/SC name commands take, and the
+/// user's alias for it (`set_reader_alias`), if any.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ReaderEntry {
+    pub name: String,
+    pub alias: Option<String>,
+}
 
-    if sw1 == 0x90 && sw2 == 0x00 {
-        Ok(data_resp.to_vec())
-    } else if sw1 == 0x69 && sw2 == 0x82 {
-        Err("PIN verification required. Please verify your PIN first.".to_string())
-    } else if sw1 == 0x69 && sw2 == 0x84 {
-        Err("Card is locked. Too many incorrect PIN attempts.".to_string())
-    } else if sw1 == 0x6A && sw2 == 0x84 {
-        Err("Card storage full. Data too large for this card.".to_string())
-    } else {
-        Err(format!("Card returned error: SW={:02X}{:02X}", sw1, sw2))
+/// What a reader reports about itself, from `reader_capabilities`.
+/// `max_send` is the largest command APDU it accepts (SCARD_ATTR_MAXINPUT)
+/// and `max_recv` the largest block it takes from the card (T=1 IFSD);
+/// either is `None` when the driver doesn't report it. `has_pinpad` means
+/// `verify_pin_on_reader` can take the PIN on the reader. `write_chunk_size`
+/// is the STORE_DATA chunk a write to the card in this reader would use, or
+/// `None` when the reader is too limited to write to it.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ReaderCaps {
+    pub max_send: Option<u32>,
+    pub max_recv: Option<u32>,
+    /// "T=0", "T=1", "raw" or "unknown"
+    pub protocol: String,
+    pub has_pinpad: bool,
+    pub write_chunk_size: Option<usize>,
+    pub interface: CardInterface,
+}
+
+/// Applet version reported by GET_VERSION; ordered major first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct AppletVersion {
+    major: u8,
+    minor: u8,
+}
+
+impl std::fmt::Display for AppletVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
     }
 }
 
-/// Send a SELECT APDU to activate the seQRets applet on the card.
-fn select_applet(card: &Card) -> Result<(), String> {
-    // SELECT command: CLA=0x00, INS=0xA4, P1=0x04 (by DF name), P2=0x00
-    let mut cmd = vec![0x00, 0xA4, 0x04, 0x00];
-    cmd.push(SEQRETS_AID.len() as u8);
-    cmd.extend_from_slice(SEQRETS_AID);
+/// One entry of a `write_shares_to_cards` batch. Zeroized on drop.
+#[derive(Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct ShareAssignment {
+    pub reader: String,
+    pub share: String,
+    pub label: String,
+    pub pin: Option<String>,
+    /// Wraps the share to the card (see `write_item_to_card`).
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// Stored as the item's `vault_id`.
+    #[serde(default)]
+    pub vault_id: Option<String>,
+    /// Signs the card's items after the share is added (see `sign_card`).
+    #[serde(default)]
+    pub signing_password: Option<String>,
+}
 
-    let mut resp_buf = [0u8; 258];
-    let resp = card
-        .transmit(&cmd, &mut resp_buf)
-        .map_err(|e| format!("SELECT failed: {}", e))?;
+/// Per-card outcome of a `write_shares_to_cards` batch.
+#[derive(Serialize, Clone)]
+pub struct CardWriteReport {
+    pub reader: String,
+    pub label: String,
+    pub success: bool,
+    pub error: Option<SeqretsError>,
+}
 
-    if resp.len() < 2 {
-        return Err("SELECT response too short".to_string());
-    }
+/// One card of a `recover_from_cards` call.
+#[derive(Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct RecoveryCard {
+    pub reader: String,
+    pub pin: Option<String>,
+    /// Unwraps a share written with a passphrase.
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
 
-    let sw1 = resp[resp.len() - 2];
-    let sw2 = resp[resp.len() - 1];
+/// Per-card outcome of `recover_from_cards`. `index` is the share's index
+/// when it went into the reconstruction; otherwise `error` says why not.
+#[derive(Serialize, Clone)]
+pub struct CardRecoveryReport {
+    pub reader: String,
+    pub index: Option<u8>,
+    pub error: Option<SeqretsError>,
+}
 
-    if sw1 == 0x90 && sw2 == 0x00 {
-        Ok(())
-    } else if sw1 == 0x6A && sw2 == 0x82 {
-        Err("seQRets applet not found on this card. Please install the applet first.".to_string())
-    } else {
-        Err(format!("SELECT failed: SW={:02X}{:02X}", sw1, sw2))
-    }
+/// Returned by `recover_from_cards`.
+#[derive(Serialize)]
+pub struct RecoveryResult {
+    /// The recombined secret (the still-encrypted vault blob); `None` when
+    /// too few good shares were read, with `error` saying so.
+    pub secret_b64: Option<String>,
+    /// Shares needed, as recorded in the shares read.
+    pub threshold: Option<u8>,
+    pub shares_used: usize,
+    pub cards: Vec<CardRecoveryReport>,
+    pub error: Option<SeqretsError>,
 }
 
-/// Connect to a specific reader and return a Card handle.
-fn connect_reader(reader_name: &str) -> Result<(Context, Card), String> {
-    let ctx = Context::establish(Scope::User)
-        .map_err(|e| format!("Cannot access smart card system: {}", e))?;
+/// Returned by `verify_card_authenticity`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct CardAuthenticity {
+    /// The card holds a signature item.
+    pub signed: bool,
+    /// The signature matches the card's items under the given password.
+    pub authentic: bool,
+}
 
-    let card = ctx
-        .connect(
-            &std::ffi::CString::new(reader_name).map_err(|_| "Invalid reader name")?,
-            ShareMode::Shared,
-            Protocols::ANY,
-        )
-        .map_err(|e| format!("Cannot connect to card in '{}': {}", reader_name, e))?;
+/// Returned by `distribute_share`. The QR and the card write succeed or
+/// fail independently, so a failed write still hands back the QR to print.
+#[derive(Serialize)]
+pub struct DistributeResult {
+    /// Base64 PNG of the share's QR code; `None` when rendering failed.
+    pub qr_png: Option<String>,
+    pub qr_error: Option<SeqretsError>,
+    /// The share was appended to the card and read back intact.
+    pub card_written: bool,
+    pub card_error: Option<SeqretsError>,
+}
 
-    Ok((ctx, card))
+/// Outcome of one `preflight_write` check. `error` is `None` when it passed.
+#[derive(Serialize, Clone)]
+pub struct PreflightCheck {
+    pub check: String,
+    pub passed: bool,
+    pub error: Option<SeqretsError>,
 }
 
-/// Explicitly disconnect the card with a reset disposition.
-/// This forces the PC/SC subsystem to clear the session state,
-/// preventing stale connections when the same reader is used again.
-fn disconnect_with_reset(card: Card) {
-    let _ = card.disconnect(Disposition::ResetCard);
+/// Result of `preflight_write`: every check in order, and whether the write
+/// can go ahead (all checks passed).
+#[derive(Serialize, Clone, Default)]
+pub struct PreflightReport {
+    pub ready: bool,
+    pub checks: Vec<PreflightCheck>,
 }
 
-/// If a PIN is provided, verify it on the current connection.
-/// This must be called in the same connection as the protected operation
-/// because PIN verification state is transient (cleared on applet re-select).
-fn verify_pin_if_needed(card: &Card, pin: &Option<String>) -> Result<(), String> {
-    if let Some(ref p) = pin {
-        if !p.is_empty() {
-            send_apdu(card, CLA, INS_VERIFY_PIN, 0x00, 0x00, p.as_bytes())?;
+impl PreflightReport {
+    fn record(&mut self, check: &str, result: Result<(), SeqretsError>) -> bool {
+        let passed = result.is_ok();
+        self.checks.push(PreflightCheck {
+            check: check.to_string(),
+            passed,
+            error: result.err(),
+        });
+        passed
+    }
+
+    /// Mark checks that could not run because an earlier one failed.
+    fn skip(&mut self, checks: &[&str], because: &str) {
+        for check in checks {
+            let message = format!("Not checked: {} check failed.", because);
+            self.record(check, Err(SeqretsError::Other(message)));
         }
     }
-    Ok(())
+
+    fn finish(mut self) -> Self {
+        self.ready = self.checks.iter().all(|c| c.passed);
+        self
+    }
 }
 
-/// Parse total card capacity from a GET_STATUS response.
-///
-/// The capacity field is 2 big-endian bytes appended after the label bytes:
-///   [0-1]=dataLen, [2]=type, [3]=pinSet, [4]=pinVerified,
-///   [5]=pinRetries, [6]=labelLen, [7..7+labelLen]=label,
-///   [7+labelLen..7+labelLen+2]=capacity
-///
-/// Returns `false` if the response is too short (older applet without the field).
-fn parse_wipe_protected(status_resp: &[u8]) -> bool {
-    if status_resp.len() < 7 {
-        return false;
+/// Payload of the `card-write-progress` and `card-read-progress` events.
+/// `chunk` is the 1-based number of chunks transferred so far.
+#[derive(Serialize, Clone)]
+pub struct ChunkProgress {
+    pub chunk: usize,
+    pub total: usize,
+}
+
+/// Payload of the `apdu-trace` event, as space-separated hex. Data bytes
+/// are masked as `[N bytes]` except where `trace_shows_data` allows them;
+/// the header, Lc/Le and status word are always shown. `response` is
+/// `None` when the card never answered (timeout or transport error).
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ApduTrace {
+    pub command: String,
+    pub response: Option<String>,
+}
+
+/// One share of an enrollment, as reported by `enrollment_status`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct EnrolledShare {
+    pub index: usize,
+    pub label: String,
+    /// Written to a card and read back intact.
+    pub enrolled: bool,
+    /// Reader the card was in when the share was enrolled.
+    pub reader: Option<String>,
+}
+
+/// Progress of the guided enrollment started by `begin_enrollment`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct EnrollmentStatus {
+    pub active: bool,
+    pub shares: Vec<EnrolledShare>,
+    /// Share the next `enroll_next_card` writes; `None` once every share is enrolled.
+    pub next_index: Option<usize>,
+    pub complete: bool,
+}
+
+// ── Managed state ───────────────────────────────────────────────────────
+
+/// Where `apdu-trace` events go while tracing is on (`set_apdu_trace`).
+/// Process-wide rather than in `SmartcardState` so every exchange can reach
+/// it without threading the app handle through.
+static APDU_TRACE: Mutex<Option<AppHandle>> = Mutex::new(None);
+
+/// App-wide smartcard settings, registered with `tauri::Builder::manage`.
+#[derive(Default)]
+pub struct SmartcardState {
+    /// AID override for SELECT. `None` means use `SEQRETS_AID`.
+    aid_override: Mutex<Option<Vec<u8>>>,
+    /// Cancellation token of the chunked transfer in flight, if any.
+    operation: Mutex<Option<Arc<AtomicBool>>>,
+    /// Per-APDU timeout override. `None` means `DEFAULT_TRANSMIT_TIMEOUT`.
+    timeout_override: Mutex<Option<Duration>>,
+    /// STORE_DATA chunk size override. `None` sizes chunks from the reader.
+    chunk_override: Mutex<Option<usize>>,
+    /// Refuse writes unless the card is known to be in a contact slot.
+    require_contact: AtomicBool,
+    /// Refuse every command that changes a card (`set_read_only`).
+    read_only: AtomicBool,
+    /// Audit log file, when auditing is on (`set_audit_logging`).
+    audit_log: Mutex<Option<PathBuf>>,
+    /// Guided share enrollment in progress (`begin_enrollment`).
+    enrollment: Mutex<Option<Enrollment>>,
+    /// User-chosen reader names, keyed by `reader_alias_key`.
+    reader_aliases: Mutex<BTreeMap<String, String>>,
+}
+
+impl SmartcardState {
+    /// Snapshot of the current AID override (if any).
+    fn aid(&self) -> Option<Vec<u8>> {
+        self.aid_override
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
-    let label_length = status_resp[6] as usize;
-    let wipe_protect_offset = 7 + label_length + 2; // after capacity (2 bytes)
-    if status_resp.len() > wipe_protect_offset {
-        status_resp[wipe_protect_offset] == 0x01
-    } else {
-        false
+
+    /// Timeout applied to each APDU of a command.
+    fn transmit_timeout(&self) -> Duration {
+        self.timeout_override
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .unwrap_or(DEFAULT_TRANSMIT_TIMEOUT)
     }
-}
 
-/// Returns DEFAULT_CARD_CAPACITY if the response is too short (older applet).
-fn parse_card_capacity(status_resp: &[u8]) -> usize {
-    if status_resp.len() < 7 {
-        return DEFAULT_CARD_CAPACITY;
+    /// STORE_DATA chunk size set with `set_write_chunk_size`, if any.
+    fn write_chunk_override(&self) -> Option<usize> {
+        *self
+            .chunk_override
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
     }
-    let label_length = status_resp[6] as usize;
-    let capacity_offset = 7 + label_length;
-    if status_resp.len() >= capacity_offset + 2 {
-        ((status_resp[capacity_offset] as usize) << 8) | (status_resp[capacity_offset + 1] as usize)
-    } else {
-        DEFAULT_CARD_CAPACITY
+
+    /// Whether writes must go over a contact interface (`set_require_contact`).
+    fn require_contact(&self) -> bool {
+        self.require_contact.load(Ordering::SeqCst)
     }
-}
 
-/// Write a data blob to the card in chunks, with type and label metadata.
-fn write_data_to_card(
-    card: &Card,
-    data: &[u8],
-    data_type: u8,
-    label_str: &str,
-) -> Result<(), String> {
-    // Step 1: Erase existing data
-    send_apdu(card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[])?;
+    /// Fails with `InvalidInput` while the session is read-only. Commands that
+    /// write, erase or change PIN state call this before connecting.
+    fn check_writable(&self) -> Result<(), SeqretsError> {
+        if self.read_only.load(Ordering::SeqCst) {
+            return Err(SeqretsError::InvalidInput(
+                "The session is read-only. Turn off read-only mode to change the card.".to_string(),
+            ));
+        }
+        Ok(())
+    }
 
-    // Step 2: Set data type
-    send_apdu(card, CLA, INS_SET_TYPE, data_type, 0x00, &[])?;
+    /// Append an audit entry for `command` on `reader`, if auditing is on.
+    fn audit<T>(&self, command: &str, reader: &str, result: &Result<T, SeqretsError>) {
+        if let Some(path) = self
+            .audit_log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_deref()
+        {
+            audit::record(path, command, reader, result);
+        }
+    }
 
-    // Step 3: Set label (truncate at a valid UTF-8 character boundary)
-    let label_bytes = label_str.as_bytes();
-    let label_to_send = if label_bytes.len() > 64 {
-        let truncate_at = label_str[..64.min(label_str.len())]
-            .char_indices()
-            .map(|(i, c)| i + c.len_utf8())
-            .take_while(|&end| end <= 64)
-            .last()
-            .unwrap_or(0);
-        &label_bytes[..truncate_at]
-    } else {
-        label_bytes
-    };
-    if !label_to_send.is_empty() {
-        send_apdu(card, CLA, INS_SET_LABEL, 0x00, 0x00, label_to_send)?;
+    /// Alias set for `reader`, matched on its name without the index suffix.
+    fn reader_alias(&self, reader: &str) -> Option<String> {
+        self.reader_aliases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(reader_alias_key(reader))
+            .cloned()
     }
 
-    // Step 4: Write data in chunks
-    let chunks: Vec<&[u8]> = data.chunks(CHUNK_SIZE).collect();
-    let num_chunks = chunks.len();
+    /// Set (or with `None`/empty, remove) the alias for `reader`.
+    fn set_reader_alias(&self, reader: &str, alias: Option<&str>) -> Result<(), SeqretsError> {
+        let key = reader_alias_key(reader);
+        if key.is_empty() {
+            return Err(SeqretsError::InvalidInput(
+                "A reader name is required.".to_string(),
+            ));
+        }
+        let mut aliases = self
+            .reader_aliases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match alias.map(str::trim) {
+            None | Some("") => {
+                aliases.remove(key);
+            }
+            Some(alias)
+                if alias.chars().count() > MAX_READER_ALIAS_LENGTH
+                    || alias.chars().any(char::is_control) =>
+            {
+                return Err(SeqretsError::InvalidInput(format!(
+                    "Reader aliases must be at most {MAX_READER_ALIAS_LENGTH} characters, without control characters."
+                )));
+            }
+            Some(alias) => {
+                aliases.insert(key.to_string(), alias.to_string());
+            }
+        }
+        Ok(())
+    }
 
-    if num_chunks > 255 {
-        return Err(format!(
-            "Data too large: {} bytes exceeds maximum write size of {} bytes",
-            data.len(),
-            255 * CHUNK_SIZE
-        ));
+    /// Register a new cancellable operation. The token is unregistered when
+    /// the guard drops, so a late `cancel_card_operation` is a no-op.
+    fn begin_operation(&self) -> CardOperation<'_> {
+        let token = Arc::new(AtomicBool::new(false));
+        *self
+            .operation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(token.clone());
+        CardOperation { state: self, token }
     }
 
-    for (i, chunk) in chunks.iter().enumerate() {
-        let p1 = i as u8; // chunk index
-        let p2 = if i == num_chunks - 1 { 0x01 } else { 0x00 }; // last chunk flag
-        send_apdu(card, CLA, INS_STORE_DATA, p1, p2, chunk)?;
+    /// Flag the in-flight operation (if any) as cancelled.
+    fn cancel_operation(&self) {
+        if let Some(token) = self
+            .operation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            token.store(true, Ordering::SeqCst);
+        }
     }
+}
 
-    Ok(())
+/// A guided enrollment: the shares to write, one per card, and the card
+/// each has been verified on so far.
+struct Enrollment {
+    shares: Vec<Zeroizing<String>>,
+    labels: Vec<String>,
+    vault_id: Option<String>,
+    /// Per share, the card it went to once written and verified.
+    cards: Vec<Option<EnrolledCard>>,
 }
 
-/// Read the raw data bytes from the card.
-/// Returns (raw_data_bytes, type_byte, label_string).
-/// Must be called after select_applet and verify_pin_if_needed.
-fn read_raw_card_data(card: &Card) -> Result<(Vec<u8>, u8, String), String> {
-    let status_resp = send_apdu(card, CLA, INS_GET_STATUS, 0x00, 0x00, &[])?;
+struct EnrolledCard {
+    reader: String,
+    /// Hex `card_identifier`, when the card reports one.
+    card_id: Option<String>,
+}
+
+/// What `enroll_next_card` does with the card it was handed.
+#[derive(Debug, PartialEq)]
+enum EnrollStep {
+    /// Append this share and verify it.
+    Write(usize),
+    /// The card already holds this pending share (a write that landed before
+    /// the flow was interrupted); record it without writing again.
+    Adopt(usize),
+}
 
-    if status_resp.len() < 6 {
-        return Err("Invalid status response".to_string());
+impl Enrollment {
+    fn new(
+        shares: Vec<String>,
+        labels: Vec<String>,
+        vault_id: Option<String>,
+    ) -> Result<Self, SeqretsError> {
+        let shares: Vec<Zeroizing<String>> = shares.into_iter().map(Zeroizing::new).collect();
+        if shares.is_empty() {
+            return Err(SeqretsError::InvalidInput(
+                "No shares to enroll.".to_string(),
+            ));
+        }
+        if labels.len() != shares.len() {
+            return Err(SeqretsError::InvalidInput(format!(
+                "Got {} labels for {} shares; give one label per share.",
+                labels.len(),
+                shares.len()
+            )));
+        }
+        if shares.iter().any(|share| share.trim().is_empty()) {
+            return Err(SeqretsError::InvalidInput(
+                "A share to enroll is empty.".to_string(),
+            ));
+        }
+        for (i, share) in shares.iter().enumerate() {
+            if shares[..i]
+                .iter()
+                .any(|earlier| crypto::ct_eq(earlier.as_bytes(), share.as_bytes()))
+            {
+                return Err(SeqretsError::InvalidInput(format!(
+                    "Share {} is a duplicate of an earlier share.",
+                    i + 1
+                )));
+            }
+        }
+        let cards = shares.iter().map(|_| None).collect();
+        Ok(Enrollment {
+            shares,
+            labels,
+            vault_id: normalize_vault_id(vault_id.as_deref())?,
+            cards,
+        })
     }
 
-    let data_length = ((status_resp[0] as u16) << 8) | (status_resp[1] as u16);
-    let data_type_byte = status_resp[2];
-    let label_length = status_resp[5] as usize;
+    /// First share not yet enrolled. Shares go out in order, so none is skipped.
+    fn next_pending(&self) -> Option<usize> {
+        self.cards.iter().position(Option::is_none)
+    }
 
-    let label = if label_length > 0 && status_resp.len() >= 6 + label_length {
-        String::from_utf8_lossy(&status_resp[6..6 + label_length]).to_string()
-    } else {
-        String::new()
-    };
+    /// Decide what to do with a card identified by `card_id` that currently
+    /// holds `items`. A card already carrying an enrolled share, or already
+    /// used for one, is refused so no share ends up on two cards and no card
+    /// holds two shares.
+    fn plan(&self, card_id: Option<&str>, items: &[CardItem]) -> Result<EnrollStep, SeqretsError> {
+        for item in items.iter().filter(|item| item.item_type == "share") {
+            let held = self
+                .shares
+                .iter()
+                .position(|share| crypto::ct_eq(share.as_bytes(), item.data.as_bytes()));
+            if let Some(index) = held {
+                return match self.cards[index] {
+                    None => Ok(EnrollStep::Adopt(index)),
+                    Some(_) => Err(SeqretsError::InvalidInput(format!(
+                        "This card already holds share {} ({}). Insert the card for the next share.",
+                        index + 1,
+                        self.labels[index]
+                    ))),
+                };
+            }
+        }
+        if let Some(card_id) = card_id {
+            let used = self
+                .cards
+                .iter()
+                .position(|card| card.as_ref().and_then(|c| c.card_id.as_deref()) == Some(card_id));
+            if let Some(index) = used {
+                return Err(SeqretsError::InvalidInput(format!(
+                    "This card was already enrolled with share {}. Insert a different card.",
+                    index + 1
+                )));
+            }
+        }
+        self.next_pending().map(EnrollStep::Write).ok_or_else(|| {
+            SeqretsError::InvalidInput("Every share has already been enrolled.".to_string())
+        })
+    }
 
-    if data_length == 0 {
-        return Ok((Vec::new(), data_type_byte, label));
+    fn record(&mut self, index: usize, reader: &str, card_id: Option<String>) {
+        self.cards[index] = Some(EnrolledCard {
+            reader: reader.to_string(),
+            card_id,
+        });
     }
 
-    // Read data in chunks
-    let mut all_data: Vec<u8> = Vec::with_capacity(data_length as usize);
-    let mut chunk_index: u8 = 0;
+    fn status(&self) -> EnrollmentStatus {
+        let next_index = self.next_pending();
+        EnrollmentStatus {
+            active: true,
+            shares: self
+                .labels
+                .iter()
+                .zip(&self.cards)
+                .enumerate()
+                .map(|(index, (label, card))| EnrolledShare {
+                    index,
+                    label: label.clone(),
+                    enrolled: card.is_some(),
+                    reader: card.as_ref().map(|c| c.reader.clone()),
+                })
+                .collect(),
+            next_index,
+            complete: next_index.is_none(),
+        }
+    }
+}
 
-    while all_data.len() < data_length as usize {
-        let chunk = send_apdu(card, CLA, INS_READ_DATA, chunk_index, 0x00, &[])?;
-        if chunk.is_empty() {
-            break;
+/// Guard for one cancellable card operation; see `SmartcardState::begin_operation`.
+struct CardOperation<'a> {
+    state: &'a SmartcardState,
+    token: Arc<AtomicBool>,
+}
+
+impl CardOperation<'_> {
+    /// Hooks for a read: `card-read-progress` events plus this operation's token.
+    fn reading<'b>(&'b self, app: &'b AppHandle) -> Transfer<'b> {
+        Transfer {
+            on_chunk: Box::new(progress_emitter(app, CARD_READ_PROGRESS_EVENT)),
+            cancel: &self.token,
         }
-        all_data.extend_from_slice(&chunk);
-        chunk_index += 1;
+    }
 
-        // Safety check to prevent infinite loop
-        if chunk_index > 100 {
-            return Err("Too many chunks — data may be corrupted".to_string());
+    /// Hooks for a write: `card-write-progress` events plus this operation's token.
+    fn writing<'b>(&'b self, app: &'b AppHandle) -> Transfer<'b> {
+        Transfer {
+            on_chunk: Box::new(progress_emitter(app, CARD_WRITE_PROGRESS_EVENT)),
+            cancel: &self.token,
         }
     }
+}
 
-    all_data.truncate(data_length as usize);
-    Ok((all_data, data_type_byte, label))
+impl Drop for CardOperation<'_> {
+    fn drop(&mut self) {
+        let mut current = self
+            .state
+            .operation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // Only clear our own token; a newer operation may have replaced it.
+        if current
+            .as_ref()
+            .is_some_and(|t| Arc::ptr_eq(t, &self.token))
+        {
+            *current = None;
+        }
+    }
 }
 
-/// Parse card data into a list of CardItem.
-/// First tries to parse as a JSON array (multi-item format).
-/// Falls back to treating it as a legacy single-item blob.
-fn parse_card_items(raw_data: &[u8], type_byte: u8, label: &str) -> Result<Vec<CardItem>, String> {
-    if raw_data.is_empty() {
-        return Ok(Vec::new());
+/// Per-chunk hooks for multi-APDU transfers: progress reporting and
+/// cancellation, both checked between chunks.
+struct Transfer<'a> {
+    on_chunk: Box<dyn Fn(usize, usize) + 'a>,
+    cancel: &'a AtomicBool,
+}
+
+impl Transfer<'_> {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
     }
+}
 
-    let data_string = String::from_utf8(raw_data.to_vec())
-        .map_err(|_| "Card data is not valid UTF-8".to_string())?;
+const CANCELLED_MESSAGE: &str = "Operation cancelled.";
 
-    // Try multi-item JSON array format first
-    if let Ok(items) = serde_json::from_str::<Vec<CardItem>>(&data_string) {
-        if !items.is_empty() {
-            return Ok(items);
+/// Longest accepted reader alias, in characters.
+const MAX_READER_ALIAS_LENGTH: usize = 64;
+
+/// The part of a reader name that survives replugging: PC/SC appends index
+/// tokens ("Generic Reader 00 00", "... 0") that can change between plug
+/// events, so trailing all-digit words are dropped. Two identical readers
+/// share a key and so an alias.
+fn reader_alias_key(reader: &str) -> &str {
+    let mut key = reader.trim_end();
+    while let Some((head, last)) = key.rsplit_once(' ') {
+        if last.is_empty() || !last.bytes().all(|b| b.is_ascii_digit()) {
+            break;
         }
+        key = head.trim_end();
     }
+    key
+}
 
-    // Legacy single-item format: wrap in a Vec
-    let item_type = match type_byte {
-        TYPE_SHARE => "share".to_string(),
-        TYPE_VAULT => "vault".to_string(),
-        _ => "unknown".to_string(),
-    };
-    Ok(vec![CardItem {
-        item_type,
-        label: label.to_string(),
-        data: data_string,
-    }])
-}
+// ── Helper functions ────────────────────────────────────────────────────
 
-/// Serialize a list of CardItem to JSON, then write to card as TYPE_MULTI.
-fn write_items_to_card(card: &Card, items: &[CardItem]) -> Result<(), String> {
-    let json = serde_json::to_string(items)
-        .map_err(|e| format!("Failed to serialize items: {}", e))?;
-    let data_bytes = json.as_bytes();
+/// Check that an AID is within the 5–16 byte range required by ISO 7816-5.
+fn validate_aid(aid: &[u8]) -> Result<(), SeqretsError> {
+    if aid.len() < AID_MIN_LENGTH || aid.len() > AID_MAX_LENGTH {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Invalid applet AID: must be {}-{} bytes, got {}.",
+            AID_MIN_LENGTH,
+            AID_MAX_LENGTH,
+            aid.len()
+        )));
+    }
+    Ok(())
+}
 
-    // Query actual card capacity via GET_STATUS
-    let status_resp = send_apdu(card, CLA, INS_GET_STATUS, 0x00, 0x00, &[])?;
-    let capacity = parse_card_capacity(&status_resp);
+/// Parse a hex AID string. Spaces and colons between bytes are ignored,
+/// so "F0 53 51 52 54", "F0:53:51:52:54" and "F053515254" are all accepted.
+fn parse_aid_hex(aid_hex: &str) -> Result<Vec<u8>, SeqretsError> {
+    let digits: String = aid_hex
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect();
 
-    // Size check against the card's reported capacity
-    if data_bytes.len() > capacity {
-        return Err(format!(
-            "Combined data ({} bytes) exceeds card capacity ({} bytes). Remove some items first.",
-            data_bytes.len(),
-            capacity
+    // Checked before slicing: a multi-byte character would split mid-char
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(SeqretsError::InvalidInput(
+            "Invalid applet AID: not a valid hex string.".to_string(),
+        ));
+    }
+    if digits.len() % 2 != 0 {
+        return Err(SeqretsError::InvalidInput(
+            "Invalid applet AID: hex string has an odd number of digits.".to_string(),
         ));
     }
 
-    let summary_label = format!(
-        "{} item{}",
-        items.len(),
-        if items.len() == 1 { "" } else { "s" }
-    );
-    write_data_to_card(card, data_bytes, TYPE_VAULT, &summary_label)
-}
+    let aid = digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).expect("ASCII hex digits"), 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| {
+            SeqretsError::InvalidInput("Invalid applet AID: not a valid hex string.".to_string())
+        })?;
 
-// ── Tauri commands ──────────────────────────────────────────────────────
+    validate_aid(&aid)?;
+    Ok(aid)
+}
 
-/// List all available PC/SC readers.
-#[tauri::command]
-pub fn list_readers() -> Result<Vec<String>, String> {
-    let ctx = Context::establish(Scope::User)
-        .map_err(|e| format!("Cannot access smart card system: {}", e))?;
+fn format_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    let mut readers_buf = [0u8; 4096];
-    let readers = ctx
-        .list_readers(&mut readers_buf)
-        .map_err(|e| format!("Cannot list readers: {}", e))?;
+/// Check that a PIN or PUK is within the length bounds enforced by the applet.
+fn check_pin_length(value: &str, what: &str) -> Result<(), SeqretsError> {
+    let len = value.len();
+    if !(PIN_MIN_LENGTH..=PIN_MAX_LENGTH).contains(&len) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "{} must be {}-{} characters.",
+            what, PIN_MIN_LENGTH, PIN_MAX_LENGTH
+        )));
+    }
+    Ok(())
+}
 
-    let result: Vec<String> = readers
-        .map(|r| r.to_str().unwrap_or("Unknown reader").to_string())
-        .collect();
+/// Build a short command APDU. Lc is omitted when there is no data field.
+fn build_apdu(cla: u8, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Vec<u8> {
+    let mut cmd = vec![cla, ins, p1, p2];
 
-    if result.is_empty() {
-        Err("No smart card readers detected. Please connect a reader.".to_string())
-    } else {
-        Ok(result)
+    if !data.is_empty() {
+        cmd.push(data.len() as u8); // Lc
+        cmd.extend_from_slice(data);
     }
+
+    cmd
 }
 
-/// Get the status of the card in the given reader, including item summaries.
-#[tauri::command]
-pub fn get_card_status(reader: String, pin: Option<String>) -> Result<CardStatus, String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    verify_pin_if_needed(&card, &pin)?;
+/// Something that exchanges one APDU with a card. `PcscCard` in
+/// production; the tests substitute readers that misbehave.
+trait ApduTransport: Send + Sync + 'static {
+    fn exchange(&self, cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error>;
+    /// Re-establish the session after PC/SC reported the card was reset.
+    fn reconnect(&self) -> Result<(), pcsc::Error>;
+}
 
-    let resp = send_apdu(&card, CLA, INS_GET_STATUS, 0x00, 0x00, &[])?;
+/// A PC/SC card handle. The lock lets a reset card be reconnected while
+/// transmit workers share it; a worker stuck in PC/SC holds it until the
+/// call returns, so later exchanges time out behind it.
+struct PcscCard(Mutex<Card>);
 
-    if resp.len() < 7 {
-        disconnect_with_reset(card);
-        return Err("Invalid status response from card".to_string());
+impl PcscCard {
+    fn handle(&self) -> MutexGuard<'_, Card> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
     }
+}
 
-    let data_length = ((resp[0] as u16) << 8) | (resp[1] as u16);
-    let data_type_byte = resp[2];
-    let pin_set = resp[3] == 0x01;
-    let pin_verified = resp[4] == 0x01;
-    let pin_retries_remaining = resp[5];
-    let label_length = resp[6] as usize;
+impl ApduTransport for PcscCard {
+    fn exchange(&self, cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+        let mut resp_buf = [0u8; 258]; // max short APDU response
+        self.handle()
+            .transmit(cmd, &mut resp_buf)
+            .map(<[u8]>::to_vec)
+    }
 
-    let label = if label_length > 0 && resp.len() >= 7 + label_length {
-        String::from_utf8_lossy(&resp[7..7 + label_length]).to_string()
-    } else {
-        String::new()
-    };
+    fn reconnect(&self) -> Result<(), pcsc::Error> {
+        self.handle()
+            .reconnect(ShareMode::Shared, Protocols::ANY, Disposition::ResetCard)
+    }
+}
 
-    // Parse card capacity from GET_STATUS response (falls back to default for older applets)
-    let card_capacity = parse_card_capacity(&resp) as u16;
-    let wipe_protected = parse_wipe_protected(&resp);
+/// A connected card plus the timeout applied to each APDU sent to it and
+/// the interface it was found on.
+struct CardLink<T: ApduTransport = PcscCard> {
+    card: Arc<T>,
+    timeout: Duration,
+    interface: CardInterface,
+    /// AID of the last successful SELECT, selected again after a reset.
+    selected_aid: Mutex<Option<Vec<u8>>>,
+    /// FCI capability bitmap of that applet; 0 when it reported none.
+    capabilities: AtomicU16,
+    /// Set when VERIFY_PIN succeeds; a reset clears the card's PIN state.
+    pin_verified: AtomicBool,
+    /// Set while recovering from a reset, so a second reset fails instead
+    /// of recursing.
+    recovering: AtomicBool,
+}
 
-    // If there's data, read and parse to get item summaries
-    let (total_items, items) = if data_length > 0 {
-        match read_raw_card_data(&card) {
-            Ok((raw_data, type_byte, raw_label)) => {
-                match parse_card_items(&raw_data, type_byte, &raw_label) {
-                    Ok(parsed_items) => {
-                        let summaries: Vec<CardItemSummary> = parsed_items
-                            .iter()
-                            .enumerate()
-                            .map(|(i, item)| CardItemSummary {
-                                index: i,
-                                item_type: item.item_type.clone(),
-                                label: item.label.clone(),
-                                data_size: item.data.len(),
-                            })
-                            .collect();
-                        (summaries.len(), summaries)
-                    }
-                    Err(_) => {
-                        // Parse failed — show as single unknown item
-                        let fallback_type = match data_type_byte {
-                            TYPE_SHARE => "share".to_string(),
-                            TYPE_VAULT => "vault".to_string(),
-                            _ => "unknown".to_string(),
-                        };
-                        (
-                            1,
-                            vec![CardItemSummary {
-                                index: 0,
-                                item_type: fallback_type,
-                                label: label.clone(),
-                                data_size: data_length as usize,
-                            }],
-                        )
-                    }
-                }
-            }
-            Err(_) => {
-                // Read failed — show minimal status from the status response
-                let fallback_type = match data_type_byte {
-                    TYPE_SHARE => "share".to_string(),
-                    TYPE_VAULT => "vault".to_string(),
-                    _ => "unknown".to_string(),
-                };
-                (
-                    1,
-                    vec![CardItemSummary {
-                        index: 0,
-                        item_type: fallback_type,
-                        label: label.clone(),
-                        data_size: data_length as usize,
-                    }],
-                )
-            }
+impl<T: ApduTransport> CardLink<T> {
+    fn new(card: T, timeout: Duration, interface: CardInterface) -> Self {
+        CardLink {
+            card: Arc::new(card),
+            timeout,
+            interface,
+            selected_aid: Mutex::new(None),
+            capabilities: AtomicU16::new(0),
+            pin_verified: AtomicBool::new(false),
+            recovering: AtomicBool::new(false),
         }
-    } else {
-        (0, Vec::new())
-    };
+    }
+}
 
-    let free_bytes_estimate = card_capacity as i32 - data_length as i32;
+/// Run one exchange on a worker thread and give up after `timeout`.
+/// On timeout the worker is left blocked in PC/SC; it holds its own
+/// reference to the card, which is reset when the call finally returns.
+/// `what` prefixes transport errors ("APDU transmit", "SELECT").
+fn transmit_with_timeout<T: ApduTransport>(
+    card: &Arc<T>,
+    cmd: &[u8],
+    timeout: Duration,
+    what: &str,
+) -> Result<Vec<u8>, SeqretsError> {
+    exchange_with_timeout(card, cmd, timeout, what)?
+        .map_err(|e| format!("{} failed: {}", what, e).into())
+}
 
-    disconnect_with_reset(card);
+/// `transmit_with_timeout`, handing back the PC/SC error itself so callers
+/// can react to a specific one.
+fn exchange_with_timeout<T: ApduTransport>(
+    card: &Arc<T>,
+    cmd: &[u8],
+    timeout: Duration,
+    what: &str,
+) -> Result<Result<Vec<u8>, pcsc::Error>, SeqretsError> {
+    let (tx, rx) = mpsc::channel();
+    let worker_card = Arc::clone(card);
+    // The command may carry a PIN or share bytes.
+    let worker_cmd = Zeroizing::new(cmd.to_vec());
+    thread::Builder::new()
+        .name("apdu-transmit".to_string())
+        .spawn(move || {
+            let _ = tx.send(worker_card.exchange(&worker_cmd));
+        })
+        .map_err(|e| format!("{} failed: cannot start transmit thread: {}", what, e))?;
 
-    Ok(CardStatus {
-        has_data: data_length > 0,
-        data_length,
-        card_capacity,
-        total_items,
-        items,
-        pin_set,
-        pin_verified,
-        pin_retries_remaining,
-        free_bytes_estimate,
-        wipe_protected,
-    })
+    let received = rx.recv_timeout(timeout);
+    if let Some(app) = APDU_TRACE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        let resp = match &received {
+            Ok(Ok(resp)) => Some(resp.as_slice()),
+            _ => None,
+        };
+        let _ = app.emit(APDU_TRACE_EVENT, trace_apdu(cmd, resp));
+    }
+
+    match received {
+        Ok(resp) => Ok(resp),
+        Err(RecvTimeoutError::Timeout) => Err(SeqretsError::Timeout(format!(
+            "The card did not respond within {} ms. Remove and reinsert it, then try again.",
+            timeout.as_millis()
+        ))),
+        Err(RecvTimeoutError::Disconnected) => {
+            Err(format!("{} failed: transmit thread exited", what).into())
+        }
+    }
 }
 
-/// Write an item to the card, appending to any existing items.
-/// Reads existing items, appends the new one, erases, and writes the combined data.
-#[tauri::command]
-pub fn write_item_to_card(
-    reader: String,
-    item_type: String,
-    data: String,
-    label: String,
-    pin: Option<String>,
-) -> Result<(), String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    verify_pin_if_needed(&card, &pin)?;
+/// Whether a trace may show the data of this command (`response` false) or
+/// of its response. Only SELECT's AID and FCI and GET_VERSION's answer do:
+/// PINs, PUKs, shares, labels and card identifiers never appear, whatever
+/// the command. Anything not listed here is masked.
+fn trace_shows_data(cmd: &[u8], response: bool) -> bool {
+    match cmd {
+        [0x00, 0xA4, 0x04, ..] => true,
+        [CLA, INS_GET_VERSION, ..] => response,
+        _ => false,
+    }
+}
 
-    // Read existing items (if any)
-    let (raw_data, type_byte, existing_label) = read_raw_card_data(&card)?;
-    let mut items = if raw_data.is_empty() {
-        Vec::new()
+/// `[N bytes]` in place of data a trace may not show.
+fn trace_data(data: &[u8], shown: bool) -> String {
+    if shown {
+        format_hex(data)
     } else {
-        parse_card_items(&raw_data, type_byte, &existing_label)?
-    };
+        format!("[{} bytes]", data.len())
+    }
+}
 
-    // Append the new item
-    items.push(CardItem {
-        item_type,
-        label,
-        data,
+/// Build the `apdu-trace` payload for `cmd` and its response, if any.
+fn trace_apdu(cmd: &[u8], resp: Option<&[u8]>) -> ApduTrace {
+    // Header and at most Le: nothing to mask
+    let command = if cmd.len() <= APDU_HEADER_LENGTH {
+        format_hex(cmd)
+    } else {
+        let (header, body) = cmd.split_at(APDU_HEADER_LENGTH);
+        let (data, le) = body.split_at((header[4] as usize).min(body.len()));
+        let mut parts = vec![
+            format_hex(header),
+            trace_data(data, trace_shows_data(cmd, false)),
+        ];
+        if !le.is_empty() {
+            parts.push(format_hex(le));
+        }
+        parts.join(" ")
+    };
+    let response = resp.map(|resp| match split_status_word(resp) {
+        Some((data, sw1, sw2)) if !data.is_empty() => {
+            format!(
+                "{} {}",
+                trace_data(&data, trace_shows_data(cmd, true)),
+                format_hex(&[sw1, sw2])
+            )
+        }
+        _ => format_hex(resp),
     });
-
-    // Write combined items (internally erases first)
-    let result = write_items_to_card(&card, &items);
-    disconnect_with_reset(card);
-    result
+    ApduTrace { command, response }
 }
 
-/// Read all items from the card.
-#[tauri::command]
-pub fn read_card_items(reader: String, pin: Option<String>) -> Result<Vec<CardItem>, String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    verify_pin_if_needed(&card, &pin)?;
-
-    let (raw_data, type_byte, label) = read_raw_card_data(&card)?;
-
-    if raw_data.is_empty() {
-        disconnect_with_reset(card);
-        return Err("No data stored on this card.".to_string());
+/// Split a response into (data, SW1, SW2) without interpreting the status word.
+fn split_status_word(resp: &[u8]) -> Option<(Vec<u8>, u8, u8)> {
+    match resp {
+        [data @ .., sw1, sw2] => Some((data.to_vec(), *sw1, *sw2)),
+        _ => None,
     }
-
-    let items = parse_card_items(&raw_data, type_byte, &label);
-    disconnect_with_reset(card);
-    items
 }
 
-/// Read a single item by index from the card.
-#[tauri::command]
-pub fn read_card_item(
-    reader: String,
-    index: usize,
-    pin: Option<String>,
-) -> Result<CardItem, String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    verify_pin_if_needed(&card, &pin)?;
-
-    let (raw_data, type_byte, label) = read_raw_card_data(&card)?;
-
-    if raw_data.is_empty() {
-        disconnect_with_reset(card);
-        return Err("No data stored on this card.".to_string());
+/// Transmit a command APDU and split the response into (data, SW1, SW2)
+/// without interpreting the status word.
+///
+/// If PC/SC reports the card was reset (SCARD_W_RESET_CARD: another
+/// application reset it, or it browned out), reconnect, select the applet
+/// again and retry the command once.
+fn transmit_apdu<T: ApduTransport>(
+    card: &CardLink<T>,
+    cmd: &[u8],
+) -> Result<(Vec<u8>, u8, u8), SeqretsError> {
+    let timeout = apdu_timeout(card, cmd);
+    let resp = match exchange_with_timeout(&card.card, cmd, timeout, "APDU transmit")? {
+        Err(pcsc::Error::ResetCard) if !card.recovering.swap(true, Ordering::SeqCst) => {
+            let recovered = recover_from_reset(card);
+            card.recovering.store(false, Ordering::SeqCst);
+            recovered?;
+            transmit_with_timeout(&card.card, cmd, timeout, "APDU transmit")?
+        }
+        resp => resp.map_err(|e| format!("APDU transmit failed: {}", e))?,
+    };
+    let (data, sw1, sw2) = split_status_word(&resp).ok_or("Response too short")?;
+    if cmd.starts_with(&[CLA, INS_VERIFY_PIN]) && (sw1, sw2) == (0x90, 0x00) {
+        card.pin_verified.store(true, Ordering::SeqCst);
     }
-
-    let items = parse_card_items(&raw_data, type_byte, &label)?;
-    disconnect_with_reset(card);
-
-    items
-        .get(index)
-        .cloned()
-        .ok_or_else(|| format!("Item index {} out of range (card has {} items)", index, items.len()))
+    Ok((data, sw1, sw2))
 }
 
-/// Delete a single item by index, rewriting the remaining items.
-#[tauri::command]
-pub fn delete_card_item(
-    reader: String,
-    index: usize,
-    pin: Option<String>,
-) -> Result<(), String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    verify_pin_if_needed(&card, &pin)?;
-
-    let (raw_data, type_byte, label) = read_raw_card_data(&card)?;
-
-    if raw_data.is_empty() {
-        disconnect_with_reset(card);
-        return Err("No data stored on this card.".to_string());
+/// How long `cmd` may take: the link's timeout, raised to
+/// `ERASE_TRANSMIT_TIMEOUT` for ERASE_DATA and FACTORY_RESET whichever
+/// command sends them, so a write that starts by erasing the card can't
+/// time out halfway through the erase.
+fn apdu_timeout<T: ApduTransport>(card: &CardLink<T>, cmd: &[u8]) -> Duration {
+    match cmd {
+        [CLA, INS_ERASE_DATA | INS_FACTORY_RESET, ..] => card.timeout.max(ERASE_TRANSMIT_TIMEOUT),
+        _ => card.timeout,
     }
+}
 
-    let mut items = parse_card_items(&raw_data, type_byte, &label)?;
-
-    if index >= items.len() {
-        disconnect_with_reset(card);
-        return Err(format!(
-            "Item index {} out of range (card has {} items)",
-            index,
-            items.len()
+/// Reconnect a reset card and select the applet it had selected. A reset
+/// also drops a verified PIN; rather than let the retried command fail on
+/// a missing PIN, ask for it again.
+fn recover_from_reset<T: ApduTransport>(card: &CardLink<T>) -> Result<(), SeqretsError> {
+    log::warn!("Card was reset during a command; reconnecting");
+    card.card
+        .reconnect()
+        .map_err(|e| format!("Cannot reconnect to the card after it was reset: {}", e))?;
+    let aid = card
+        .selected_aid
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    if let Some(aid) = aid {
+        select_applet(card, Some(&aid))?;
+    }
+    if card.pin_verified.swap(false, Ordering::SeqCst) {
+        return Err(SeqretsError::PinRequired(
+            "The card was reset and no longer has your PIN verified. Please verify your PIN again."
+                .to_string(),
         ));
     }
+    Ok(())
+}
 
-    items.remove(index);
+/// Send a raw APDU and return the response data (without SW1/SW2).
+/// Returns an error if SW != 0x9000.
+fn send_apdu<T: ApduTransport>(
+    card: &CardLink<T>,
+    cla: u8,
+    ins: u8,
+    p1: u8,
+    p2: u8,
+    data: &[u8],
+) -> Result<Vec<u8>, SeqretsError> {
+    let cmd = build_apdu(cla, ins, p1, p2, data);
+    let (data_resp, sw1, sw2) = transmit_apdu(card, &cmd)?;
 
-    let result = if items.is_empty() {
-        // No items left — just erase the card
-        send_apdu(&card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[]).map(|_| ())
+    if sw1 == 0x90 && sw2 == 0x00 {
+        Ok(data_resp)
     } else {
-        write_items_to_card(&card, &items)
-    };
-
-    disconnect_with_reset(card);
-    result
-}
-
-/// Erase all data from the card.
-#[tauri::command]
-pub fn erase_card(reader: String, pin: Option<String>) -> Result<(), String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    verify_pin_if_needed(&card, &pin)?;
-    let result = send_apdu(&card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[]);
-    disconnect_with_reset(card);
-    result.map(|_| ())
+        Err(sw_to_error(sw1, sw2))
+    }
 }
 
-/// Write a complete set of items to the card, replacing any existing data.
-/// Used by the clone-card feature to bulk-write items read from another card.
-#[tauri::command]
-pub fn write_all_items(
-    reader: String,
-    items: Vec<CardItem>,
-    pin: Option<String>,
-) -> Result<(), String> {
-    if items.is_empty() {
-        return Err("No items to write.".to_string());
+/// Translate an ISO 7816-4 status word into a user-facing message.
+/// Unknown codes keep the raw hex so support can still identify them.
+fn sw_to_message(sw1: u8, sw2: u8) -> String {
+    match (sw1, sw2) {
+        (0x61, n) => format!("Card has {} more response bytes available.", n),
+        (0x62, 0x81) => "Part of the returned data may be corrupted.".to_string(),
+        (0x62, 0x82) => "End of data reached before the requested length.".to_string(),
+        (0x63, n) if n & 0xF0 == 0xC0 => {
+            let remaining = n & 0x0F;
+            format!(
+                "Verification failed. {} attempt{} remaining.",
+                remaining,
+                if remaining == 1 { "" } else { "s" }
+            )
+        }
+        (0x64, 0x00) => "Card failed to execute the command (state unchanged).".to_string(),
+        (0x65, 0x81) => "Card memory failure. The card may be damaged.".to_string(),
+        (0x67, 0x00) => "Wrong length. The command data was rejected by the card.".to_string(),
+        (0x68, 0x81) => "Logical channels are not supported by this card.".to_string(),
+        (0x68, 0x82) => "Secure messaging is not supported by this card.".to_string(),
+        (0x69, 0x82) => "PIN verification required. Please verify your PIN first.".to_string(),
+        (0x69, 0x83) | (0x69, 0x84) => "Card is locked. Too many incorrect PIN attempts.".to_string(),
+        (0x69, 0x85) => "Conditions of use not satisfied. The card is not in the right state for this operation.".to_string(),
+        (0x69, 0x86) => "Command not allowed on this card.".to_string(),
+        (0x6A, 0x80) => "The card rejected the command data as invalid.".to_string(),
+        (0x6A, 0x81) => "Function not supported by this card.".to_string(),
+        (0x6A, 0x82) => "Application or file not found on this card.".to_string(),
+        (0x6A, 0x84) => "Card storage full. Data too large for this card.".to_string(),
+        (0x6A, 0x86) | (0x6B, 0x00) => "Wrong parameters (P1/P2) for this command.".to_string(),
+        (0x6A, 0x88) => "Referenced data not found on the card.".to_string(),
+        (0x6C, n) => format!("Wrong expected length; the card can return {} bytes.", n),
+        (0x6D, 0x00) => "Instruction not supported. The card applet may be outdated.".to_string(),
+        (0x6E, 0x00) => "Command class not supported. This may not be a seQRets card.".to_string(),
+        (0x6F, 0x00) => "The card reported an internal error.".to_string(),
+        _ => format!("Card returned error: SW={:02X}{:02X}", sw1, sw2),
     }
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    verify_pin_if_needed(&card, &pin)?;
-    let result = write_items_to_card(&card, &items);
-    disconnect_with_reset(card);
-    result
 }
 
-/// Force-erase a card without PIN verification.
-/// Used to recover locked cards (PIN retries exhausted) when wipe protection is off.
-/// Will fail with SW_SECURITY_STATUS_NOT_SATISFIED if wipe protection is enabled.
-#[tauri::command]
-pub fn force_erase_card(reader: String) -> Result<(), String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    // No PIN verification — send erase directly
-    let result = send_apdu(&card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[]);
-    disconnect_with_reset(card);
-    result.map(|_| ())
+/// Classify a failing status word so the frontend can branch on its kind.
+fn sw_to_error(sw1: u8, sw2: u8) -> SeqretsError {
+    let message = sw_to_message(sw1, sw2);
+    match (sw1, sw2) {
+        (0x63, n) if n & 0xF0 == 0xC0 => SeqretsError::WrongPin(message),
+        (0x69, 0x82) => SeqretsError::PinRequired(message),
+        (0x69, 0x83) | (0x69, 0x84) => SeqretsError::CardLocked(message),
+        (0x6A, 0x84) => SeqretsError::StorageFull(message),
+        _ => SeqretsError::Other(message),
+    }
 }
 
-/// Enable or disable wipe protection on the card.
-/// Requires PIN verification. When enabled, ERASE_DATA requires PIN.
-#[tauri::command]
-pub fn set_wipe_protect(reader: String, pin: String, enabled: bool) -> Result<(), String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    send_apdu(&card, CLA, INS_VERIFY_PIN, 0x00, 0x00, pin.as_bytes())?;
-    let p1 = if enabled { 0x01 } else { 0x00 };
-    let result = send_apdu(&card, CLA, INS_SET_WIPE_PROTECT, p1, 0x00, &[]);
-    disconnect_with_reset(card);
-    result.map(|_| ())
+/// What SELECT reported about the applet.
+struct SelectedApplet {
+    version: AppletVersion,
+    fci: AppletFci,
 }
 
-/// Verify the PIN on the card.
-#[tauri::command]
-pub fn verify_pin(reader: String, pin: String) -> Result<(), String> {
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    let result = send_apdu(&card, CLA, INS_VERIFY_PIN, 0x00, 0x00, pin.as_bytes());
-    disconnect_with_reset(card);
-    result.map(|_| ())
+/// Proprietary FCI fields returned on SELECT (applet 1.2+). All `None`
+/// when the applet returned no FCI or one this build can't parse.
+#[derive(Debug, Default, PartialEq)]
+struct AppletFci {
+    version: Option<AppletVersion>,
+    capabilities: Option<u16>,
+    lifecycle: Option<u8>,
+    /// ISO 7816-4 life cycle status byte (tag 8A), which some card managers
+    /// add to the FCI with the GlobalPlatform state of the application.
+    content_state: Option<u8>,
 }
 
-/// Set initial PIN on the card (only works if no PIN is set).
-#[tauri::command]
-pub fn set_pin(reader: String, pin: String) -> Result<(), String> {
-    let pin_bytes = pin.as_bytes();
-    if pin_bytes.len() < 8 || pin_bytes.len() > 16 {
-        return Err("PIN must be 8-16 characters.".to_string());
+/// GlobalPlatform application lifecycle name for the FCI's `content_state`:
+/// "installed" (0x03), "selectable" (0x07), "personalized" (an
+/// applet-specific state on top of SELECTABLE) or "locked" (bit 8 set).
+/// Without the byte the applet is reported "selectable", since it just
+/// answered SELECT.
+fn gp_lifecycle(content_state: Option<u8>) -> &'static str {
+    match content_state {
+        None | Some(0x07) => "selectable",
+        Some(state) if state & 0x80 != 0 => "locked",
+        Some(0x03) => "installed",
+        Some(state) if state & 0x07 == 0x07 => "personalized",
+        Some(_) => "unknown",
     }
+}
 
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    let result = send_apdu(&card, CLA, INS_SET_PIN, 0x00, 0x00, pin_bytes);
-    disconnect_with_reset(card);
-    result.map(|_| ())
+fn applet_locked_error() -> SeqretsError {
+    SeqretsError::AppletLocked(
+        "This card's seQRets applet is locked by its issuer. Only the issuer can unlock it; your data on it cannot be read or changed until then."
+            .to_string(),
+    )
 }
 
-/// Change the PIN on the card (must be verified first).
-#[tauri::command]
-pub fn change_pin(reader: String, old_pin: String, new_pin: String) -> Result<(), String> {
-    let new_pin_bytes = new_pin.as_bytes();
-    if new_pin_bytes.len() < 8 || new_pin_bytes.len() > 16 {
-        return Err("New PIN must be 8-16 characters.".to_string());
-    }
+/// Send a SELECT APDU to activate the seQRets applet on the card.
+/// `aid_override` replaces the default `SEQRETS_AID` when set. Returns the
+/// applet version and FCI, refusing applets older than
+/// `MIN_SUPPORTED_APPLET_VERSION`.
+fn select_applet<T: ApduTransport>(
+    card: &CardLink<T>,
+    aid_override: Option<&[u8]>,
+) -> Result<SelectedApplet, SeqretsError> {
+    let aid = aid_override.unwrap_or(SEQRETS_AID);
+    validate_aid(aid)?;
 
-    let old_pin_bytes = old_pin.as_bytes();
-    let mut data = Vec::with_capacity(old_pin_bytes.len() + new_pin_bytes.len());
-    data.extend_from_slice(old_pin_bytes);
-    data.extend_from_slice(new_pin_bytes);
+    // SELECT command: CLA=0x00, INS=0xA4, P1=0x04 (by DF name), P2=0x00, Le=0x00
+    let mut cmd = vec![0x00, 0xA4, 0x04, 0x00];
+    cmd.push(aid.len() as u8);
+    cmd.extend_from_slice(aid);
+    cmd.push(0x00);
+
+    let resp = transmit_with_timeout(&card.card, &cmd, card.timeout, "SELECT")?;
+    let (mut fci, mut sw1, mut sw2) =
+        split_status_word(&resp).ok_or("SELECT response too short")?;
+
+    // T=0 readers hand back 61xx; fetch the FCI with GET RESPONSE
+    let mut rounds = 0;
+    while sw1 == 0x61 && rounds < 4 {
+        let (more, next_sw1, next_sw2) = transmit_apdu(card, &[0x00, 0xC0, 0x00, 0x00, sw2])?;
+        fci.extend_from_slice(&more);
+        (sw1, sw2) = (next_sw1, next_sw2);
+        rounds += 1;
+    }
+
+    if sw1 == 0x90 && sw2 == 0x00 {
+        let fci = parse_fci(&fci);
+        if gp_lifecycle(fci.content_state) == "locked" {
+            return Err(applet_locked_error());
+        }
+        let version = match fci.version {
+            Some(version) => version,
+            None => read_applet_version(card)?,
+        };
+        check_applet_version(version)?;
+        *card
+            .selected_aid
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(aid.to_vec());
+        card.capabilities
+            .store(fci.capabilities.unwrap_or(0), Ordering::SeqCst);
+        Ok(SelectedApplet { version, fci })
+    } else if sw1 == 0x6A && sw2 == 0x82 {
+        Err(format!(
+            "seQRets applet not found on this card (AID {}). Please install the applet first.",
+            format_hex(aid)
+        )
+        .into())
+    } else if sw1 == 0x62 && sw2 == 0x83 {
+        // ISO 7816-4 "selected file deactivated": GlobalPlatform card
+        // managers answer this way for a LOCKED application.
+        Err(applet_locked_error())
+    } else if sw1 == 0x69 && sw2 == 0x99 {
+        Err("The card refused to select the seQRets applet (6999). It may be locked by its issuer or not fully installed.".into())
+    } else {
+        Err(format!("SELECT failed: {}", sw_to_message(sw1, sw2)).into())
+    }
+}
+
+/// Split BER-TLV data with one-byte tags into (tag, value) pairs.
+/// Returns `None` if the data is malformed.
+fn parse_tlv(mut data: &[u8]) -> Option<Vec<(u8, &[u8])>> {
+    let mut fields = Vec::new();
+    while let [tag, rest @ ..] = data {
+        let (len, rest) = match rest {
+            [0x81, len, rest @ ..] => (*len as usize, rest),
+            [len, rest @ ..] if *len < 0x80 => (*len as usize, rest),
+            _ => return None,
+        };
+        if rest.len() < len {
+            return None;
+        }
+        fields.push((*tag, &rest[..len]));
+        data = &rest[len..];
+    }
+    Some(fields)
+}
+
+/// Parse the FCI template `6F { 84 <AID>, A5 { 80 version, 81 capabilities,
+/// 82 lifecycle } }`. Empty, unknown or malformed FCI yields all `None`.
+fn parse_fci(data: &[u8]) -> AppletFci {
+    fn child(data: &[u8], wanted: u8) -> Option<&[u8]> {
+        parse_tlv(data)?
+            .into_iter()
+            .find(|(tag, _)| *tag == wanted)
+            .map(|(_, value)| value)
+    }
+
+    let mut fci = AppletFci::default();
+    let template = child(data, 0x6F);
+    fci.content_state = template
+        .and_then(|template| child(template, 0x8A))
+        .and_then(|value| match value {
+            [state] => Some(*state),
+            _ => None,
+        });
+    let proprietary = template.and_then(|template| child(template, 0xA5));
+    for (tag, value) in proprietary.and_then(parse_tlv).unwrap_or_default() {
+        match (tag, value) {
+            (0x80, [major, minor]) => {
+                fci.version = Some(AppletVersion {
+                    major: *major,
+                    minor: *minor,
+                })
+            }
+            (0x81, [hi, lo]) => fci.capabilities = Some(u16::from_be_bytes([*hi, *lo])),
+            (0x82, [state]) => fci.lifecycle = Some(*state),
+            _ => {}
+        }
+    }
+    fci
+}
+
+/// Ask the selected applet for its version. Applets without GET_VERSION
+/// reject the instruction and count as `OLDEST_APPLET_VERSION`.
+fn read_applet_version<T: ApduTransport>(
+    card: &CardLink<T>,
+) -> Result<AppletVersion, SeqretsError> {
+    let cmd = build_apdu(CLA, INS_GET_VERSION, 0x00, 0x00, &[]);
+    let (data, sw1, sw2) = transmit_apdu(card, &cmd)?;
+    parse_applet_version(&data, sw1, sw2)
+}
+
+fn parse_applet_version(data: &[u8], sw1: u8, sw2: u8) -> Result<AppletVersion, SeqretsError> {
+    match (sw1, sw2, data) {
+        (0x90, 0x00, [major, minor, ..]) => Ok(AppletVersion {
+            major: *major,
+            minor: *minor,
+        }),
+        (0x90, 0x00, _) => Err(SeqretsError::CorruptData(
+            "Invalid version response from card".to_string(),
+        )),
+        (0x6D, 0x00, _) => Ok(OLDEST_APPLET_VERSION),
+        _ => Err(sw_to_error(sw1, sw2)),
+    }
+}
+
+fn check_applet_version(version: AppletVersion) -> Result<(), SeqretsError> {
+    if version < MIN_SUPPORTED_APPLET_VERSION {
+        return Err(format!(
+            "This card's seQRets applet is version {}, but version {} or newer is required. Please update your card applet.",
+            version, MIN_SUPPORTED_APPLET_VERSION
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Connect to a specific reader and return a card handle whose APDUs time
+/// out after `timeout`.
+fn connect_reader(
+    reader_name: &str,
+    timeout: Duration,
+) -> Result<(Context, CardLink), SeqretsError> {
+    let ctx = Context::establish(Scope::User)
+        .map_err(|e| format!("Cannot access smart card system: {}", e))?;
+    connect_in(ctx, reader_name, timeout)
+}
+
+/// `connect_reader` for commands with a `wait_for_card` argument: with a
+/// non-zero wait (in ms, capped at two minutes) it first waits for a card
+/// to be tapped or inserted, so the UI can say "tap your card now" before
+/// the command is sent. Running out of time is `NoCardPresented`; cancelling
+/// `operation` stops the wait.
+fn connect_when_present(
+    reader_name: &str,
+    timeout: Duration,
+    wait_for_card: Option<u32>,
+    operation: &CardOperation,
+) -> Result<(Context, CardLink), SeqretsError> {
+    let wait = match wait_for_card {
+        None | Some(0) => return connect_reader(reader_name, timeout),
+        Some(ms) => Duration::from_millis(ms.min(WAIT_FOR_CARD_MAX_MS) as u64),
+    };
+    let ctx = Context::establish(Scope::User)
+        .map_err(|e| format!("Cannot access smart card system: {}", e))?;
+    wait_for_card_present(&ctx, reader_name, wait, &operation.token)?;
+    connect_in(ctx, reader_name, timeout)
+}
+
+/// Block until the reader reports a responsive card, `wait` passes or
+/// `cancel` is set. Status changes are awaited in `READER_BUSY_POLL` slices
+/// so a cancellation is noticed promptly.
+fn wait_for_card_present(
+    ctx: &Context,
+    reader_name: &str,
+    wait: Duration,
+    cancel: &AtomicBool,
+) -> Result<(), SeqretsError> {
+    let name = std::ffi::CString::new(reader_name).map_err(|_| "Invalid reader name")?;
+    let deadline = Instant::now() + wait;
+    let mut readers = [ReaderState::new(name, pcsc::State::UNAWARE)];
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(SeqretsError::Cancelled(
+                "Stopped waiting for a card.".to_string(),
+            ));
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match ctx.get_status_change(remaining.min(READER_BUSY_POLL), &mut readers) {
+            Ok(()) | Err(pcsc::Error::Timeout) => {}
+            Err(e) => {
+                return Err(format!("Cannot watch '{}' for a card: {}", reader_name, e).into())
+            }
+        }
+        if card_ready(reader_name, readers[0].event_state())? {
+            return Ok(());
+        }
+        readers[0].sync_current_state();
+        if remaining.is_zero() {
+            return Err(SeqretsError::NoCardPresented(format!(
+                "No card was presented to '{}' within {} seconds.",
+                reader_name,
+                wait.as_secs()
+            )));
+        }
+    }
+}
+
+/// Whether a reader in `event` state holds a card that can be connected
+/// to. A reader that has gone away is an error rather than something to
+/// keep waiting on.
+fn card_ready(reader_name: &str, event: pcsc::State) -> Result<bool, SeqretsError> {
+    if event.intersects(pcsc::State::UNKNOWN | pcsc::State::IGNORE) {
+        return Err(format!("Reader '{}' is not connected.", reader_name).into());
+    }
+    Ok(event.contains(pcsc::State::PRESENT) && !event.contains(pcsc::State::MUTE))
+}
+
+/// Connect to the card in `reader_name` through `ctx`.
+fn connect_in(
+    ctx: Context,
+    reader_name: &str,
+    timeout: Duration,
+) -> Result<(Context, CardLink), SeqretsError> {
+    let card = ctx
+        .connect(
+            &std::ffi::CString::new(reader_name).map_err(|_| "Invalid reader name")?,
+            ShareMode::Shared,
+            Protocols::ANY,
+        )
+        .map_err(|e| connect_error(reader_name, e))?;
+    Ok((ctx, link_card(reader_name, card, timeout)))
+}
+
+/// The error for a failed connect. A sharing violation means another process
+/// holds the card exclusively, typically other smartcard software or, on
+/// Windows, a minidriver that grabbed the card when it was inserted.
+fn connect_error(reader_name: &str, e: pcsc::Error) -> SeqretsError {
+    match e {
+        pcsc::Error::SharingViolation => SeqretsError::ReaderBusy(format!(
+            "'{reader_name}' is in use by another application. Close any other smart card software (wallet apps, \
+             middleware, card managers) and try again."
+        )),
+        e => format!("Cannot connect to card in '{}': {}", reader_name, e).into(),
+    }
+}
+
+/// Run `attempt` again every `poll` while it fails with `ReaderBusy`, until
+/// it succeeds, fails some other way or `timeout` has passed.
+fn retry_while_busy<T>(
+    timeout: Duration,
+    poll: Duration,
+    mut attempt: impl FnMut() -> Result<T, SeqretsError>,
+) -> Result<T, SeqretsError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match attempt() {
+            Err(SeqretsError::ReaderBusy(_)) if Instant::now() + poll < deadline => {
+                thread::sleep(poll)
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Wrap a freshly connected card, detecting its interface from the ATR.
+fn link_card(reader_name: &str, card: Card, timeout: Duration) -> CardLink {
+    let atr = card
+        .status2_owned()
+        .ok()
+        .map(|status| status.atr().to_vec());
+    let interface = detect_interface(reader_name, atr.as_deref());
+    CardLink::new(PcscCard(Mutex::new(card)), timeout, interface)
+}
+
+/// Classify the card's interface. PC/SC readers synthesize the ATR of a
+/// contactless card as `3B 8n 80 01 ...` (PC/SC part 3); failing that, a
+/// contactless word in the reader name ("PICC", "5422CL") decides. Any other
+/// card with a readable ATR is taken to be in a contact slot.
+fn detect_interface(reader_name: &str, atr: Option<&[u8]>) -> CardInterface {
+    let synthesized_atr = matches!(atr, Some([0x3B, t0, 0x80, 0x01, ..]) if t0 & 0xF0 == 0x80);
+    let contactless_name = reader_name
+        .to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| {
+            CONTACTLESS_READER_WORDS.contains(&word)
+                // model numbers such as "5422cl" or "5022cl"
+                || (word.ends_with("cl") && word.starts_with(|c: char| c.is_ascii_digit()))
+        });
+    match atr {
+        _ if synthesized_atr || contactless_name => CardInterface::Contactless,
+        Some(_) => CardInterface::Contact,
+        None => CardInterface::Unknown,
+    }
+}
+
+/// Refuse a write when contact-only writes are required and the card isn't
+/// known to be in a contact slot.
+fn check_write_interface(
+    interface: CardInterface,
+    require_contact: bool,
+) -> Result<(), SeqretsError> {
+    if require_contact && interface != CardInterface::Contact {
+        return Err(SeqretsError::InvalidInput(match interface {
+            CardInterface::Contactless => {
+                "Writing over a contactless interface is disabled. Insert the card in a contact reader.".to_string()
+            }
+            _ => "Writing is limited to contact readers, and this reader's interface couldn't be determined."
+                .to_string(),
+        }));
+    }
+    Ok(())
+}
 
-    let (_ctx, card) = connect_reader(&reader)?;
-    select_applet(&card)?;
-    let result = send_apdu(
-        &card,
-        CLA,
-        INS_CHANGE_PIN,
-        old_pin_bytes.len() as u8,
+/// A numeric reader attribute (a host-order DWORD), if the driver reports it.
+fn read_u32_attribute(card: &Card, attribute: Attribute) -> Option<u32> {
+    parse_dword(&card.get_attribute_owned(attribute).ok()?)
+}
+
+/// Drivers return DWORD attributes in host byte order, sometimes shortened.
+fn parse_dword(bytes: &[u8]) -> Option<u32> {
+    if bytes.is_empty() || bytes.len() > 4 {
+        return None;
+    }
+    let mut word = [0u8; 4];
+    if cfg!(target_endian = "little") {
+        word[..bytes.len()].copy_from_slice(bytes);
+        Some(u32::from_le_bytes(word))
+    } else {
+        word[4 - bytes.len()..].copy_from_slice(bytes);
+        Some(u32::from_be_bytes(word))
+    }
+}
+
+/// The control code a CM_IOCTL_GET_FEATURE_REQUEST list gives for `feature`.
+/// Entries are tag, length (4) and the code, big-endian.
+fn feature_control_code(mut features: &[u8], feature: u8) -> Option<u32> {
+    while let [tag, len, rest @ ..] = features {
+        let (value, next) = (rest.get(..*len as usize)?, &rest[*len as usize..]);
+        if *tag == feature {
+            return Some(u32::from_be_bytes(value.try_into().ok()?));
+        }
+        features = next;
+    }
+    None
+}
+
+/// Control code for FEATURE_VERIFY_PIN_DIRECT, or `None` without a PIN pad.
+fn pinpad_verify_code(card: &Card) -> Option<u32> {
+    let mut buf = [0u8; 256];
+    let features = card
+        .control(ctl_code(IOCTL_GET_FEATURE_REQUEST.into()), &[], &mut buf)
+        .ok()?;
+    feature_control_code(features, FEATURE_VERIFY_PIN_DIRECT)
+}
+
+/// PIN_VERIFY_STRUCTURE (PC/SC part 10) for the applet's VERIFY_PIN: an
+/// ASCII PIN of PIN_MIN_LENGTH–PIN_MAX_LENGTH bytes, unpadded, confirmed
+/// with the OK key. The reader fills in Lc and the PIN.
+fn pin_verify_structure() -> Vec<u8> {
+    let apdu = [CLA, INS_VERIFY_PIN, 0x00, 0x00, 0x00];
+    let mut out = vec![
+        PINPAD_TIMEOUT_SECS,  // bTimerOut
+        PINPAD_TIMEOUT_SECS,  // bTimerOut2
+        0x82,                 // bmFormatString: byte units, no offset, ASCII
+        0x00,                 // bmPINBlockString: no length field, variable block
+        0x00,                 // bmPINLengthFormat
+        PIN_MAX_LENGTH as u8, // wPINMaxExtraDigit (LE): max, then min
+        PIN_MIN_LENGTH as u8,
+        0x02, // bEntryValidationCondition: OK key
+        0x01, // bNumberMessage
+        0x09,
+        0x04, // wLangId: en-US
+        0x00, // bMsgIndex
         0x00,
-        &data,
-    );
-    disconnect_with_reset(card);
-    result.map(|_| ())
+        0x00,
+        0x00, // bTeoPrologue
+    ];
+    out.extend_from_slice(&(apdu.len() as u32).to_le_bytes()); // ulDataLength
+    out.extend_from_slice(&apdu);
+    out
+}
+
+/// Classify the status word from a PIN pad verify. 64xx are the reader's
+/// own outcomes; anything else is the card's answer.
+fn pinpad_status(sw1: u8, sw2: u8) -> Result<(), SeqretsError> {
+    match (sw1, sw2) {
+        (0x90, 0x00) => Ok(()),
+        (0x64, 0x00) => Err(SeqretsError::Timeout(
+            "PIN entry on the reader timed out.".to_string(),
+        )),
+        (0x64, 0x01) => Err(SeqretsError::Cancelled(
+            "PIN entry was cancelled on the reader.".to_string(),
+        )),
+        (0x64, 0x03) => Err(SeqretsError::InvalidInput(format!(
+            "The PIN must be {}-{} characters.",
+            PIN_MIN_LENGTH, PIN_MAX_LENGTH
+        ))),
+        _ => Err(sw_to_error(sw1, sw2)),
+    }
+}
+
+/// Run VERIFY_PIN through the PIN pad behind `code`. Blocks until the user
+/// finishes or the reader times out.
+fn verify_pin_with_pinpad(card: &Card, code: u32) -> Result<(), SeqretsError> {
+    let mut buf = [0u8; 258];
+    let resp = card
+        .control(code.into(), &pin_verify_structure(), &mut buf)
+        .map_err(|e| format!("PIN pad verify failed: {}", e))?;
+    let (_, sw1, sw2) = split_status_word(resp).ok_or("PIN pad response too short")?;
+    pinpad_status(sw1, sw2)
+}
+
+fn protocol_name(protocol: Option<Protocol>) -> &'static str {
+    match protocol {
+        Some(Protocol::T0) => "T=0",
+        Some(Protocol::T1) => "T=1",
+        Some(Protocol::RAW) => "raw",
+        None => "unknown",
+    }
+}
+
+/// STORE_DATA chunk size for a reader accepting `max_send`-byte commands.
+/// Applets with `CAP_VARIABLE_WRITE_CHUNK` take any chunk up to a full short
+/// APDU; older ones compute offsets from a fixed `CHUNK_SIZE`, so a reader
+/// that can't carry that is refused rather than sent shorter chunks.
+///
+/// `chunk_override` (from `set_write_chunk_size`) replaces what the reader
+/// reports, but older applets still only take `CHUNK_SIZE`.
+fn write_chunk_size(
+    max_send: Option<u32>,
+    capabilities: Option<u16>,
+    chunk_override: Option<usize>,
+) -> Result<usize, SeqretsError> {
+    let variable = capabilities.is_some_and(|caps| caps & CAP_VARIABLE_WRITE_CHUNK != 0);
+    match chunk_override {
+        Some(size) if variable || size == CHUNK_SIZE => return Ok(size),
+        Some(size) => {
+            return Err(SeqretsError::InvalidInput(format!(
+                "This applet only writes {}-byte chunks; the {}-byte chunk size override needs applet 1.3 or later.",
+                CHUNK_SIZE, size
+            )))
+        }
+        None => {}
+    }
+    let Some(max_send) = max_send else {
+        return Ok(CHUNK_SIZE);
+    };
+    let fits = (max_send as usize)
+        .saturating_sub(APDU_HEADER_LENGTH)
+        .min(MAX_SHORT_APDU_DATA);
+    if variable && fits >= MIN_WRITE_CHUNK_SIZE {
+        Ok(fits)
+    } else if fits >= CHUNK_SIZE {
+        Ok(CHUNK_SIZE)
+    } else {
+        Err(SeqretsError::InvalidInput(format!(
+            "This reader accepts at most {} bytes per command, too few to write to this card{}. Try another reader.",
+            max_send,
+            if variable { "" } else { " (applet 1.3 writes in smaller chunks)" }
+        )))
+    }
+}
+
+/// SELECT the applet and work out the STORE_DATA chunk size to write with.
+fn select_for_write(
+    card: &CardLink,
+    aid_override: Option<&[u8]>,
+    chunk_override: Option<usize>,
+) -> Result<usize, SeqretsError> {
+    let applet = select_applet(card, aid_override)?;
+    write_chunk_size(
+        read_u32_attribute(&card.card.handle(), Attribute::Maxinput),
+        applet.fci.capabilities,
+        chunk_override,
+    )
+}
+
+/// Identifies the physical card for share wrapping: the UID the reader
+/// reports for contactless cards, otherwise the GlobalPlatform CPLC data,
+/// which carries the chip serial number. Getting the CPLC selects the card
+/// manager, so select the seQRets applet again afterwards.
+fn card_identifier(card: &CardLink) -> Result<Vec<u8>, SeqretsError> {
+    if let (uid, 0x90, 0x00) = transmit_apdu(card, &GET_UID_APDU)? {
+        if !uid.is_empty() {
+            return Ok(uid);
+        }
+    }
+
+    transmit_apdu(card, &SELECT_CARD_MANAGER_APDU)?;
+    let (mut resp, mut sw1, mut sw2) = transmit_apdu(card, &GET_CPLC_APDU)?;
+    // T=0 cards ask for the exact Le (6Cxx) or hand the data back via 61xx
+    if sw1 == 0x6C {
+        let mut cmd = GET_CPLC_APDU;
+        cmd[4] = sw2;
+        (resp, sw1, sw2) = transmit_apdu(card, &cmd)?;
+    }
+    if sw1 == 0x61 {
+        (resp, sw1, sw2) = transmit_apdu(card, &[0x00, 0xC0, 0x00, 0x00, sw2])?;
+    }
+
+    match parse_cplc(&resp) {
+        Some(cplc) if sw1 == 0x90 && sw2 == 0x00 => Ok(cplc.to_vec()),
+        _ => Err(SeqretsError::InvalidInput(
+            "This card doesn't report a unique identifier, so a share can't be wrapped to it."
+                .to_string(),
+        )),
+    }
+}
+
+/// The CPLC value in a GET DATA response (`9F 7F len value`).
+fn parse_cplc(resp: &[u8]) -> Option<&[u8]> {
+    match resp {
+        [0x9F, 0x7F, len, value @ ..] if *len > 0 && value.len() >= *len as usize => {
+            Some(&value[..*len as usize])
+        }
+        _ => None,
+    }
+}
+
+/// The data to store for a share: wrapped to this card when a passphrase is
+/// given (must run before the applet is selected, see `card_identifier`).
+fn share_for_card(
+    card: &CardLink,
+    share: &str,
+    passphrase: Option<&str>,
+) -> Result<(String, bool), SeqretsError> {
+    match passphrase {
+        Some(passphrase) => {
+            let card_id = card_identifier(card)?;
+            Ok((crypto::wrap_card_share(share, passphrase, &card_id)?, true))
+        }
+        None => Ok((share.to_string(), false)),
+    }
+}
+
+/// Replace wrapped shares in `items` with their plaintext when a passphrase
+/// is given; without one they are returned still wrapped. Call after the
+/// card data has been read, since this deselects the applet.
+fn unwrap_card_items(
+    card: &CardLink,
+    items: &mut [CardItem],
+    passphrase: Option<&str>,
+) -> Result<(), SeqretsError> {
+    let Some(passphrase) = passphrase else {
+        return Ok(());
+    };
+    if !items.iter().any(|item| item.wrapped) {
+        return Ok(());
+    }
+    let card_id = card_identifier(card)?;
+    for item in items.iter_mut().filter(|item| item.wrapped) {
+        item.data = crypto::unwrap_card_share(&item.data, passphrase, &card_id)?.to_string();
+        item.wrapped = false;
+    }
+    Ok(())
+}
+
+/// Explicitly disconnect the card with a reset disposition.
+/// This forces the PC/SC subsystem to clear the session state,
+/// preventing stale connections when the same reader is used again.
+/// If a timed-out transmit still holds the card, dropping it leaves the
+/// reset to that worker (`Card`'s drop also resets).
+fn disconnect_with_reset(link: CardLink) {
+    if let Ok(card) = Arc::try_unwrap(link.card) {
+        let card = card.0.into_inner().unwrap_or_else(PoisonError::into_inner);
+        let _ = card.disconnect(Disposition::ResetCard);
+    }
+}
+
+/// If a PIN is provided, verify it on the current connection.
+/// This must be called in the same connection as the protected operation
+/// because PIN verification state is transient (cleared on applet re-select).
+fn verify_pin_if_needed(card: &CardLink, pin: &Option<String>) -> Result<(), SeqretsError> {
+    if let Some(ref p) = pin {
+        if !p.is_empty() {
+            send_apdu(card, CLA, INS_VERIFY_PIN, 0x00, 0x00, p.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse total card capacity from a GET_STATUS response.
+///
+/// The capacity field is 2 big-endian bytes appended after the label bytes:
+///   [0-1]=dataLen, [2]=type, [3]=pinSet, [4]=pinVerified,
+///   [5]=pinRetries, [6]=labelLen, [7..7+labelLen]=label,
+///   [7+labelLen..7+labelLen+2]=capacity
+///
+/// Returns `false` if the response is too short (older applet without the field).
+fn parse_wipe_protected(status_resp: &[u8]) -> bool {
+    if status_resp.len() < 7 {
+        return false;
+    }
+    let label_length = status_resp[6] as usize;
+    let wipe_protect_offset = 7 + label_length + 2; // after capacity (2 bytes)
+    if status_resp.len() > wipe_protect_offset {
+        status_resp[wipe_protect_offset] == 0x01
+    } else {
+        false
+    }
+}
+
+/// The applet's write counter, or `None` when it lacks `CAP_WRITE_COUNT` or
+/// won't answer. Needs no PIN.
+fn read_write_count<T: ApduTransport>(card: &CardLink<T>) -> Option<u32> {
+    if card.capabilities.load(Ordering::SeqCst) & CAP_WRITE_COUNT == 0 {
+        return None;
+    }
+    let resp = send_apdu(card, CLA, INS_GET_WRITE_COUNT, 0x00, 0x00, &[]).ok()?;
+    <[u8; 4]>::try_from(resp.as_slice())
+        .ok()
+        .map(u32::from_be_bytes)
+}
+
+/// Parse PUK state from a GET_STATUS response.
+///
+/// Two bytes follow the wipe-protect flag: [pukSet][pukRetries].
+/// Returns `(false, 0)` for older applets that predate PUK support.
+fn parse_puk_status(status_resp: &[u8]) -> (bool, u8) {
+    if status_resp.len() < 7 {
+        return (false, 0);
+    }
+    let label_length = status_resp[6] as usize;
+    let puk_offset = 7 + label_length + 3; // after capacity (2) + wipe protect (1)
+    if status_resp.len() >= puk_offset + 2 {
+        (status_resp[puk_offset] == 0x01, status_resp[puk_offset + 1])
+    } else {
+        (false, 0)
+    }
+}
+
+/// Parse the label from a GET_STATUS response ([6]=labelLen, then the bytes).
+/// Returns an empty string if the response is shorter than it claims.
+fn parse_status_label(status_resp: &[u8]) -> String {
+    let label_length = match status_resp.get(6) {
+        Some(&len) => len as usize,
+        None => return String::new(),
+    };
+    match status_resp.get(7..7 + label_length) {
+        Some(bytes) => String::from_utf8_lossy(bytes).to_string(),
+        None => String::new(),
+    }
+}
+
+/// Returns DEFAULT_CARD_CAPACITY if the response is too short (older applet).
+fn parse_card_capacity(status_resp: &[u8]) -> usize {
+    if status_resp.len() < 7 {
+        return DEFAULT_CARD_CAPACITY;
+    }
+    let label_length = status_resp[6] as usize;
+    let capacity_offset = 7 + label_length;
+    if status_resp.len() >= capacity_offset + 2 {
+        ((status_resp[capacity_offset] as usize) << 8) | (status_resp[capacity_offset + 1] as usize)
+    } else {
+        DEFAULT_CARD_CAPACITY
+    }
+}
+
+/// Chunk callback that emits `event` with a `ChunkProgress` payload.
+/// Emit failures are ignored — progress is advisory.
+fn progress_emitter<'a>(app: &'a AppHandle, event: &'static str) -> impl Fn(usize, usize) + 'a {
+    move |chunk, total| {
+        let _ = app.emit(event, ChunkProgress { chunk, total });
+    }
+}
+
+/// `StorageFull` when `data_len` bytes don't fit in one write of
+/// `chunk_size`-byte chunks: STORE_DATA numbers its chunks with a single byte.
+fn check_write_size(data_len: usize, chunk_size: usize) -> Result<(), SeqretsError> {
+    if data_len.div_ceil(chunk_size) > 255 {
+        return Err(SeqretsError::StorageFull(format!(
+            "Data too large: {} bytes exceeds maximum write size of {} bytes with this reader",
+            data_len,
+            255 * chunk_size
+        )));
+    }
+    Ok(())
+}
+
+/// Write a data blob to the card in `chunk_size` chunks (see
+/// `select_for_write`), with type and label metadata.
+/// Progress is reported after each STORE_DATA chunk. If the transfer is
+/// cancelled between chunks the partial data is erased again.
+fn write_data_to_card<T: ApduTransport>(
+    card: &CardLink<T>,
+    data: &[u8],
+    data_type: u8,
+    label_str: &str,
+    chunk_size: usize,
+    require_contact: bool,
+    transfer: &Transfer,
+) -> Result<(), SeqretsError> {
+    check_write_interface(card.interface, require_contact)?;
+
+    // Refuse before anything is erased; the applet would reject it anyway
+    let label_bytes = label_str.as_bytes();
+    if label_bytes.len() > LABEL_MAX_LENGTH {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Card label is {} bytes; the card stores at most {}.",
+            label_bytes.len(),
+            LABEL_MAX_LENGTH
+        )));
+    }
+    check_write_size(data.len(), chunk_size)?;
+
+    // Step 1: Erase existing data
+    send_apdu(card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[])?;
+
+    // Step 2: Set data type
+    send_apdu(card, CLA, INS_SET_TYPE, data_type, 0x00, &[])?;
+
+    // Step 3: Set label
+    if !label_bytes.is_empty() {
+        send_apdu(card, CLA, INS_SET_LABEL, 0x00, 0x00, label_bytes)?;
+    }
+
+    // Step 4: Write data in chunks
+    let chunks: Vec<&[u8]> = data.chunks(chunk_size).collect();
+    let num_chunks = chunks.len();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        // Checked before every chunk but never after the last, so a cancel
+        // that arrives once the data is complete has no effect.
+        if transfer.is_cancelled() {
+            send_apdu(card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[])?;
+            return Err(SeqretsError::Cancelled(CANCELLED_MESSAGE.to_string()));
+        }
+        let p1 = i as u8; // chunk index
+        let p2 = if i == num_chunks - 1 { 0x01 } else { 0x00 }; // last chunk flag
+        send_apdu(card, CLA, INS_STORE_DATA, p1, p2, chunk)?;
+        (transfer.on_chunk)(i + 1, num_chunks);
+    }
+
+    Ok(())
+}
+
+/// Read the raw data bytes from the card.
+/// Returns (raw_data_bytes, type_byte, label_string).
+/// Must be called after select_applet and verify_pin_if_needed.
+/// Progress is reported after each READ_DATA chunk; cancellation is checked
+/// before each one.
+///
+/// Applets with `CAP_SEQUENCED_READ` tag each chunk with its index and the
+/// write that stored it; a chunk other than the one asked for (a gap, a
+/// repeat or out of order) or chunks from two different writes (a torn
+/// write) are `CorruptData` instead of being spliced together. Otherwise the
+/// data is assembled in the order chunks arrive.
+fn read_raw_card_data<T: ApduTransport>(
+    card: &CardLink<T>,
+    transfer: &Transfer,
+) -> Result<(Vec<u8>, u8, String), SeqretsError> {
+    let status_resp = send_apdu(card, CLA, INS_GET_STATUS, 0x00, 0x00, &[])?;
+
+    if status_resp.len() < 7 {
+        return Err(SeqretsError::CorruptData(
+            "Invalid status response".to_string(),
+        ));
+    }
+
+    let data_length = ((status_resp[0] as u16) << 8) | (status_resp[1] as u16);
+    let data_type_byte = status_resp[2];
+    let label = parse_status_label(&status_resp);
+
+    if data_length == 0 {
+        return Ok((Vec::new(), data_type_byte, label));
+    }
+
+    // Read data in chunks; P1 addresses at most 256 of them
+    let mut all_data: Vec<u8> = Vec::with_capacity(data_length as usize);
+    let total_chunks = (data_length as usize).div_ceil(CHUNK_SIZE);
+    if total_chunks > usize::from(u8::MAX) + 1 {
+        return Err(SeqretsError::CorruptData(format!(
+            "Card reports {} bytes of data, more than it can return.",
+            data_length
+        )));
+    }
+    let sequenced = card.capabilities.load(Ordering::SeqCst) & CAP_SEQUENCED_READ != 0;
+    let p2 = if sequenced { READ_SEQUENCED } else { 0x00 };
+    let mut generation = None;
+
+    for chunk_index in 0..total_chunks {
+        if all_data.len() >= data_length as usize {
+            break;
+        }
+        if transfer.is_cancelled() {
+            return Err(SeqretsError::Cancelled(CANCELLED_MESSAGE.to_string()));
+        }
+        let chunk = send_apdu(card, CLA, INS_READ_DATA, chunk_index as u8, p2, &[])?;
+        let chunk = if sequenced {
+            check_chunk_sequence(&chunk, chunk_index as u8, &mut generation)?
+        } else {
+            &chunk[..]
+        };
+        if chunk.is_empty() {
+            break;
+        }
+        all_data.extend_from_slice(chunk);
+        (transfer.on_chunk)(chunk_index + 1, total_chunks);
+    }
+
+    if all_data.len() < data_length as usize {
+        return Err(SeqretsError::CorruptData(format!(
+            "Card returned {} of {} bytes — data may be corrupted",
+            all_data.len(),
+            data_length
+        )));
+    }
+
+    all_data.truncate(data_length as usize);
+    Ok((all_data, data_type_byte, label))
+}
+
+/// The data of a sequenced READ_DATA response, after checking its two
+/// leading bytes: the chunk index, which must be `index` (the chunk asked
+/// for), and the write generation, which must match `generation`, the one
+/// the first chunk of this read carried.
+fn check_chunk_sequence<'a>(
+    chunk: &'a [u8],
+    index: u8,
+    generation: &mut Option<u8>,
+) -> Result<&'a [u8], SeqretsError> {
+    let [seq, tag, data @ ..] = chunk else {
+        return Err(SeqretsError::CorruptData(
+            "Card returned a chunk without its sequence number".to_string(),
+        ));
+    };
+    if *seq != index {
+        return Err(SeqretsError::CorruptData(format!(
+            "Card returned chunk {} where chunk {} was expected; the data was read out of order. Read the card again.",
+            seq, index
+        )));
+    }
+    let tag = *tag;
+    match *generation.get_or_insert(tag) {
+        first if first == tag => Ok(data),
+        first => Err(SeqretsError::CorruptData(format!(
+            "Card data mixes two writes (generations {} and {}); the last write was interrupted. Write the data again.",
+            first, tag
+        ))),
+    }
+}
+
+/// Parse card data into a list of CardItem.
+/// First tries to parse as a JSON array (multi-item format).
+/// Falls back to treating it as a legacy single-item blob.
+fn parse_card_items(
+    raw_data: &[u8],
+    type_byte: u8,
+    label: &str,
+) -> Result<Vec<CardItem>, SeqretsError> {
+    if raw_data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let data_string = String::from_utf8(raw_data.to_vec())
+        .map_err(|_| SeqretsError::CorruptData("Card data is not valid UTF-8".to_string()))?;
+
+    // Try multi-item JSON array format first
+    if let Ok(items) = serde_json::from_str::<Vec<CardItem>>(&data_string) {
+        if !items.is_empty() {
+            return Ok(items);
+        }
+    }
+
+    // Legacy single-item format: wrap in a Vec
+    let item_type = match type_byte {
+        TYPE_SHARE => "share".to_string(),
+        TYPE_VAULT => "vault".to_string(),
+        _ => "unknown".to_string(),
+    };
+    Ok(vec![CardItem {
+        item_type,
+        label: label.to_string(),
+        data: data_string,
+        wrapped: false,
+        vault_id: None,
+    }])
+}
+
+/// Serialize a list of CardItem to JSON, then write to card as TYPE_MULTI.
+fn write_items_to_card(
+    card: &CardLink,
+    items: &[CardItem],
+    chunk_size: usize,
+    require_contact: bool,
+    transfer: &Transfer,
+) -> Result<(), SeqretsError> {
+    let json =
+        serde_json::to_string(items).map_err(|e| format!("Failed to serialize items: {}", e))?;
+    let data_bytes = json.as_bytes();
+
+    // Query actual card capacity via GET_STATUS
+    let status_resp = send_apdu(card, CLA, INS_GET_STATUS, 0x00, 0x00, &[])?;
+    let capacity = parse_card_capacity(&status_resp);
+
+    // Size check against the card's reported capacity
+    if data_bytes.len() > capacity {
+        return Err(SeqretsError::StorageFull(format!(
+            "Combined data ({} bytes) exceeds card capacity ({} bytes). Remove some items first.",
+            data_bytes.len(),
+            capacity
+        )));
+    }
+
+    let summary_label = format!(
+        "{} item{}",
+        items.len(),
+        if items.len() == 1 { "" } else { "s" }
+    );
+    write_data_to_card(
+        card,
+        data_bytes,
+        TYPE_VAULT,
+        &summary_label,
+        chunk_size,
+        require_contact,
+        transfer,
+    )
+}
+
+/// Append a share to the card in `assignment.reader`, then read the card back
+/// and confirm the share is stored intact.
+fn write_and_verify_share(
+    app: &AppHandle,
+    state: &SmartcardState,
+    operation: &CardOperation,
+    assignment: &ShareAssignment,
+) -> Result<(), SeqretsError> {
+    let (_ctx, card) = connect_reader(&assignment.reader, state.transmit_timeout())?;
+    let result = (|| {
+        let (data, wrapped) =
+            share_for_card(&card, &assignment.share, assignment.passphrase.as_deref())?;
+        let signing_key = match assignment.signing_password.as_deref() {
+            Some(password) => {
+                let card_id = card_identifier(&card)?;
+                Some((crypto::card_signing_key(password, &card_id)?, card_id))
+            }
+            None => None,
+        };
+        let chunk_size =
+            select_for_write(&card, state.aid().as_deref(), state.write_chunk_override())?;
+        verify_pin_if_needed(&card, &assignment.pin)?;
+
+        let (raw_data, type_byte, existing_label) =
+            read_raw_card_data(&card, &operation.reading(app))?;
+        let mut items = parse_card_items(&raw_data, type_byte, &existing_label)?;
+        items.push(CardItem {
+            item_type: "share".to_string(),
+            label: assignment.label.clone(),
+            data,
+            wrapped,
+            vault_id: normalize_vault_id(assignment.vault_id.as_deref())?,
+        });
+        if let Some((key, card_id)) = &signing_key {
+            sign_items(&mut items, key, card_id);
+        }
+        write_and_verify_items(app, state, operation, &card, &items, chunk_size)
+    })();
+    disconnect_with_reset(card);
+    result
+}
+
+/// Write `items` to a selected card, then read them back and confirm the
+/// last one (the share being added) is stored intact.
+fn write_and_verify_items(
+    app: &AppHandle,
+    state: &SmartcardState,
+    operation: &CardOperation,
+    card: &CardLink,
+    items: &[CardItem],
+    chunk_size: usize,
+) -> Result<(), SeqretsError> {
+    write_items_to_card(
+        card,
+        items,
+        chunk_size,
+        state.require_contact(),
+        &operation.writing(app),
+    )?;
+
+    // Verify: the last item read back must be the share just written
+    let (raw_data, type_byte, label) = read_raw_card_data(card, &operation.reading(app))?;
+    let stored = parse_card_items(&raw_data, type_byte, &label)?;
+    match (stored.last(), items.last()) {
+        (Some(item), Some(written))
+            if crypto::ct_eq(item.data.as_bytes(), written.data.as_bytes())
+                && stored.len() == items.len() =>
+        {
+            Ok(())
+        }
+        _ => Err(SeqretsError::CorruptData(
+            "Verification failed: the share read back does not match what was written.".to_string(),
+        )),
+    }
+}
+
+const NO_READERS_MESSAGE: &str = "No smart card readers detected. Please connect a reader.";
+
+/// Returned by `pcsc_status`, serialized as `{ "state": "ready", ... }`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum PcscStatus {
+    /// The PC/SC service isn't running or can't be reached (pcscd down on
+    /// Linux, the Smart Card service stopped on Windows).
+    ServiceUnavailable {
+        message: String,
+    },
+    /// The service is up but no reader is connected.
+    NoReaders,
+    Ready {
+        readers: Vec<String>,
+    },
+}
+
+/// Classifies the outcome of establishing a context and listing readers.
+fn pcsc_status_from(readers: Result<Vec<String>, pcsc::Error>) -> PcscStatus {
+    match readers {
+        Ok(readers) if readers.is_empty() => PcscStatus::NoReaders,
+        Ok(readers) => PcscStatus::Ready { readers },
+        Err(pcsc::Error::NoReadersAvailable) => PcscStatus::NoReaders,
+        Err(e @ (pcsc::Error::NoService | pcsc::Error::ServiceStopped)) => PcscStatus::ServiceUnavailable {
+            message: format!(
+                "The smart card service is not running ({e}). On Linux, start it with `sudo systemctl start pcscd`."
+            ),
+        },
+        Err(e) => PcscStatus::ServiceUnavailable {
+            message: format!("Cannot access smart card system: {e}"),
+        },
+    }
+}
+
+// ── Tauri commands ──────────────────────────────────────────────────────
+
+/// Reports whether the PC/SC service is reachable and has readers, so the
+/// UI can tell "start pcscd" apart from "plug in a reader".
+#[tauri::command]
+pub fn pcsc_status() -> PcscStatus {
+    pcsc_status_from(Context::establish(Scope::User).and_then(|ctx| {
+        let mut readers_buf = [0u8; 4096];
+        let readers = ctx
+            .list_readers(&mut readers_buf)?
+            .map(|r| r.to_str().unwrap_or("Unknown reader").to_string())
+            .collect();
+        Ok(readers)
+    }))
+}
+
+/// List all available PC/SC readers, with their aliases.
+#[tauri::command]
+pub fn list_readers(state: State<'_, SmartcardState>) -> Result<Vec<ReaderEntry>, SeqretsError> {
+    Ok(reader_names()?
+        .into_iter()
+        .map(|name| ReaderEntry {
+            alias: state.reader_alias(&name),
+            name,
+        })
+        .collect())
+}
+
+/// Names of all available PC/SC readers.
+fn reader_names() -> Result<Vec<String>, SeqretsError> {
+    let ctx = Context::establish(Scope::User)
+        .map_err(|e| format!("Cannot access smart card system: {}", e))?;
+
+    let mut readers_buf = [0u8; 4096];
+    let readers = ctx.list_readers(&mut readers_buf).map_err(|e| match e {
+        pcsc::Error::NoReadersAvailable => SeqretsError::NoReaders(NO_READERS_MESSAGE.to_string()),
+        e => format!("Cannot list readers: {}", e).into(),
+    })?;
+
+    let result: Vec<String> = readers
+        .map(|r| r.to_str().unwrap_or("Unknown reader").to_string())
+        .collect();
+
+    if result.is_empty() {
+        Err(SeqretsError::NoReaders(NO_READERS_MESSAGE.to_string()))
+    } else {
+        Ok(result)
+    }
+}
+
+/// SELECT the applet and read its PIN-free status for `enumerate_cards`.
+/// Errors are reported as an absent applet or unknown status, never raised.
+fn probe_card<T: ApduTransport>(card: &CardLink<T>, aid: Option<&[u8]>, info: &mut ReaderCardInfo) {
+    if let Err(e) = select_applet(card, aid) {
+        log::debug!("No seQRets applet in '{}': {e}", info.reader);
+        return;
+    }
+    info.applet_present = true;
+    if let Ok(resp) = send_apdu(card, CLA, INS_GET_STATUS, 0x00, 0x00, &[]) {
+        if resp.len() >= 7 {
+            info.has_data = Some(u16::from_be_bytes([resp[0], resp[1]]) > 0);
+            info.label = Some(parse_status_label(&resp)).filter(|label| !label.is_empty());
+        }
+    }
+}
+
+/// List every reader with whether it holds a card, whether that card has
+/// the seQRets applet, and (when the card reports it without a PIN) whether
+/// it holds data and its label. Each card is connected to only briefly; no
+/// PIN is sent, so no retry counter is touched. A reader that fails is
+/// listed as empty rather than failing the call; only a missing PC/SC
+/// service or no readers at all is an error.
+#[tauri::command]
+pub async fn enumerate_cards(
+    state: State<'_, SmartcardState>,
+) -> Result<Vec<ReaderCardInfo>, SeqretsError> {
+    let readers = reader_names()?;
+    let ctx = Context::establish(Scope::User)
+        .map_err(|e| format!("Cannot access smart card system: {}", e))?;
+    let aid = state.aid();
+
+    Ok(readers
+        .into_iter()
+        .map(|reader| {
+            let mut info = ReaderCardInfo {
+                alias: state.reader_alias(&reader),
+                card_present: false,
+                applet_present: false,
+                has_data: None,
+                label: None,
+                reader,
+            };
+            let Ok(name) = std::ffi::CString::new(info.reader.as_str()) else {
+                return info;
+            };
+            match ctx.connect(&name, ShareMode::Shared, Protocols::ANY) {
+                Ok(card) => {
+                    info.card_present = true;
+                    let link = link_card(&info.reader, card, state.transmit_timeout());
+                    probe_card(&link, aid.as_deref(), &mut info);
+                    disconnect_with_reset(link);
+                }
+                Err(
+                    pcsc::Error::NoSmartcard
+                    | pcsc::Error::RemovedCard
+                    | pcsc::Error::ReaderUnavailable,
+                ) => {}
+                // Present but busy or unresponsive (e.g. held exclusively by another app)
+                Err(e) => {
+                    log::debug!("Cannot connect to card in '{}': {e}", info.reader);
+                    info.card_present = true;
+                }
+            }
+            info
+        })
+        .collect())
+}
+
+/// Wait until `reader` is free, retrying while another application holds it
+/// exclusively, for up to `timeout_ms` (capped at a minute). Fails with
+/// `ReaderBusy` if it's still held by then; any other connect error (no
+/// card, no reader) is returned straight away.
+#[tauri::command]
+pub async fn wait_for_reader(
+    reader: String,
+    timeout_ms: u64,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let timeout = Duration::from_millis(timeout_ms.min(WAIT_FOR_READER_MAX_MS));
+    let transmit_timeout = state.transmit_timeout();
+    tauri::async_runtime::spawn_blocking(move || {
+        retry_while_busy(timeout, READER_BUSY_POLL, || {
+            connect_reader(&reader, transmit_timeout)
+                .map(|(_ctx, card)| disconnect_with_reset(card))
+        })
+    })
+    .await
+    .map_err(|e| SeqretsError::from(format!("Reader wait task failed: {}", e)))?
+}
+
+/// Name the reader `reader` for display (e.g. "my YubiKey"); `None` or an
+/// empty alias removes it. The alias is matched on the name without its
+/// PC/SC index suffix, so it survives the reader being replugged as "00 01".
+/// Kept for the session only: the frontend stores the aliases and sets them
+/// again at startup.
+#[tauri::command]
+pub fn set_reader_alias(
+    reader: String,
+    alias: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    state.set_reader_alias(&reader, alias.as_deref())
+}
+
+/// Every reader alias, keyed by reader name without the index suffix.
+#[tauri::command]
+pub fn get_reader_aliases(state: State<'_, SmartcardState>) -> BTreeMap<String, String> {
+    state
+        .reader_aliases
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Point the app at a non-default applet AID (hex, e.g. "F0 53 51 52 54 53 01 00 01").
+/// Pass `None` or an empty string to revert to the built-in seQRets AID.
+#[tauri::command]
+pub fn set_applet_aid(
+    aid_hex: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let aid = match aid_hex.as_deref().map(str::trim) {
+        Some(hex) if !hex.is_empty() => Some(parse_aid_hex(hex)?),
+        _ => None,
+    };
+    *state
+        .aid_override
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = aid;
+    Ok(())
+}
+
+/// Validate a `set_transmit_timeout` argument. `None` means the default.
+fn parse_transmit_timeout(timeout_ms: Option<u64>) -> Result<Option<Duration>, SeqretsError> {
+    match timeout_ms {
+        Some(ms) if !(TRANSMIT_TIMEOUT_MIN_MS..=TRANSMIT_TIMEOUT_MAX_MS).contains(&ms) => {
+            Err(SeqretsError::InvalidInput(format!(
+                "Transmit timeout must be {}-{} ms, got {}.",
+                TRANSMIT_TIMEOUT_MIN_MS, TRANSMIT_TIMEOUT_MAX_MS, ms
+            )))
+        }
+        Some(ms) => Ok(Some(Duration::from_millis(ms))),
+        None => Ok(None),
+    }
+}
+
+/// Override the per-APDU timeout (`DEFAULT_TRANSMIT_TIMEOUT`, 5 s) for all
+/// later card commands. Pass `None` to restore the default. Erase and factory
+/// reset APDUs never wait less than `ERASE_TRANSMIT_TIMEOUT`.
+#[tauri::command]
+pub fn set_transmit_timeout(
+    timeout_ms: Option<u64>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let timeout = parse_transmit_timeout(timeout_ms)?;
+    *state
+        .timeout_override
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = timeout;
+    Ok(())
+}
+
+/// Validate a `set_write_chunk_size` argument. `None` means size from the reader.
+fn parse_write_chunk_size(chunk_size: Option<u32>) -> Result<Option<usize>, SeqretsError> {
+    match chunk_size.map(|size| size as usize) {
+        Some(size) if !(WRITE_CHUNK_OVERRIDE_MIN..=WRITE_CHUNK_OVERRIDE_MAX).contains(&size) => {
+            Err(SeqretsError::InvalidInput(format!(
+                "Write chunk size must be {}-{} bytes, got {}.",
+                WRITE_CHUNK_OVERRIDE_MIN, WRITE_CHUNK_OVERRIDE_MAX, size
+            )))
+        }
+        size => Ok(size),
+    }
+}
+
+/// Force the STORE_DATA chunk size for all later writes instead of sizing it
+/// from the reader's reported limit, for debugging readers that misreport
+/// it. Pass `None` to go back to the reader's figure (240 bytes when it
+/// reports none). Applets before 1.3 only accept 240.
+///
+/// A write is at most 255 chunks, so a smaller chunk also lowers the largest
+/// item set that fits; a write over that is refused before the card is erased.
+#[tauri::command]
+pub fn set_write_chunk_size(
+    chunk_size: Option<u32>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let chunk_size = parse_write_chunk_size(chunk_size)?;
+    *state
+        .chunk_override
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = chunk_size;
+    Ok(())
+}
+
+/// Turn APDU tracing on or off. While on, every APDU sent to any card emits
+/// an `apdu-trace` event with the command and response in hex; PINs, share
+/// data and other payloads are masked (see `trace_shows_data`). Nothing is
+/// written to the log. Off by default and not persisted.
+#[tauri::command]
+pub fn set_apdu_trace(app: AppHandle, enabled: bool) {
+    *APDU_TRACE.lock().unwrap_or_else(PoisonError::into_inner) = enabled.then_some(app);
+}
+
+/// Refuse card writes unless the card is in a contact slot, so shares never
+/// travel over the air. Applies to every later write; off by default.
+#[tauri::command]
+pub fn set_require_contact(required: bool, state: State<'_, SmartcardState>) {
+    state.require_contact.store(required, Ordering::SeqCst);
+}
+
+/// Make the session read-only, or writable again. While read-only, every
+/// command that writes, erases, deletes, imports or changes the PIN, PUK or
+/// wipe protection of a card fails before sending an APDU, so a UI bug
+/// during a recovery can't touch the card. A guard rail against mistakes in
+/// this app, not a security boundary: it doesn't stop other software, and
+/// anything able to call this command can turn it off again.
+#[tauri::command]
+pub fn set_read_only(read_only: bool, state: State<'_, SmartcardState>) {
+    state.read_only.store(read_only, Ordering::SeqCst);
+}
+
+/// Turn the card audit log (see `audit`) on or off. While on, every command
+/// that reads items from, writes to or changes the PIN state of a card
+/// appends an entry. The frontend calls this at startup with the user's
+/// setting; the log file itself is kept when auditing is turned off.
+#[tauri::command]
+pub fn set_audit_logging(
+    app: AppHandle,
+    enabled: bool,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let path = if enabled {
+        Some(audit::log_path(&app)?)
+    } else {
+        None
+    };
+    *state
+        .audit_log
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = path;
+    Ok(())
+}
+
+/// Whether the PC/SC service can be reached at all (readers or not).
+pub(crate) fn pcsc_available() -> bool {
+    Context::establish(Scope::User).is_ok()
+}
+
+/// Return the AID currently used for SELECT, as space-separated hex.
+#[tauri::command]
+pub fn get_applet_aid(state: State<'_, SmartcardState>) -> String {
+    format_hex(state.aid().as_deref().unwrap_or(SEQRETS_AID))
+}
+
+/// Describe the applet on the card in `reader`: AID, version, lifecycle
+/// state and capability bitmap from the SELECT response.
+#[tauri::command]
+pub fn applet_info(
+    reader: String,
+    state: State<'_, SmartcardState>,
+) -> Result<AppletInfo, SeqretsError> {
+    let aid = state.aid();
+    let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+    let result = select_applet(&card, aid.as_deref());
+    disconnect_with_reset(card);
+
+    let applet = result?;
+    Ok(AppletInfo {
+        aid: format_hex(aid.as_deref().unwrap_or(SEQRETS_AID)),
+        version: applet.version.to_string(),
+        lifecycle: AppletLifecycle::from_fci(applet.fci.lifecycle),
+        capabilities: applet.fci.capabilities,
+    })
+}
+
+/// Probe the reader holding a card: APDU size limits, active protocol, PIN
+/// pad support, and the STORE_DATA chunk size writes to this card will use.
+#[tauri::command]
+pub fn reader_capabilities(
+    reader: String,
+    state: State<'_, SmartcardState>,
+) -> Result<ReaderCaps, SeqretsError> {
+    let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+    // A card without the applet still says something about the reader
+    let capabilities = select_applet(&card, state.aid().as_deref())
+        .ok()
+        .and_then(|applet| applet.fci.capabilities);
+    let handle = card.card.handle();
+    let max_send = read_u32_attribute(&handle, Attribute::Maxinput);
+    let caps = ReaderCaps {
+        max_send,
+        max_recv: read_u32_attribute(&handle, Attribute::MaxIfsd),
+        protocol: protocol_name(handle.status2_owned().ok().and_then(|s| s.protocol2()))
+            .to_string(),
+        has_pinpad: pinpad_verify_code(&handle).is_some(),
+        write_chunk_size: write_chunk_size(max_send, capabilities, state.write_chunk_override())
+            .ok(),
+        interface: card.interface,
+    };
+    drop(handle);
+    disconnect_with_reset(card);
+    Ok(caps)
+}
+
+/// Get the status of the card in the given reader, including item summaries.
+/// `pin` is optional: without it the status is read PIN-free, and a card
+/// that refuses with 6982 yields `pin_required` instead of an error.
+#[tauri::command]
+pub async fn get_card_status(
+    app: AppHandle,
+    reader: String,
+    pin: Option<String>,
+    wait_for_card: Option<u32>,
+    state: State<'_, SmartcardState>,
+) -> Result<CardStatus, SeqretsError> {
+    let operation = state.begin_operation();
+    let (_ctx, card) =
+        connect_when_present(&reader, state.transmit_timeout(), wait_for_card, &operation)?;
+    let interface = card.interface;
+    let applet = select_applet(&card, state.aid().as_deref())?;
+    let applet_version = applet.version;
+    let lifecycle = gp_lifecycle(applet.fci.content_state).to_string();
+    let write_count = read_write_count(&card);
+    verify_pin_if_needed(&card, &pin)?;
+
+    let resp = match send_apdu(&card, CLA, INS_GET_STATUS, 0x00, 0x00, &[]) {
+        Ok(resp) => resp,
+        Err(SeqretsError::PinRequired(_)) => {
+            disconnect_with_reset(card);
+            return Ok(CardStatus {
+                lifecycle,
+                write_count,
+                ..CardStatus::pin_required(applet_version, interface)
+            });
+        }
+        Err(e) => {
+            disconnect_with_reset(card);
+            return Err(e);
+        }
+    };
+
+    if resp.len() < 7 {
+        disconnect_with_reset(card);
+        return Err(SeqretsError::CorruptData(
+            "Invalid status response from card".to_string(),
+        ));
+    }
+
+    let data_length = ((resp[0] as u16) << 8) | (resp[1] as u16);
+    let data_type_byte = resp[2];
+    let pin_set = resp[3] == 0x01;
+    let pin_verified = resp[4] == 0x01;
+    let pin_retries_remaining = resp[5];
+    let label = parse_status_label(&resp);
+
+    // Parse card capacity from GET_STATUS response (falls back to default for older applets)
+    let card_capacity = parse_card_capacity(&resp) as u16;
+    let wipe_protected = parse_wipe_protected(&resp);
+    let (puk_set, puk_retries_remaining) = parse_puk_status(&resp);
+
+    // If there's data, read and parse to get item summaries
+    let mut pin_required = false;
+    let (total_items, items) = if data_length > 0 {
+        match read_raw_card_data(&card, &operation.reading(&app)) {
+            Ok((raw_data, type_byte, raw_label)) => {
+                match parse_card_items(&raw_data, type_byte, &raw_label) {
+                    Ok(parsed_items) => {
+                        let summaries: Vec<CardItemSummary> = parsed_items
+                            .iter()
+                            .enumerate()
+                            .map(|(i, item)| CardItemSummary {
+                                index: i,
+                                item_type: item.item_type.clone(),
+                                label: item.label.clone(),
+                                data_size: item.data.len(),
+                                wrapped: item.wrapped,
+                                vault_id: item.vault_id.clone(),
+                            })
+                            .collect();
+                        (summaries.len(), summaries)
+                    }
+                    Err(_) => {
+                        // Parse failed — show as single unknown item
+                        let fallback_type = match data_type_byte {
+                            TYPE_SHARE => "share".to_string(),
+                            TYPE_VAULT => "vault".to_string(),
+                            _ => "unknown".to_string(),
+                        };
+                        (
+                            1,
+                            vec![CardItemSummary {
+                                index: 0,
+                                item_type: fallback_type,
+                                label: label.clone(),
+                                data_size: data_length as usize,
+                                wrapped: false,
+                                vault_id: None,
+                            }],
+                        )
+                    }
+                }
+            }
+            Err(e) => {
+                // Read failed — show minimal status from the status response
+                pin_required = matches!(e, SeqretsError::PinRequired(_));
+                let fallback_type = match data_type_byte {
+                    TYPE_SHARE => "share".to_string(),
+                    TYPE_VAULT => "vault".to_string(),
+                    _ => "unknown".to_string(),
+                };
+                (
+                    1,
+                    vec![CardItemSummary {
+                        index: 0,
+                        item_type: fallback_type,
+                        label: label.clone(),
+                        data_size: data_length as usize,
+                        wrapped: false,
+                        vault_id: None,
+                    }],
+                )
+            }
+        }
+    } else {
+        (0, Vec::new())
+    };
+
+    let free_bytes_estimate = card_capacity as i32 - data_length as i32;
+
+    disconnect_with_reset(card);
+
+    Ok(CardStatus {
+        has_data: data_length > 0,
+        data_length,
+        card_capacity,
+        total_items,
+        items,
+        pin_set,
+        pin_verified,
+        pin_retries_remaining,
+        free_bytes_estimate,
+        wipe_protected,
+        puk_set,
+        puk_retries_remaining,
+        applet_version: applet_version.to_string(),
+        pin_required,
+        interface,
+        lifecycle,
+        write_count,
+    })
+}
+
+/// Bytes the card holds after appending an item with `data_len` bytes of
+/// data to `existing_len` bytes of stored JSON. The label is not counted.
+fn appended_size(existing_len: usize, item_type: &str, data_len: usize) -> usize {
+    let empty_item = CardItem {
+        item_type: item_type.to_string(),
+        label: String::new(),
+        data: String::new(),
+        wrapped: false,
+        vault_id: None,
+    };
+    let item_len = serde_json::to_string(&empty_item).map_or(0, |j| j.len()) + data_len;
+    if existing_len == 0 {
+        item_len + 2 // [item]
+    } else {
+        existing_len + 1 + item_len // existing array plus ",item"
+    }
+}
+
+/// The payload check: a known item type with something in it. Whether it
+/// fits in one write depends on the card's chunk size, so `preflight_card`
+/// checks that.
+fn check_write_payload(data_len: usize, data_type: &str) -> Result<(), SeqretsError> {
+    if !ITEM_TYPES.contains(&data_type) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Unknown item type '{}'.",
+            data_type
+        )));
+    }
+    if data_len == 0 {
+        return Err(SeqretsError::InvalidInput(
+            "Nothing to write: the item is empty.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The card-side checks of `preflight_write`, run on a connected card.
+fn preflight_card(
+    card: &CardLink,
+    aid: Option<&[u8]>,
+    chunk_override: Option<usize>,
+    data_len: usize,
+    data_type: &str,
+    pin: &Option<String>,
+    report: &mut PreflightReport,
+) {
+    // Also refuses readers too limited to write to this applet
+    let chunk_size = match select_for_write(card, aid, chunk_override) {
+        Ok(chunk_size) => {
+            report.record(PREFLIGHT_APPLET, Ok(()));
+            chunk_size
+        }
+        Err(e) => {
+            report.record(PREFLIGHT_APPLET, Err(e));
+            report.skip(&[PREFLIGHT_PIN, PREFLIGHT_CAPACITY], PREFLIGHT_APPLET);
+            return;
+        }
+    };
+
+    let status = send_apdu(card, CLA, INS_GET_STATUS, 0x00, 0x00, &[]).and_then(|resp| {
+        if resp.len() < 7 {
+            Err(SeqretsError::CorruptData(
+                "Invalid status response from card".to_string(),
+            ))
+        } else {
+            Ok(resp)
+        }
+    });
+    let pin_check = status.clone().and_then(|resp| {
+        let pin_set = resp[3] == 0x01;
+        let pin_given = pin.as_deref().is_some_and(|p| !p.is_empty());
+        if pin_set && !pin_given {
+            Err(SeqretsError::PinRequired(
+                "This card is PIN-protected. Enter the PIN to write to it.".to_string(),
+            ))
+        } else {
+            verify_pin_if_needed(card, pin)
+        }
+    });
+    if !report.record(PREFLIGHT_PIN, pin_check) {
+        report.skip(&[PREFLIGHT_CAPACITY], PREFLIGHT_PIN);
+        return;
+    }
+
+    let capacity_check = status.and_then(|resp| {
+        let existing_len = (((resp[0] as u16) << 8) | (resp[1] as u16)) as usize;
+        let capacity = parse_card_capacity(&resp);
+        let needed = appended_size(existing_len, data_type, data_len);
+        if needed > capacity {
+            Err(SeqretsError::StorageFull(format!(
+                "Combined data (about {} bytes) exceeds card capacity ({} bytes). Remove some items first.",
+                needed, capacity
+            )))
+        } else {
+            check_write_size(needed, chunk_size)
+        }
+    });
+    report.record(PREFLIGHT_CAPACITY, capacity_check);
+}
+
+/// Check that appending a `data_len`-byte item of `data_type` would
+/// succeed, without touching the stored data: payload, reader (and its
+/// interface, under `set_require_contact`), applet, PIN
+/// (verified if the card has one) and capacity. A wrong PIN costs a retry,
+/// as it would on the real write.
+#[tauri::command]
+pub fn preflight_write(
+    reader: String,
+    data_len: usize,
+    data_type: String,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> PreflightReport {
+    let mut report = PreflightReport::default();
+    report.record(PREFLIGHT_PAYLOAD, check_write_payload(data_len, &data_type));
+
+    match connect_reader(&reader, state.transmit_timeout()) {
+        Ok((_ctx, card)) => {
+            // A contact-only policy can still rule out a connected reader
+            let interface = check_write_interface(card.interface, state.require_contact());
+            if report.record(PREFLIGHT_READER, interface) {
+                let aid = state.aid();
+                let chunk_override = state.write_chunk_override();
+                preflight_card(
+                    &card,
+                    aid.as_deref(),
+                    chunk_override,
+                    data_len,
+                    &data_type,
+                    &pin,
+                    &mut report,
+                );
+            } else {
+                report.skip(
+                    &[PREFLIGHT_APPLET, PREFLIGHT_PIN, PREFLIGHT_CAPACITY],
+                    PREFLIGHT_READER,
+                );
+            }
+            disconnect_with_reset(card);
+        }
+        Err(e) => {
+            report.record(PREFLIGHT_READER, Err(e));
+            report.skip(
+                &[PREFLIGHT_APPLET, PREFLIGHT_PIN, PREFLIGHT_CAPACITY],
+                PREFLIGHT_READER,
+            );
+        }
+    }
+    report.finish()
+}
+
+/// Write an item to the card, appending to any existing items.
+/// Reads existing items, appends the new one, erases, and writes the combined data.
+///
+/// With a `passphrase`, a share is wrapped to this card before it is stored,
+/// so reading it back needs the passphrase as well as the card (and its PIN).
+/// Only shares can be wrapped.
+///
+/// `vault_id` (typically the vault's `vault_fingerprint`) is stored with the
+/// item so `card_matches_vault` can later tell which vault the card is for.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn write_item_to_card(
+    app: AppHandle,
+    reader: String,
+    item_type: String,
+    data: String,
+    label: String,
+    pin: Option<String>,
+    passphrase: Option<String>,
+    vault_id: Option<String>,
+    wait_for_card: Option<u32>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let vault_id = normalize_vault_id(vault_id.as_deref())?;
+        let passphrase = passphrase.map(Zeroizing::new);
+        if passphrase.is_some() && item_type != "share" {
+            return Err(SeqretsError::InvalidInput(
+                "Only shares can be wrapped with a card passphrase.".to_string(),
+            ));
+        }
+        let operation = state.begin_operation();
+        let (_ctx, card) =
+            connect_when_present(&reader, state.transmit_timeout(), wait_for_card, &operation)?;
+        let (data, wrapped) =
+            share_for_card(&card, &data, passphrase.as_ref().map(|p| p.as_str()))?;
+        let chunk_size =
+            select_for_write(&card, state.aid().as_deref(), state.write_chunk_override())?;
+        verify_pin_if_needed(&card, &pin)?;
+
+        // Read existing items (if any)
+        let (raw_data, type_byte, existing_label) =
+            read_raw_card_data(&card, &operation.reading(&app))?;
+        let mut items = if raw_data.is_empty() {
+            Vec::new()
+        } else {
+            parse_card_items(&raw_data, type_byte, &existing_label)?
+        };
+
+        // Append the new item
+        items.push(CardItem {
+            item_type,
+            label,
+            data,
+            wrapped,
+            vault_id,
+        });
+
+        // Write combined items (internally erases first)
+        let result = write_items_to_card(
+            &card,
+            &items,
+            chunk_size,
+            state.require_contact(),
+            &operation.writing(&app),
+        );
+        disconnect_with_reset(card);
+        result
+    })();
+    state.audit("write_item_to_card", &reader, &result);
+    result
+}
+
+/// Write one share per card for an N-of-M distribution. Each assignment is
+/// processed in turn (connect, append, read back and verify); a failure is
+/// recorded in that card's report and the batch moves on, so the caller can
+/// retry just the failed readers.
+#[tauri::command]
+pub async fn write_shares_to_cards(
+    app: AppHandle,
+    assignments: Vec<ShareAssignment>,
+    state: State<'_, SmartcardState>,
+) -> Result<Vec<CardWriteReport>, SeqretsError> {
+    if assignments.is_empty() {
+        return Err(SeqretsError::InvalidInput(
+            "No shares to write.".to_string(),
+        ));
+    }
+    state.check_writable()?;
+    let operation = state.begin_operation();
+
+    let reports = assignments
+        .iter()
+        .map(|assignment| {
+            let result = write_and_verify_share(&app, &state, &operation, assignment);
+            state.audit("write_shares_to_cards", &assignment.reader, &result);
+            CardWriteReport {
+                reader: assignment.reader.clone(),
+                label: assignment.label.clone(),
+                success: result.is_ok(),
+                error: result.err(),
+            }
+        })
+        .collect();
+    Ok(reports)
+}
+
+/// Render `share` as a QR code at `qr_ecc` and append it to the card in
+/// `reader` (with a read-back check, as in `write_shares_to_cards`) in one
+/// call, so the share crosses IPC once for both outputs. The QR is rendered
+/// first and returned even when the write fails; `DistributeResult` says
+/// which half failed. The share and PIN are zeroized when the call returns.
+/// `vault_id` behaves as in `write_item_to_card`.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn distribute_share(
+    app: AppHandle,
+    reader: String,
+    share: String,
+    label: String,
+    pin: Option<String>,
+    qr_ecc: EccLevel,
+    vault_id: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<DistributeResult, SeqretsError> {
+    let assignment = ShareAssignment {
+        reader,
+        share,
+        label,
+        pin,
+        passphrase: None,
+        vault_id,
+        signing_password: None,
+    };
+    let qr = qr::render_qr_png(assignment.share.as_bytes(), qr_ecc)
+        .map(|png| STANDARD.encode(png))
+        .map_err(SeqretsError::InvalidInput);
+
+    let written = state.check_writable().and_then(|_| {
+        let operation = state.begin_operation();
+        write_and_verify_share(&app, &state, &operation, &assignment)
+    });
+    state.audit("distribute_share", &assignment.reader, &written);
+
+    Ok(DistributeResult {
+        card_written: written.is_ok(),
+        card_error: written.err(),
+        qr_error: qr.as_ref().err().cloned(),
+        qr_png: qr.ok(),
+    })
+}
+
+/// Start a guided enrollment of `shares` (labelled by `labels`), one share
+/// per card, replacing any enrollment in progress. Each `enroll_next_card`
+/// then writes the next share to the card it is given. `vault_id` is stored
+/// with every share as in `write_item_to_card`. The shares stay in memory
+/// until `end_enrollment` or the next `begin_enrollment`.
+#[tauri::command]
+pub fn begin_enrollment(
+    shares: Vec<String>,
+    labels: Vec<String>,
+    vault_id: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<EnrollmentStatus, SeqretsError> {
+    let enrollment = Enrollment::new(shares, labels, vault_id)?;
+    let status = enrollment.status();
+    *state
+        .enrollment
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(enrollment);
+    Ok(status)
+}
+
+/// Write the next pending share of the enrollment to the card in `reader`,
+/// read it back and mark it enrolled. Shares go out strictly in order. A card
+/// that already holds an enrolled share, or was already used for one, is
+/// refused; a card that holds the pending share (a write that finished before
+/// the flow was interrupted) is marked enrolled without writing again. A
+/// failed or cancelled write leaves the share pending, so swapping cards and
+/// calling again resumes where the flow stopped.
+#[tauri::command]
+pub async fn enroll_next_card(
+    app: AppHandle,
+    reader: String,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<EnrollmentStatus, SeqretsError> {
+    let result = (|| -> Result<EnrollmentStatus, SeqretsError> {
+        state.check_writable()?;
+        let mut guard = state
+            .enrollment
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let enrollment = guard.as_mut().ok_or_else(|| {
+            SeqretsError::InvalidInput(
+                "No enrollment in progress. Start one with begin_enrollment.".to_string(),
+            )
+        })?;
+        if enrollment.next_pending().is_none() {
+            return Err(SeqretsError::InvalidInput(
+                "Every share has already been enrolled.".to_string(),
+            ));
+        }
+
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        let step = (|| -> Result<_, SeqretsError> {
+            let card_id = card_identifier(&card)
+                .ok()
+                .map(|id| id.iter().map(|b| format!("{b:02x}")).collect::<String>());
+            let chunk_size =
+                select_for_write(&card, state.aid().as_deref(), state.write_chunk_override())?;
+            verify_pin_if_needed(&card, &pin)?;
+
+            let (raw_data, type_byte, existing_label) =
+                read_raw_card_data(&card, &operation.reading(&app))?;
+            let mut items = parse_card_items(&raw_data, type_byte, &existing_label)?;
+            let step = enrollment.plan(card_id.as_deref(), &items)?;
+            if let EnrollStep::Write(index) = step {
+                items.push(CardItem {
+                    item_type: "share".to_string(),
+                    label: enrollment.labels[index].clone(),
+                    data: enrollment.shares[index].to_string(),
+                    wrapped: false,
+                    vault_id: enrollment.vault_id.clone(),
+                });
+                write_and_verify_items(&app, &state, &operation, &card, &items, chunk_size)?;
+            }
+            Ok((step, card_id))
+        })();
+        disconnect_with_reset(card);
+
+        let (EnrollStep::Write(index) | EnrollStep::Adopt(index), card_id) = step?;
+        enrollment.record(index, &reader, card_id);
+        Ok(enrollment.status())
+    })();
+    state.audit("enroll_next_card", &reader, &result);
+    result
+}
+
+/// Progress of the current enrollment; `active` is false when none is running.
+#[tauri::command]
+pub fn enrollment_status(state: State<'_, SmartcardState>) -> EnrollmentStatus {
+    match state
+        .enrollment
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        Some(enrollment) => enrollment.status(),
+        None => EnrollmentStatus {
+            active: false,
+            shares: Vec::new(),
+            next_index: None,
+            complete: false,
+        },
+    }
+}
+
+/// Drop the current enrollment and the shares it holds.
+#[tauri::command]
+pub fn end_enrollment(state: State<'_, SmartcardState>) {
+    state
+        .enrollment
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+}
+
+/// Read all items from the card. Wrapped shares are unwrapped when
+/// `passphrase` is given and returned with `wrapped` set otherwise.
+#[tauri::command]
+pub async fn read_card_items(
+    app: AppHandle,
+    reader: String,
+    pin: Option<String>,
+    passphrase: Option<String>,
+    wait_for_card: Option<u32>,
+    state: State<'_, SmartcardState>,
+) -> Result<Vec<CardItem>, SeqretsError> {
+    let result = (|| -> Result<Vec<CardItem>, SeqretsError> {
+        let passphrase = passphrase.map(Zeroizing::new);
+        let operation = state.begin_operation();
+        let (_ctx, card) =
+            connect_when_present(&reader, state.transmit_timeout(), wait_for_card, &operation)?;
+        select_applet(&card, state.aid().as_deref())?;
+        verify_pin_if_needed(&card, &pin)?;
+
+        let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(&app))?;
+
+        if raw_data.is_empty() {
+            disconnect_with_reset(card);
+            return Err("No data stored on this card.".into());
+        }
+
+        let items = parse_card_items(&raw_data, type_byte, &label).and_then(|mut items| {
+            unwrap_card_items(&card, &mut items, passphrase.as_ref().map(|p| p.as_str()))?;
+            Ok(items)
+        });
+        disconnect_with_reset(card);
+        items
+    })();
+    state.audit("read_card_items", &reader, &result);
+    result
+}
+
+/// Sign the items on the card in `reader` with a key derived from `password`
+/// (see `crypto::card_signing_key`), storing the signature as a "signature"
+/// item ahead of them, so `verify_card_authenticity` can later tell whether
+/// anyone without the password changed the card's contents. Any earlier
+/// signature is replaced. Writing to the card afterwards without signing
+/// again leaves a signature that no longer verifies.
+#[tauri::command]
+pub async fn sign_card(
+    app: AppHandle,
+    reader: String,
+    password: String,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let password = Zeroizing::new(password);
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        let result = (|| {
+            let card_id = card_identifier(&card)?;
+            let key = crypto::card_signing_key(&password, &card_id)?;
+            let chunk_size =
+                select_for_write(&card, state.aid().as_deref(), state.write_chunk_override())?;
+            verify_pin_if_needed(&card, &pin)?;
+            let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(&app))?;
+            if raw_data.is_empty() {
+                return Err("No data stored on this card.".into());
+            }
+            let mut items = parse_card_items(&raw_data, type_byte, &label)?;
+            sign_items(&mut items, &key, &card_id);
+            write_items_to_card(
+                &card,
+                &items,
+                chunk_size,
+                state.require_contact(),
+                &operation.writing(&app),
+            )
+        })();
+        disconnect_with_reset(card);
+        result
+    })();
+    state.audit("sign_card", &reader, &result);
+    result
+}
+
+/// Check the signature `sign_card` stored on the card in `reader` against
+/// its items, with the key derived from `password`. A wrong password and
+/// altered contents both come back as `authentic: false`; a card that was
+/// never signed, or whose signature was removed, has `signed: false`.
+#[tauri::command]
+pub async fn verify_card_authenticity(
+    app: AppHandle,
+    reader: String,
+    password: String,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<CardAuthenticity, SeqretsError> {
+    let password = Zeroizing::new(password);
+    let result = (|| -> Result<CardAuthenticity, SeqretsError> {
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        let result = (|| {
+            select_applet(&card, state.aid().as_deref())?;
+            verify_pin_if_needed(&card, &pin)?;
+            let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(&app))?;
+            let items = parse_card_items(&raw_data, type_byte, &label)?;
+            let card_id = card_identifier(&card)?;
+            let key = crypto::card_signing_key(&password, &card_id)?;
+            Ok(check_signature(&items, &key, &card_id))
+        })();
+        disconnect_with_reset(card);
+        result
+    })();
+    state.audit("verify_card_authenticity", &reader, &result);
+    result
+}
+
+/// True when any item on the card in `reader` was written with `vault_id`
+/// `vault_fingerprint` (compared as `normalize_vault_id` does), so the
+/// recovery UI can tell cards for the vault being restored from unrelated
+/// ones. Cards written before vault ids existed never match. Reads the
+/// items, so a PIN-protected card needs `pin`.
+#[tauri::command]
+pub async fn card_matches_vault(
+    app: AppHandle,
+    reader: String,
+    pin: Option<String>,
+    vault_fingerprint: String,
+    state: State<'_, SmartcardState>,
+) -> Result<bool, SeqretsError> {
+    let result = (|| -> Result<bool, SeqretsError> {
+        let wanted = normalize_vault_id(Some(&vault_fingerprint))?.ok_or_else(|| {
+            SeqretsError::InvalidInput("A vault fingerprint is required.".to_string())
+        })?;
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        let items = (|| {
+            select_applet(&card, state.aid().as_deref())?;
+            verify_pin_if_needed(&card, &pin)?;
+            let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(&app))?;
+            parse_card_items(&raw_data, type_byte, &label)
+        })();
+        disconnect_with_reset(card);
+        Ok(items_match_vault(&items?, &wanted))
+    })();
+    state.audit("card_matches_vault", &reader, &result);
+    result
+}
+
+fn items_match_vault(items: &[CardItem], vault_id: &str) -> bool {
+    items
+        .iter()
+        .any(|item| item.vault_id.as_deref() == Some(vault_id))
+}
+
+/// What a card signature covers: the card's identity, then each item's type,
+/// label, vault id, wrapped flag and a hash of its data, in order.
+fn card_metadata(items: &[CardItem], card_id: &[u8]) -> Vec<u8> {
+    let mut metadata = Vec::new();
+    let mut push = |field: &[u8]| {
+        metadata.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        metadata.extend_from_slice(field);
+    };
+    push(card_id);
+    for item in items
+        .iter()
+        .filter(|item| item.item_type != ITEM_TYPE_SIGNATURE)
+    {
+        push(item.item_type.as_bytes());
+        push(item.label.as_bytes());
+        push(item.vault_id.as_deref().unwrap_or("").as_bytes());
+        push(&[item.wrapped as u8]);
+        push(&Sha256::digest(item.data.as_bytes()));
+    }
+    metadata
+}
+
+/// Replace any signature in `items` with one over the rest, made with `key`.
+fn sign_items(items: &mut Vec<CardItem>, key: &[u8; crypto::KEY_LENGTH], card_id: &[u8]) {
+    items.retain(|item| item.item_type != ITEM_TYPE_SIGNATURE);
+    let mac = crypto::hmac_sha256(key, &card_metadata(items, card_id));
+    let data = [&[CARD_SIGNATURE_VERSION][..], &mac].concat();
+    items.insert(
+        0,
+        CardItem {
+            item_type: ITEM_TYPE_SIGNATURE.to_string(),
+            label: "Card signature".to_string(),
+            data: STANDARD.encode(data),
+            wrapped: false,
+            vault_id: None,
+        },
+    );
+}
+
+/// Check the signature in `items` against the rest under `key`. An item
+/// added, removed, reordered or changed since signing fails, as does a
+/// second signature item.
+fn check_signature(
+    items: &[CardItem],
+    key: &[u8; crypto::KEY_LENGTH],
+    card_id: &[u8],
+) -> CardAuthenticity {
+    let mut signatures = items
+        .iter()
+        .filter(|item| item.item_type == ITEM_TYPE_SIGNATURE);
+    let (Some(signature), None) = (signatures.next(), signatures.next()) else {
+        let signed = items
+            .iter()
+            .any(|item| item.item_type == ITEM_TYPE_SIGNATURE);
+        return CardAuthenticity {
+            signed,
+            authentic: false,
+        };
+    };
+    let expected = crypto::hmac_sha256(key, &card_metadata(items, card_id));
+    let authentic = match STANDARD.decode(&signature.data).as_deref() {
+        Ok([CARD_SIGNATURE_VERSION, mac @ ..]) => crypto::ct_eq(mac, &expected),
+        _ => false,
+    };
+    CardAuthenticity {
+        signed: true,
+        authentic,
+    }
+}
+
+/// The share on a card for `recover_from_cards`: the first "share" item
+/// `shamir_combine` can read. Text-format shares from the web app aren't.
+fn recoverable_share(items: &[CardItem]) -> Result<&str, SeqretsError> {
+    let shares = || items.iter().filter(|item| item.item_type == "share");
+    if let Some(item) = shares().find(|item| !item.wrapped && shamir::is_share(&item.data)) {
+        return Ok(&item.data);
+    }
+    if shares().any(|item| item.wrapped) {
+        return Err(SeqretsError::InvalidInput(
+            "The share on this card is wrapped; give its passphrase.".to_string(),
+        ));
+    }
+    Err(SeqretsError::CorruptData(
+        "No share on this card can be combined.".to_string(),
+    ))
+}
+
+/// Read the share on one card for `recover_from_cards`.
+fn read_recovery_share(
+    app: &AppHandle,
+    state: &SmartcardState,
+    operation: &CardOperation,
+    entry: &RecoveryCard,
+) -> Result<Zeroizing<String>, SeqretsError> {
+    let (_ctx, card) = connect_reader(&entry.reader, state.transmit_timeout())?;
+    let share = (|| {
+        select_applet(&card, state.aid().as_deref())?;
+        verify_pin_if_needed(&card, &entry.pin)?;
+        let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(app))?;
+        if raw_data.is_empty() {
+            return Err("No data stored on this card.".into());
+        }
+        let mut items = parse_card_items(&raw_data, type_byte, &label)?;
+        unwrap_card_items(&card, &mut items, entry.passphrase.as_deref())?;
+        let share = recoverable_share(&items).map(|share| Zeroizing::new(share.to_string()));
+        for item in items.iter_mut() {
+            item.data.zeroize();
+        }
+        share
+    })();
+    disconnect_with_reset(card);
+    share
+}
+
+/// Read the share on each card in `readers` and recombine them natively (as
+/// `shamir_combine` does) into the still-encrypted vault blob. A card that
+/// can't be read, holds a share of a different secret or repeats another
+/// card's share is reported in `cards` and left out; the recovery succeeds
+/// as long as the threshold of good shares remains. When shares of several
+/// secrets are present, the one with the most cards wins. Only invalid
+/// input or a cancellation fail the call. Text-format shares written by the
+/// web app are not recognised.
+#[tauri::command]
+pub async fn recover_from_cards(
+    app: AppHandle,
+    readers: Vec<RecoveryCard>,
+    state: State<'_, SmartcardState>,
+) -> Result<RecoveryResult, SeqretsError> {
+    if readers.is_empty() {
+        return Err(SeqretsError::InvalidInput(
+            "No cards to recover from.".to_string(),
+        ));
+    }
+    let operation = state.begin_operation();
+
+    let mut reads = Vec::with_capacity(readers.len());
+    for entry in &readers {
+        let result = read_recovery_share(&app, &state, &operation, entry);
+        state.audit("recover_from_cards", &entry.reader, &result);
+        if let Err(SeqretsError::Cancelled(message)) = result {
+            return Err(SeqretsError::Cancelled(message));
+        }
+        reads.push(result);
+    }
+
+    let shares: Vec<&str> = reads
+        .iter()
+        .filter_map(|r| r.as_ref().ok())
+        .map(|s| s.as_str())
+        .collect();
+    let recovery = shamir::recover(&shares);
+    let mut combined = recovery.shares.into_iter();
+    let cards: Vec<CardRecoveryReport> = readers
+        .iter()
+        .zip(reads)
+        .map(|(entry, read)| {
+            let (index, error) = match read.map(|_| combined.next()) {
+                Err(e) => (None, Some(e)),
+                Ok(Some(Ok(index))) => (Some(index), None),
+                Ok(Some(Err(reason))) => (None, Some(SeqretsError::CorruptData(reason))),
+                Ok(None) => (None, None),
+            };
+            CardRecoveryReport {
+                reader: entry.reader.clone(),
+                index,
+                error,
+            }
+        })
+        .collect();
+
+    let (secret_b64, error) = match recovery.secret {
+        Ok(secret) => (Some(secret), None),
+        Err(e) => (None, Some(e)),
+    };
+    Ok(RecoveryResult {
+        secret_b64,
+        threshold: recovery.threshold,
+        shares_used: cards.iter().filter(|card| card.index.is_some()).count(),
+        cards,
+        error,
+    })
+}
+
+/// Read a single item by index from the card. `passphrase` behaves as in
+/// `read_card_items`.
+#[tauri::command]
+pub async fn read_card_item(
+    app: AppHandle,
+    reader: String,
+    index: usize,
+    pin: Option<String>,
+    passphrase: Option<String>,
+    wait_for_card: Option<u32>,
+    state: State<'_, SmartcardState>,
+) -> Result<CardItem, SeqretsError> {
+    let result = (|| -> Result<CardItem, SeqretsError> {
+        let passphrase = passphrase.map(Zeroizing::new);
+        let operation = state.begin_operation();
+        let (_ctx, card) =
+            connect_when_present(&reader, state.transmit_timeout(), wait_for_card, &operation)?;
+        select_applet(&card, state.aid().as_deref())?;
+        verify_pin_if_needed(&card, &pin)?;
+
+        let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(&app))?;
+
+        if raw_data.is_empty() {
+            disconnect_with_reset(card);
+            return Err("No data stored on this card.".into());
+        }
+
+        let items = parse_card_items(&raw_data, type_byte, &label);
+        let item = items.and_then(|items| {
+            let count = items.len();
+            let mut item = items.into_iter().nth(index).ok_or_else(|| {
+                SeqretsError::InvalidInput(format!(
+                    "Item index {} out of range (card has {} items)",
+                    index, count
+                ))
+            })?;
+            unwrap_card_items(
+                &card,
+                std::slice::from_mut(&mut item),
+                passphrase.as_ref().map(|p| p.as_str()),
+            )?;
+            Ok(item)
+        });
+        disconnect_with_reset(card);
+        item
+    })();
+    state.audit("read_card_item", &reader, &result);
+    result
+}
+
+/// The keyfile among `items`: the only `keyfile` item, or the one labelled
+/// `label` when the card holds several. Its data must be the standard
+/// base64 the crypto commands take. Keyfile shares are skipped; they need
+/// `combine_keyfile` first.
+fn find_keyfile(
+    items: Vec<CardItem>,
+    label: Option<&str>,
+) -> Result<Zeroizing<String>, SeqretsError> {
+    let mut keyfiles: Vec<CardItem> = items
+        .into_iter()
+        .filter(|item| {
+            item.item_type == ITEM_TYPE_KEYFILE
+                && !shamir::is_keyfile_share(&item.data)
+                && label.map_or(true, |label| item.label == label)
+        })
+        .collect();
+    let keyfile = match (keyfiles.len(), label) {
+        (1, _) => Zeroizing::new(std::mem::take(&mut keyfiles[0].data)),
+        (0, Some(label)) => {
+            return Err(SeqretsError::InvalidInput(format!(
+                "No keyfile labelled '{}' on this card.",
+                label
+            )))
+        }
+        (0, None) => {
+            return Err(SeqretsError::InvalidInput(
+                "No keyfile is stored on this card.".to_string(),
+            ))
+        }
+        (n, _) => {
+            return Err(SeqretsError::InvalidInput(format!(
+                "This card holds {} keyfiles; choose one by its label.",
+                n
+            )))
+        }
+    };
+    match STANDARD.decode(keyfile.as_bytes()).map(Zeroizing::new) {
+        Ok(bytes) if !bytes.is_empty() => Ok(keyfile),
+        _ => Err(SeqretsError::CorruptData(
+            "The keyfile on this card is not valid base64.".to_string(),
+        )),
+    }
+}
+
+/// Read the keyfile stored on the card in `reader` and return it as the
+/// base64 the crypto commands take in `keyfiles_b64`, so a vault can require
+/// the card to open. The card stores the keyfile itself; nothing is derived
+/// from the card's serial, so a copy of the keyfile (or a clone of the card)
+/// still opens the vault if this card dies. `label` picks one of several.
+#[tauri::command]
+pub async fn keyfile_from_card(
+    app: AppHandle,
+    reader: String,
+    pin: Option<String>,
+    label: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<String, SeqretsError> {
+    let result = (|| -> Result<String, SeqretsError> {
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        verify_pin_if_needed(&card, &pin)?;
+        let read = read_raw_card_data(&card, &operation.reading(&app));
+        disconnect_with_reset(card);
+
+        let (raw_data, type_byte, item_label) = read?;
+        let raw_data = Zeroizing::new(raw_data);
+        let items = parse_card_items(&raw_data, type_byte, &item_label)?;
+        find_keyfile(items, label.as_deref()).map(|keyfile| keyfile.to_string())
+    })();
+    state.audit("keyfile_from_card", &reader, &result);
+    result
+}
+
+/// Delete a single item by index, rewriting the remaining items.
+///
+/// This is how one share is taken off a card that holds several, leaving the
+/// others intact. The applet keeps every item in its single data slot and
+/// ERASE_DATA always clears all of it, so there is no slot-addressed erase.
+#[tauri::command]
+pub async fn delete_card_item(
+    app: AppHandle,
+    reader: String,
+    index: usize,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        let chunk_size =
+            select_for_write(&card, state.aid().as_deref(), state.write_chunk_override())?;
+        verify_pin_if_needed(&card, &pin)?;
+
+        let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(&app))?;
+
+        if raw_data.is_empty() {
+            disconnect_with_reset(card);
+            return Err("No data stored on this card.".into());
+        }
+
+        let mut items = parse_card_items(&raw_data, type_byte, &label)?;
+
+        if index >= items.len() {
+            disconnect_with_reset(card);
+            return Err(SeqretsError::InvalidInput(format!(
+                "Item index {} out of range (card has {} items)",
+                index,
+                items.len()
+            )));
+        }
+
+        items.remove(index);
+
+        let result = if items.is_empty() {
+            // No items left — just erase the card
+            send_apdu(&card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[]).map(|_| ())
+        } else {
+            write_items_to_card(
+                &card,
+                &items,
+                chunk_size,
+                state.require_contact(),
+                &operation.writing(&app),
+            )
+        };
+
+        disconnect_with_reset(card);
+        result
+    })();
+    state.audit("delete_card_item", &reader, &result);
+    result
+}
+
+/// Erase all data from the card, along with its label, PIN and PUK.
+#[tauri::command]
+pub fn erase_card(
+    reader: String,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        verify_pin_if_needed(&card, &pin)?;
+        let result = send_apdu(&card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[]);
+        disconnect_with_reset(card);
+        result.map(|_| ())
+    })();
+    state.audit("erase_card", &reader, &result);
+    result
+}
+
+/// Write a complete set of items to the card, replacing any existing data.
+/// Used by the clone-card feature to bulk-write items read from another card.
+#[tauri::command]
+pub async fn write_all_items(
+    app: AppHandle,
+    reader: String,
+    items: Vec<CardItem>,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let operation = state.begin_operation();
+        if items.is_empty() {
+            return Err(SeqretsError::InvalidInput("No items to write.".to_string()));
+        }
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        let chunk_size =
+            select_for_write(&card, state.aid().as_deref(), state.write_chunk_override())?;
+        verify_pin_if_needed(&card, &pin)?;
+        let result = write_items_to_card(
+            &card,
+            &items,
+            chunk_size,
+            state.require_contact(),
+            &operation.writing(&app),
+        );
+        disconnect_with_reset(card);
+        result
+    })();
+    state.audit("write_all_items", &reader, &result);
+    result
+}
+
+/// Serialize `backup` and encrypt it with `password` as a blob labelled
+/// `CARD_BACKUP_LABEL`.
+fn seal_card_backup(
+    backup: &CardBackup,
+    password: String,
+    params: Option<crypto::CryptoParams>,
+) -> Result<crypto::CryptoResult, SeqretsError> {
+    let json = Zeroizing::new(
+        serde_json::to_string(backup)
+            .map_err(|e| format!("Failed to serialize card backup: {}", e))?,
+    );
+    crypto::crypto_encrypt_blob(
+        json.to_string(),
+        password,
+        None,
+        params,
+        None,
+        Some(CARD_BACKUP_LABEL.to_string()),
+        None,
+        None,
+    )
+}
+
+/// Decrypt and parse a backup from `seal_card_backup`. A backup from a newer
+/// build is refused rather than restored partially.
+fn open_card_backup(
+    backup: &crypto::CryptoResult,
+    password: &str,
+) -> Result<CardBackup, SeqretsError> {
+    let plaintext = crypto::decrypt_blob(
+        &backup.salt,
+        &backup.data,
+        password,
+        &[],
+        Some(CARD_BACKUP_LABEL),
+        &crypto::CancelToken::default(),
+        &mut Duration::default(),
+    )?;
+    let json = Zeroizing::new(crypto::decompress(&plaintext)?);
+    drop(plaintext);
+    let backup: CardBackup = serde_json::from_slice(&json)
+        .map_err(|e| SeqretsError::CorruptData(format!("Not a card backup: {}", e)))?;
+    if backup.version > CARD_BACKUP_VERSION {
+        return Err(SeqretsError::InvalidInput(format!(
+            "This card backup is format version {}; this version of seQRets reads up to {}. Please update.",
+            backup.version, CARD_BACKUP_VERSION
+        )));
+    }
+    if backup.items.is_empty() {
+        return Err(SeqretsError::CorruptData(
+            "The card backup holds no items.".to_string(),
+        ));
+    }
+    Ok(backup)
+}
+
+/// Read every item from the card in `reader`, types, labels and wrapped
+/// flags included, and encrypt them with `password` into one backup blob
+/// for migrating to another card with `import_card`. Wrapped shares are
+/// exported still wrapped. `params` behaves as in `crypto_create`.
+#[tauri::command]
+pub async fn export_card(
+    app: AppHandle,
+    reader: String,
+    pin: Option<String>,
+    password: String,
+    params: Option<crypto::CryptoParams>,
+    state: State<'_, SmartcardState>,
+) -> Result<crypto::CryptoResult, SeqretsError> {
+    let result = (|| -> Result<crypto::CryptoResult, SeqretsError> {
+        let password = Zeroizing::new(password);
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        verify_pin_if_needed(&card, &pin)?;
+
+        let (raw_data, type_byte, label) = read_raw_card_data(&card, &operation.reading(&app))?;
+
+        if raw_data.is_empty() {
+            disconnect_with_reset(card);
+            return Err("No data stored on this card.".into());
+        }
+
+        let backup = parse_card_items(&raw_data, type_byte, &label).and_then(|items| {
+            let card_id = match items.iter().any(|item| item.wrapped) {
+                true => Some(card_identifier(&card)?),
+                false => None,
+            };
+            Ok(CardBackup {
+                version: CARD_BACKUP_VERSION,
+                card_id,
+                items,
+            })
+        });
+        disconnect_with_reset(card);
+        seal_card_backup(&backup?, password.to_string(), params)
+    })();
+    state.audit("export_card", &reader, &result);
+    result
+}
+
+/// Restore a backup from `export_card` to the card in `reader`, replacing
+/// any data on it. Shares wrapped to the original card only open on that
+/// card, so a backup holding them is refused for any other; unwrap them by
+/// reading with the passphrase and write them to the new card instead.
+#[tauri::command]
+pub async fn import_card(
+    app: AppHandle,
+    reader: String,
+    backup: crypto::CryptoResult,
+    password: String,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let password = Zeroizing::new(password);
+        let backup = open_card_backup(&backup, password.as_str())?;
+        drop(password);
+        let operation = state.begin_operation();
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        if backup.items.iter().any(|item| item.wrapped) {
+            let card_id = card_identifier(&card);
+            if card_id.as_ref().ok() != backup.card_id.as_ref() {
+                disconnect_with_reset(card);
+                return Err(SeqretsError::InvalidInput(
+                    "This backup holds shares wrapped to a different card. Read them with their passphrase and write them to this card instead.".to_string(),
+                ));
+            }
+        }
+        let chunk_size =
+            select_for_write(&card, state.aid().as_deref(), state.write_chunk_override())?;
+        verify_pin_if_needed(&card, &pin)?;
+        let result = write_items_to_card(
+            &card,
+            &backup.items,
+            chunk_size,
+            state.require_contact(),
+            &operation.writing(&app),
+        );
+        disconnect_with_reset(card);
+        result
+    })();
+    state.audit("import_card", &reader, &result);
+    result
+}
+
+/// Cancel the chunked read or write currently in flight. The operation stops
+/// before its next chunk with "Operation cancelled." (an interrupted write
+/// erases the partial data). A no-op if nothing is running or the last chunk
+/// has already been sent.
+#[tauri::command]
+pub fn cancel_card_operation(state: State<'_, SmartcardState>) {
+    state.cancel_operation();
+}
+
+/// Force-erase a card without PIN verification.
+/// Used to recover locked cards (PIN retries exhausted) when wipe protection is off.
+/// Like `erase_card` it also clears the PUK, so a previous owner can't unblock
+/// the card later.
+/// Will fail with SW_SECURITY_STATUS_NOT_SATISFIED if wipe protection is enabled.
+#[tauri::command]
+pub fn force_erase_card(
+    reader: String,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        // No PIN verification — send erase directly
+        let result = send_apdu(&card, CLA, INS_ERASE_DATA, 0x00, 0x00, &[]);
+        disconnect_with_reset(card);
+        result.map(|_| ())
+    })();
+    state.audit("force_erase_card", &reader, &result);
+    result
+}
+
+/// Enable or disable wipe protection on the card.
+/// Requires PIN verification. When enabled, ERASE_DATA requires PIN.
+#[tauri::command]
+pub fn set_wipe_protect(
+    reader: String,
+    pin: String,
+    enabled: bool,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        send_apdu(&card, CLA, INS_VERIFY_PIN, 0x00, 0x00, pin.as_bytes())?;
+        let p1 = if enabled { 0x01 } else { 0x00 };
+        let result = send_apdu(&card, CLA, INS_SET_WIPE_PROTECT, p1, 0x00, &[]);
+        disconnect_with_reset(card);
+        result.map(|_| ())
+    })();
+    state.audit("set_wipe_protect", &reader, &result);
+    result
+}
+
+/// Verify the PIN on the reader's own PIN pad (FEATURE_VERIFY_PIN_DIRECT),
+/// so the PIN never enters host memory. Returns `false` without prompting
+/// when the reader has no PIN pad; the caller then asks for the PIN and
+/// uses `verify_pin`. Waits for the user on a blocking thread, up to
+/// `PINPAD_TIMEOUT_SECS` per key on the reader's side.
+///
+/// Like `verify_pin` this checks the PIN only: verification ends with the
+/// connection.
+#[tauri::command]
+pub async fn verify_pin_on_reader(
+    reader: String,
+    state: State<'_, SmartcardState>,
+) -> Result<bool, SeqretsError> {
+    let aid = state.aid();
+    let timeout = state.transmit_timeout();
+    let reader_name = reader.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let (_ctx, card) = connect_reader(&reader_name, timeout)?;
+        let result = select_applet(&card, aid.as_deref()).and_then(|_| {
+            match pinpad_verify_code(&card.card.handle()) {
+                Some(code) => verify_pin_with_pinpad(&card.card.handle(), code).map(|_| true),
+                None => Ok(false),
+            }
+        });
+        disconnect_with_reset(card);
+        result
+    })
+    .await
+    .map_err(|e| SeqretsError::from(format!("PIN pad task failed: {}", e)))
+    .and_then(|result| result);
+    // Only an actual PIN pad attempt is an operation on the card
+    if !matches!(result, Ok(false)) {
+        state.audit("verify_pin_on_reader", &reader, &result);
+    }
+    result
+}
+
+/// Verify the PIN on the card.
+#[tauri::command]
+pub fn verify_pin(
+    reader: String,
+    pin: String,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        let result = send_apdu(&card, CLA, INS_VERIFY_PIN, 0x00, 0x00, pin.as_bytes());
+        disconnect_with_reset(card);
+        result.map(|_| ())
+    })();
+    state.audit("verify_pin", &reader, &result);
+    result
+}
+
+/// Set initial PIN on the card (only works if no PIN is set).
+#[tauri::command]
+pub fn set_pin(
+    reader: String,
+    pin: String,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        check_pin_length(&pin, "PIN")?;
+        let pin_bytes = pin.as_bytes();
+
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        let result = send_apdu(&card, CLA, INS_SET_PIN, 0x00, 0x00, pin_bytes);
+        disconnect_with_reset(card);
+        result.map(|_| ())
+    })();
+    state.audit("set_pin", &reader, &result);
+    result
+}
+
+/// Change the PIN on the card (must be verified first).
+#[tauri::command]
+pub fn change_pin(
+    reader: String,
+    old_pin: String,
+    new_pin: String,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        check_pin_length(&new_pin, "New PIN")?;
+        let new_pin_bytes = new_pin.as_bytes();
+
+        let old_pin_bytes = old_pin.as_bytes();
+        let mut data = Vec::with_capacity(old_pin_bytes.len() + new_pin_bytes.len());
+        data.extend_from_slice(old_pin_bytes);
+        data.extend_from_slice(new_pin_bytes);
+
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        let result = send_apdu(
+            &card,
+            CLA,
+            INS_CHANGE_PIN,
+            old_pin_bytes.len() as u8,
+            0x00,
+            &data,
+        );
+        disconnect_with_reset(card);
+        result.map(|_| ())
+    })();
+    state.audit("change_pin", &reader, &result);
+    result
+}
+
+/// Set the PUK (PIN Unblocking Key) on the card. Only works if no PUK is set.
+/// If the card has a PIN, it must be supplied so the applet can verify the
+/// owner before accepting a PUK.
+#[tauri::command]
+pub fn set_puk(
+    reader: String,
+    puk: String,
+    pin: Option<String>,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        check_pin_length(&puk, "PUK")?;
+
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+        verify_pin_if_needed(&card, &pin)?;
+        let result = send_apdu(&card, CLA, INS_SET_PUK, 0x00, 0x00, puk.as_bytes());
+        disconnect_with_reset(card);
+        result.map(|_| ())
+    })();
+    state.audit("set_puk", &reader, &result);
+    result
+}
+
+/// Map the status word of a PUK-authenticated command (UNBLOCK_PIN,
+/// FACTORY_RESET) to a result, surfacing remaining PUK attempts.
+fn check_puk_response(sw1: u8, sw2: u8) -> Result<(), SeqretsError> {
+    match (sw1, sw2) {
+        (0x90, 0x00) => Ok(()),
+        (0x63, sw2) if sw2 & 0xF0 == 0xC0 => {
+            let remaining = sw2 & 0x0F;
+            Err(SeqretsError::WrongPin(format!(
+                "Incorrect PUK. {} attempt{} remaining.",
+                remaining,
+                if remaining == 1 { "" } else { "s" }
+            )))
+        }
+        (0x69, 0x83) => Err(SeqretsError::CardLocked(
+            "PUK is locked. Too many incorrect PUK attempts.".to_string(),
+        )),
+        (0x69, 0x85) => Err("No PUK has been set on this card.".into()),
+        _ => Err(sw_to_error(sw1, sw2)),
+    }
+}
+
+/// Unblock a locked PIN with the PUK and set a new PIN.
+/// On success the PIN try counter is reset. A wrong PUK returns the number
+/// of PUK attempts left; when those run out the PUK is locked too.
+#[tauri::command]
+pub fn unblock_pin(
+    reader: String,
+    puk: String,
+    new_pin: String,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        check_pin_length(&puk, "PUK")?;
+        check_pin_length(&new_pin, "New PIN")?;
+
+        let puk_bytes = puk.as_bytes();
+        let mut data = Vec::with_capacity(puk_bytes.len() + new_pin.len());
+        data.extend_from_slice(puk_bytes);
+        data.extend_from_slice(new_pin.as_bytes());
+
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+
+        // P1 = PUK length, data = PUK || new PIN (mirrors CHANGE_PIN)
+        let cmd = build_apdu(CLA, INS_UNBLOCK_PIN, puk_bytes.len() as u8, 0x00, &data);
+        let result = transmit_apdu(&card, &cmd);
+        disconnect_with_reset(card);
+
+        let (_, sw1, sw2) = result?;
+        check_puk_response(sw1, sw2)
+    })();
+    state.audit("unblock_pin", &reader, &result);
+    result
+}
+
+/// Factory-reset the card: wipes all stored data, the label and type,
+/// the PIN, the PUK and wipe protection, returning the applet to its
+/// just-installed state. **This is irreversible.**
+///
+/// If the card has a PUK it must be supplied. Cards without a PUK can be
+/// reset only with `confirm: true` (and, if wipe protection is on, are
+/// refused until a PUK is set or protection is disabled).
+#[tauri::command]
+pub fn factory_reset(
+    reader: String,
+    puk: Option<String>,
+    confirm: bool,
+    state: State<'_, SmartcardState>,
+) -> Result<(), SeqretsError> {
+    let result = (|| -> Result<(), SeqretsError> {
+        state.check_writable()?;
+        let puk = puk.filter(|p| !p.is_empty());
+        if let Some(ref p) = puk {
+            check_pin_length(p, "PUK")?;
+        } else if !confirm {
+            return Err(SeqretsError::InvalidInput(
+                "Factory reset is irreversible. Provide the PUK or confirm the reset.".to_string(),
+            ));
+        }
+
+        let (_ctx, card) = connect_reader(&reader, state.transmit_timeout())?;
+        select_applet(&card, state.aid().as_deref())?;
+
+        let data = puk.as_deref().map(str::as_bytes).unwrap_or(&[]);
+        let cmd = build_apdu(CLA, INS_FACTORY_RESET, 0x00, 0x00, data);
+        let result = transmit_apdu(&card, &cmd);
+        disconnect_with_reset(card);
+
+        let (_, sw1, sw2) = result?;
+        check_puk_response(sw1, sw2)
+    })();
+    state.audit("factory_reset", &reader, &result);
+    result
+}
+
+// ── Unit tests ──────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_reaches_in_flight_operation() {
+        let state = SmartcardState::default();
+        let operation = state.begin_operation();
+        state.cancel_operation();
+        assert!(operation.token.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_cancel_after_operation_is_noop() {
+        let state = SmartcardState::default();
+        drop(state.begin_operation());
+        state.cancel_operation();
+
+        // A later operation must not inherit the stale cancel.
+        let operation = state.begin_operation();
+        assert!(!operation.token.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_finished_operation_keeps_newer_token() {
+        let state = SmartcardState::default();
+        let first = state.begin_operation();
+        let second = state.begin_operation();
+        drop(first);
+
+        state.cancel_operation();
+        assert!(second.token.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_pcsc_status_distinguishes_service_from_readers() {
+        assert!(matches!(
+            pcsc_status_from(Err(pcsc::Error::NoService)),
+            PcscStatus::ServiceUnavailable { message } if message.contains("pcscd")
+        ));
+        assert!(matches!(
+            pcsc_status_from(Err(pcsc::Error::ServiceStopped)),
+            PcscStatus::ServiceUnavailable { .. }
+        ));
+        assert_eq!(
+            pcsc_status_from(Err(pcsc::Error::NoReadersAvailable)),
+            PcscStatus::NoReaders
+        );
+        assert_eq!(pcsc_status_from(Ok(vec![])), PcscStatus::NoReaders);
+        assert_eq!(
+            pcsc_status_from(Ok(vec!["ACS ACR39U".to_string()])),
+            PcscStatus::Ready {
+                readers: vec!["ACS ACR39U".to_string()]
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(PcscStatus::NoReaders).unwrap(),
+            serde_json::json!({ "state": "noReaders" })
+        );
+    }
+
+    #[test]
+    fn test_status_words_map_to_error_kinds() {
+        assert!(matches!(sw_to_error(0x63, 0xC2), SeqretsError::WrongPin(_)));
+        assert!(matches!(
+            sw_to_error(0x69, 0x82),
+            SeqretsError::PinRequired(_)
+        ));
+        assert!(matches!(
+            sw_to_error(0x69, 0x83),
+            SeqretsError::CardLocked(_)
+        ));
+        assert!(matches!(
+            sw_to_error(0x6A, 0x84),
+            SeqretsError::StorageFull(_)
+        ));
+        assert_eq!(
+            sw_to_error(0x6F, 0x00),
+            SeqretsError::Other("The card reported an internal error.".to_string())
+        );
+        assert!(matches!(
+            check_puk_response(0x69, 0x83),
+            Err(SeqretsError::CardLocked(_))
+        ));
+    }
+
+    #[test]
+    fn test_status_word_messages() {
+        let cases = [
+            (
+                0x63,
+                0xC3,
+                SeqretsError::WrongPin("Verification failed. 3 attempts remaining.".to_string()),
+            ),
+            (
+                0x63,
+                0xC1,
+                SeqretsError::WrongPin("Verification failed. 1 attempt remaining.".to_string()),
+            ),
+            (
+                0x63,
+                0xC0,
+                SeqretsError::WrongPin("Verification failed. 0 attempts remaining.".to_string()),
+            ),
+            (
+                0x69,
+                0x82,
+                SeqretsError::PinRequired(
+                    "PIN verification required. Please verify your PIN first.".to_string(),
+                ),
+            ),
+            (
+                0x69,
+                0x83,
+                SeqretsError::CardLocked(
+                    "Card is locked. Too many incorrect PIN attempts.".to_string(),
+                ),
+            ),
+            (
+                0x69,
+                0x84,
+                SeqretsError::CardLocked(
+                    "Card is locked. Too many incorrect PIN attempts.".to_string(),
+                ),
+            ),
+            (
+                0x6A,
+                0x82,
+                SeqretsError::Other("Application or file not found on this card.".to_string()),
+            ),
+            (
+                0x6A,
+                0x84,
+                SeqretsError::StorageFull(
+                    "Card storage full. Data too large for this card.".to_string(),
+                ),
+            ),
+            (
+                0x6D,
+                0x00,
+                SeqretsError::Other(
+                    "Instruction not supported. The card applet may be outdated.".to_string(),
+                ),
+            ),
+            (
+                0x6E,
+                0x00,
+                SeqretsError::Other(
+                    "Command class not supported. This may not be a seQRets card.".to_string(),
+                ),
+            ),
+            // 63 xx outside the Cx retry-counter range and unknown words keep the raw hex
+            (
+                0x63,
+                0x00,
+                SeqretsError::Other("Card returned error: SW=6300".to_string()),
+            ),
+            (
+                0x6A,
+                0x8A,
+                SeqretsError::Other("Card returned error: SW=6A8A".to_string()),
+            ),
+            (
+                0x9F,
+                0x0B,
+                SeqretsError::Other("Card returned error: SW=9F0B".to_string()),
+            ),
+        ];
+        for (sw1, sw2, expected) in cases {
+            assert_eq!(
+                sw_to_message(sw1, sw2),
+                expected.message(),
+                "SW={sw1:02X}{sw2:02X}"
+            );
+            assert_eq!(sw_to_error(sw1, sw2), expected, "SW={sw1:02X}{sw2:02X}");
+        }
+    }
+
+    #[test]
+    fn test_parse_aid_hex() {
+        assert_eq!(
+            parse_aid_hex("F0 53 51 52 54 53 01 00 00").unwrap(),
+            SEQRETS_AID
+        );
+        assert_eq!(
+            parse_aid_hex("f0:53:51:52:54").unwrap(),
+            [0xF0, 0x53, 0x51, 0x52, 0x54]
+        );
+        for bad in [
+            "F05351525",
+            "F0535152ZZ",
+            "+F05351525",
+            "F053 51 52 5",
+            "F053515254é1",
+            "Fé1",
+            "F05351525€",
+        ] {
+            assert!(
+                matches!(parse_aid_hex(bad), Err(SeqretsError::InvalidInput(_))),
+                "accepted {bad:?}"
+            );
+        }
+        // Well-formed hex, but not 5-16 bytes
+        assert!(matches!(
+            parse_aid_hex("F0 53 51 52"),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            parse_aid_hex(&"AB".repeat(17)),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    /// A reader that never answers, like a card wedged mid-command.
+    struct DeadReader;
+
+    impl ApduTransport for DeadReader {
+        fn exchange(&self, _cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            loop {
+                thread::park();
+            }
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            Ok(())
+        }
+    }
+
+    /// A reader that answers every command with 90 00.
+    struct OkReader;
+
+    impl ApduTransport for OkReader {
+        fn exchange(&self, _cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            Ok(vec![0x01, 0x90, 0x00])
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            Ok(())
+        }
+    }
+
+    /// A reader that answers every command with the same bytes.
+    struct FixedReader(Vec<u8>);
+
+    impl ApduTransport for FixedReader {
+        fn exchange(&self, _cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            Ok(self.0.clone())
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_locked_applet_is_reported() {
+        let select = |resp: &[u8]| {
+            let link = CardLink::new(
+                FixedReader(resp.to_vec()),
+                Duration::from_secs(1),
+                CardInterface::Contact,
+            );
+            select_applet(&link, None)
+        };
+        assert!(matches!(
+            select(&[0x62, 0x83]),
+            Err(SeqretsError::AppletLocked(_))
+        ));
+        // A card manager that selects the applet but reports it LOCKED
+        let fci = [
+            0x6F, 0x07, 0x8A, 0x01, 0x83, 0xA5, 0x02, 0x00, 0x00, 0x90, 0x00,
+        ];
+        assert!(matches!(select(&fci), Err(SeqretsError::AppletLocked(_))));
+        assert!(!matches!(
+            select(&[0x6A, 0x82]),
+            Err(SeqretsError::AppletLocked(_))
+        ));
+
+        assert_eq!(parse_fci(&fci[..9]).content_state, Some(0x83));
+        assert_eq!(gp_lifecycle(None), "selectable");
+        assert_eq!(gp_lifecycle(Some(0x03)), "installed");
+        assert_eq!(gp_lifecycle(Some(0x0F)), "personalized");
+        assert_eq!(gp_lifecycle(Some(0x87)), "locked");
+    }
+
+    /// A card with an applet that reports `status` from GET_STATUS.
+    struct StatusReader {
+        status: Vec<u8>,
+    }
+
+    impl ApduTransport for StatusReader {
+        fn exchange(&self, cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            let mut resp = match cmd[1] {
+                INS_GET_VERSION => vec![0x01, 0x04],
+                INS_GET_STATUS => self.status.clone(),
+                _ => Vec::new(),
+            };
+            resp.extend_from_slice(&[0x90, 0x00]);
+            Ok(resp)
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_probe_card_reports_status_without_pin() {
+        fn probe<T: ApduTransport>(link: &CardLink<T>) -> (bool, Option<bool>, Option<String>) {
+            let mut info = ReaderCardInfo {
+                reader: "R".into(),
+                alias: None,
+                card_present: true,
+                applet_present: false,
+                has_data: None,
+                label: None,
+            };
+            probe_card(link, None, &mut info);
+            (info.applet_present, info.has_data, info.label)
+        }
+        let status = vec![0x00, 0x10, TYPE_VAULT, 0, 0, 5, 4, b'H', b'o', b'm', b'e'];
+        let link = CardLink::new(
+            StatusReader { status },
+            Duration::from_secs(1),
+            CardInterface::Contact,
+        );
+        assert_eq!(probe(&link), (true, Some(true), Some("Home".to_string())));
+
+        let link = CardLink::new(
+            FixedReader(vec![0x6A, 0x82]),
+            Duration::from_secs(1),
+            CardInterface::Contact,
+        );
+        assert_eq!(probe(&link), (false, None, None));
+        let link = CardLink::new(
+            StatusReader { status: Vec::new() },
+            Duration::from_secs(1),
+            CardInterface::Contact,
+        );
+        assert_eq!(probe(&link), (true, None, None));
+    }
+
+    /// A reader whose card is reset under the next `resets` commands other
+    /// than SELECT/GET_VERSION. Records every command it is sent.
+    #[derive(Default)]
+    struct ResettingReader {
+        resets: std::sync::atomic::AtomicUsize,
+        reconnects: std::sync::atomic::AtomicUsize,
+        sent: Mutex<Vec<u8>>,
+    }
+
+    impl ApduTransport for ResettingReader {
+        fn exchange(&self, cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            self.sent.lock().unwrap().push(cmd[1]);
+            match cmd[1] {
+                0xA4 => Ok(vec![0x90, 0x00]),
+                INS_GET_VERSION => Ok(vec![0x01, 0x02, 0x90, 0x00]),
+                _ if self.resets.load(Ordering::SeqCst) > 0 => {
+                    self.resets.fetch_sub(1, Ordering::SeqCst);
+                    Err(pcsc::Error::ResetCard)
+                }
+                _ => Ok(vec![0x90, 0x00]),
+            }
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            self.reconnects.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_reset_card_reconnects_and_retries_once() {
+        let link = CardLink::new(
+            ResettingReader::default(),
+            Duration::from_secs(1),
+            CardInterface::Contact,
+        );
+        select_applet(&link, None).unwrap();
+        link.card.sent.lock().unwrap().clear();
+
+        link.card.resets.store(1, Ordering::SeqCst);
+        send_apdu(&link, CLA, INS_GET_STATUS, 0x00, 0x00, &[]).unwrap();
+        assert_eq!(link.card.reconnects.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            *link.card.sent.lock().unwrap(),
+            [INS_GET_STATUS, 0xA4, INS_GET_VERSION, INS_GET_STATUS]
+        );
+
+        // Reset again during the retry: no second recovery
+        link.card.resets.store(2, Ordering::SeqCst);
+        assert!(send_apdu(&link, CLA, INS_GET_STATUS, 0x00, 0x00, &[]).is_err());
+        assert_eq!(link.card.reconnects.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_reset_after_pin_verify_asks_for_pin_again() {
+        let link = CardLink::new(
+            ResettingReader::default(),
+            Duration::from_secs(1),
+            CardInterface::Contact,
+        );
+        select_applet(&link, None).unwrap();
+        send_apdu(&link, CLA, INS_VERIFY_PIN, 0x00, 0x00, b"1234").unwrap();
+        assert!(link.pin_verified.load(Ordering::SeqCst));
+
+        link.card.resets.store(1, Ordering::SeqCst);
+        let result = send_apdu(&link, CLA, INS_GET_STATUS, 0x00, 0x00, &[]);
+        assert!(matches!(result, Err(SeqretsError::PinRequired(_))));
+        assert_eq!(link.card.reconnects.load(Ordering::SeqCst), 1);
+        assert!(!link.pin_verified.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_transmit_times_out_on_dead_reader() {
+        let started = std::time::Instant::now();
+        let result = transmit_with_timeout(
+            &Arc::new(DeadReader),
+            &[0x00, 0xA4],
+            Duration::from_millis(50),
+            "SELECT",
+        );
+        assert!(matches!(result, Err(SeqretsError::Timeout(_))));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let resp = transmit_with_timeout(
+            &Arc::new(OkReader),
+            &[0x00, 0xA4],
+            Duration::from_millis(50),
+            "SELECT",
+        )
+        .unwrap();
+        assert_eq!(split_status_word(&resp), Some((vec![0x01], 0x90, 0x00)));
+        assert_eq!(split_status_word(&[0x90]), None);
+    }
+
+    #[test]
+    fn test_find_keyfile() {
+        let item = |item_type: &str, label: &str, data: &str| CardItem {
+            item_type: item_type.into(),
+            label: label.into(),
+            data: data.into(),
+            wrapped: false,
+            vault_id: None,
+        };
+        let one = vec![
+            item("share", "s", "seQRets|x|y"),
+            item("keyfile", "Main", "a2V5"),
+        ];
+        assert_eq!(find_keyfile(one.clone(), None).unwrap().as_str(), "a2V5");
+        assert!(matches!(
+            find_keyfile(one, Some("Other")),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+
+        let with_share = vec![
+            item("keyfile", "Share 1", "seQRets-keyfile|AQEC"),
+            item("keyfile", "Main", "a2V5"),
+        ];
+        assert_eq!(find_keyfile(with_share, None).unwrap().as_str(), "a2V5");
+        let two = vec![
+            item("keyfile", "Main", "a2V5"),
+            item("keyfile", "Spare", "c3BhcmU="),
+        ];
+        assert!(matches!(
+            find_keyfile(two.clone(), None),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert_eq!(
+            find_keyfile(two, Some("Spare")).unwrap().as_str(),
+            "c3BhcmU="
+        );
+
+        assert!(matches!(
+            find_keyfile(vec![item("keyfile", "", "not base64")], None),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        assert!(matches!(
+            find_keyfile(Vec::new(), None),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_only_session_refuses_writes() {
+        let state = SmartcardState::default();
+        assert!(state.check_writable().is_ok());
+        state.read_only.store(true, Ordering::SeqCst);
+        assert!(matches!(
+            state.check_writable(),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_transmit_timeout_override() {
+        let state = SmartcardState::default();
+        assert_eq!(state.transmit_timeout(), DEFAULT_TRANSMIT_TIMEOUT);
+
+        *state.timeout_override.lock().unwrap() = parse_transmit_timeout(Some(250)).unwrap();
+        assert_eq!(state.transmit_timeout(), Duration::from_millis(250));
+
+        assert_eq!(parse_transmit_timeout(None).unwrap(), None);
+        for ms in [0, TRANSMIT_TIMEOUT_MIN_MS - 1, TRANSMIT_TIMEOUT_MAX_MS + 1] {
+            assert!(matches!(
+                parse_transmit_timeout(Some(ms)),
+                Err(SeqretsError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_applet_version_parsing_and_minimum() {
+        assert_eq!(
+            parse_applet_version(&[1, 1], 0x90, 0x00).unwrap(),
+            AppletVersion { major: 1, minor: 1 }
+        );
+        // Applets predating GET_VERSION reject the instruction
+        assert_eq!(
+            parse_applet_version(&[], 0x6D, 0x00).unwrap(),
+            OLDEST_APPLET_VERSION
+        );
+        assert!(matches!(
+            parse_applet_version(&[1], 0x90, 0x00),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        assert!(matches!(
+            parse_applet_version(&[], 0x69, 0x82),
+            Err(SeqretsError::PinRequired(_))
+        ));
+
+        assert_eq!(
+            AppletVersion {
+                major: 2,
+                minor: 10
+            }
+            .to_string(),
+            "2.10"
+        );
+        assert!(AppletVersion { major: 1, minor: 9 } < AppletVersion { major: 2, minor: 0 });
+        assert!(check_applet_version(OLDEST_APPLET_VERSION).is_ok());
+        assert!(check_applet_version(AppletVersion { major: 0, minor: 9 })
+            .unwrap_err()
+            .message()
+            .contains("update your card applet"));
+    }
+
+    #[test]
+    fn test_status_label_follows_length_byte() {
+        // len=10, type=vault, pin set, not verified, 5 retries, label "3 items", capacity 8192
+        let mut resp = vec![0x00, 0x0A, TYPE_VAULT, 0x01, 0x00, 0x05, 0x07];
+        resp.extend_from_slice(b"3 items");
+        resp.extend_from_slice(&[0x20, 0x00]);
+        assert_eq!(parse_status_label(&resp), "3 items");
+        assert_eq!(parse_card_capacity(&resp), 8192);
+
+        // Truncated response: claims more label bytes than it carries
+        assert_eq!(parse_status_label(&resp[..9]), "");
+        assert_eq!(parse_status_label(&resp[..6]), "");
+    }
+
+    #[test]
+    fn test_preflight_payload_and_size_estimate() {
+        assert!(check_write_payload(100, "share").is_ok());
+        assert!(matches!(
+            check_write_payload(100, "photo"),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            check_write_payload(0, "vault"),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert!(check_write_size(255 * CHUNK_SIZE, CHUNK_SIZE).is_ok());
+        assert!(matches!(
+            check_write_size(255 * CHUNK_SIZE + 1, CHUNK_SIZE),
+            Err(SeqretsError::StorageFull(_))
+        ));
+        assert!(matches!(
+            check_write_size(255 * 64 + 1, 64),
+            Err(SeqretsError::StorageFull(_))
+        ));
+
+        // Matches what write_items_to_card actually serializes
+        let item = CardItem {
+            item_type: "share".to_string(),
+            label: String::new(),
+            data: "x".repeat(50),
+            wrapped: false,
+            vault_id: None,
+        };
+        let first = serde_json::to_string(std::slice::from_ref(&item)).unwrap();
+        assert_eq!(appended_size(0, "share", 50), first.len());
+        let both = serde_json::to_string(&[item.clone(), item]).unwrap();
+        assert_eq!(appended_size(first.len(), "share", 50), both.len());
+    }
+
+    #[test]
+    fn test_preflight_report_skips_after_failure() {
+        let mut report = PreflightReport::default();
+        report.record(PREFLIGHT_PAYLOAD, Ok(()));
+        report.record(
+            PREFLIGHT_READER,
+            Err(SeqretsError::NoReaders("gone".to_string())),
+        );
+        report.skip(&[PREFLIGHT_APPLET, PREFLIGHT_PIN], PREFLIGHT_READER);
+        let report = report.finish();
+
+        assert!(!report.ready);
+        let names: Vec<&str> = report.checks.iter().map(|c| c.check.as_str()).collect();
+        assert_eq!(names, ["payload", "reader", "applet", "pin"]);
+        assert!(report.checks[0].passed && report.checks[0].error.is_none());
+        assert!(!report.checks[3].passed);
+
+        assert!(PreflightReport::default().finish().ready);
+    }
+
+    #[test]
+    fn test_fci_parsing() {
+        let aid = [0xF0, 0x53, 0x51, 0x52, 0x54, 0x53, 0x01, 0x00, 0x00];
+        let mut fci = vec![0x6F, 24, 0x84, aid.len() as u8];
+        fci.extend_from_slice(&aid);
+        fci.extend_from_slice(&[0xA5, 11, 0x80, 2, 1, 2, 0x81, 2, 0x00, 0x1F, 0x82, 1, 0x02]);
+        assert_eq!(
+            parse_fci(&fci),
+            AppletFci {
+                version: Some(AppletVersion { major: 1, minor: 2 }),
+                capabilities: Some(0x001F),
+                lifecycle: Some(0x02),
+                content_state: None,
+            }
+        );
+        assert_eq!(
+            AppletLifecycle::from_fci(Some(0x02)),
+            AppletLifecycle::InUse
+        );
+        assert_eq!(AppletLifecycle::from_fci(None), AppletLifecycle::Unknown);
+
+        // Older applets send nothing; truncated or foreign FCI is ignored
+        assert_eq!(parse_fci(&[]), AppletFci::default());
+        assert_eq!(parse_fci(&fci[..fci.len() - 1]), AppletFci::default());
+        assert_eq!(
+            parse_fci(&[0x6F, 0x03, 0x84, 0x01, 0xAA]),
+            AppletFci::default()
+        );
+        assert_eq!(
+            parse_tlv(&[0x80, 0x81, 0x02, 0xAA, 0xBB]),
+            Some(vec![(0x80, &[0xAA, 0xBB][..])])
+        );
+    }
+
+    #[test]
+    fn test_pin_required_status_hides_card_contents() {
+        let status = CardStatus::pin_required(OLDEST_APPLET_VERSION, CardInterface::Contact);
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["pin_required"], true);
+        assert_eq!(json["pin_set"], true);
+        assert_eq!(json["total_items"], 0);
+        assert_eq!(json["applet_version"], "1.0");
+    }
+
+    #[test]
+    fn test_write_chunk_size_follows_reader() {
+        let variable = Some(0x003F);
+        let fixed = Some(0x001F);
+        // Unknown limits keep the historical chunk size
+        assert_eq!(write_chunk_size(None, variable, None).unwrap(), CHUNK_SIZE);
+        // Large readers fill a short APDU when the applet allows it
+        assert_eq!(
+            write_chunk_size(Some(261), variable, None).unwrap(),
+            MAX_SHORT_APDU_DATA
+        );
+        assert_eq!(
+            write_chunk_size(Some(261), fixed, None).unwrap(),
+            CHUNK_SIZE
+        );
+        assert_eq!(write_chunk_size(Some(133), variable, None).unwrap(), 128);
+        // Older applets can't take shorter chunks
+        assert!(matches!(
+            write_chunk_size(Some(133), fixed, None),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            write_chunk_size(Some(133), None, None),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            write_chunk_size(Some(10), variable, None),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        // An override wins over the reader, within what the applet accepts
+        assert_eq!(write_chunk_size(Some(261), variable, Some(64)).unwrap(), 64);
+        assert_eq!(
+            write_chunk_size(Some(133), fixed, Some(CHUNK_SIZE)).unwrap(),
+            CHUNK_SIZE
+        );
+        assert!(matches!(
+            write_chunk_size(None, fixed, Some(64)),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert_eq!(parse_write_chunk_size(Some(200)).unwrap(), Some(200));
+        assert!(parse_write_chunk_size(Some(8)).is_err());
+        assert!(parse_write_chunk_size(Some(256)).is_err());
+    }
+
+    #[test]
+    fn test_reader_attribute_parsing() {
+        let word = 261u32;
+        let bytes = if cfg!(target_endian = "little") {
+            word.to_le_bytes()
+        } else {
+            word.to_be_bytes()
+        };
+        assert_eq!(parse_dword(&bytes), Some(261));
+        assert_eq!(parse_dword(&[]), None);
+        assert_eq!(parse_dword(&[0; 8]), None);
+
+        // FEATURE_MODIFY_PIN_DIRECT (0x07) alone, then with VERIFY_PIN_DIRECT
+        let modify_only = [0x07, 0x04, 0x42, 0x33, 0x00, 0x07];
+        assert_eq!(
+            feature_control_code(&modify_only, FEATURE_VERIFY_PIN_DIRECT),
+            None
+        );
+        let both = [
+            0x07, 0x04, 0x42, 0x33, 0x00, 0x07, 0x06, 0x04, 0x42, 0x33, 0x00, 0x06,
+        ];
+        assert_eq!(
+            feature_control_code(&both, FEATURE_VERIFY_PIN_DIRECT),
+            Some(0x4233_0006)
+        );
+        assert_eq!(
+            feature_control_code(&[0x06, 0x09], FEATURE_VERIFY_PIN_DIRECT),
+            None
+        );
+        assert_eq!(protocol_name(Some(Protocol::T1)), "T=1");
+    }
+
+    #[test]
+    fn test_pinpad_verify_structure() {
+        let s = pin_verify_structure();
+        assert_eq!(s.len(), 19 + 5);
+        assert_eq!(
+            &s[2..7],
+            &[0x82, 0x00, 0x00, PIN_MAX_LENGTH as u8, PIN_MIN_LENGTH as u8]
+        );
+        assert_eq!(&s[15..19], &5u32.to_le_bytes());
+        assert_eq!(&s[19..], &[CLA, INS_VERIFY_PIN, 0x00, 0x00, 0x00]);
+
+        assert!(pinpad_status(0x90, 0x00).is_ok());
+        assert!(matches!(
+            pinpad_status(0x64, 0x00),
+            Err(SeqretsError::Timeout(_))
+        ));
+        assert!(matches!(
+            pinpad_status(0x64, 0x01),
+            Err(SeqretsError::Cancelled(_))
+        ));
+        assert!(matches!(
+            pinpad_status(0x63, 0xC4),
+            Err(SeqretsError::WrongPin(_))
+        ));
+    }
+
+    #[test]
+    fn test_interface_detection_and_contact_policy() {
+        let contact_atr = [0x3B, 0xDC, 0x18, 0xFF, 0x81, 0x91, 0xFE, 0x1F, 0xC3];
+        let contactless_atr = [0x3B, 0x8F, 0x80, 0x01, 0x80, 0x4F, 0x0C, 0xA0];
+        assert_eq!(
+            detect_interface("ACS ACR39U ICC Reader 00 00", Some(&contact_atr)),
+            CardInterface::Contact
+        );
+        assert_eq!(
+            detect_interface("Generic Reader 0", Some(&contactless_atr)),
+            CardInterface::Contactless
+        );
+        assert_eq!(
+            detect_interface("ACS ACR122U PICC Interface 00 00", Some(&contact_atr)),
+            CardInterface::Contactless
+        );
+        assert_eq!(
+            detect_interface("HID OMNIKEY 5422CL Smartcard Reader 0", Some(&contact_atr)),
+            CardInterface::Contactless
+        );
+        assert_eq!(
+            detect_interface("HID OMNIKEY 5422 Smartcard Reader 0", None),
+            CardInterface::Unknown
+        );
+        assert_eq!(
+            serde_json::to_value(CardInterface::Contactless).unwrap(),
+            "contactless"
+        );
+
+        assert!(check_write_interface(CardInterface::Contactless, false).is_ok());
+        assert!(check_write_interface(CardInterface::Contact, true).is_ok());
+        for interface in [CardInterface::Contactless, CardInterface::Unknown] {
+            assert!(matches!(
+                check_write_interface(interface, true),
+                Err(SeqretsError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_write_count_needs_the_capability() {
+        let link = CardLink::new(
+            FixedReader(vec![0x00, 0x01, 0x02, 0x03, 0x90, 0x00]),
+            Duration::from_secs(1),
+            CardInterface::Contact,
+        );
+        assert_eq!(read_write_count(&link), None);
+        link.capabilities.store(CAP_WRITE_COUNT, Ordering::SeqCst);
+        assert_eq!(read_write_count(&link), Some(0x0001_0203));
+
+        let link = CardLink::new(
+            FixedReader(vec![0x6D, 0x00]),
+            Duration::from_secs(1),
+            CardInterface::Contact,
+        );
+        link.capabilities.store(CAP_WRITE_COUNT, Ordering::SeqCst);
+        assert_eq!(read_write_count(&link), None);
+    }
+
+    #[test]
+    fn test_card_ready_needs_a_responsive_card() {
+        assert!(card_ready("Reader A", pcsc::State::PRESENT | pcsc::State::CHANGED).unwrap());
+        assert!(!card_ready("Reader A", pcsc::State::EMPTY).unwrap());
+        assert!(!card_ready("Reader A", pcsc::State::PRESENT | pcsc::State::MUTE).unwrap());
+        assert!(card_ready("Reader A", pcsc::State::UNKNOWN).is_err());
+    }
+
+    #[test]
+    fn test_sharing_violation_is_reader_busy_and_retried() {
+        assert!(matches!(
+            connect_error("Reader A", pcsc::Error::SharingViolation),
+            SeqretsError::ReaderBusy(_)
+        ));
+        assert!(matches!(
+            connect_error("Reader A", pcsc::Error::NoSmartcard),
+            SeqretsError::Other(_)
+        ));
+
+        let busy = || Err::<(), _>(connect_error("Reader A", pcsc::Error::SharingViolation));
+        let mut attempts = 0;
+        let freed = retry_while_busy(Duration::from_secs(5), Duration::from_millis(1), || {
+            attempts += 1;
+            if attempts < 3 {
+                busy()
+            } else {
+                Ok(())
+            }
+        });
+        assert!(freed.is_ok());
+        assert_eq!(attempts, 3);
+
+        let started = Instant::now();
+        let held = retry_while_busy(Duration::from_millis(30), Duration::from_millis(5), busy);
+        assert!(matches!(held, Err(SeqretsError::ReaderBusy(_))));
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        attempts = 0;
+        let missing = retry_while_busy(Duration::from_secs(5), Duration::from_millis(1), || {
+            attempts += 1;
+            Err::<(), _>(SeqretsError::NoReaders(NO_READERS_MESSAGE.to_string()))
+        });
+        assert!(matches!(missing, Err(SeqretsError::NoReaders(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_reader_alias_survives_index_change() {
+        assert_eq!(reader_alias_key("Generic Reader 00 00"), "Generic Reader");
+        assert_eq!(
+            reader_alias_key("Yubico YubiKey OTP+FIDO+CCID 0"),
+            "Yubico YubiKey OTP+FIDO+CCID"
+        );
+        assert_eq!(
+            reader_alias_key("ACS ACR1252 1S CL Reader PICC"),
+            "ACS ACR1252 1S CL Reader PICC"
+        );
+        assert_eq!(reader_alias_key("00 00"), "00");
+
+        let state = SmartcardState::default();
+        state
+            .set_reader_alias("Generic Reader 00 00", Some(" my YubiKey "))
+            .unwrap();
+        assert_eq!(
+            state.reader_alias("Generic Reader 00 01").as_deref(),
+            Some("my YubiKey")
+        );
+        assert_eq!(state.reader_alias("Other Reader 00 00"), None);
+        assert!(state
+            .set_reader_alias("Generic Reader 00 00", Some(&"x".repeat(65)))
+            .is_err());
+        state
+            .set_reader_alias("Generic Reader 01 00", None)
+            .unwrap();
+        assert_eq!(state.reader_alias("Generic Reader 00 00"), None);
+    }
+
+    #[test]
+    fn test_enrollment_writes_each_share_once_in_order() {
+        let share_item = |data: &str| CardItem {
+            item_type: "share".into(),
+            label: String::new(),
+            data: data.into(),
+            wrapped: false,
+            vault_id: None,
+        };
+        let labels = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let shares = vec!["s1".to_string(), "s2".to_string(), "s3".to_string()];
+        let mut enrollment = Enrollment::new(shares, labels.clone(), None).unwrap();
+
+        assert_eq!(
+            enrollment.plan(Some("c1"), &[]).unwrap(),
+            EnrollStep::Write(0)
+        );
+        enrollment.record(0, "Reader", Some("c1".into()));
+        // The same card again, by identity or by content, is refused
+        assert!(enrollment.plan(Some("c1"), &[]).is_err());
+        assert!(enrollment.plan(None, &[share_item("s1")]).is_err());
+        // A card that already got the pending share before an interruption is adopted
+        assert_eq!(
+            enrollment.plan(Some("c2"), &[share_item("s2")]).unwrap(),
+            EnrollStep::Adopt(1)
+        );
+        enrollment.record(1, "Reader", Some("c2".into()));
+        assert_eq!(
+            enrollment.plan(None, &[share_item("other")]).unwrap(),
+            EnrollStep::Write(2)
+        );
+        enrollment.record(2, "Reader", None);
+
+        let status = enrollment.status();
+        assert!(status.complete && status.active);
+        assert_eq!(status.next_index, None);
+        assert!(status.shares.iter().all(|share| share.enrolled));
+        assert!(enrollment.plan(Some("c3"), &[]).is_err());
+
+        assert!(
+            Enrollment::new(vec!["s1".into(), "s1".into()], labels[..2].to_vec(), None).is_err()
+        );
+        assert!(Enrollment::new(vec!["s1".into()], labels.clone(), None).is_err());
+        assert!(Enrollment::new(Vec::new(), Vec::new(), None).is_err());
+    }
+
+    #[test]
+    fn test_card_signature_detects_changes() {
+        let item = |label: &str, data: &str| CardItem {
+            item_type: "share".to_string(),
+            label: label.to_string(),
+            data: data.to_string(),
+            wrapped: false,
+            vault_id: None,
+        };
+        let key = [0x11u8; crypto::KEY_LENGTH];
+        let card_id = b"card-1";
+        let mut items = vec![item("Share 1", "AQID"), item("Notes", "aGk=")];
+        assert_eq!(
+            check_signature(&items, &key, card_id),
+            CardAuthenticity {
+                signed: false,
+                authentic: false
+            }
+        );
+
+        sign_items(&mut items, &key, card_id);
+        assert_eq!(items[0].item_type, ITEM_TYPE_SIGNATURE);
+        assert_eq!(
+            check_signature(&items, &key, card_id),
+            CardAuthenticity {
+                signed: true,
+                authentic: true
+            }
+        );
+        sign_items(&mut items, &key, card_id);
+        assert_eq!(items.len(), 3);
+        assert!(check_signature(&items, &key, card_id).authentic);
+
+        assert!(!check_signature(&items, &[0x12u8; crypto::KEY_LENGTH], card_id).authentic);
+        assert!(!check_signature(&items, &key, b"card-2").authentic);
+        let mut swapped = items.clone();
+        swapped[1].data = "BAUG".to_string();
+        assert!(!check_signature(&swapped, &key, card_id).authentic);
+        let mut relabelled = items.clone();
+        relabelled[2].label = "Share 2".to_string();
+        assert!(!check_signature(&relabelled, &key, card_id).authentic);
+        assert!(!check_signature(&items[..2], &key, card_id).authentic);
+        let doubled = [items.clone(), items[..1].to_vec()].concat();
+        assert!(!check_signature(&doubled, &key, card_id).authentic);
+    }
+
+    #[test]
+    fn test_recoverable_share_skips_other_items() {
+        let shares = shamir::shamir_split(STANDARD.encode([0x42u8; 32]), 2, 2, None).unwrap();
+        let item = |item_type: &str, data: &str, wrapped| CardItem {
+            item_type: item_type.to_string(),
+            label: "a".to_string(),
+            data: data.to_string(),
+            wrapped,
+            vault_id: None,
+        };
+        let items = vec![
+            item("vault", &shares[0], false),
+            item("share", "seQRets|c2FsdA==|ZGF0YQ==|00", false),
+            item("share", &shares[1], false),
+        ];
+        assert_eq!(recoverable_share(&items).unwrap(), shares[1]);
+        assert!(matches!(
+            recoverable_share(&items[..2]),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        let wrapped = [item("share", "c2VhbGVk", true)];
+        assert!(matches!(
+            recoverable_share(&wrapped),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrapped_flag_and_cplc_parsing() {
+        // Unwrapped items serialize exactly as before, and old card JSON still parses
+        let item = CardItem {
+            item_type: "share".to_string(),
+            label: "a".to_string(),
+            data: "x".to_string(),
+            wrapped: false,
+            vault_id: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&item).unwrap(),
+            r#"{"item_type":"share","label":"a","data":"x"}"#
+        );
+        let grouped = CardItem {
+            vault_id: normalize_vault_id(Some(" 3F9A-0c21-77de-b410 ")).unwrap(),
+            ..item.clone()
+        };
+        let json = serde_json::to_string(std::slice::from_ref(&grouped)).unwrap();
+        let stored = parse_card_items(json.as_bytes(), TYPE_VAULT, "").unwrap();
+        assert!(items_match_vault(&stored, "3f9a-0c21-77de-b410"));
+        assert!(!items_match_vault(&stored, "0000-0000-0000-0000"));
+        assert!(!items_match_vault(
+            std::slice::from_ref(&item),
+            "3f9a-0c21-77de-b410"
+        ));
+        assert_eq!(normalize_vault_id(Some("  ")).unwrap(), None);
+        assert!(normalize_vault_id(Some("has space")).is_err());
+        let wrapped = CardItem {
+            wrapped: true,
+            ..item
+        };
+        let json = serde_json::to_string(&[wrapped]).unwrap();
+        assert!(parse_card_items(json.as_bytes(), TYPE_VAULT, "").unwrap()[0].wrapped);
+        let old = parse_card_items(
+            br#"[{"item_type":"share","label":"a","data":"x"}]"#,
+            TYPE_VAULT,
+            "",
+        )
+        .unwrap();
+        assert!(!old[0].wrapped);
+
+        assert_eq!(
+            parse_cplc(&[0x9F, 0x7F, 0x03, 1, 2, 3, 0x90]),
+            Some(&[1u8, 2, 3][..])
+        );
+        assert_eq!(parse_cplc(&[0x9F, 0x7F, 0x04, 1, 2, 3]), None);
+        assert_eq!(parse_cplc(&[0x9F, 0x7F, 0x00]), None);
+    }
+
+    /// A card holding `data` behind a sequenced READ_DATA. Asked for chunk
+    /// P1 it answers with chunk `order[P1]`, tagged with its own index and
+    /// `generations[P1]`.
+    struct ChunkedReader {
+        data: Vec<u8>,
+        generations: Vec<u8>,
+        order: Vec<usize>,
+    }
+
+    impl ApduTransport for ChunkedReader {
+        fn exchange(&self, cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            let mut resp = match cmd[1] {
+                INS_GET_STATUS => vec![
+                    (self.data.len() >> 8) as u8,
+                    self.data.len() as u8,
+                    TYPE_VAULT,
+                    0,
+                    0,
+                    5,
+                    0,
+                ],
+                INS_READ_DATA => {
+                    assert_eq!(cmd[3], READ_SEQUENCED);
+                    let index = self.order[cmd[2] as usize];
+                    let start = index * CHUNK_SIZE;
+                    let end = (start + CHUNK_SIZE).min(self.data.len());
+                    [
+                        &[index as u8, self.generations[cmd[2] as usize]][..],
+                        &self.data[start..end],
+                    ]
+                    .concat()
+                }
+                _ => Vec::new(),
+            };
+            resp.extend_from_slice(&[0x90, 0x00]);
+            Ok(resp)
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_sequenced_read_rejects_torn_writes() {
+        let data: Vec<u8> = (0..600u32).map(|i| i as u8).collect();
+        let cancel = AtomicBool::new(false);
+        let transfer = Transfer {
+            on_chunk: Box::new(|_, _| {}),
+            cancel: &cancel,
+        };
+        let read = |generations: Vec<u8>| {
+            let reader = ChunkedReader {
+                data: data.clone(),
+                generations,
+                order: vec![0, 1, 2],
+            };
+            let link = CardLink::new(reader, Duration::from_secs(1), CardInterface::Contact);
+            link.capabilities
+                .store(CAP_SEQUENCED_READ, Ordering::SeqCst);
+            read_raw_card_data(&link, &transfer)
+        };
+
+        assert_eq!(read(vec![7, 7, 7]).unwrap().0, data);
+        // The last chunk still holds an older write
+        assert!(matches!(
+            read(vec![7, 7, 6]),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        assert!(matches!(
+            read(vec![8, 7, 7]),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        let mut generation = None;
+        assert_eq!(
+            check_chunk_sequence(&[0, 3, 0xAA], 0, &mut generation).unwrap(),
+            [0xAA]
+        );
+        assert_eq!(generation, Some(3));
+        assert!(check_chunk_sequence(&[1], 1, &mut generation).is_err());
+    }
+
+    #[test]
+    fn test_sequenced_read_rejects_chunks_out_of_order() {
+        let data: Vec<u8> = (0..600u32).map(|i| i as u8).collect();
+        let cancel = AtomicBool::new(false);
+        let transfer = Transfer {
+            on_chunk: Box::new(|_, _| {}),
+            cancel: &cancel,
+        };
+        let read = |order: Vec<usize>| {
+            let reader = ChunkedReader {
+                data: data.clone(),
+                generations: vec![7, 7, 7],
+                order,
+            };
+            let link = CardLink::new(reader, Duration::from_secs(1), CardInterface::Contact);
+            link.capabilities
+                .store(CAP_SEQUENCED_READ, Ordering::SeqCst);
+            read_raw_card_data(&link, &transfer)
+        };
+
+        assert_eq!(read(vec![0, 1, 2]).unwrap().0, data);
+        // Chunk 1 before chunk 0, a repeated chunk and a skipped one, all
+        // from the same write
+        for order in [vec![1, 0, 2], vec![0, 0, 2], vec![0, 2, 2]] {
+            let result = read(order.clone());
+            assert!(
+                matches!(result, Err(SeqretsError::CorruptData(_))),
+                "order {order:?}: {result:?}"
+            );
+        }
+    }
+
+    /// A card that answers 90 00 and records the INS byte of each command.
+    #[derive(Default)]
+    struct RecordingReader {
+        sent: Mutex<Vec<u8>>,
+    }
+
+    impl ApduTransport for RecordingReader {
+        fn exchange(&self, cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            self.sent.lock().unwrap().push(cmd[1]);
+            Ok(vec![0x90, 0x00])
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            Ok(())
+        }
+    }
+
+    /// A card that takes `delay` to answer `slow_ins` and answers 90 00 at once
+    /// otherwise.
+    struct SlowReader {
+        slow_ins: u8,
+        delay: Duration,
+    }
+
+    impl ApduTransport for SlowReader {
+        fn exchange(&self, cmd: &[u8]) -> Result<Vec<u8>, pcsc::Error> {
+            if cmd[1] == self.slow_ins {
+                thread::sleep(self.delay);
+            }
+            Ok(vec![0x90, 0x00])
+        }
+
+        fn reconnect(&self) -> Result<(), pcsc::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_waits_out_a_slow_erase() {
+        let cancel = AtomicBool::new(false);
+        let transfer = Transfer {
+            on_chunk: Box::new(|_, _| {}),
+            cancel: &cancel,
+        };
+        let timeout = Duration::from_millis(50);
+        let write = |slow_ins: u8| {
+            let link = CardLink::new(
+                SlowReader {
+                    slow_ins,
+                    delay: timeout * 4,
+                },
+                timeout,
+                CardInterface::Contact,
+            );
+            write_data_to_card(
+                &link,
+                &[0xA5; 300],
+                TYPE_VAULT,
+                "label",
+                CHUNK_SIZE,
+                false,
+                &transfer,
+            )
+        };
+
+        // The erase outlasts the link's timeout but not ERASE_TRANSMIT_TIMEOUT
+        assert!(write(INS_ERASE_DATA).is_ok());
+        // Other commands keep the link's timeout
+        assert!(matches!(
+            write(INS_STORE_DATA),
+            Err(SeqretsError::Timeout(_))
+        ));
+    }
+
+    #[test]
+    fn test_oversized_write_leaves_card_untouched() {
+        let cancel = AtomicBool::new(false);
+        let transfer = Transfer {
+            on_chunk: Box::new(|_, _| {}),
+            cancel: &cancel,
+        };
+        let write = |len: usize, chunk_size: usize| {
+            let link = CardLink::new(
+                RecordingReader::default(),
+                Duration::from_secs(1),
+                CardInterface::Contact,
+            );
+            let result = write_data_to_card(
+                &link,
+                &vec![0xA5; len],
+                TYPE_VAULT,
+                "label",
+                chunk_size,
+                false,
+                &transfer,
+            );
+            let sent = link.card.sent.lock().unwrap().clone();
+            (result, sent)
+        };
+
+        // A 64-byte chunk override caps a write well below 255 * CHUNK_SIZE
+        let (result, sent) = write(255 * 64 + 1, 64);
+        assert!(
+            matches!(result, Err(SeqretsError::StorageFull(_))),
+            "got: {result:?}"
+        );
+        assert!(!sent.contains(&INS_ERASE_DATA), "sent: {sent:02X?}");
+        let (result, sent) = write(255 * MIN_WRITE_CHUNK_SIZE + 1, MIN_WRITE_CHUNK_SIZE);
+        assert!(result.is_err() && sent.is_empty());
+
+        let (result, sent) = write(255 * 64, 64);
+        assert!(result.is_ok());
+        assert_eq!(sent[..3], [INS_ERASE_DATA, INS_SET_TYPE, INS_SET_LABEL]);
+        assert_eq!(
+            sent.iter().filter(|&&ins| ins == INS_STORE_DATA).count(),
+            255
+        );
+    }
+
+    #[test]
+    fn test_apdu_trace_masks_secrets() {
+        let pin = build_apdu(CLA, INS_VERIFY_PIN, 0x00, 0x00, b"12345678");
+        let trace = trace_apdu(&pin, Some(&[0x90, 0x00]));
+        assert_eq!(trace.command, "80 20 00 00 08 [8 bytes]");
+        assert_eq!(trace.response.as_deref(), Some("90 00"));
+
+        let read = build_apdu(CLA, INS_READ_DATA, 0x01, 0x00, &[]);
+        let trace = trace_apdu(&read, Some(b"seQRets|share\x90\x00"));
+        assert!(!trace.response.as_deref().unwrap().contains("73"));
+        assert_eq!(trace.response.as_deref(), Some("[13 bytes] 90 00"));
+
+        // SELECT and GET_VERSION are shown in full
+        let mut select = vec![0x00, 0xA4, 0x04, 0x00, SEQRETS_AID.len() as u8];
+        select.extend_from_slice(SEQRETS_AID);
+        select.push(0x00);
+        assert_eq!(
+            trace_apdu(&select, None).command,
+            "00 A4 04 00 09 F0 53 51 52 54 53 01 00 00 00"
+        );
+        assert_eq!(trace_apdu(&select, None).response, None);
+        let version = build_apdu(CLA, INS_GET_VERSION, 0x00, 0x00, &[]);
+        assert_eq!(
+            trace_apdu(&version, Some(&[0x01, 0x04, 0x90, 0x00]))
+                .response
+                .as_deref(),
+            Some("01 04 90 00")
+        );
+    }
+
+    #[test]
+    fn test_card_backup_roundtrip() {
+        let params = crypto::CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..Default::default()
+        };
+        let item = CardItem {
+            item_type: "share".into(),
+            label: "Share 1".into(),
+            data: "sealed".into(),
+            wrapped: true,
+            vault_id: None,
+        };
+        let backup = CardBackup {
+            version: CARD_BACKUP_VERSION,
+            card_id: Some(vec![0x04, 0xA1]),
+            items: vec![item],
+        };
+        let sealed = seal_card_backup(&backup, "backup password".into(), Some(params)).unwrap();
+
+        let opened = open_card_backup(&sealed, "backup password").unwrap();
+        assert_eq!(opened.card_id, Some(vec![0x04, 0xA1]));
+        assert_eq!(opened.items.len(), 1);
+        assert!(opened.items[0].wrapped && opened.items[0].label == "Share 1");
+        assert!(matches!(
+            open_card_backup(&sealed, "wrong"),
+            Err(SeqretsError::WrongPassword(_))
+        ));
+
+        // A vault blob under the same password is not a backup
+        let vault = crypto::crypto_encrypt_blob(
+            "{}".into(),
+            "backup password".into(),
+            None,
+            Some(params),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(open_card_backup(&vault, "backup password").is_err());
+
+        let newer = CardBackup {
+            version: CARD_BACKUP_VERSION + 1,
+            ..backup
+        };
+        let sealed = seal_card_backup(&newer, "backup password".into(), Some(params)).unwrap();
+        assert!(matches!(
+            open_card_backup(&sealed, "backup password"),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
 }
@@ -0,0 +1,594 @@
+//! Chunked streaming encryption for large vault files.
+//!
+//! `crypto_encrypt_blob` buffers the whole plaintext, which is fine for a
+//! few KB of JSON but not for vaults carrying attachments. These commands
+//! read the input in fixed 1 MiB segments, gzip (or store, when gzip
+//! wouldn't help) and seal each one with
+//! XChaCha20-Poly1305 under the STREAM construction (nonce = 19-byte random
+//! prefix || 32-bit BE counter || last-segment flag), and write framed
+//! output, so peak memory stays bounded regardless of file size.
+//!
+//! File format:
+//!   - Header : "SQRS" || version (1) || m, t, p (u32 BE each)   — 17 bytes
+//!   - Salt   : 16 random bytes
+//!   - Nonce  : 19-byte STREAM nonce prefix
+//!   - Frames : last flag (u8) || ciphertext length (u32 BE) || ciphertext
+//!
+//! Every frame authenticates `header || salt` as associated data. The STREAM
+//! counter rejects reordered or dropped segments, and the last-segment flag
+//! in the nonce rejects truncation.
+//!
+//! A `crypto-stream-progress` event (`{ processed, total }` in input bytes)
+//! is emitted after every segment.
+
+use chacha20poly1305::{
+    aead::{
+        stream::{DecryptorBE32, EncryptorBE32},
+        Payload,
+    },
+    KeyInit, XChaCha20Poly1305,
+};
+use flate2::read::GzDecoder;
+use rand::RngCore;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+use zeroize::Zeroizing;
+
+use crate::crypto::{
+    derive_key, gzip_compress, secret_keyfiles, stored_payload, weak_params, Argon2Variant,
+    CryptoParams, ParamsWarning, KEY_LENGTH, SALT_LENGTH,
+};
+use crate::error::SeqretsError;
+use crate::vault::write_atomic_with;
+
+const STREAM_MAGIC: &[u8; 4] = b"SQRS";
+pub(crate) const STREAM_VERSION: u8 = 1;
+const STREAM_HEADER_LENGTH: usize = 17;
+const NONCE_PREFIX_LENGTH: usize = 19; // 24-byte XNonce minus 5 bytes of STREAM counter/flag
+const CHUNK_SIZE: usize = 1024 * 1024; // plaintext bytes per segment
+
+/// Upper bound on a single frame's ciphertext. Gzip can expand incompressible
+/// input slightly; anything beyond this is corrupt and is refused before
+/// allocating.
+const MAX_FRAME_LENGTH: usize = CHUNK_SIZE + 64 * 1024;
+
+const FRAME_MORE: u8 = 0;
+const FRAME_LAST: u8 = 1;
+
+const PROGRESS_EVENT: &str = "crypto-stream-progress";
+
+/// Payload of the `crypto-stream-progress` event.
+#[derive(Serialize, Clone)]
+pub struct StreamProgress {
+    pub processed: u64, // input bytes consumed so far
+    pub total: u64,     // input file size
+}
+
+fn encode_header(params: &CryptoParams) -> [u8; STREAM_HEADER_LENGTH] {
+    let mut out = [0u8; STREAM_HEADER_LENGTH];
+    out[..4].copy_from_slice(STREAM_MAGIC);
+    out[4] = STREAM_VERSION;
+    out[5..9].copy_from_slice(&params.m_cost.to_be_bytes());
+    out[9..13].copy_from_slice(&params.t_cost.to_be_bytes());
+    out[13..17].copy_from_slice(&params.p_cost.to_be_bytes());
+    out
+}
+
+fn decode_header(bytes: &[u8; STREAM_HEADER_LENGTH]) -> Result<CryptoParams, SeqretsError> {
+    if &bytes[..4] != STREAM_MAGIC {
+        return Err(SeqretsError::CorruptData(
+            "Not a seQRets encrypted stream".to_string(),
+        ));
+    }
+    if bytes[4] != STREAM_VERSION {
+        return Err(SeqretsError::CorruptData(format!(
+            "Unsupported encrypted stream version {}. Please update seQRets.",
+            bytes[4]
+        )));
+    }
+    let u32_at =
+        |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+    Ok(CryptoParams {
+        m_cost: u32_at(5),
+        t_cost: u32_at(9),
+        p_cost: u32_at(13),
+        argon2_variant: Argon2Variant::Argon2id,
+    })
+}
+
+/// The stream header has no variant byte, so streams are always Argon2id.
+fn stream_params(params: Option<CryptoParams>) -> Result<CryptoParams, SeqretsError> {
+    let params = params.unwrap_or_default();
+    if params.argon2_variant != Argon2Variant::Argon2id {
+        return Err(SeqretsError::InvalidInput(
+            "Encrypted streams only support Argon2id.".to_string(),
+        ));
+    }
+    Ok(params)
+}
+
+/// Reads until `buf` is full or EOF; returns the number of bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize, SeqretsError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(format!("Read error: {e}").into()),
+        }
+    }
+    Ok(filled)
+}
+
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), SeqretsError> {
+    match read_full(reader, buf)? {
+        n if n == buf.len() => Ok(()),
+        _ => Err(SeqretsError::CorruptData(
+            "Encrypted stream is truncated".to_string(),
+        )),
+    }
+}
+
+/// Gzip-decompresses one segment (or unwraps a stored one), refusing output
+/// larger than a segment.
+fn gunzip_segment(data: &[u8]) -> Result<Zeroizing<Vec<u8>>, SeqretsError> {
+    let mut out = Zeroizing::new(Vec::new());
+    if let Some(raw) = stored_payload(data) {
+        if raw.len() > CHUNK_SIZE {
+            return Err(SeqretsError::CorruptData(
+                "Encrypted stream segment exceeds the maximum size".to_string(),
+            ));
+        }
+        out.extend_from_slice(raw);
+        return Ok(out);
+    }
+    GzDecoder::new(data)
+        .take(CHUNK_SIZE as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| SeqretsError::CorruptData(format!("Gzip decompress error: {e}")))?;
+    if out.len() > CHUNK_SIZE {
+        return Err(SeqretsError::CorruptData(
+            "Encrypted stream segment exceeds the maximum size".to_string(),
+        ));
+    }
+    Ok(out)
+}
+
+fn encrypt_stream(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    key: &[u8; KEY_LENGTH],
+    aad_prefix: &[u8],
+    nonce_prefix: &[u8; NONCE_PREFIX_LENGTH],
+    total: u64,
+    progress: &mut dyn FnMut(StreamProgress),
+) -> Result<(), SeqretsError> {
+    let write = |output: &mut dyn Write, bytes: &[u8]| {
+        output
+            .write_all(bytes)
+            .map_err(|e| SeqretsError::from(format!("Write error: {e}")))
+    };
+    write(output, aad_prefix)?;
+    write(output, nonce_prefix)?;
+
+    let aead = XChaCha20Poly1305::new_from_slice(key)
+        .map_err(|_| "Cipher init error (invalid key length)".to_string())?;
+    let mut encryptor = Some(EncryptorBE32::from_aead(aead, nonce_prefix.into()));
+
+    let mut current = Zeroizing::new(vec![0u8; CHUNK_SIZE]);
+    let mut next = Zeroizing::new(vec![0u8; CHUNK_SIZE]);
+    let mut current_len = read_full(input, &mut current)?;
+    let mut processed = 0u64;
+
+    while let Some(mut current_encryptor) = encryptor.take() {
+        // Read one segment ahead so the final segment can be flagged as last.
+        let next_len = if current_len == CHUNK_SIZE {
+            read_full(input, &mut next)?
+        } else {
+            0
+        };
+        let is_last = next_len == 0;
+
+        let compressed = Zeroizing::new(gzip_compress(&current[..current_len], None)?);
+        let payload = Payload {
+            msg: compressed.as_slice(),
+            aad: aad_prefix,
+        };
+        let (flag, ciphertext) = if is_last {
+            (FRAME_LAST, current_encryptor.encrypt_last(payload))
+        } else {
+            let result = current_encryptor.encrypt_next(payload);
+            encryptor = Some(current_encryptor);
+            (FRAME_MORE, result)
+        };
+        let ciphertext = ciphertext.map_err(|_| "Encryption error".to_string())?;
+
+        write(output, &[flag])?;
+        write(output, &(ciphertext.len() as u32).to_be_bytes())?;
+        write(output, &ciphertext)?;
+
+        processed += current_len as u64;
+        progress(StreamProgress { processed, total });
+
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+
+    output
+        .flush()
+        .map_err(|e| format!("Write error: {e}").into())
+}
+
+fn decrypt_stream(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    password: &str,
+    keyfiles_b64: &[Zeroizing<String>],
+    total: u64,
+    progress: &mut dyn FnMut(StreamProgress),
+) -> Result<(), SeqretsError> {
+    let mut aad_prefix = [0u8; STREAM_HEADER_LENGTH + SALT_LENGTH];
+    read_exact(input, &mut aad_prefix)?;
+    let (header, salt) = aad_prefix.split_at(STREAM_HEADER_LENGTH);
+    let params = decode_header(header.try_into().expect("header slice length"))?;
+    let mut nonce_prefix = [0u8; NONCE_PREFIX_LENGTH];
+    read_exact(input, &mut nonce_prefix)?;
+
+    let key = derive_key(password, salt, keyfiles_b64, &params)?;
+    let aead = XChaCha20Poly1305::new_from_slice(key.as_slice())
+        .map_err(|_| "Cipher init error (invalid key length)".to_string())?;
+    let mut decryptor = Some(DecryptorBE32::from_aead(aead, (&nonce_prefix).into()));
+
+    let mut processed = (aad_prefix.len() + nonce_prefix.len()) as u64;
+    let mut frame_header = [0u8; 5];
+    let mut ciphertext = Vec::with_capacity(MAX_FRAME_LENGTH);
+
+    while let Some(mut current_decryptor) = decryptor.take() {
+        read_exact(input, &mut frame_header)?;
+        let flag = frame_header[0];
+        let length = u32::from_be_bytes([
+            frame_header[1],
+            frame_header[2],
+            frame_header[3],
+            frame_header[4],
+        ]) as usize;
+        if length > MAX_FRAME_LENGTH {
+            return Err(SeqretsError::CorruptData(
+                "Encrypted stream segment exceeds the maximum size".to_string(),
+            ));
+        }
+        ciphertext.resize(length, 0);
+        read_exact(input, &mut ciphertext)?;
+
+        let payload = Payload {
+            msg: ciphertext.as_slice(),
+            aad: &aad_prefix,
+        };
+        let compressed = match flag {
+            FRAME_MORE => {
+                let result = current_decryptor.decrypt_next(payload);
+                decryptor = Some(current_decryptor);
+                result
+            }
+            FRAME_LAST => current_decryptor.decrypt_last(payload),
+            _ => {
+                return Err(SeqretsError::CorruptData(
+                    "Encrypted stream has an invalid segment flag".to_string(),
+                ))
+            }
+        }
+        .map_err(|_| {
+            SeqretsError::WrongPassword(
+                "Decryption failed — wrong password, keyfile, or corrupted data".to_string(),
+            )
+        })?;
+        let compressed = Zeroizing::new(compressed);
+
+        let plaintext = gunzip_segment(&compressed)?;
+        output
+            .write_all(&plaintext)
+            .map_err(|e| format!("Write error: {e}"))?;
+
+        processed += (frame_header.len() + length) as u64;
+        progress(StreamProgress { processed, total });
+    }
+
+    if read_full(input, &mut [0u8; 1])? != 0 {
+        return Err(SeqretsError::CorruptData(
+            "Encrypted stream has trailing data after the final segment".to_string(),
+        ));
+    }
+    output
+        .flush()
+        .map_err(|e| format!("Write error: {e}").into())
+}
+
+/// Opens `input_path` and runs `body` over it and a temp file beside
+/// `output_path`, which replaces `output_path` only once `body` succeeds
+/// (see `write_atomic_with`). On failure an existing output file is left
+/// as it was. An output path naming the input file is refused up front.
+fn with_files(
+    input_path: &str,
+    output_path: &str,
+    body: impl FnOnce(&mut BufReader<File>, &mut BufWriter<&mut File>, u64) -> Result<(), SeqretsError>,
+) -> Result<(), SeqretsError> {
+    let input = File::open(input_path).map_err(|e| format!("Cannot open {input_path}: {e}"))?;
+    let total = input
+        .metadata()
+        .map_err(|e| format!("Cannot stat {input_path}: {e}"))?
+        .len();
+    if same_file(input_path, output_path) {
+        return Err(SeqretsError::InvalidInput(
+            "The output file can't be the input file; choose a different destination.".to_string(),
+        ));
+    }
+
+    let mut input = BufReader::new(input);
+    write_atomic_with(Path::new(output_path), |output| {
+        body(&mut input, &mut BufWriter::new(output), total)
+    })
+}
+
+/// True when both paths resolve to the same existing file.
+fn same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Encrypts the file at `input_path` into `output_path` segment by segment.
+/// `params` defaults to the standard Argon2 cost factors when omitted and
+/// must use Argon2id, since the stream header doesn't record a variant. Emits
+/// `crypto-stream-progress` after each segment. Returns a `ParamsWarning`
+/// when `params` are below the recommended floor.
+#[tauri::command]
+pub async fn crypto_encrypt_stream(
+    app: AppHandle,
+    input_path: String,
+    output_path: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    params: Option<CryptoParams>,
+) -> Result<Option<ParamsWarning>, SeqretsError> {
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let params = stream_params(params)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut salt = [0u8; SALT_LENGTH];
+        rand::rng().fill_bytes(&mut salt);
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LENGTH];
+        rand::rng().fill_bytes(&mut nonce_prefix);
+
+        let key = derive_key(password.as_str(), &salt, &keyfiles_b64, &params)?;
+        drop(password);
+        drop(keyfiles_b64);
+        let aad_prefix = [&encode_header(&params)[..], &salt].concat();
+
+        with_files(&input_path, &output_path, |input, output, total| {
+            encrypt_stream(
+                input,
+                output,
+                &key,
+                &aad_prefix,
+                &nonce_prefix,
+                total,
+                &mut |p| {
+                    let _ = app.emit(PROGRESS_EVENT, p);
+                },
+            )
+        })
+        .map(|_| weak_params(&params))
+    })
+    .await
+    .map_err(|e| format!("Stream encryption task failed: {e}"))?
+}
+
+/// Decrypts a file written by `crypto_encrypt_stream` into `output_path`.
+/// The Argon2 parameters are read from the stream header. Emits
+/// `crypto-stream-progress` after each segment; on any failure, such as a
+/// wrong password, `output_path` is left untouched.
+#[tauri::command]
+pub async fn crypto_decrypt_stream(
+    app: AppHandle,
+    input_path: String,
+    output_path: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+) -> Result<(), SeqretsError> {
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    tauri::async_runtime::spawn_blocking(move || {
+        with_files(&input_path, &output_path, |input, output, total| {
+            decrypt_stream(
+                input,
+                output,
+                password.as_str(),
+                &keyfiles_b64,
+                total,
+                &mut |p| {
+                    let _ = app.emit(PROGRESS_EVENT, p);
+                },
+            )
+        })
+    })
+    .await
+    .map_err(|e| format!("Stream decryption task failed: {e}"))?
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap KDF so the tests stay fast.
+    const TEST_PARAMS: CryptoParams = CryptoParams {
+        m_cost: 8,
+        t_cost: 1,
+        p_cost: 1,
+        argon2_variant: Argon2Variant::Argon2id,
+    };
+
+    fn seal(plaintext: &[u8], password: &str) -> (Vec<u8>, usize) {
+        let salt = [3u8; SALT_LENGTH];
+        let nonce_prefix = [5u8; NONCE_PREFIX_LENGTH];
+        let key = derive_key(password, &salt, &[], &TEST_PARAMS).unwrap();
+        let aad_prefix = [&encode_header(&TEST_PARAMS)[..], &salt].concat();
+
+        let mut out = Vec::new();
+        let mut events = 0;
+        encrypt_stream(
+            &mut &plaintext[..],
+            &mut out,
+            &key,
+            &aad_prefix,
+            &nonce_prefix,
+            plaintext.len() as u64,
+            &mut |_| events += 1,
+        )
+        .unwrap();
+        (out, events)
+    }
+
+    fn open(sealed: &[u8], password: &str) -> Result<Vec<u8>, SeqretsError> {
+        let mut out = Vec::new();
+        decrypt_stream(
+            &mut &sealed[..],
+            &mut out,
+            password,
+            &[],
+            sealed.len() as u64,
+            &mut |_| {},
+        )?;
+        Ok(out)
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_stream_roundtrip_multi_segment() {
+        let plaintext = sample(CHUNK_SIZE * 2 + 1234);
+        let (sealed, events) = seal(&plaintext, "pw");
+        assert_eq!(events, 3, "one progress event per segment");
+        assert_eq!(open(&sealed, "pw").unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_stream_roundtrip_exact_and_empty() {
+        for len in [0, CHUNK_SIZE] {
+            let plaintext = sample(len);
+            let (sealed, events) = seal(&plaintext, "pw");
+            assert_eq!(events, 1);
+            assert_eq!(open(&sealed, "pw").unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_stream_wrong_password_fails() {
+        let (sealed, _) = seal(&sample(100), "pw");
+        assert!(open(&sealed, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_stream_truncation_detected() {
+        let (sealed, _) = seal(&sample(CHUNK_SIZE + 10), "pw");
+        // Drop the final frame entirely: the remaining frame isn't flagged last.
+        let first_frame_end = {
+            let start = STREAM_HEADER_LENGTH + SALT_LENGTH + NONCE_PREFIX_LENGTH;
+            let len = u32::from_be_bytes(sealed[start + 1..start + 5].try_into().unwrap()) as usize;
+            start + 5 + len
+        };
+        let err = open(&sealed[..first_frame_end], "pw").unwrap_err();
+        assert!(matches!(err, SeqretsError::CorruptData(_)), "got: {err:?}");
+        assert!(err.to_string().contains("truncated"), "got: {err}");
+    }
+
+    #[test]
+    fn test_stream_forged_last_flag_fails() {
+        let (mut sealed, _) = seal(&sample(CHUNK_SIZE + 10), "pw");
+        let start = STREAM_HEADER_LENGTH + SALT_LENGTH + NONCE_PREFIX_LENGTH;
+        sealed[start] = FRAME_LAST;
+        assert!(open(&sealed, "pw").is_err());
+    }
+
+    #[test]
+    fn test_stream_header_tamper_fails() {
+        let (mut sealed, _) = seal(&sample(100), "pw");
+        sealed[STREAM_HEADER_LENGTH] ^= 0x01; // first salt byte
+        assert!(open(&sealed, "pw").is_err());
+    }
+
+    #[test]
+    fn test_stream_incompressible_segments_roundtrip() {
+        let mut plaintext = vec![0u8; CHUNK_SIZE + 500];
+        rand::rng().fill_bytes(&mut plaintext);
+        let (sealed, _) = seal(&plaintext, "pw");
+        // Stored segments cost one flag byte plus the frame overhead each.
+        assert!(sealed.len() < plaintext.len() + 200);
+        assert_eq!(open(&sealed, "pw").unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_failed_decrypt_keeps_existing_output_and_input() {
+        let dir = std::env::temp_dir().join(format!("seqrets-stream-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (sealed, _) = seal(&sample(100), "pw");
+        let input = dir.join("vault.sqrs");
+        let output = dir.join("vault.json");
+        fs::write(&input, &sealed).unwrap();
+        fs::write(&output, b"keep me").unwrap();
+        let (input_str, output_str) = (input.to_str().unwrap(), output.to_str().unwrap());
+        let run = |output: &str, password: &str| {
+            with_files(input_str, output, |input, output, total| {
+                decrypt_stream(input, output, password, &[], total, &mut |_| {})
+            })
+        };
+
+        assert!(matches!(
+            run(output_str, "wrong"),
+            Err(SeqretsError::WrongPassword(_))
+        ));
+        assert_eq!(fs::read(&output).unwrap(), b"keep me");
+
+        let err = run(input_str, "pw");
+        assert!(
+            matches!(err, Err(SeqretsError::InvalidInput(_))),
+            "got: {err:?}"
+        );
+        let aliased = dir.join(".").join("vault.sqrs");
+        assert!(matches!(
+            run(aliased.to_str().unwrap(), "pw"),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert_eq!(fs::read(&input).unwrap(), sealed);
+
+        run(output_str, "pw").unwrap();
+        assert_eq!(fs::read(&output).unwrap(), sample(100));
+        // Only the input and output are left: no temp files.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stream_requires_argon2id() {
+        assert_eq!(stream_params(None).unwrap(), CryptoParams::default());
+        let argon2i = CryptoParams {
+            argon2_variant: Argon2Variant::Argon2i,
+            ..TEST_PARAMS
+        };
+        assert!(matches!(
+            stream_params(Some(argon2i)),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+}
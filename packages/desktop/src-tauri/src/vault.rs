@@ -0,0 +1,1343 @@
+//! Vault backup self-check for seQRets desktop.
+//!
+//! An exported `.seqrets` vault is the JSON written by `getVaultJsonString`
+//! in qr-code-display.tsx, encrypted with `crypto_encrypt_blob`. A "test my
+//! backup" check needs to prove the password, keyfiles and file all work
+//! without putting any of the contents on screen, so `verify_vault` reports
+//! only what it could do with the blob and never returns the plaintext.
+//!
+//! The secret itself travels as a small JSON payload (`secret`, `label`,
+//! `isMnemonic`, built by `createShares`). `VaultEnvelope` gives the backend
+//! a typed view of it while carrying any fields it doesn't know about
+//! through untouched, so older builds never strip data a newer one wrote.
+//! Payloads from older builds are brought up to date one schema version at a
+//! time by the steps in `MIGRATIONS`.
+//!
+//! Encrypted vault files are saved with `save_vault_atomic`, which writes a
+//! sibling temp file and renames it over the target so a crash mid-write
+//! leaves the old file or the new one, never a truncated mix. The file being
+//! replaced is first kept as `<name>.bak.1`, shifting older backups up to
+//! `<name>.bak.K`.
+//!
+//! `vault_fingerprint` names an encrypted vault by its salt and ciphertext,
+//! so backups can be told apart, and duplicates spotted, without a password.
+//! Copies of one file share a fingerprint; encrypting the same secret again
+//! draws a new salt and nonce and so gives a different one.
+//!
+//! A sectioned vault (`SectionedVaultFile`) holds several independently
+//! encrypted sections, each with its own salt, password and keyfiles, e.g. a
+//! section shared with family next to a personal one. Each section is an
+//! ordinary `crypto_encrypt_blob` blob whose associated data names the
+//! section id, so a section can't be passed off as another by swapping the
+//! ciphertexts around. `decrypt_vault_section` opens one section; the others
+//! stay opaque.
+//!
+//! `diff_vaults` compares two vaults entry by entry (the sections of a
+//! sectioned vault, or the single secret of a plain one) by digest, so it
+//! can say which entries were added, removed or changed without returning
+//! anything they hold.
+
+use rand::RngCore;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::crypto::{
+    self, decompress_json, decrypt_blob, secret_keyfiles, CancelToken, CryptoParams,
+};
+use crate::encoding;
+use crate::error::SeqretsError;
+use crate::redact::Secret;
+
+/// The fields `verify_vault` checks for. The shares are parsed with
+/// `IgnoredAny` so no copy of them is made.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultSchema {
+    version: u32,
+    shares: Vec<IgnoredAny>,
+    required_shares: u32,
+    total_shares: u32,
+}
+
+/// Returned by `verify_vault`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct VaultReport {
+    /// The password and keyfiles opened the blob.
+    pub decryptable: bool,
+    /// The decrypted payload decompressed, matched the vault schema and has
+    /// a usable threshold (1 ≤ requiredShares ≤ totalShares).
+    pub parseable: bool,
+    /// Number of shares in the vault.
+    pub entry_count: Option<usize>,
+    /// The vault's `version` field.
+    pub schema_version: Option<u32>,
+    /// Why the check stopped, when it did.
+    pub error: Option<SeqretsError>,
+}
+
+impl VaultReport {
+    fn failed(decryptable: bool, error: SeqretsError) -> VaultReport {
+        VaultReport {
+            decryptable,
+            parseable: false,
+            entry_count: None,
+            schema_version: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// The decrypted secret payload. Unknown fields are kept in `extra` and
+/// written back unchanged on serialization.
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultEnvelope {
+    /// Absent in payloads written before the field existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    /// Plain text, or base64 of the concatenated BIP39 entropy when
+    /// `is_mnemonic` is set.
+    pub secret: String,
+    #[serde(default)]
+    pub label: String,
+    pub is_mnemonic: bool,
+    /// Word count of each phrase packed into `secret`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic_lengths: Option<Vec<usize>>,
+    #[serde(flatten)]
+    #[zeroize(skip)]
+    pub extra: Map<String, Value>,
+}
+
+// Hand-written so the secret never reaches a log or panic message.
+impl fmt::Debug for VaultEnvelope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VaultEnvelope")
+            .field("schema_version", &self.schema_version)
+            .field("secret", &Secret(&self.secret))
+            .field("label", &self.label)
+            .field("is_mnemonic", &self.is_mnemonic)
+            .field("mnemonic_lengths", &self.mnemonic_lengths)
+            .field("extra", &Secret(&self.extra))
+            .finish()
+    }
+}
+
+/// The position of a serde_json error, without its message: the message can
+/// quote the plaintext being parsed.
+fn json_position(e: &serde_json::Error) -> String {
+    format!("line {}, column {}", e.line(), e.column())
+}
+
+/// Parses a decrypted payload into a `VaultEnvelope`.
+pub(crate) fn parse_envelope(json: &str) -> Result<VaultEnvelope, SeqretsError> {
+    serde_json::from_str(json).map_err(|e| {
+        SeqretsError::CorruptData(format!(
+            "Secret payload is not valid ({}).",
+            json_position(&e)
+        ))
+    })
+}
+
+/// Schema version written by this build.
+pub(crate) const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// One step from schema version `from` to `from + 1`. Steps only reshape
+/// fields; `migrate_payload` stamps the new `schemaVersion` itself.
+type MigrationStep = fn(&mut Map<String, Value>) -> Result<(), SeqretsError>;
+
+/// Ordered migration steps keyed by the version they upgrade from.
+const MIGRATIONS: &[(u32, MigrationStep)] = &[(0, migrate_v0_to_v1)];
+
+/// Version 0 payloads predate `schemaVersion`; `label` becomes explicit so
+/// every versioned payload carries it.
+fn migrate_v0_to_v1(payload: &mut Map<String, Value>) -> Result<(), SeqretsError> {
+    if !payload.contains_key("secret") {
+        return Err(SeqretsError::CorruptData(
+            "Version 0 payload has no secret field.".to_string(),
+        ));
+    }
+    payload
+        .entry("label")
+        .or_insert_with(|| Value::String(String::new()));
+    Ok(())
+}
+
+/// The payload's `schemaVersion`, or 0 when absent.
+fn payload_version(payload: &Map<String, Value>) -> Result<u32, SeqretsError> {
+    match payload.get("schemaVersion") {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| SeqretsError::CorruptData(format!("Invalid schemaVersion: {v}"))),
+    }
+}
+
+/// Applies the migration steps from `from_version` up to `to_version`.
+/// A payload already at `to_version` is left as it is, so running a
+/// migration twice is harmless.
+pub(crate) fn migrate_payload(
+    payload: &mut Map<String, Value>,
+    from_version: u32,
+    to_version: u32,
+) -> Result<(), SeqretsError> {
+    let mut version = payload_version(payload)?;
+    if version == to_version {
+        return Ok(());
+    }
+    if version != from_version {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Payload is at schema version {version}, not {from_version}."
+        )));
+    }
+    if to_version < from_version {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Cannot migrate a payload down from schema version {from_version} to {to_version}."
+        )));
+    }
+    while version < to_version {
+        let step = MIGRATIONS
+            .iter()
+            .find(|(from, _)| *from == version)
+            .map(|(_, step)| step)
+            .ok_or_else(|| {
+                SeqretsError::InvalidInput(format!(
+                    "No migration from schema version {version} to {}.",
+                    version + 1
+                ))
+            })?;
+        step(payload)?;
+        version += 1;
+        payload.insert("schemaVersion".to_string(), Value::from(version));
+    }
+    Ok(())
+}
+
+/// The `.seqrets` file an encrypted vault is saved as (`EncryptedVaultFile`
+/// in packages/crypto).
+#[derive(Serialize)]
+struct EncryptedVaultFile<'a> {
+    version: u32,
+    encrypted: bool,
+    salt: &'a str,
+    data: &'a str,
+}
+
+const ENCRYPTED_VAULT_FILE_VERSION: u32 = 2;
+
+/// `version` of a `SectionedVaultFile`.
+const SECTIONED_VAULT_FILE_VERSION: u32 = 3;
+
+/// Longest accepted section id, in bytes.
+const MAX_SECTION_ID_LENGTH: usize = 64;
+
+/// A `.seqrets` file made of independently encrypted sections.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SectionedVaultFile {
+    pub version: u32,
+    pub encrypted: bool,
+    pub sections: Vec<VaultSection>,
+}
+
+/// One section of a `SectionedVaultFile`: a `crypto_encrypt_blob` result
+/// sealed under `section_label(id)`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct VaultSection {
+    pub id: String,
+    /// Shown in the UI before the section is unlocked, so it is stored in
+    /// the clear; it is not authenticated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub salt: String,
+    pub data: String,
+}
+
+/// The associated-data label a section is encrypted under.
+fn section_label(id: &str) -> String {
+    format!("seqrets-vault-section:{id}")
+}
+
+fn check_section_id(id: &str) -> Result<(), SeqretsError> {
+    if id.is_empty() || id.len() > MAX_SECTION_ID_LENGTH || id.chars().any(char::is_control) {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Section ids must be 1–{MAX_SECTION_ID_LENGTH} bytes without control characters."
+        )));
+    }
+    Ok(())
+}
+
+/// Parses and checks a sectioned vault: a known version and unique, valid
+/// section ids.
+pub(crate) fn parse_sectioned_vault(vault_json: &str) -> Result<SectionedVaultFile, SeqretsError> {
+    let vault: SectionedVaultFile = serde_json::from_str(vault_json).map_err(|e| {
+        SeqretsError::CorruptData(format!("Not a sectioned vault ({}).", json_position(&e)))
+    })?;
+    if vault.version != SECTIONED_VAULT_FILE_VERSION || !vault.encrypted {
+        return Err(SeqretsError::CorruptData(format!(
+            "Unsupported sectioned vault version {}.",
+            vault.version
+        )));
+    }
+    for (i, section) in vault.sections.iter().enumerate() {
+        check_section_id(&section.id).map_err(|e| SeqretsError::CorruptData(e.to_string()))?;
+        if vault.sections[..i]
+            .iter()
+            .any(|other| other.id == section.id)
+        {
+            return Err(SeqretsError::CorruptData(format!(
+                "Section '{}' appears twice.",
+                section.id
+            )));
+        }
+    }
+    Ok(vault)
+}
+
+/// How an entry differs between the two vaults given to `diff_vaults`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryChange {
+    Added,
+    Removed,
+    /// Present in both, with a different name or content.
+    Modified,
+    Unchanged,
+}
+
+/// One entry of a `VaultDiff`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct EntryDiff {
+    /// Section id, or the label of a plain vault's single secret.
+    pub id: String,
+    /// Display name: the section name (or id), as in vault B unless removed.
+    pub label: String,
+    pub change: EntryChange,
+    /// The content differs: the section's salt or ciphertext, or the plain
+    /// vault's shares, threshold or instructions. False for a rename alone;
+    /// always true for added and removed entries.
+    pub changed: bool,
+}
+
+/// Returned by `diff_vaults`. `added`, `removed` and `modified` list entry
+/// labels; `entries` has every entry of either vault, in A's order with
+/// B's new entries last.
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct VaultDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    pub entries: Vec<EntryDiff>,
+    /// Nothing was added, removed or modified.
+    pub identical: bool,
+}
+
+/// An entry as `diff_vaults` compares it: the digest stands in for the content.
+struct DiffEntry {
+    id: String,
+    label: String,
+    digest: [u8; 32],
+}
+
+/// SHA-256 over length-prefixed `parts`.
+fn entry_digest(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// The entries of a vault file: the sections of a sectioned vault, or the
+/// one secret of a plain (unencrypted) vault export, keyed by its label.
+/// Returns whether the vault is sectioned, so two kinds aren't compared.
+fn vault_entries(vault_json: &str) -> Result<(bool, Vec<DiffEntry>), SeqretsError> {
+    let mut value: Value = serde_json::from_str(vault_json).map_err(|e| {
+        SeqretsError::CorruptData(format!("Vault is not valid JSON ({}).", json_position(&e)))
+    })?;
+    let version = value.get("version").and_then(Value::as_u64);
+
+    let entries = if version == Some(SECTIONED_VAULT_FILE_VERSION as u64) {
+        let vault = parse_sectioned_vault(vault_json)?;
+        let entries = vault
+            .sections
+            .into_iter()
+            .map(|section| DiffEntry {
+                digest: entry_digest(&[section.salt.as_bytes(), section.data.as_bytes()]),
+                label: section.name.unwrap_or_else(|| section.id.clone()),
+                id: section.id,
+            })
+            .collect();
+        Ok((true, entries))
+    } else if value.get("encrypted").and_then(Value::as_bool) == Some(true) {
+        Err(SeqretsError::InvalidInput(
+            "An encrypted vault can only be compared after decrypting it.".to_string(),
+        ))
+    } else if let Some(shares) = value.get("shares").filter(|shares| shares.is_array()) {
+        let field =
+            |name: &str| Zeroizing::new(serde_json::to_vec(&value[name]).unwrap_or_default());
+        let shares = Zeroizing::new(serde_json::to_vec(shares).unwrap_or_default());
+        let digest = entry_digest(&[
+            &shares,
+            &field("requiredShares"),
+            &field("totalShares"),
+            &field("encryptedInstructions"),
+        ]);
+        let label = value
+            .get("label")
+            .and_then(Value::as_str)
+            .unwrap_or("Untitled")
+            .to_string();
+        Ok((
+            false,
+            vec![DiffEntry {
+                id: label.clone(),
+                label,
+                digest,
+            }],
+        ))
+    } else {
+        Err(SeqretsError::CorruptData(
+            "Not a seQRets vault: no shares or sections.".to_string(),
+        ))
+    };
+    scrub(&mut value);
+    entries
+}
+
+/// Matches `b`'s entries to `a`'s by id and classifies each.
+fn diff_entries(a: &[DiffEntry], b: &[DiffEntry]) -> VaultDiff {
+    let mut diff = VaultDiff::default();
+    for old in a {
+        let entry = match b.iter().find(|new| new.id == old.id) {
+            None => EntryDiff {
+                id: old.id.clone(),
+                label: old.label.clone(),
+                change: EntryChange::Removed,
+                changed: true,
+            },
+            Some(new) => {
+                let changed = !crypto::ct_eq(&old.digest, &new.digest);
+                let change = if changed || old.label != new.label {
+                    EntryChange::Modified
+                } else {
+                    EntryChange::Unchanged
+                };
+                EntryDiff {
+                    id: new.id.clone(),
+                    label: new.label.clone(),
+                    change,
+                    changed,
+                }
+            }
+        };
+        diff.entries.push(entry);
+    }
+    for new in b.iter().filter(|new| !a.iter().any(|old| old.id == new.id)) {
+        diff.entries.push(EntryDiff {
+            id: new.id.clone(),
+            label: new.label.clone(),
+            change: EntryChange::Added,
+            changed: true,
+        });
+    }
+    for entry in &diff.entries {
+        match entry.change {
+            EntryChange::Added => diff.added.push(entry.label.clone()),
+            EntryChange::Removed => diff.removed.push(entry.label.clone()),
+            EntryChange::Modified => diff.modified.push(entry.label.clone()),
+            EntryChange::Unchanged => {}
+        }
+    }
+    diff.identical = diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty();
+    diff
+}
+
+/// Backups kept by `save_vault_atomic` when the caller doesn't say.
+const DEFAULT_VAULT_BACKUPS: usize = 3;
+
+/// Hashed ahead of the salt and ciphertext in `vault_fingerprint`.
+const FINGERPRINT_DOMAIN: &[u8] = b"seQRets-vault-fingerprint-v1";
+
+/// One `<name>.bak.N` file, as listed by `list_vault_backups`.
+#[derive(Serialize, Debug)]
+pub struct VaultBackup {
+    pub path: String,
+    /// N in `.bak.N`; 1 is the most recent.
+    pub generation: u32,
+    pub size: u64,
+    /// Last modification time, milliseconds since the Unix epoch.
+    pub modified_ms: u64,
+}
+
+/// `<path>.bak.<generation>`.
+fn backup_path(path: &Path, generation: usize) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".bak.{generation}"));
+    PathBuf::from(name)
+}
+
+/// The generation of a `<name>.bak.N` file name.
+fn backup_generation(file_name: &str) -> Option<u32> {
+    let (base, generation) = file_name.rsplit_once(".bak.")?;
+    if base.is_empty() || generation.is_empty() || !generation.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    generation.parse().ok().filter(|&n| n > 0)
+}
+
+/// Copies the current `path` to `<path>.bak.1`, first renaming each older
+/// backup up one generation (the rename onto `.bak.<keep>` drops the
+/// oldest) and deleting any beyond `keep`. `path` itself is never touched,
+/// so a crash part-way loses at most a backup, never the live file.
+fn rotate_backups(path: &Path, keep: usize) -> Result<(), SeqretsError> {
+    if keep == 0 || !path.is_file() {
+        return Ok(());
+    }
+    let mut stale = keep + 1;
+    while backup_path(path, stale).exists() {
+        fs::remove_file(backup_path(path, stale))
+            .map_err(|e| format!("Could not remove old backup: {e}"))?;
+        stale += 1;
+    }
+    for generation in (1..keep).rev() {
+        let from = backup_path(path, generation);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, generation + 1))
+                .map_err(|e| format!("Could not rotate backup {}: {e}", from.display()))?;
+        }
+    }
+    let current = fs::read(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    write_atomic(&backup_path(path, 1), &current)
+}
+
+/// Writes `bytes` to `path` via a uniquely named temp file in the same
+/// directory: write → fsync → rename over `path` → fsync the directory
+/// (unix). The rename replaces an existing file on every platform; on
+/// Windows std uses `MoveFileExW` with `MOVEFILE_REPLACE_EXISTING`. The temp
+/// file is removed if any step fails.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), SeqretsError> {
+    write_atomic_with(path, |f| {
+        f.write_all(bytes)
+            .map_err(|e| format!("Could not write temp file: {e}").into())
+    })
+}
+
+/// `write_atomic` with the temp file's contents written by `write`, for
+/// output too large to hold in memory. When `write` fails its error is
+/// returned and `path` is left as it was.
+pub(crate) fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> Result<(), SeqretsError>,
+) -> Result<(), SeqretsError> {
+    let file_name = path.file_name().ok_or_else(|| {
+        SeqretsError::InvalidInput(format!("Not a file path: {}", path.display()))
+    })?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if path.is_dir() {
+        return Err(SeqretsError::InvalidInput(format!(
+            "{} is a directory",
+            path.display()
+        )));
+    }
+
+    let mut suffix = [0u8; 8];
+    rand::rng().fill_bytes(&mut suffix);
+    let suffix: String = suffix.iter().map(|b| format!("{b:02x}")).collect();
+    let tmp_path = dir.join(format!(".{}.{suffix}.tmp", file_name.to_string_lossy()));
+
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+        opts.custom_flags(libc::O_NOFOLLOW);
+    }
+
+    let result = opts
+        .open(&tmp_path)
+        .map_err(|e| {
+            SeqretsError::from(format!(
+                "Could not create temp file in {}: {e}",
+                dir.display()
+            ))
+        })
+        .and_then(|mut f| {
+            write(&mut f)?;
+            f.sync_all()
+                .map_err(|e| format!("Could not fsync temp file: {e}").into())
+        })
+        .and_then(|_| {
+            fs::rename(&tmp_path, path)
+                .map_err(|e| format!("Could not replace {}: {e}", path.display()).into())
+        });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    // Persist the rename itself; the file contents are already on disk.
+    #[cfg(unix)]
+    {
+        fs::File::open(dir)
+            .and_then(|d| d.sync_all())
+            .map_err(|e| format!("Could not fsync {}: {e}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Overwrites every string in `value`, so a parsed payload doesn't leave
+/// copies of the secret behind when it drops.
+fn scrub(value: &mut Value) {
+    match value {
+        Value::String(s) => s.zeroize(),
+        Value::Array(items) => items.iter_mut().for_each(scrub),
+        Value::Object(map) => map.values_mut().for_each(scrub),
+        _ => {}
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Decrypts, decompresses and schema-checks an encrypted vault without
+/// returning its contents. Failures are reported in the `VaultReport` rather
+/// than as an error, so the UI can say which stage went wrong. All
+/// intermediate plaintext is zeroized before returning.
+#[tauri::command]
+pub fn verify_vault(
+    salt_b64: String,
+    data_b64: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+) -> VaultReport {
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let decrypted = decrypt_blob(
+        &salt_b64,
+        &data_b64,
+        password.as_str(),
+        &keyfiles_b64,
+        None,
+        &CancelToken::default(),
+        &mut Duration::default(),
+    );
+    drop(password);
+    drop(keyfiles_b64);
+    let plaintext = match decrypted {
+        Ok(plaintext) => plaintext,
+        Err(e) => return VaultReport::failed(false, e),
+    };
+
+    let json = match crypto::decompress(&plaintext) {
+        Ok(json) => Zeroizing::new(json),
+        Err(e) => return VaultReport::failed(true, e),
+    };
+    drop(plaintext);
+
+    match serde_json::from_slice::<VaultSchema>(&json) {
+        Ok(vault) if vault.required_shares == 0 || vault.required_shares > vault.total_shares => {
+            VaultReport::failed(
+                true,
+                SeqretsError::CorruptData(format!(
+                    "Vault threshold is inconsistent: {} of {} shares required.",
+                    vault.required_shares, vault.total_shares
+                )),
+            )
+        }
+        Ok(vault) => VaultReport {
+            decryptable: true,
+            parseable: true,
+            entry_count: Some(vault.shares.len()),
+            schema_version: Some(vault.version),
+            error: None,
+        },
+        Err(e) => VaultReport::failed(
+            true,
+            SeqretsError::CorruptData(format!(
+                "Decrypted vault does not match the expected format ({}).",
+                json_position(&e)
+            )),
+        ),
+    }
+}
+
+/// Fingerprints the encrypted vault `salt_b64`/`data_b64` (base64 or
+/// base64url) without decrypting it: the first 8 bytes of
+/// SHA-256(domain || salt length || salt || data) as four dash-separated
+/// hex groups, e.g. `3f9a-0c21-77de-b410`. The text encoding doesn't
+/// matter, only the bytes.
+#[tauri::command]
+pub fn vault_fingerprint(salt_b64: String, data_b64: String) -> Result<String, SeqretsError> {
+    let salt = encoding::decode(salt_b64.trim())
+        .map_err(|e| SeqretsError::CorruptData(format!("Salt base64 decode error: {e}")))?;
+    let data = encoding::decode(data_b64.trim())
+        .map_err(|e| SeqretsError::CorruptData(format!("Vault base64 decode error: {e}")))?;
+    if salt.is_empty() || data.is_empty() {
+        return Err(SeqretsError::InvalidInput(
+            "Both the salt and the vault data are required.".to_string(),
+        ));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(FINGERPRINT_DOMAIN);
+    hasher.update((salt.len() as u32).to_be_bytes());
+    hasher.update(&salt);
+    hasher.update(&data);
+    let digest = hasher.finalize();
+    Ok(digest[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02x}{:02x}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join("-"))
+}
+
+/// Parses a decrypted secret payload (the JSON returned by `crypto_restore`)
+/// into its known fields, keeping unknown ones in `extra`. The input string
+/// is zeroized once parsed.
+#[tauri::command]
+pub fn parse_vault(json: String) -> Result<VaultEnvelope, SeqretsError> {
+    let json = Zeroizing::new(json);
+    parse_envelope(&json)
+}
+
+/// Saves an encrypted vault (`salt_b64` and `data_b64` from
+/// `crypto_encrypt_blob`) to `path` as a `.seqrets` file, replacing any
+/// existing file atomically. The replaced file is kept as `<path>.bak.1`,
+/// with up to `keep_backups` generations (default 3, 0 for none). Returns
+/// the path written.
+#[tauri::command]
+pub fn save_vault_atomic(
+    path: String,
+    salt_b64: String,
+    data_b64: String,
+    keep_backups: Option<usize>,
+) -> Result<String, SeqretsError> {
+    let file = EncryptedVaultFile {
+        version: ENCRYPTED_VAULT_FILE_VERSION,
+        encrypted: true,
+        salt: &salt_b64,
+        data: &data_b64,
+    };
+    let json =
+        serde_json::to_string_pretty(&file).map_err(|e| format!("JSON encode error: {e}"))?;
+    rotate_backups(
+        Path::new(&path),
+        keep_backups.unwrap_or(DEFAULT_VAULT_BACKUPS),
+    )?;
+    write_atomic(Path::new(&path), json.as_bytes())?;
+    Ok(path)
+}
+
+/// Lists the `<name>.bak.N` vault backups in `dir`, most recently modified
+/// first.
+#[tauri::command]
+pub fn list_vault_backups(dir: String) -> Result<Vec<VaultBackup>, SeqretsError> {
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Could not read {dir}: {e}"))?;
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Could not read {dir}: {e}"))?;
+        let Some(generation) = entry.file_name().to_str().and_then(backup_generation) else {
+            continue;
+        };
+        let meta = entry
+            .metadata()
+            .map_err(|e| format!("Could not stat backup: {e}"))?;
+        if !meta.is_file() {
+            continue;
+        }
+        let modified_ms = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as u64);
+        backups.push(VaultBackup {
+            path: entry.path().to_string_lossy().into_owned(),
+            generation,
+            size: meta.len(),
+            modified_ms,
+        });
+    }
+    backups.sort_by(|a, b| {
+        b.modified_ms
+            .cmp(&a.modified_ms)
+            .then(a.generation.cmp(&b.generation))
+    });
+    Ok(backups)
+}
+
+/// Restores `backup_path` over `dest` atomically. The vault being replaced
+/// is itself backed up first, so a restore can be undone. Returns `dest`.
+#[tauri::command]
+pub fn restore_vault_backup(backup_path: String, dest: String) -> Result<String, SeqretsError> {
+    let file_name = Path::new(&backup_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("");
+    if backup_generation(file_name).is_none() {
+        return Err(SeqretsError::InvalidInput(format!(
+            "{backup_path} is not a vault backup."
+        )));
+    }
+    // Read first: rotating `dest`'s backups may move `backup_path`.
+    let bytes = fs::read(&backup_path).map_err(|e| format!("Could not read {backup_path}: {e}"))?;
+    rotate_backups(Path::new(&dest), DEFAULT_VAULT_BACKUPS)?;
+    write_atomic(Path::new(&dest), &bytes)?;
+    Ok(dest)
+}
+
+/// Migrates a decrypted secret payload from `from_version` to `to_version`
+/// (at most `CURRENT_SCHEMA_VERSION`) and returns the migrated JSON. Unknown
+/// fields are kept. Fails if a step in between is missing or the payload
+/// isn't at `from_version`; a payload already at `to_version` comes back
+/// unchanged.
+#[tauri::command]
+pub fn migrate_vault(
+    json: String,
+    from_version: u32,
+    to_version: u32,
+) -> Result<String, SeqretsError> {
+    let json = Zeroizing::new(json);
+    if to_version > CURRENT_SCHEMA_VERSION {
+        return Err(SeqretsError::InvalidInput(format!(
+            "Schema version {to_version} is newer than this build supports ({CURRENT_SCHEMA_VERSION})."
+        )));
+    }
+    let mut value: Value = serde_json::from_str(&json).map_err(|e| {
+        SeqretsError::CorruptData(format!(
+            "Secret payload is not valid ({}).",
+            json_position(&e)
+        ))
+    })?;
+    drop(json);
+
+    let result = match value.as_object_mut() {
+        Some(payload) => migrate_payload(payload, from_version, to_version).and_then(|_| {
+            serde_json::to_string(&value).map_err(|e| format!("JSON encode error: {e}").into())
+        }),
+        None => Err(SeqretsError::CorruptData(
+            "Secret payload is not a JSON object.".to_string(),
+        )),
+    };
+    scrub(&mut value);
+    result
+}
+
+/// Encrypts `json` as section `section_id` of the sectioned vault
+/// `vault_json` (or of a new vault when `None`) and returns the updated
+/// vault JSON. An existing section with that id is replaced; the others are
+/// copied through without being decrypted. `params` behave as in
+/// `crypto_encrypt_blob`.
+#[tauri::command]
+pub fn encrypt_vault_section(
+    vault_json: Option<String>,
+    section_id: String,
+    name: Option<String>,
+    json: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+    params: Option<CryptoParams>,
+) -> Result<String, SeqretsError> {
+    check_section_id(&section_id)?;
+    let mut vault = match vault_json {
+        Some(vault_json) => parse_sectioned_vault(&vault_json)?,
+        None => SectionedVaultFile {
+            version: SECTIONED_VAULT_FILE_VERSION,
+            encrypted: true,
+            sections: Vec::new(),
+        },
+    };
+    let sealed = crypto::crypto_encrypt_blob(
+        json,
+        password,
+        keyfiles_b64,
+        params,
+        None,
+        Some(section_label(&section_id)),
+        None,
+        None,
+    )?;
+    let section = VaultSection {
+        id: section_id,
+        name,
+        salt: sealed.salt,
+        data: sealed.data,
+    };
+    match vault.sections.iter_mut().find(|s| s.id == section.id) {
+        Some(existing) => *existing = section,
+        None => vault.sections.push(section),
+    }
+    serde_json::to_string_pretty(&vault).map_err(|e| format!("JSON encode error: {e}").into())
+}
+
+/// Decrypts section `section_id` of the sectioned vault `vault_json` and
+/// returns its JSON. Only that section is touched, so a password that opens
+/// one section reveals nothing about the others. A wrong password fails with
+/// `WrongPassword`, an unknown id with `InvalidInput`.
+#[tauri::command]
+pub fn decrypt_vault_section(
+    vault_json: String,
+    section_id: String,
+    password: String,
+    keyfiles_b64: Option<Vec<String>>,
+) -> Result<String, SeqretsError> {
+    let vault = parse_sectioned_vault(&vault_json)?;
+    let section = vault
+        .sections
+        .iter()
+        .find(|s| s.id == section_id)
+        .ok_or_else(|| {
+            SeqretsError::InvalidInput(format!("The vault has no section '{section_id}'."))
+        })?;
+    let password = Zeroizing::new(password);
+    let keyfiles_b64 = secret_keyfiles(keyfiles_b64);
+    let plaintext = decrypt_blob(
+        &section.salt,
+        &section.data,
+        password.as_str(),
+        &keyfiles_b64,
+        Some(&section_label(&section.id)),
+        &CancelToken::default(),
+        &mut Duration::default(),
+    )?;
+    decompress_json(plaintext)
+}
+
+/// Compares two vaults and reports which entries were added, removed or
+/// modified from `vault_a_json` to `vault_b_json`, without returning any
+/// secret: contents are compared by digest. Entries of a sectioned vault are
+/// matched by section id, so one with the same name but different content
+/// shows as modified; a plain vault export is a single entry keyed by its
+/// label. Encrypted single-blob vaults must be decrypted first, and both
+/// vaults must be of the same kind. The inputs are zeroized once parsed.
+#[tauri::command]
+pub fn diff_vaults(vault_a_json: String, vault_b_json: String) -> Result<VaultDiff, SeqretsError> {
+    let (vault_a_json, vault_b_json) = (Zeroizing::new(vault_a_json), Zeroizing::new(vault_b_json));
+    let (a_sectioned, a) = vault_entries(&vault_a_json)?;
+    let (b_sectioned, b) = vault_entries(&vault_b_json)?;
+    if a_sectioned != b_sectioned {
+        return Err(SeqretsError::InvalidInput(
+            "A sectioned vault can only be compared with another sectioned vault.".to_string(),
+        ));
+    }
+    Ok(diff_entries(&a, &b))
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::crypto_encrypt_blob;
+
+    const VAULT_JSON: &str = r#"{
+      "version": 1,
+      "label": "Wallet",
+      "setId": "abcd1234",
+      "shares": ["seQRets|c2FsdA==|c2hhcmUx|h", "seQRets|c2FsdA==|c2hhcmUy|h", "seQRets|c2FsdA==|c2hhcmUz|h"],
+      "requiredShares": 2,
+      "totalShares": 3,
+      "createdAt": "2026-01-01T00:00:00.000Z",
+      "encryptedInstructions": null,
+      "keyfileUsed": false
+    }"#;
+
+    fn encrypt(json: &str) -> (String, String) {
+        let result = crypto_encrypt_blob(
+            json.to_string(),
+            "pw".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        (result.salt, result.data)
+    }
+
+    #[test]
+    fn test_valid_vault_reports_counts() {
+        let (salt, data) = encrypt(VAULT_JSON);
+        let report = verify_vault(salt, data, "pw".to_string(), None);
+        assert_eq!(
+            report,
+            VaultReport {
+                decryptable: true,
+                parseable: true,
+                entry_count: Some(3),
+                schema_version: Some(1),
+                error: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_wrong_password_is_not_decryptable() {
+        let (salt, data) = encrypt(VAULT_JSON);
+        let report = verify_vault(salt, data, "nope".to_string(), None);
+        assert!(!report.decryptable && !report.parseable);
+        assert!(matches!(report.error, Some(SeqretsError::WrongPassword(_))));
+    }
+
+    #[test]
+    fn test_inconsistent_threshold_is_not_parseable() {
+        let (salt, data) =
+            encrypt(&VAULT_JSON.replace(r#""requiredShares": 2"#, r#""requiredShares": 4"#));
+        let report = verify_vault(salt, data, "pw".to_string(), None);
+        assert!(report.decryptable && !report.parseable);
+        assert!(matches!(report.error, Some(SeqretsError::CorruptData(_))));
+    }
+
+    #[test]
+    fn test_parse_vault_known_fields() {
+        let envelope = parse_vault(
+            r#"{"secret":"AAAA","label":"wallet","isMnemonic":true,"mnemonicLengths":[12]}"#
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(envelope.schema_version, None);
+        assert_eq!(envelope.secret, "AAAA");
+        assert_eq!(envelope.label, "wallet");
+        assert!(envelope.is_mnemonic);
+        assert_eq!(envelope.mnemonic_lengths, Some(vec![12]));
+        assert!(envelope.extra.is_empty());
+    }
+
+    #[test]
+    fn test_parse_vault_preserves_unknown_fields() {
+        let json = r#"{"schemaVersion":3,"secret":"s","label":"","isMnemonic":false,"notes":{"a":[1,2]},"future":true}"#;
+        let envelope = parse_vault(json.to_string()).unwrap();
+        assert_eq!(envelope.schema_version, Some(3));
+        assert_eq!(envelope.extra["future"], Value::Bool(true));
+
+        let original: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_value(&envelope).unwrap(), original);
+    }
+
+    #[test]
+    fn test_envelope_debug_hides_secret() {
+        let envelope =
+            parse_vault(r#"{"secret":"hunter2","isMnemonic":false}"#.to_string()).unwrap();
+        let debug = format!("{envelope:?}");
+        assert!(
+            !debug.contains("hunter2") && debug.contains("secret: ***"),
+            "got: {debug}"
+        );
+    }
+
+    #[test]
+    fn test_parse_vault_error_does_not_leak_secret() {
+        let err = parse_vault(r#"{"secret":42,"isMnemonic":"hunter2"}"#.to_string()).unwrap_err();
+        assert!(matches!(err, SeqretsError::CorruptData(_)), "got: {err:?}");
+        assert!(!err.to_string().contains("hunter2"), "got: {err}");
+    }
+
+    #[test]
+    fn test_migrate_v0_to_current() {
+        let migrated = migrate_vault(
+            r#"{"secret":"s","isMnemonic":false,"extra":1}"#.to_string(),
+            0,
+            CURRENT_SCHEMA_VERSION,
+        )
+        .unwrap();
+        let envelope = parse_vault(migrated.clone()).unwrap();
+        assert_eq!(envelope.schema_version, Some(CURRENT_SCHEMA_VERSION));
+        assert_eq!(envelope.label, "");
+        assert_eq!(envelope.extra["extra"], Value::from(1));
+
+        // Already migrated: a second run is a no-op.
+        assert_eq!(
+            migrate_vault(migrated.clone(), 0, CURRENT_SCHEMA_VERSION).unwrap(),
+            migrated
+        );
+    }
+
+    #[test]
+    fn test_migrate_rejects_gaps_and_mismatches() {
+        let v0 = r#"{"secret":"s","isMnemonic":false}"#.to_string();
+
+        let mut payload = serde_json::from_str::<Value>(&v0)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .clone();
+        let err = migrate_payload(&mut payload, 0, CURRENT_SCHEMA_VERSION + 1).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("No migration from schema version 1 to 2"),
+            "got: {err}"
+        );
+
+        let err = migrate_vault(v0.clone(), 0, CURRENT_SCHEMA_VERSION + 1).unwrap_err();
+        assert!(matches!(err, SeqretsError::InvalidInput(_)), "got: {err:?}");
+
+        let err = migrate_vault(v0, 1, CURRENT_SCHEMA_VERSION).unwrap_err();
+        assert!(err.to_string().contains("not 1"), "got: {err}");
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("seqrets-vault-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_save_vault_atomic_writes_and_replaces() {
+        let dir = temp_dir("save");
+        let path = dir.join("wallet.seqrets");
+        let path_str = path.to_str().unwrap().to_string();
+
+        assert_eq!(
+            save_vault_atomic(path_str.clone(), "c2FsdA==".into(), "b2xk".into(), Some(0)).unwrap(),
+            path_str
+        );
+        save_vault_atomic(path_str, "c2FsdA==".into(), "bmV3".into(), Some(0)).unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            saved,
+            serde_json::json!({ "version": 2, "encrypted": true, "salt": "c2FsdA==", "data": "bmV3" })
+        );
+        // Only the vault is left: no temp files.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_save_keeps_existing_file() {
+        let dir = temp_dir("fail");
+        let target = dir.join("wallet.seqrets");
+        fs::create_dir(&target).unwrap(); // a directory can't be replaced by the vault
+
+        let err = save_vault_atomic(
+            target.to_str().unwrap().to_string(),
+            "s".into(),
+            "d".into(),
+            None,
+        );
+        assert!(err.is_err());
+        assert!(target.is_dir());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backups_rotate_oldest_out() {
+        let dir = temp_dir("rotate");
+        let path = dir.join("wallet.seqrets");
+        let path_str = path.to_str().unwrap().to_string();
+        for data in ["djE=", "djI=", "djM=", "djQ=", "djU="] {
+            save_vault_atomic(path_str.clone(), "c2FsdA==".into(), data.into(), Some(3)).unwrap();
+        }
+
+        let data_of = |p: &Path| {
+            let v: Value = serde_json::from_str(&fs::read_to_string(p).unwrap()).unwrap();
+            v["data"].as_str().unwrap().to_string()
+        };
+        assert_eq!(data_of(&path), "djU=");
+        assert_eq!(data_of(&backup_path(&path, 1)), "djQ=");
+        assert_eq!(data_of(&backup_path(&path, 2)), "djM=");
+        assert_eq!(data_of(&backup_path(&path, 3)), "djI=");
+        assert!(!backup_path(&path, 4).exists());
+
+        let backups = list_vault_backups(dir.to_str().unwrap().to_string()).unwrap();
+        let mut generations: Vec<u32> = backups.iter().map(|b| b.generation).collect();
+        generations.sort();
+        assert_eq!(generations, [1, 2, 3]);
+        assert!(backups.iter().all(|b| b.size > 0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_restore_backup_keeps_replaced_vault() {
+        let dir = temp_dir("restore");
+        let path = dir.join("wallet.seqrets");
+        let path_str = path.to_str().unwrap().to_string();
+        save_vault_atomic(path_str.clone(), "c2FsdA==".into(), "Z29vZA==".into(), None).unwrap();
+        save_vault_atomic(path_str.clone(), "c2FsdA==".into(), "YmFk".into(), None).unwrap();
+        let good = fs::read(backup_path(&path, 1)).unwrap();
+        let bad = fs::read(&path).unwrap();
+
+        let backup = backup_path(&path, 1).to_str().unwrap().to_string();
+        restore_vault_backup(backup, path_str.clone()).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), good);
+        assert_eq!(fs::read(backup_path(&path, 1)).unwrap(), bad);
+        assert_eq!(fs::read(backup_path(&path, 2)).unwrap(), good);
+
+        let err = restore_vault_backup(path_str.clone(), path_str);
+        assert!(matches!(err, Err(SeqretsError::InvalidInput(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_generation_parsing() {
+        assert_eq!(backup_generation("wallet.seqrets.bak.2"), Some(2));
+        assert_eq!(backup_generation("wallet.seqrets.bak.0"), None);
+        assert_eq!(backup_generation("wallet.seqrets.bak."), None);
+        assert_eq!(backup_generation("wallet.seqrets.bak.1x"), None);
+        assert_eq!(backup_generation("wallet.seqrets"), None);
+    }
+
+    #[test]
+    fn test_non_vault_payload_is_not_parseable() {
+        let (salt, data) = encrypt(r#"{"secret":"do not echo me","isMnemonic":false}"#);
+        let report = verify_vault(salt, data, "pw".to_string(), None);
+        assert!(report.decryptable && !report.parseable);
+        let error = report.error.unwrap();
+        assert!(matches!(error, SeqretsError::CorruptData(_)));
+        assert!(
+            !error.to_string().contains("do not echo me"),
+            "got: {error}"
+        );
+    }
+
+    #[test]
+    fn test_vault_fingerprint() {
+        let (salt, data) = encrypt(VAULT_JSON);
+        let fingerprint = vault_fingerprint(salt.clone(), data.clone()).unwrap();
+        assert_eq!(fingerprint.len(), 19);
+        assert_eq!(fingerprint.matches('-').count(), 3);
+
+        // Same bytes in another encoding: same fingerprint
+        let url = encoding::PayloadEncoding::Base64Url.encode(&encoding::decode(&data).unwrap());
+        assert_eq!(vault_fingerprint(salt.clone(), url).unwrap(), fingerprint);
+
+        // Re-encrypting the same vault gives a new one
+        let (salt2, data2) = encrypt(VAULT_JSON);
+        assert_ne!(vault_fingerprint(salt2, data2).unwrap(), fingerprint);
+
+        assert!(matches!(
+            vault_fingerprint(salt, "not base64!".into()),
+            Err(SeqretsError::CorruptData(_))
+        ));
+        assert!(matches!(
+            vault_fingerprint(String::new(), data),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_vaults_reports_entries_without_contents() {
+        let section = |id: &str, name: Option<&str>, data: &str| VaultSection {
+            id: id.into(),
+            name: name.map(str::to_string),
+            salt: "c2FsdA==".into(),
+            data: data.into(),
+        };
+        let vault = |sections: Vec<VaultSection>| {
+            serde_json::to_string(&SectionedVaultFile {
+                version: SECTIONED_VAULT_FILE_VERSION,
+                encrypted: true,
+                sections,
+            })
+            .unwrap()
+        };
+        let a = vault(vec![
+            section("family", Some("Family"), "AAAA"),
+            section("me", None, "BBBB"),
+            section("old", None, "CCCC"),
+        ]);
+        let b = vault(vec![
+            section("family", Some("Family"), "ZZZZ"),
+            section("me", Some("Personal"), "BBBB"),
+            section("new", None, "DDDD"),
+        ]);
+
+        let diff = diff_vaults(a.clone(), b).unwrap();
+        assert_eq!(diff.modified, ["Family", "Personal"]);
+        assert_eq!(diff.removed, ["old"]);
+        assert_eq!(diff.added, ["new"]);
+        // Same name, different content vs. a rename alone
+        assert!(diff.entries[0].changed);
+        assert!(!diff.entries[1].changed && diff.entries[1].change == EntryChange::Modified);
+        assert!(!serde_json::to_string(&diff).unwrap().contains("ZZZZ"));
+        assert!(diff_vaults(a.clone(), a.clone()).unwrap().identical);
+
+        let tampered = VAULT_JSON.replace("c2hhcmUz", "c2hhcmU0");
+        let plain = diff_vaults(VAULT_JSON.to_string(), tampered).unwrap();
+        assert_eq!(plain.modified, ["Wallet"]);
+        assert!(
+            diff_vaults(VAULT_JSON.to_string(), VAULT_JSON.to_string())
+                .unwrap()
+                .identical
+        );
+        assert!(matches!(
+            diff_vaults(VAULT_JSON.to_string(), a),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_sections_open_independently() {
+        let params = CryptoParams {
+            m_cost: 8,
+            t_cost: 1,
+            p_cost: 1,
+            ..CryptoParams::default()
+        };
+        let add = |vault: Option<String>, id: &str, json: &str, password: &str| {
+            encrypt_vault_section(
+                vault,
+                id.into(),
+                None,
+                json.into(),
+                password.into(),
+                None,
+                Some(params),
+            )
+            .unwrap()
+        };
+        let vault = add(None, "shared", r#"{"who":"family"}"#, "shared-pw");
+        let vault = add(Some(vault), "personal", r#"{"who":"me"}"#, "personal-pw");
+        assert!(!vault.contains("family"));
+
+        let open = |id: &str, password: &str| {
+            decrypt_vault_section(vault.clone(), id.into(), password.into(), None)
+        };
+        assert_eq!(open("shared", "shared-pw").unwrap(), r#"{"who":"family"}"#);
+        assert_eq!(open("personal", "personal-pw").unwrap(), r#"{"who":"me"}"#);
+        assert!(matches!(
+            open("personal", "shared-pw"),
+            Err(SeqretsError::WrongPassword(_))
+        ));
+        assert!(matches!(
+            open("missing", "shared-pw"),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+
+        // Swapping ciphertexts between sections fails authentication.
+        let mut swapped = parse_sectioned_vault(&vault).unwrap();
+        swapped.sections[0].id = "personal".into();
+        swapped.sections[1].id = "shared".into();
+        let swapped = serde_json::to_string(&swapped).unwrap();
+        assert!(
+            decrypt_vault_section(swapped, "personal".into(), "shared-pw".into(), None).is_err()
+        );
+
+        let replaced = add(Some(vault), "shared", r#"{"who":"friends"}"#, "new-pw");
+        assert_eq!(parse_sectioned_vault(&replaced).unwrap().sections.len(), 2);
+        assert_eq!(
+            decrypt_vault_section(replaced, "shared".into(), "new-pw".into(), None).unwrap(),
+            r#"{"who":"friends"}"#
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Best-effort secure deletion for seQRets desktop.
+//!
+//! Once a keyfile has been imported or a plaintext vault export is no longer
+//! needed, `secure_delete` overwrites the file with random bytes, fsyncs and
+//! only then unlinks it, so casual undelete tools find noise instead of the
+//! secret.
+//!
+//! This is best-effort. SSD wear levelling, copy-on-write filesystems (APFS,
+//! Btrfs, ZFS), snapshots, backups and journaling can all keep older copies
+//! of the blocks that the overwrite never touches. Full-disk encryption is
+//! the real defence; the overwrite still raises the bar on everything else.
+
+use rand::RngCore;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use crate::error::SeqretsError;
+
+/// Overwrite buffer size; also the unit of the random refill.
+const WIPE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Overwrites `path` in place with one pass of random bytes and fsyncs.
+fn overwrite_with_random(path: &Path, len: u64) -> Result<(), SeqretsError> {
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.custom_flags(libc::O_NOFOLLOW);
+    }
+
+    let mut f = opts
+        .open(path)
+        .map_err(|e| format!("Could not open {} for wiping: {e}", path.display()))?;
+
+    let mut buf = vec![0u8; WIPE_CHUNK_SIZE];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = remaining.min(WIPE_CHUNK_SIZE as u64) as usize;
+        rand::rng().fill_bytes(&mut buf[..n]);
+        f.write_all(&buf[..n])
+            .map_err(|e| format!("Could not overwrite {}: {e}", path.display()))?;
+        remaining -= n as u64;
+    }
+    f.sync_all()
+        .map_err(|e| format!("Could not fsync {}: {e}", path.display()))?;
+    Ok(())
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Overwrites the file at `path` with random bytes, fsyncs, then deletes it.
+/// Directories, symlinks and missing paths are refused with `InvalidInput`.
+/// Best-effort on SSDs and copy-on-write filesystems (see the module docs).
+#[tauri::command]
+pub fn secure_delete(path: String) -> Result<(), SeqretsError> {
+    let path = Path::new(&path);
+    let meta = match fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(SeqretsError::InvalidInput(format!(
+                "{} does not exist.",
+                path.display()
+            )));
+        }
+        Err(e) => return Err(format!("Could not inspect {}: {e}", path.display()).into()),
+    };
+    if meta.is_dir() {
+        return Err(SeqretsError::InvalidInput(format!(
+            "{} is a directory; only files can be securely deleted.",
+            path.display()
+        )));
+    }
+    if !meta.is_file() {
+        return Err(SeqretsError::InvalidInput(format!(
+            "{} is not a regular file (symlinks are not followed).",
+            path.display()
+        )));
+    }
+
+    overwrite_with_random(path, meta.len())?;
+    fs::remove_file(path).map_err(|e| format!("Could not delete {}: {e}", path.display()))?;
+    Ok(())
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("seqrets-wipe-test-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_overwrites_then_removes() {
+        let path = temp_path("file");
+        let secret = vec![0x5Au8; WIPE_CHUNK_SIZE + 100];
+        fs::write(&path, &secret).unwrap();
+
+        overwrite_with_random(&path, secret.len() as u64).unwrap();
+        let wiped = fs::read(&path).unwrap();
+        assert_eq!(wiped.len(), secret.len());
+        assert_ne!(wiped, secret);
+
+        secure_delete(path.to_str().unwrap().to_string()).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_refuses_missing_paths_and_directories() {
+        let missing = temp_path("missing");
+        assert!(matches!(
+            secure_delete(missing.to_str().unwrap().to_string()),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+
+        let dir = temp_path("dir");
+        fs::create_dir_all(&dir).unwrap();
+        assert!(matches!(
+            secure_delete(dir.to_str().unwrap().to_string()),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert!(dir.is_dir());
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_does_not_follow_symlinks() {
+        let target = temp_path("target");
+        let link = temp_path("link");
+        fs::write(&target, b"keep me").unwrap();
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(matches!(
+            secure_delete(link.to_str().unwrap().to_string()),
+            Err(SeqretsError::InvalidInput(_))
+        ));
+        assert_eq!(fs::read(&target).unwrap(), b"keep me");
+        fs::remove_file(&link).unwrap();
+        fs::remove_file(&target).unwrap();
+    }
+}
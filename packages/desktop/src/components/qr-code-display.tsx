@@ -3,7 +3,7 @@ import { Printer, FileArchive, TriangleAlert, Loader2, Lock, Save, Eye, EyeOff,
 import { useEffect, useState } from 'react';
 import JSZip from 'jszip';
 import { useToast } from '@/hooks/use-toast';
-import { CreateSharesResult, EncryptedVaultFile } from '@/lib/types';
+import { CreateSharesResult } from '@/lib/types';
 import QRCode from 'qrcode';
 import { cn } from '@/lib/utils';
 import { Alert, AlertDescription, AlertTitle } from '@/components/ui/alert';
@@ -11,7 +11,7 @@ import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription, Di
 import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
 import { SmartCardDialog, SmartCardMode } from '@/components/smartcard-dialog';
-import { saveFileNative, saveTextFileNative, dataUrlToUint8Array, PNG_FILTERS, TXT_FILTERS, ZIP_FILTERS, SEQRETS_FILTERS } from '@/lib/native-save';
+import { saveFileNative, saveTextFileNative, saveEncryptedVaultNative, dataUrlToUint8Array, PNG_FILTERS, TXT_FILTERS, ZIP_FILTERS, SEQRETS_FILTERS } from '@/lib/native-save';
 import { encryptVault } from '@/lib/desktop-crypto';
 import { computeShareHash, truncateHash } from '@seqrets/crypto';
 import { writeTextFile } from '@tauri-apps/plugin-fs';
@@ -415,11 +415,11 @@ export function QrCodeDisplay({ qrCodeData, keyfileUsed }: QrCodeDisplayProps) {
     return JSON.stringify(vaultData, null, 2);
   };
 
-  const downloadVaultFile = async (content: string, isEncrypted: boolean) => {
+  const downloadVaultFile = async (saveAs: (filename: string) => Promise<string | null>, isEncrypted: boolean) => {
     const vaultLabel = qrCodeData.label || 'Untitled';
     const sanitizedLabel = (vaultLabel).replace(/[^a-zA-Z0-9_-]/g, '-');
     const filename = `${sanitizedLabel}-${new Date().toISOString().split('T')[0]}.seqrets`;
-    const savedPath = await saveTextFileNative(filename, SEQRETS_FILTERS, content);
+    const savedPath = await saveAs(filename);
     if (savedPath) {
       toast({
         title: isEncrypted ? 'Encrypted Vault Exported!' : 'Vault Exported!',
@@ -433,7 +433,7 @@ export function QrCodeDisplay({ qrCodeData, keyfileUsed }: QrCodeDisplayProps) {
   };
 
   const handleExportWithoutPassword = async () => {
-    await downloadVaultFile(getVaultJsonString(), false);
+    await downloadVaultFile((filename) => saveTextFileNative(filename, SEQRETS_FILTERS, getVaultJsonString()), false);
     setIsVaultDialogOpen(false);
     resetVaultDialog();
   };
@@ -454,13 +454,7 @@ export function QrCodeDisplay({ qrCodeData, keyfileUsed }: QrCodeDisplayProps) {
     setIsEncryptingVault(true);
     try {
       const result = await encryptVault(getVaultJsonString(), vaultPassword);
-      const encryptedFile: EncryptedVaultFile = {
-        version: 2,
-        encrypted: true,
-        salt: result.salt,
-        data: result.data,
-      };
-      await downloadVaultFile(JSON.stringify(encryptedFile, null, 2), true);
+      await downloadVaultFile((filename) => saveEncryptedVaultNative(filename, result.salt, result.data), true);
       setIsVaultDialogOpen(false);
       resetVaultDialog();
     } catch (e: any) {
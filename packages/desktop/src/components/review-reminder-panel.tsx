@@ -27,6 +27,7 @@ import {
   todayIso,
   type ReminderState,
 } from '@/lib/review-reminder';
+import { errorMessage } from '@/lib/errors';
 
 interface ReviewReminderPanelProps {
   /**
@@ -61,7 +62,7 @@ export function ReviewReminderPanel({ canMarkReviewed = true }: ReviewReminderPa
       toast({
         variant: 'destructive',
         title: 'Reminder update failed',
-        description: e instanceof Error ? e.message : String(e),
+        description: errorMessage(e),
       });
     } finally {
       setBusy(false);
@@ -24,6 +24,7 @@ import {
   enableReminder,
   todayIso,
 } from '@/lib/review-reminder';
+import { errorMessage } from '@/lib/errors';
 
 interface ReviewReminderPromptProps {
   open: boolean;
@@ -50,7 +51,7 @@ export function ReviewReminderPrompt({ open, onClose }: ReviewReminderPromptProp
       toast({
         variant: 'destructive',
         title: 'Could not enable reminder',
-        description: e instanceof Error ? e.message : String(e),
+        description: errorMessage(e),
       });
     } finally {
       setBusy(false);
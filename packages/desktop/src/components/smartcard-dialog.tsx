@@ -30,7 +30,9 @@ import {
   setPin,
   CardStatus,
   CardItem,
+  ReaderEntry,
 } from '@/lib/smartcard';
+import { errorMessage } from '@/lib/errors';
 
 export type SmartCardMode = 'write-share' | 'write-vault' | 'read';
 
@@ -60,7 +62,7 @@ export function SmartCardDialog({
   const { toast } = useToast();
 
   // Reader state
-  const [readers, setReaders] = useState<string[]>([]);
+  const [readers, setReaders] = useState<ReaderEntry[]>([]);
   const [selectedReader, setSelectedReader] = useState<string>('');
   const [isLoadingReaders, setIsLoadingReaders] = useState(false);
   const [readerError, setReaderError] = useState<string | null>(null);
@@ -96,10 +98,10 @@ export function SmartCardDialog({
       const r = await listReaders();
       setReaders(r);
       if (r.length === 1) {
-        setSelectedReader(r[0]);
+        setSelectedReader(r[0].name);
       }
     } catch (e: any) {
-      setReaderError(e?.toString() || 'Failed to detect readers');
+      setReaderError(errorMessage(e) || 'Failed to detect readers');
       setReaders([]);
     } finally {
       setIsLoadingReaders(false);
@@ -142,7 +144,7 @@ export function SmartCardDialog({
       }
     } catch (e: any) {
       setCardStatus(null);
-      setActionError(e?.toString() || 'Failed to read card status');
+      setActionError(errorMessage(e) || 'Failed to read card status');
     } finally {
       setIsLoadingStatus(false);
     }
@@ -169,7 +171,7 @@ export function SmartCardDialog({
       await loadCardStatus(undefined, pinInput);
       setPinInput('');
     } catch (e: any) {
-      setActionError(e?.toString() || 'PIN verification failed');
+      setActionError(errorMessage(e) || 'PIN verification failed');
       // Reload card status to get updated pin_retries_remaining from the card
       try {
         await loadCardStatus(selectedReader, null);
@@ -195,7 +197,7 @@ export function SmartCardDialog({
       setShowPinSetup(false);
       await loadCardStatus();
     } catch (e: any) {
-      setActionError(e?.toString() || 'Failed to set PIN');
+      setActionError(errorMessage(e) || 'Failed to set PIN');
     }
   };
 
@@ -217,7 +219,7 @@ export function SmartCardDialog({
         description: `${dataLabel} added to card successfully.`,
       });
     } catch (e: any) {
-      setActionError(e?.toString() || 'Write failed');
+      setActionError(errorMessage(e) || 'Write failed');
     } finally {
       setIsWriting(false);
     }
@@ -239,7 +241,7 @@ export function SmartCardDialog({
       });
       onDataRead?.(item);
     } catch (e: any) {
-      setActionError(e?.toString() || 'Read failed');
+      setActionError(errorMessage(e) || 'Read failed');
     } finally {
       setIsReading(false);
     }
@@ -278,7 +280,7 @@ export function SmartCardDialog({
       setVerifiedPin(null);
       await loadCardStatus();
     } catch (e: any) {
-      setActionError(e?.toString() || 'Erase failed');
+      setActionError(errorMessage(e) || 'Erase failed');
     } finally {
       setIsErasing(false);
     }
@@ -348,8 +350,8 @@ export function SmartCardDialog({
                 </SelectTrigger>
                 <SelectContent>
                   {readers.map((r) => (
-                    <SelectItem key={r} value={r}>
-                      {r}
+                    <SelectItem key={r.name} value={r.name}>
+                      {r.alias ?? r.name}
                     </SelectItem>
                   ))}
                 </SelectContent>
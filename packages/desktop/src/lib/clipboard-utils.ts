@@ -1,11 +1,23 @@
+import { invoke } from '@tauri-apps/api/core';
+
 const CLIPBOARD_CLEAR_TIMEOUT_MS = 60_000;
 
 /**
  * Copies text to clipboard and automatically clears it after a timeout.
  * Only clears if the clipboard still contains the copied text (avoids
  * overwriting something the user copied separately).
+ *
+ * The Rust backend does the copy and the clear, so the timer survives the
+ * window losing focus; the browser clipboard API is the fallback.
  */
 export async function copyWithAutoClear(text: string): Promise<void> {
+  try {
+    await invoke<void>('copy_secret_with_timeout', { value: text, timeoutMs: CLIPBOARD_CLEAR_TIMEOUT_MS });
+    return;
+  } catch {
+    // Fall back to the browser clipboard below.
+  }
+
   try {
     await navigator.clipboard.writeText(text);
   } catch {
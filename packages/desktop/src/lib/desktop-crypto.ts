@@ -8,17 +8,31 @@
  * ciphertext bytes and carries no key material), while all key derivation and
  * symmetric encryption/decryption happen natively in Rust.
  *
- * Wire format is bit-for-bit identical to the @noble/* JS implementation:
- *   Share string : seQRets|<salt_base64>|<share_data_base64>|sha256:<64_hex_chars>
- *   Encrypted blob : base64( nonce[24] || xchacha20_ciphertext_with_tag )
+ * Wire format:
+ *   Share string   : seQRets|<salt_base64>|<share_data_base64>|sha256:<64_hex_chars>
+ *   Default share  : base64( nonce[24] || xchacha20_ciphertext_with_tag )  (format version 0)
+ *   Headered blob  : base64( header[33] || nonce || ciphertext_with_tag )  (format version 1)
+ *
+ * Shares created with the default options (Argon2id defaults, XChaCha20-Poly1305, no
+ * label or padding, at most one keyfile) use the header-less version 0 layout of the
+ * @noble/* JS implementation, so the web app and seQRets Recover can still restore them.
+ * Their plaintext must be a real gzip stream — those readers unpack it with pako's
+ * `ungzip`, which rejects the backend's stored (uncompressed) fallback.
+ *
+ * The 33-byte header (magic, format version, algorithm id, Argon2 m/t/p, keyfile count,
+ * Argon2 variant, compressed / opaque flags and a key commitment) is only written for
+ * shares with non-default options and for the other blobs (vaults, sealed data,
+ * instructions), which only the native backend reads.
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { split, combine } from 'shamir-secret-sharing';
 import { buildSharePayload, parseSharePayload, appendShareHash, parseShare } from '@seqrets/crypto';
 // buffer-setup provides a Buffer polyfill for WKWebView (macOS) which does not
 // expose globalThis.Buffer. We still need Buffer for base64 encoding/decoding.
 import { Buffer } from './buffer-setup';
+import type { SeqretsError } from './errors';
 import type {
     CreateSharesRequest,
     CreateSharesResult,
@@ -33,7 +47,17 @@ import type {
 // Shape of the { salt, data } object returned by crypto_create / crypto_encrypt_blob.
 interface NativeCryptoResult {
     salt: string; // base64-encoded 16-byte salt
-    data: string; // base64-encoded (nonce[24] || ciphertext)
+    data: string; // base64-encoded (header[33] || nonce[24] || ciphertext)
+    verifier?: string; // base64 key verifier for verifyKeyMaterial
+    params_warning?: ParamsWarning; // set when the Argon2 settings are weak
+}
+
+// Present when the blob was encrypted with Argon2 settings below the
+// recommended floor. Encryption still succeeded; the UI should warn.
+export interface ParamsWarning {
+    params: CryptoCapabilities['default_params'];
+    minimum: CryptoCapabilities['default_params'];
+    message: string;
 }
 
 // ── Share creation ────────────────────────────────────────────────────────────
@@ -43,7 +67,8 @@ interface NativeCryptoResult {
  *
  * Flow:
  *   1. Build JSON payload (with BIP-39 entropy compaction if applicable)
- *   2. Rust: gzip → Argon2id key derivation → XChaCha20 encrypt → return (salt, nonce||ciphertext)
+ *   2. Rust: gzip → Argon2id key derivation → XChaCha20 encrypt → return (salt, nonce||ciphertext).
+ *      No header, so the web app and seQRets Recover can restore the shares.
  *   3. TypeScript: Shamir split the raw (nonce||ciphertext) bytes
  *   4. Format each share as `seQRets|<salt>|<shareData_base64>`
  */
@@ -61,7 +86,7 @@ export async function createShares(request: CreateSharesRequest): Promise<Create
     const { salt, data } = await invoke<NativeCryptoResult>('crypto_create', {
         jsonPayload,
         password,
-        keyfileB64: keyfile ?? null,
+        keyfilesB64: keyfile ? [keyfile] : null,
     });
 
     // Step 3: Shamir-split the raw (nonce||ciphertext) bytes.
@@ -163,7 +188,7 @@ export async function restoreSecret(request: RestoreSecretRequest): Promise<Rest
             saltB64: saltBase64,
             encryptedB64: Buffer.from(combinedBytes).toString('base64'),
             password,
-            keyfileB64: keyfile ?? null,
+            keyfilesB64: keyfile ? [keyfile] : null,
         });
     } catch (e: any) {
         // Surface Rust error (wrong password / keyfile / corrupted) cleanly.
@@ -180,31 +205,246 @@ export async function restoreSecret(request: RestoreSecretRequest): Promise<Rest
 
 // ── Vault encryption / decryption ─────────────────────────────────────────────
 
+// `padTo` pads the compressed vault to a multiple of that many bytes so its
+// ciphertext length doesn't reveal how much it holds.
 export async function encryptVault(
     jsonString: string,
-    password: string
+    password: string,
+    padTo?: number
 ): Promise<{ salt: string; data: string }> {
     const result = await invoke<NativeCryptoResult>('crypto_encrypt_blob', {
         json: jsonString,
         password,
-        keyfileB64: null,
+        keyfilesB64: null,
+        padTo: padTo ?? null,
     });
     return { salt: result.salt, data: result.data };
 }
 
+// `operationId` lets cancelKdf abort this unlock (and only this one).
 export async function decryptVault(
     salt: string,
     data: string,
-    password: string
+    password: string,
+    operationId?: string
 ): Promise<string> {
     return invoke<string>('crypto_decrypt_blob', {
         saltB64: salt,
         dataB64: data,
         password,
-        keyfileB64: null,
+        keyfilesB64: null,
+        operationId: operationId ?? null,
+    });
+}
+
+// Changes a vault's password (and keyfile, and optionally its Argon2 settings)
+// without the plaintext ever crossing into JS. Rejects with 'wrongPassword'
+// when the old key material doesn't open the vault.
+export async function rekeyVault(
+    salt: string,
+    data: string,
+    oldPassword: string,
+    newPassword: string,
+    oldKeyfile?: string,
+    newKeyfile?: string,
+    newParams?: CryptoCapabilities['default_params'],
+): Promise<{ salt: string; data: string }> {
+    const result = await invoke<NativeCryptoResult>('rekey_vault', {
+        saltB64: salt,
+        dataB64: data,
+        oldPassword,
+        oldKeyfilesB64: oldKeyfile ? [oldKeyfile] : null,
+        newPassword,
+        newKeyfilesB64: newKeyfile ? [newKeyfile] : null,
+        newParams: newParams ?? null,
+    });
+    return { salt: result.salt, data: result.data };
+}
+
+// One item of decryptVaultBatch: serde's encoding of Result<String, SeqretsError>.
+export type BatchItem = { Ok: string } | { Err: SeqretsError };
+
+// Decrypts many entries that share a salt and password with one Argon2
+// derivation. Results are in input order; a bad entry doesn't fail the rest.
+export async function decryptVaultBatch(
+    salt: string,
+    items: string[],
+    password: string,
+    keyfile?: string
+): Promise<BatchItem[]> {
+    return invoke<BatchItem[]>('crypto_decrypt_batch', {
+        saltB64: salt,
+        items,
+        password,
+        keyfilesB64: keyfile ? [keyfile] : null,
+    });
+}
+
+// Returned by crypto_restore / crypto_decrypt_blob when `withTiming` is set.
+export interface KdfTimed<T> {
+    value: T;
+    kdf_ms: number;
+}
+
+// Like decryptVault, but also reports how long Argon2 took so the UI can
+// warn about slow unlocks on this device next time.
+export async function decryptVaultTimed(
+    salt: string,
+    data: string,
+    password: string,
+    operationId?: string
+): Promise<KdfTimed<string>> {
+    return invoke<KdfTimed<string>>('crypto_decrypt_blob', {
+        saltB64: salt,
+        dataB64: data,
+        password,
+        keyfilesB64: null,
+        withTiming: true,
+        operationId: operationId ?? null,
+    });
+}
+
+// Returned by verify_vault. Never contains any of the vault's plaintext.
+export interface VaultReport {
+    decryptable: boolean;
+    parseable: boolean;
+    entry_count: number | null;
+    schema_version: number | null;
+    error: SeqretsError | null;
+}
+
+// "Test my backup": checks that an encrypted vault opens and parses without
+// returning its contents.
+export async function verifyVault(
+    salt: string,
+    data: string,
+    password: string
+): Promise<VaultReport> {
+    return invoke<VaultReport>('verify_vault', {
+        saltB64: salt,
+        dataB64: data,
+        password,
+        keyfilesB64: null,
+    });
+}
+
+// Checks that a password (and keyfile) reproduce the key behind a stored
+// verifier from crypto_create / crypto_encrypt_blob, without decrypting
+// anything. `params` must match the ones used at encryption.
+export async function verifyKeyMaterial(
+    password: string,
+    salt: string,
+    verifier: string,
+    keyfile?: string,
+    params?: CryptoCapabilities['default_params']
+): Promise<boolean> {
+    return invoke<boolean>('verify_key_material', {
+        password,
+        keyfilesB64: keyfile ? [keyfile] : null,
+        saltB64: salt,
+        verifierB64: verifier,
+        params: params ?? null,
+    });
+}
+
+// Constant-time comparison of two secrets in the backend (e.g. a re-typed
+// seed phrase against the original). Compared exactly; normalize first.
+export async function secretsMatch(a: string, b: string): Promise<boolean> {
+    return invoke<boolean>('secrets_match', { a, b });
+}
+
+// Aborts a slow unlock started with `operationId`: that restore or decrypt
+// rejects with kind 'cancelled', and nothing else is affected. Resolves
+// false when no operation with that id is running.
+export async function cancelKdf(operationId: string): Promise<boolean> {
+    return invoke<boolean>('cancel_kdf', { operationId });
+}
+
+// Payload of the `kdf-progress` event. `percent` is an estimate from elapsed
+// time (Argon2 reports no progress): it holds at 95 until the derivation
+// returns, then reaches 100 on success.
+export interface KdfProgress {
+    percent: number;
+    done: boolean;
+}
+
+// Turns `kdf-progress` events on, estimated against the target duration the
+// parameters were benchmarked for (`expectedMs`), or off with null.
+export async function setKdfProgress(expectedMs: number | null): Promise<void> {
+    return invoke<void>('set_kdf_progress', { expectedMs });
+}
+
+export function onKdfProgress(handler: (progress: KdfProgress) => void): Promise<UnlistenFn> {
+    return listen<KdfProgress>('kdf-progress', (event) => handler(event.payload));
+}
+
+// Short fingerprint of an encrypted vault ("3f9a-0c21-77de-b410"), computed
+// from the ciphertext alone so no password is needed. Re-encrypting the same
+// secret gives a different fingerprint.
+export async function vaultFingerprint(salt: string, data: string): Promise<string> {
+    return invoke<string>('vault_fingerprint', { saltB64: salt, dataB64: data });
+}
+
+// Adds or replaces one independently encrypted section of a sectioned vault
+// (pass null to start a new one) and returns the updated vault JSON.
+export async function encryptVaultSection(
+    vaultJson: string | null,
+    sectionId: string,
+    json: string,
+    password: string,
+    keyfile?: string,
+    name?: string,
+    params?: CryptoCapabilities['default_params'],
+): Promise<string> {
+    return invoke<string>('encrypt_vault_section', {
+        vaultJson,
+        sectionId,
+        name: name ?? null,
+        json,
+        password,
+        keyfilesB64: keyfile ? [keyfile] : null,
+        params: params ?? null,
+    });
+}
+
+// Decrypts a single section of a sectioned vault. Sections encrypted under a
+// different password stay opaque.
+export async function decryptVaultSection(
+    vaultJson: string,
+    sectionId: string,
+    password: string,
+    keyfile?: string,
+): Promise<string> {
+    return invoke<string>('decrypt_vault_section', {
+        vaultJson,
+        sectionId,
+        password,
+        keyfilesB64: keyfile ? [keyfile] : null,
     });
 }
 
+// One entry of a VaultDiff: a section, or a plain vault's single secret.
+export interface EntryDiff {
+    id: string;
+    label: string;
+    change: 'added' | 'removed' | 'modified' | 'unchanged';
+    changed: boolean; // content differs; false for a rename alone
+}
+
+export interface VaultDiff {
+    added: string[];
+    removed: string[];
+    modified: string[];
+    entries: EntryDiff[];
+    identical: boolean;
+}
+
+// Compares two vaults (both sectioned, or both plain exports) and reports
+// which entries differ, by label. No secret values are returned.
+export async function diffVaults(vaultAJson: string, vaultBJson: string): Promise<VaultDiff> {
+    return invoke<VaultDiff>('diff_vaults', { vaultAJson, vaultBJson });
+}
+
 // ── Instructions encryption / decryption ──────────────────────────────────────
 
 export async function encryptInstructions(
@@ -216,7 +456,7 @@ export async function encryptInstructions(
     const result = await invoke<NativeCryptoResult>('crypto_encrypt_blob', {
         json,
         password,
-        keyfileB64: keyfile ?? null,
+        keyfilesB64: keyfile ? [keyfile] : null,
     });
     return { salt: result.salt, data: result.data };
 }
@@ -231,8 +471,188 @@ export async function decryptInstructions(
         saltB64: parsed.salt,
         dataB64: parsed.data,
         password,
-        keyfileB64: keyfile ?? null,
+        keyfilesB64: keyfile ? [keyfile] : null,
     });
 
     return JSON.parse(jsonResult) as DecryptInstructionResult;
 }
+
+// ── Backend capabilities ──────────────────────────────────────────────────────
+
+// Returned by crypto_capabilities: what this build of the backend supports.
+export interface CryptoCapabilities {
+    ciphers: { id: number; name: string; nonce_length: number }[];
+    default_cipher: string;
+    kdfs: string[];
+    encodings: string[];
+    format_version: number;
+    readable_format_versions: number[];
+    default_params: {
+        m_cost: number;
+        t_cost: number;
+        p_cost: number;
+        argon2_variant: 'argon2id' | 'argon2i' | 'argon2d';
+    };
+    max_keyfiles: number;
+    stream_format_version: number;
+    payload_schema_version: number;
+    applet_aid: string;
+    pcsc_available: boolean;
+}
+
+export async function getCryptoCapabilities(): Promise<CryptoCapabilities> {
+    return invoke<CryptoCapabilities>('crypto_capabilities');
+}
+
+// ── Nonce audit ───────────────────────────────────────────────────────────────
+
+// Returned by check_nonce_uniqueness. Indices refer to the blobs passed in.
+export interface NonceReport {
+    checked: number;
+    unreadable: number[];
+    collisions: number[][]; // groups of distinct blobs sharing one nonce
+    unique: boolean;
+}
+
+/** Checks a batch of encrypted blobs for repeated nonces (a sign of a broken RNG). */
+export async function checkNonceUniqueness(blobs: string[]): Promise<NonceReport> {
+    return invoke<NonceReport>('check_nonce_uniqueness', { blobs });
+}
+
+// ── Keyfile shares ────────────────────────────────────────────────────────────
+
+// Splits a base64 keyfile into Shamir shares ("seQRets-keyfile|..."), to be
+// stored on cards as "keyfile" items; any `threshold` of them rebuild it.
+export async function splitKeyfile(keyfileB64: string, threshold: number, shares: number): Promise<string[]> {
+    return invoke<string[]>('split_keyfile', { keyfileB64, threshold, shares });
+}
+
+// Rebuilds the base64 keyfile from a threshold of splitKeyfile shares.
+export async function combineKeyfile(shares: string[]): Promise<string> {
+    return invoke<string>('combine_keyfile', { shares });
+}
+
+// ── Share import ──────────────────────────────────────────────────────────────
+
+// Returned by import_shares_from_text, one per share found. `line` is 1-based.
+export interface ParsedShare {
+    line: number;
+    share: string;
+    index: number | null;
+    threshold: number | null;
+    group_id: string | null;
+    error: string | null; // why this share can't be combined
+}
+
+// Parses newline- or comma-separated share text (e.g. transcribed from paper);
+// pass the `share` of entries without an `error` to shamir_combine.
+export async function importSharesFromText(text: string): Promise<ParsedShare[]> {
+    return invoke<ParsedShare[]>('import_shares_from_text', { text });
+}
+
+// ── Cracking estimates ────────────────────────────────────────────────────────
+
+// Returned by estimate_password_strength (zxcvbn).
+export interface PasswordStrength {
+    entropy_bits: number;
+    score: number; // 0 (trivially guessable) to 4
+    warning: string | null;
+    suggestions: string[];
+}
+
+export interface CrackRange {
+    low: number;
+    high: number;
+}
+
+// Returned by estimate_bruteforce_cost. Order-of-magnitude only: show the
+// ranges and `assumptions`, never a single figure.
+export interface CrackEstimate {
+    params: CryptoCapabilities['default_params'];
+    password_entropy_bits: number;
+    bytes_per_guess: number;
+    scenarios: {
+        attacker: string;
+        guesses_per_second: CrackRange;
+        seconds: CrackRange;
+        cost_usd: CrackRange;
+        time_label: string; // e.g. "~3 days to ~1 month"
+    }[];
+    assumptions: string[];
+}
+
+export async function estimatePasswordStrength(password: string): Promise<PasswordStrength> {
+    return invoke<PasswordStrength>('estimate_password_strength', { password });
+}
+
+export async function estimateBruteforceCost(
+    params: CryptoCapabilities['default_params'],
+    passwordEntropyBits: number
+): Promise<CrackEstimate> {
+    return invoke<CrackEstimate>('estimate_bruteforce_cost', { params, passwordEntropyBits });
+}
+
+// For the setup screen: "at these settings, this password would take ~X to crack".
+export async function estimateCrackTime(
+    password: string,
+    params: CryptoCapabilities['default_params']
+): Promise<{ strength: PasswordStrength; estimate: CrackEstimate }> {
+    const strength = await estimatePasswordStrength(password);
+    const estimate = await estimateBruteforceCost(params, strength.entropy_bits);
+    return { strength, estimate };
+}
+
+// ── Shared (multi-recipient) vaults ───────────────────────────────────────────
+
+export interface RecipientPassword {
+    name: string; // stored in the clear, e.g. "Alice"
+    password: string;
+    keyfiles_b64?: string[];
+}
+
+// Returned by encrypt_multi_recipient; store it as JSON and pass it back whole.
+export interface MultiRecipientVault {
+    version: number;
+    data: string; // payload sealed under the random content key
+    recipients: { name: string; salt: string; data: string }[];
+    params_warning?: ParamsWarning;
+}
+
+// Encrypts `json` once and wraps its key for each recipient's password, so
+// any one of them can open it. One Argon2 derivation per recipient.
+export async function encryptMultiRecipient(
+    json: string,
+    recipients: RecipientPassword[],
+    label?: string
+): Promise<MultiRecipientVault> {
+    return invoke<MultiRecipientVault>('encrypt_multi_recipient', { json, recipients, label });
+}
+
+// Opens a shared vault with any one recipient's password (and keyfiles).
+export async function decryptMultiRecipient(
+    blob: MultiRecipientVault,
+    password: string,
+    keyfilesB64?: string[],
+    label?: string
+): Promise<string> {
+    return invoke<string>('decrypt_multi_recipient', { blob, password, keyfilesB64, label });
+}
+
+// ── Opaque blobs ──────────────────────────────────────────────────────────────
+
+// Wraps an already-protected blob (e.g. a vault encrypted elsewhere) in one
+// XChaCha20 layer without compressing it; the header is flagged opaque.
+export async function storeOpaque(dataB64: string, password: string, keyfilesB64?: string[], label?: string): Promise<{ salt: string; data: string }> {
+    const result = await invoke<NativeCryptoResult>('store_opaque', { dataB64, password, keyfilesB64, label });
+    return { salt: result.salt, data: result.data };
+}
+
+// Returns the stored blob as base64; refuses anything not from storeOpaque.
+export async function openOpaque(salt: string, data: string, password: string, keyfilesB64?: string[], label?: string): Promise<string> {
+    return invoke<string>('open_opaque', { saltB64: salt, dataB64: data, password, keyfilesB64, label });
+}
+
+// True when the blob's header says it is opaque, i.e. not vault JSON. Needs no password.
+export async function isOpaqueBlob(data: string): Promise<boolean> {
+    return invoke<boolean>('blob_is_opaque', { dataB64: data });
+}
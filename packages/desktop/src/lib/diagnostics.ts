@@ -0,0 +1,32 @@
+/**
+ * Offline self-test for bug reports. The report contains no secrets or
+ * user data, so "copy diagnostics" can paste it as JSON.
+ */
+import { invoke } from '@tauri-apps/api/core';
+import type { SeqretsError } from './errors';
+import type { PcscStatus } from './smartcard';
+
+export interface DiagnosticCheck {
+  name: string;
+  passed: boolean;
+  duration_ms: number;
+  error: SeqretsError | null;
+}
+
+export interface DiagnosticsReport {
+  app_version: string;
+  tauri_version: string;
+  os: string;
+  arch: string;
+  crypto_format_version: number;
+  stream_format_version: number;
+  payload_schema_version: number;
+  checks: DiagnosticCheck[];
+  pcsc: PcscStatus;
+}
+
+/** Run the crypto round trip and PC/SC checks (a second or two). */
+export const runDiagnostics = () => invoke<DiagnosticsReport>('run_diagnostics');
+
+/** The report as pretty-printed JSON for the clipboard. */
+export const formatDiagnostics = (report: DiagnosticsReport) => JSON.stringify(report, null, 2);
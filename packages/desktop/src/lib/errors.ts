@@ -0,0 +1,46 @@
+/**
+ * Errors returned by the Rust backend.
+ *
+ * Every Tauri command rejects with a `{ kind, message }` object. Branch on
+ * `kind` to pick the right recovery action (or translation) and fall back to
+ * the English `message` for display.
+ */
+
+export type SeqretsErrorKind =
+  | 'wrongPassword'
+  | 'headerTampered'
+  | 'appletLocked'
+  | 'wrongPin'
+  | 'cardLocked'
+  | 'pinRequired'
+  | 'storageFull'
+  | 'noReaders'
+  | 'readerBusy'
+  | 'noCardPresented'
+  | 'corruptData'
+  | 'invalidInput'
+  | 'paramsTooLarge'
+  | 'cancelled'
+  | 'timeout'
+  | 'other';
+
+export interface SeqretsError {
+  kind: SeqretsErrorKind;
+  message: string;
+}
+
+/** True for the error objects rejected by Tauri commands. */
+export function isSeqretsError(e: unknown): e is SeqretsError {
+  return (
+    typeof e === 'object' &&
+    e !== null &&
+    typeof (e as SeqretsError).kind === 'string' &&
+    typeof (e as SeqretsError).message === 'string'
+  );
+}
+
+/** User-facing text for anything thrown by `invoke` or by plain JS code. */
+export function errorMessage(e: unknown): string {
+  if (isSeqretsError(e) || e instanceof Error) return e.message;
+  return e == null ? '' : String(e);
+}
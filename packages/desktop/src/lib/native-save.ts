@@ -3,6 +3,7 @@
  * Wraps @tauri-apps/plugin-dialog (save picker) and
  * @tauri-apps/plugin-fs (binary/text write).
  */
+import { invoke } from '@tauri-apps/api/core';
 import { save } from '@tauri-apps/plugin-dialog';
 import { writeFile, writeTextFile } from '@tauri-apps/plugin-fs';
 
@@ -52,6 +53,49 @@ export async function saveTextFileNative(
   return filePath;
 }
 
+/**
+ * Show a native Save As dialog and save an encrypted vault (salt + data from
+ * encryptVault) as a .seqrets file. The backend writes a temp file and renames
+ * it into place, so a crash never leaves a truncated vault behind; the file it
+ * replaces is kept as <name>.bak.1 (up to three generations).
+ * Returns the chosen file path, or null if the user cancelled.
+ */
+export async function saveEncryptedVaultNative(
+  defaultName: string,
+  salt: string,
+  data: string,
+): Promise<string | null> {
+  const filePath = await save({
+    defaultPath: defaultName,
+    filters: SEQRETS_FILTERS,
+  });
+
+  if (!filePath) return null;
+
+  return invoke<string>('save_vault_atomic', { path: filePath, saltB64: salt, dataB64: data });
+}
+
+/** A `<name>.bak.N` vault backup kept by save_vault_atomic. */
+export interface VaultBackup {
+  path: string;
+  generation: number; // 1 = most recent
+  size: number;
+  modified_ms: number;
+}
+
+/** List the vault backups in a directory, most recently modified first. */
+export async function listVaultBackups(dir: string): Promise<VaultBackup[]> {
+  return invoke<VaultBackup[]>('list_vault_backups', { dir });
+}
+
+/**
+ * Restore a backup over the vault at `dest`. The vault being replaced is
+ * backed up first, so the restore can itself be undone.
+ */
+export async function restoreVaultBackup(backupPath: string, dest: string): Promise<string> {
+  return invoke<string>('restore_vault_backup', { backupPath, dest });
+}
+
 // ── Data conversion helpers ────────────────────────────────────────────
 
 /** Convert a canvas data URL (e.g. "data:image/png;base64,...") to Uint8Array. */
@@ -104,3 +148,11 @@ export const PDF_FILTERS: FileFilter[] = [
 export const ALL_FILES_FILTER: FileFilter[] = [
   { name: 'All Files', extensions: ['*'] },
 ];
+
+/**
+ * Overwrite a file with random bytes, fsync and delete it. Best-effort on
+ * SSDs and copy-on-write filesystems, which may keep older copies.
+ */
+export async function secureDelete(path: string): Promise<void> {
+  return invoke<void>('secure_delete', { path });
+}
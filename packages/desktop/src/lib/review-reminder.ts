@@ -22,6 +22,7 @@
 // Security: see review_reminder.rs and docs/ARCHITECTURE.md#review-reminder-sidecar.
 
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from './errors';
 
 // ── Constants ──────────────────────────────────────────────────────
 
@@ -166,7 +167,7 @@ export async function getReminderState(now: Date = new Date()): Promise<Reminder
   try {
     raw = await readSidecarRaw();
   } catch (e: unknown) {
-    return { kind: 'corrupt', error: e instanceof Error ? e.message : String(e) };
+    return { kind: 'corrupt', error: errorMessage(e) };
   }
 
   if (raw === null) return { kind: 'missing' };
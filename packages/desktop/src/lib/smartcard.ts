@@ -6,12 +6,20 @@
  * can be stored on a single card as a JSON array.
  */
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { SeqretsError } from './errors';
 
 // ── Constants ────────────────────────────────────────────────────────────
 
 /** Default card capacity (bytes) — used as an estimate when card is not connected. */
 export const DEFAULT_CARD_CAPACITY = 8192;
 
+/**
+ * Write count at which the UI starts warning about EEPROM wear. Well below the
+ * ~500k erase cycles JCOP3 EEPROM is rated for; pass another value to `isNearWriteLimit`.
+ */
+export const DEFAULT_WRITE_COUNT_WARNING = 100_000;
+
 // ── Types ───────────────────────────────────────────────────────────────
 
 /** A single item stored on the card. */
@@ -19,6 +27,10 @@ export interface CardItem {
   item_type: string; // "share" | "vault" | "instructions"
   label: string;
   data: string;
+  /** Share sealed to this card with a passphrase; pass it to the read calls to unwrap. */
+  wrapped?: boolean;
+  /** Fingerprint of the vault this item was distributed for (see `cardMatchesVault`). */
+  vault_id?: string | null;
 }
 
 /** Summary of an item (without full data) for status display. */
@@ -27,6 +39,8 @@ export interface CardItemSummary {
   item_type: string;
   label: string;
   data_size: number;
+  wrapped: boolean;
+  vault_id: string | null;
 }
 
 /** Card status including multi-item information. */
@@ -41,21 +55,205 @@ export interface CardStatus {
   pin_retries_remaining: number;
   free_bytes_estimate: number;
   wipe_protected: boolean;
+  puk_set: boolean;
+  puk_retries_remaining: number;
+  /** "major.minor" from GET_VERSION; "1.0" for applets that predate it. */
+  applet_version: string;
+  /** Refused with 6982 (PIN needed). If the status itself was refused, the other fields are unknown. */
+  pin_required: boolean;
+  interface: CardInterface;
+  /** GlobalPlatform lifecycle of the applet, from the SELECT FCI when the card manager reports it. */
+  lifecycle: 'installed' | 'selectable' | 'personalized' | 'locked' | 'unknown';
+  /** Completed writes and erases of the data area; null when the applet has no counter (before 1.5). */
+  write_count: number | null;
+}
+
+/** True when the card reports a write count at or above `threshold`. */
+export const isNearWriteLimit = (status: CardStatus, threshold = DEFAULT_WRITE_COUNT_WARNING) =>
+  status.write_count !== null && status.write_count >= threshold;
+
+/** From the ATR (PC/SC part 3 contactless form) or, failing that, the reader name. */
+export type CardInterface = 'contact' | 'contactless' | 'unknown';
+
+/** Payload of `card-write-progress` / `card-read-progress` (chunk is 1-based). */
+export interface ChunkProgress {
+  chunk: number;
+  total: number;
 }
 
+/**
+ * Payload of `apdu-trace`: one APDU and its response in hex, with PINs and
+ * data masked as `[N bytes]`. `response` is null when the card never answered.
+ */
+export interface ApduTrace {
+  command: string;
+  response: string | null;
+}
+
+// ── Progress events ─────────────────────────────────────────────────────
+
+/** Subscribe to per-chunk progress while items are written to a card. */
+export const onCardWriteProgress = (handler: (progress: ChunkProgress) => void): Promise<UnlistenFn> =>
+  listen<ChunkProgress>('card-write-progress', (event) => handler(event.payload));
+
+/** Subscribe to per-chunk progress while card data is read. */
+export const onCardReadProgress = (handler: (progress: ChunkProgress) => void): Promise<UnlistenFn> =>
+  listen<ChunkProgress>('card-read-progress', (event) => handler(event.payload));
+
+/** Subscribe to APDU traces while `setApduTrace(true)` is in effect. */
+export const onApduTrace = (handler: (trace: ApduTrace) => void): Promise<UnlistenFn> =>
+  listen<ApduTrace>('apdu-trace', (event) => handler(event.payload));
+
+/** One card in a `writeSharesToCards` batch. */
+export interface ShareAssignment {
+  reader: string;
+  share: string;
+  label: string;
+  pin: string | null;
+  /** Wraps the share to the card; needed again to read it back. */
+  passphrase?: string | null;
+  /** Stored with the share so `cardMatchesVault` can recognise the card. */
+  vault_id?: string | null;
+  /** Signs the card's items after the share is added (see `signCard`). */
+  signing_password?: string | null;
+}
+
+/** Per-card outcome of a `writeSharesToCards` batch. */
+export interface CardWriteReport {
+  reader: string;
+  label: string;
+  success: boolean;
+  error: SeqretsError | null;
+}
+
+/** Result of `verifyCardAuthenticity`. */
+export interface CardAuthenticity {
+  /** The card holds a signature. */
+  signed: boolean;
+  /** The signature matches the card's items under the given password. */
+  authentic: boolean;
+}
+
+/** One card in a `recoverFromCards` call. */
+export interface RecoveryCard {
+  reader: string;
+  pin: string | null;
+  /** Unwraps a share written with a passphrase. */
+  passphrase?: string | null;
+}
+
+/** Per-card outcome of `recoverFromCards`; `index` is set when the card's share was used. */
+export interface CardRecoveryReport {
+  reader: string;
+  index: number | null;
+  error: SeqretsError | null;
+}
+
+/** Result of `recoverFromCards`; `secret_b64` is null (and `error` set) when too few good shares were read. */
+export interface RecoveryResult {
+  secret_b64: string | null;
+  threshold: number | null;
+  shares_used: number;
+  cards: CardRecoveryReport[];
+  error: SeqretsError | null;
+}
+
+/** Outcome of `distributeShare`; the QR and the card write succeed or fail independently. */
+export interface DistributeResult {
+  /** Base64 PNG of the share's QR code, or null if rendering failed. */
+  qr_png: string | null;
+  qr_error: SeqretsError | null;
+  card_written: boolean;
+  card_error: SeqretsError | null;
+}
+
+/** PC/SC health: service down, service up without readers, or ready. */
+export type PcscStatus =
+  | { state: 'serviceUnavailable'; message: string }
+  | { state: 'noReaders' }
+  | { state: 'ready'; readers: string[] };
+
 // ── Reader operations ───────────────────────────────────────────────────
 
+/** A reader: `name` is what the other calls take, `alias` what to show when set. */
+export interface ReaderEntry {
+  name: string;
+  alias: string | null;
+}
+
 /** List all available PC/SC smart card readers. */
-export const listReaders = () => invoke<string[]>('list_readers');
+export const listReaders = () => invoke<ReaderEntry[]>('list_readers');
+
+/**
+ * Wait up to `timeoutMs` (max 60 s) for a reader another app holds exclusively.
+ * Rejects with kind 'readerBusy' if it's still held, or at once on other errors.
+ */
+export const waitForReader = (reader: string, timeoutMs: number) =>
+  invoke<void>('wait_for_reader', { reader, timeoutMs });
+
+/**
+ * Name a reader for display; null or '' removes the alias. Matches the reader
+ * even after its index suffix changes. Session-only: set saved aliases again at startup.
+ */
+export const setReaderAlias = (reader: string, alias: string | null) =>
+  invoke<void>('set_reader_alias', { reader, alias });
+
+/** Every alias, keyed by reader name without the index suffix. */
+export const getReaderAliases = () => invoke<Record<string, string>>('get_reader_aliases');
+
+/** One reader from `enumerateCards`; `has_data` and `label` are null when the card won't say without a PIN. */
+export interface ReaderCardInfo {
+  reader: string;
+  alias: string | null;
+  card_present: boolean;
+  applet_present: boolean;
+  has_data: boolean | null;
+  label: string | null;
+}
+
+/** Every reader with its card and applet presence, in one call and without a PIN. */
+export const enumerateCards = () => invoke<ReaderCardInfo[]>('enumerate_cards');
+
+/** Whether the PC/SC service is running and has readers attached. */
+export const pcscStatus = () => invoke<PcscStatus>('pcsc_status');
 
 // ── Status ──────────────────────────────────────────────────────────────
 
-/** Get the status of the card in the specified reader, including item summaries. */
-export const getCardStatus = (reader: string, pin?: string | null) =>
-  invoke<CardStatus>('get_card_status', { reader, pin: pin || null });
+/**
+ * Get the status of the card in the specified reader, including item summaries.
+ *
+ * `waitForCard` (ms, up to two minutes) waits for a card to be tapped or
+ * inserted first, rejecting with kind 'noCardPresented' if none arrives; the
+ * write and read calls below take it too.
+ */
+export const getCardStatus = (reader: string, pin?: string | null, waitForCard?: number | null) =>
+  invoke<CardStatus>('get_card_status', { reader, pin: pin || null, waitForCard: waitForCard || null });
 
 // ── Write operations ────────────────────────────────────────────────────
 
+/** One `preflightWrite` check; `error` is null when it passed. */
+export interface PreflightCheck {
+  check: 'payload' | 'reader' | 'applet' | 'pin' | 'capacity';
+  passed: boolean;
+  error: SeqretsError | null;
+}
+
+export interface PreflightReport {
+  ready: boolean;
+  checks: PreflightCheck[];
+}
+
+/**
+ * Check, without touching stored data, that appending an item of
+ * `dataLen` bytes would succeed. Enable the write only when `ready`.
+ */
+export const preflightWrite = (
+  reader: string,
+  dataLen: number,
+  dataType: string,
+  pin?: string | null,
+) => invoke<PreflightReport>('preflight_write', { reader, dataLen, dataType, pin: pin || null });
+
 /** Write an item to the card, appending to existing items. */
 export const writeItemToCard = (
   reader: string,
@@ -63,22 +261,103 @@ export const writeItemToCard = (
   data: string,
   label: string,
   pin?: string | null,
-) => invoke<void>('write_item_to_card', { reader, itemType, data, label, pin: pin || null });
+  passphrase?: string | null,
+  vaultId?: string | null,
+  waitForCard?: number | null,
+) =>
+  invoke<void>('write_item_to_card', {
+    reader,
+    itemType,
+    data,
+    label,
+    pin: pin || null,
+    passphrase: passphrase || null,
+    vaultId: vaultId || null,
+    waitForCard: waitForCard || null,
+  });
 
 // ── Read operations ─────────────────────────────────────────────────────
 
 /** Read all items from the card. */
-export const readCardItems = (reader: string, pin?: string | null) =>
-  invoke<CardItem[]>('read_card_items', { reader, pin: pin || null });
+export const readCardItems = (
+  reader: string,
+  pin?: string | null,
+  passphrase?: string | null,
+  waitForCard?: number | null,
+) =>
+  invoke<CardItem[]>('read_card_items', {
+    reader,
+    pin: pin || null,
+    passphrase: passphrase || null,
+    waitForCard: waitForCard || null,
+  });
+
+/** True when an item on the card was written for the vault with this fingerprint. */
+export const cardMatchesVault = (reader: string, pin: string | null, vaultFingerprint: string) =>
+  invoke<boolean>('card_matches_vault', { reader, pin, vaultFingerprint });
+
+/**
+ * Sign the card's items with a key derived from the master password, so
+ * `verifyCardAuthenticity` can detect contents changed without it. Sign again
+ * after any later write.
+ */
+export const signCard = (reader: string, password: string, pin: string | null) =>
+  invoke<void>('sign_card', { reader, password, pin });
+
+/** Check the card's signature; a wrong password also reports `authentic: false`. */
+export const verifyCardAuthenticity = (reader: string, password: string, pin: string | null) =>
+  invoke<CardAuthenticity>('verify_card_authenticity', { reader, password, pin });
+
+/**
+ * Read the share on each card and recombine them into the still-encrypted vault
+ * blob. Cards that can't be read or don't belong are reported and skipped.
+ */
+export const recoverFromCards = (readers: RecoveryCard[]) =>
+  invoke<RecoveryResult>('recover_from_cards', { readers });
 
 /** Read a single item by index from the card. */
-export const readCardItem = (reader: string, index: number, pin?: string | null) =>
-  invoke<CardItem>('read_card_item', { reader, index, pin: pin || null });
+export const readCardItem = (
+  reader: string,
+  index: number,
+  pin?: string | null,
+  passphrase?: string | null,
+  waitForCard?: number | null,
+) =>
+  invoke<CardItem>('read_card_item', {
+    reader,
+    index,
+    pin: pin || null,
+    passphrase: passphrase || null,
+    waitForCard: waitForCard || null,
+  });
+
+/**
+ * Read the keyfile stored on the card as base64 for the crypto commands'
+ * `keyfilesB64`. Pass `label` when the card holds more than one keyfile.
+ */
+export const keyfileFromCard = (reader: string, pin?: string | null, label?: string | null) =>
+  invoke<string>('keyfile_from_card', { reader, pin: pin || null, label: label || null });
 
 /** Write a complete set of items to the card, replacing any existing data. */
 export const writeAllItems = (reader: string, items: CardItem[], pin?: string | null) =>
   invoke<void>('write_all_items', { reader, items, pin: pin || null });
 
+// ── Backup and migration ────────────────────────────────────────────────
+
+/** A password-encrypted snapshot of a whole card, from `exportCard`. */
+export interface CardBackup {
+  salt: string;
+  data: string;
+}
+
+/** Encrypt every item on the card into one backup for moving to another card. */
+export const exportCard = (reader: string, password: string, pin?: string | null) =>
+  invoke<CardBackup>('export_card', { reader, pin: pin || null, password, params: null });
+
+/** Restore an `exportCard` backup to a card, replacing its data. */
+export const importCard = (reader: string, backup: CardBackup, password: string, pin?: string | null) =>
+  invoke<void>('import_card', { reader, backup, password, pin: pin || null });
+
 // ── Delete operations ───────────────────────────────────────────────────
 
 /** Delete a single item by index (rewrites remaining items). */
@@ -93,12 +372,98 @@ export const eraseCard = (reader: string, pin?: string | null) =>
 export const forceEraseCard = (reader: string) =>
   invoke<void>('force_erase_card', { reader });
 
+/**
+ * Cancel the chunked read/write in flight. The pending command rejects with
+ * "Operation cancelled." and an interrupted write erases its partial data.
+ * No-op if nothing is running.
+ */
+/**
+ * Write one share to each card in turn, verifying each write. Failures don't
+ * abort the batch — check each report's `success` and retry the failed readers.
+ */
+export const writeSharesToCards = (assignments: ShareAssignment[]) =>
+  invoke<CardWriteReport[]>('write_shares_to_cards', { assignments });
+
+/**
+ * Render a share's QR and append (and verify) it on a card in one call. The QR
+ * comes back even when the card write fails.
+ */
+export const distributeShare = (
+  reader: string,
+  share: string,
+  label: string,
+  pin: string | null,
+  qrEcc: 'low' | 'medium' | 'quartile' | 'high',
+  vaultId?: string | null,
+) => invoke<DistributeResult>('distribute_share', { reader, share, label, pin, qrEcc, vaultId: vaultId || null });
+
+// ── Guided enrollment ───────────────────────────────────────────────────
+
+/** One share of an enrollment. */
+export interface EnrolledShare {
+  index: number;
+  label: string;
+  enrolled: boolean;
+  reader: string | null;
+}
+
+/** Progress of the enrollment started by `beginEnrollment`. */
+export interface EnrollmentStatus {
+  active: boolean;
+  shares: EnrolledShare[];
+  /** Share the next `enrollNextCard` writes; null once complete. */
+  next_index: number | null;
+  complete: boolean;
+}
+
+/** Start enrolling one share per card, replacing any enrollment in progress. */
+export const beginEnrollment = (shares: string[], labels: string[], vaultId?: string | null) =>
+  invoke<EnrollmentStatus>('begin_enrollment', { shares, labels, vaultId: vaultId || null });
+
+/**
+ * Write and verify the next pending share on the card in `reader`. Refuses a
+ * card that already holds an enrolled share; retry with another card after a failure.
+ */
+export const enrollNextCard = (reader: string, pin?: string | null) =>
+  invoke<EnrollmentStatus>('enroll_next_card', { reader, pin: pin || null });
+
+export const enrollmentStatus = () => invoke<EnrollmentStatus>('enrollment_status');
+
+/** Drop the enrollment and the shares it holds. */
+export const endEnrollment = () => invoke<void>('end_enrollment');
+
+export const cancelCardOperation = () => invoke<void>('cancel_card_operation');
+
+/**
+ * Factory-reset the card (data, label, PIN, PUK, wipe protection). Irreversible.
+ * Requires the PUK if one is set; otherwise `confirm` must be true.
+ */
+export const factoryReset = (reader: string, puk: string | null, confirm: boolean) =>
+  invoke<void>('factory_reset', { reader, puk, confirm });
+
 // ── PIN operations ──────────────────────────────────────────────────────
 
 /** Verify the PIN on the card. */
 export const verifyPin = (reader: string, pin: string) =>
   invoke<void>('verify_pin', { reader, pin });
 
+/**
+ * Verify the PIN on the reader's PIN pad. Resolves false, without prompting,
+ * when the reader has none.
+ */
+export const verifyPinOnReader = (reader: string) =>
+  invoke<boolean>('verify_pin_on_reader', { reader });
+
+/**
+ * Verify on the PIN pad when the reader has one; otherwise ask for the PIN
+ * with `askPin` and send it from the host.
+ */
+export async function verifyPinPreferringPinPad(reader: string, askPin: () => Promise<string>): Promise<void> {
+  if (!(await verifyPinOnReader(reader))) {
+    await verifyPin(reader, await askPin());
+  }
+}
+
 /** Set the initial PIN on the card (only works if no PIN is set). */
 export const setPin = (reader: string, pin: string) =>
   invoke<void>('set_pin', { reader, pin });
@@ -107,8 +472,121 @@ export const setPin = (reader: string, pin: string) =>
 export const changePin = (reader: string, oldPin: string, newPin: string) =>
   invoke<void>('change_pin', { reader, oldPin, newPin });
 
+/** Set the PUK used to unblock a locked PIN (only works if no PUK is set). */
+export const setPuk = (reader: string, puk: string, pin?: string | null) =>
+  invoke<void>('set_puk', { reader, puk, pin: pin || null });
+
+/** Unblock a locked PIN with the PUK and set a new PIN. */
+export const unblockPin = (reader: string, puk: string, newPin: string) =>
+  invoke<void>('unblock_pin', { reader, puk, newPin });
+
 // ── Wipe protection ────────────────────────────────────────────────────
 
 /** Enable or disable wipe protection (requires PIN). */
 export const setWipeProtect = (reader: string, pin: string, enabled: boolean) =>
   invoke<void>('set_wipe_protect', { reader, pin, enabled });
+
+// ── Applet configuration ────────────────────────────────────────────────
+
+/** Override the applet AID used for SELECT (hex). Pass null to restore the default. */
+export const setAppletAid = (aidHex: string | null) =>
+  invoke<void>('set_applet_aid', { aidHex });
+
+/**
+ * Override the per-APDU timeout (default 5 s, 100–120000 ms). Pass null to
+ * restore the default. Unresponsive cards then fail with kind `timeout`.
+ */
+export const setTransmitTimeout = (timeoutMs: number | null) =>
+  invoke<void>('set_transmit_timeout', { timeoutMs });
+
+/**
+ * Force the STORE_DATA chunk size (16–255 bytes) instead of sizing it from the
+ * reader, for debugging readers that misreport their limit. Pass null to go
+ * back to the reader's figure; applets before 1.3 only accept 240.
+ */
+export const setWriteChunkSize = (chunkSize: number | null) =>
+  invoke<void>('set_write_chunk_size', { chunkSize });
+
+/** Emit an `apdu-trace` event for every APDU sent, for debugging card problems. */
+export const setApduTrace = (enabled: boolean) => invoke<void>('set_apdu_trace', { enabled });
+
+/**
+ * Refuse writes unless the card is in a contact slot (off by default). Cards
+ * whose interface is unknown are refused too. Checked by `preflightWrite`.
+ */
+export const setRequireContact = (required: boolean) =>
+  invoke<void>('set_require_contact', { required });
+
+/**
+ * Make the session read-only: writes, erases, deletes, imports and PIN/PUK
+ * changes then fail with kind `invalidInput` before reaching the card. A
+ * safety net for recovery and viewing, not a security boundary.
+ */
+export const setReadOnly = (readOnly: boolean) => invoke<void>('set_read_only', { readOnly });
+
+/** One line of the card audit log. Never contains PINs, shares or labels. */
+export interface AuditEntry {
+  /** Seconds since the Unix epoch. */
+  timestamp: number;
+  command: string;
+  reader: string;
+  /** "ok" or a SeqretsError kind. */
+  result: string;
+  prev_hash: string;
+  hash: string;
+}
+
+export interface AuditLog {
+  entries: AuditEntry[];
+  /** False when a line was edited, removed or reordered. */
+  intact: boolean;
+  broken_at: number | null;
+}
+
+/**
+ * Turn the card audit log on or off (off by default; call at startup with
+ * the user's setting). Turning it off keeps the existing log.
+ */
+export const setAuditLogging = (enabled: boolean) => invoke<void>('set_audit_logging', { enabled });
+
+/** Read the card audit log and check its hash chain. */
+export const readAuditLog = () => invoke<AuditLog>('read_audit_log');
+
+/** Bits of `AppletInfo.capabilities`. */
+export const APPLET_CAPABILITY = {
+  pin: 0x0001,
+  wipeProtect: 0x0002,
+  puk: 0x0004,
+  factoryReset: 0x0008,
+  getVersion: 0x0010,
+  variableWriteChunk: 0x0020,
+} as const;
+
+export interface AppletInfo {
+  aid: string;
+  version: string;
+  lifecycle: 'fresh' | 'inUse' | 'pinBlocked' | 'unknown';
+  /** Null when the applet doesn't report capabilities (before 1.2): assume all but variableWriteChunk. */
+  capabilities: number | null;
+}
+
+/** Describe the applet on the card in `reader` from its SELECT response. */
+export const appletInfo = (reader: string) => invoke<AppletInfo>('applet_info', { reader });
+
+export interface ReaderCaps {
+  /** Largest command APDU the reader accepts; null when the driver doesn't say. */
+  max_send: number | null;
+  /** Largest block the reader takes from the card (T=1 IFSD). */
+  max_recv: number | null;
+  protocol: 'T=0' | 'T=1' | 'raw' | 'unknown';
+  has_pinpad: boolean;
+  /** STORE_DATA chunk size writes will use; null when the reader can't write to this card. */
+  write_chunk_size: number | null;
+  interface: CardInterface;
+}
+
+/** Probe the reader holding a card for its APDU limits, protocol and PIN pad. */
+export const readerCapabilities = (reader: string) => invoke<ReaderCaps>('reader_capabilities', { reader });
+
+/** Get the applet AID currently used for SELECT, as space-separated hex. */
+export const getAppletAid = () => invoke<string>('get_applet_aid');
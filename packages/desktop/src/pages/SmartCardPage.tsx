@@ -72,7 +72,10 @@ import {
   writeAllItems,
   CardStatus,
   CardItem,
+  ReaderEntry,
+  isNearWriteLimit,
 } from '@/lib/smartcard';
+import { errorMessage } from '@/lib/errors';
 
 export default function SmartCardPage() {
   const { toast } = useToast();
@@ -81,7 +84,7 @@ export default function SmartCardPage() {
   const appIcon = isDark ? logoDark : logoLight;
 
   // ── Reader state ─────────────────────────────────────────────────
-  const [readers, setReaders] = useState<string[]>([]);
+  const [readers, setReaders] = useState<ReaderEntry[]>([]);
   const [selectedReader, setSelectedReader] = useState<string>('');
   const [isLoadingReaders, setIsLoadingReaders] = useState(false);
   const [readerError, setReaderError] = useState<string | null>(null);
@@ -144,10 +147,10 @@ export default function SmartCardPage() {
       const r = await listReaders();
       setReaders(r);
       if (r.length === 1) {
-        setSelectedReader(r[0]);
+        setSelectedReader(r[0].name);
       }
     } catch (e: any) {
-      setReaderError(e?.toString() || 'Failed to detect readers');
+      setReaderError(errorMessage(e) || 'Failed to detect readers');
       setReaders([]);
     } finally {
       setIsLoadingReaders(false);
@@ -172,7 +175,7 @@ export default function SmartCardPage() {
         setCardStatus(status);
       } catch (e: any) {
         setCardStatus(null);
-        setActionError(e?.toString() || 'Failed to read card status');
+        setActionError(errorMessage(e) || 'Failed to read card status');
       } finally {
         setIsLoadingStatus(false);
       }
@@ -210,7 +213,7 @@ export default function SmartCardPage() {
       await loadCardStatus(undefined, unlockPinInput);
       setUnlockPinInput('');
     } catch (e: any) {
-      setActionError(e?.toString() || 'PIN verification failed');
+      setActionError(errorMessage(e) || 'PIN verification failed');
       // Reload card status to get updated pin_retries_remaining from the card
       try {
         await loadCardStatus(selectedReader, null);
@@ -244,7 +247,7 @@ export default function SmartCardPage() {
       setConfirmPinInput('');
       await loadCardStatus(undefined, newPinInput);
     } catch (e: any) {
-      setActionError(e?.toString() || 'Failed to set PIN');
+      setActionError(errorMessage(e) || 'Failed to set PIN');
     } finally {
       setIsSettingPin(false);
     }
@@ -273,7 +276,7 @@ export default function SmartCardPage() {
       setConfirmChangePinInput('');
       await loadCardStatus(undefined, changePinInput);
     } catch (e: any) {
-      setActionError(e?.toString() || 'Failed to change PIN. Check your current PIN.');
+      setActionError(errorMessage(e) || 'Failed to change PIN. Check your current PIN.');
     } finally {
       setIsChangingPin(false);
     }
@@ -296,7 +299,7 @@ export default function SmartCardPage() {
       });
       await loadCardStatus();
     } catch (e: any) {
-      setActionError(e?.toString() || 'Failed to toggle wipe protection');
+      setActionError(errorMessage(e) || 'Failed to toggle wipe protection');
     } finally {
       setIsTogglingWipeProtect(false);
     }
@@ -332,7 +335,7 @@ export default function SmartCardPage() {
       setUnlockPinInput('');
       await loadCardStatus(selectedReader, null);
     } catch (e: any) {
-      setActionError(e?.toString() || 'Erase failed');
+      setActionError(errorMessage(e) || 'Erase failed');
     } finally {
       setIsErasing(false);
     }
@@ -353,7 +356,7 @@ export default function SmartCardPage() {
       });
       await loadCardStatus();
     } catch (e: any) {
-      setActionError(e?.toString() || 'Failed to delete item');
+      setActionError(errorMessage(e) || 'Failed to delete item');
     } finally {
       setIsDeletingItem(false);
       setDeletingItemIndex(null);
@@ -372,7 +375,7 @@ export default function SmartCardPage() {
       setCloneStep('ready');
       toast({ title: 'Source Card Read', description: `${items.length} item${items.length !== 1 ? 's' : ''} ready to clone.` });
     } catch (e: any) {
-      setActionError(e?.toString() || 'Failed to read source card');
+      setActionError(errorMessage(e) || 'Failed to read source card');
       setCloneStep('idle');
     }
   };
@@ -390,7 +393,7 @@ export default function SmartCardPage() {
       setCloneDestPin('');
       await loadCardStatus();
     } catch (e: any) {
-      setActionError(e?.toString() || 'Failed to write to destination card');
+      setActionError(errorMessage(e) || 'Failed to write to destination card');
       setCloneStep('ready');
     }
   };
@@ -530,8 +533,8 @@ export default function SmartCardPage() {
                     </SelectTrigger>
                     <SelectContent>
                       {readers.map((r) => (
-                        <SelectItem key={r} value={r}>
-                          {r}
+                        <SelectItem key={r.name} value={r.name}>
+                          {r.alias ?? r.name}
                         </SelectItem>
                       ))}
                     </SelectContent>
@@ -608,6 +611,11 @@ export default function SmartCardPage() {
                             {cardStatus.total_items} item{cardStatus.total_items !== 1 ? 's' : ''} stored
                             {' '}({cardStatus.data_length} of {cardStatus.card_capacity} bytes used, {Math.max(0, cardStatus.free_bytes_estimate)} bytes free)
                           </p>
+                          {isNearWriteLimit(cardStatus) && (
+                            <p className="text-xs font-medium text-amber-600 dark:text-amber-400">
+                              ⚠ This card has been written {cardStatus.write_count} times. Its memory wears with every write; consider moving long-term backups to a fresh card.
+                            </p>
+                          )}
                           <div className="space-y-1">
                             {cardStatus.items.map((item) => (
                               <div
@@ -1195,9 +1203,9 @@ export default function SmartCardPage() {
                           </SelectTrigger>
                           <SelectContent>
                             {readers
-                              .filter((r) => r !== selectedReader)
+                              .filter((r) => r.name !== selectedReader)
                               .map((r) => (
-                                <SelectItem key={r} value={r}>{r}</SelectItem>
+                                <SelectItem key={r.name} value={r.name}>{r.alias ?? r.name}</SelectItem>
                               ))}
                           </SelectContent>
                         </Select>
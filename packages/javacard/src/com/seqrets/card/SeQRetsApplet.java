@@ -8,20 +8,47 @@
  * AID: F0 53 51 52 54 53 01
  * CLA: 0x80 (proprietary)
  *
+ * SELECT returns an FCI template (since 1.2):
+ *   6F { 84 <AID>, A5 { 80 02 <major minor>, 81 02 <capability bitmap>,
+ *                       82 01 <lifecycle> } }
+ *   Capabilities: 0x0001 PIN, 0x0002 wipe protect, 0x0004 PUK,
+ *                 0x0008 factory reset, 0x0010 GET_VERSION,
+ *                 0x0020 variable write chunk (since 1.3),
+ *                 0x0040 sequenced read (since 1.4),
+ *                 0x0080 GET_WRITE_COUNT (since 1.5)
+ *   Lifecycle:    0x01 fresh (no data, PIN or PUK), 0x02 in use,
+ *                 0x03 PIN blocked
+ *
  * APDU Commands:
- *   INS 0x01  STORE_DATA    — Write data in chunks (P1=chunk#, P2=0x00 more / 0x01 last)
- *   INS 0x02  READ_DATA     — Read data in chunks (P1=chunk#)
+ *   INS 0x01  STORE_DATA    — Write data in chunks (P1=chunk#, P2=0x00 more / 0x01 last);
+ *                             chunk 0's length sets the chunk size (1–255) for the rest
+ *   INS 0x02  READ_DATA     — Read data in chunks (P1=chunk#; P2=0x01 prefixes the
+ *                             response with the chunk# and the generation of the
+ *                             write that stored it)
  *   INS 0x03  GET_STATUS    — Returns metadata (length, type, label, pin state)
- *   INS 0x04  ERASE_DATA    — Clear all stored data
+ *   INS 0x04  ERASE_DATA    — Clear stored data, label, type, PIN, PUK and wipe protection
+ *   INS 0x05  GET_VERSION   — Returns applet version (2 bytes: major, minor)
+ *   INS 0x06  GET_WRITE_COUNT — Returns how many times the data area has been
+ *                             written or erased (4 bytes, big-endian; since 1.5)
  *   INS 0x10  SET_TYPE      — Set data type byte (P1=type: 0x01=share, 0x02=vault)
  *   INS 0x11  SET_LABEL     — Set label string (data field = UTF-8 label, max 64 bytes)
  *   INS 0x20  VERIFY_PIN    — Verify PIN (data = PIN bytes)
  *   INS 0x21  CHANGE_PIN    — Change PIN (P1=old len, data = old+new)
  *   INS 0x22  SET_PIN       — Initial PIN setup (only if no PIN set)
  *   INS 0x23  SET_WIPE_PROTECT — Enable/disable wipe protection (P1=0x00 off / 0x01 on)
+ *   INS 0x24  SET_PUK       — Initial PUK setup (only if no PUK set; PIN must be verified if set)
+ *   INS 0x25  UNBLOCK_PIN   — Reset PIN with PUK (P1=PUK len, data = PUK+new PIN)
+ *   INS 0x26  FACTORY_RESET — ERASE_DATA authorized by the PUK (data = PUK if one is set),
+ *                             so a PUK holder can reset a wipe-protected card with a blocked PIN
+ *
+ * Erase rule: ERASE_DATA and FACTORY_RESET both clear the PIN *and* the PUK
+ * (with their try counters). A PUK must never outlive the PIN it unblocks:
+ * otherwise whoever held it could UNBLOCK_PIN a card that has since been
+ * erased and handed to someone else, and the new owner could neither
+ * SET_PUK nor FACTORY_RESET it.
  *
  * @author seQRets
- * @version 1.0
+ * @version 1.5
  */
 package com.seqrets.card;
 
@@ -35,12 +62,29 @@ public class SeQRetsApplet extends Applet {
     private static final byte INS_READ_DATA    = (byte) 0x02;
     private static final byte INS_GET_STATUS   = (byte) 0x03;
     private static final byte INS_ERASE_DATA   = (byte) 0x04;
+    private static final byte INS_GET_VERSION  = (byte) 0x05;
+    private static final byte INS_GET_WRITE_COUNT = (byte) 0x06;
     private static final byte INS_SET_TYPE     = (byte) 0x10;
     private static final byte INS_SET_LABEL    = (byte) 0x11;
     private static final byte INS_VERIFY_PIN   = (byte) 0x20;
     private static final byte INS_CHANGE_PIN   = (byte) 0x21;
     private static final byte INS_SET_PIN      = (byte) 0x22;
     private static final byte INS_SET_WIPE_PROTECT = (byte) 0x23;
+    private static final byte INS_SET_PUK      = (byte) 0x24;
+    private static final byte INS_UNBLOCK_PIN  = (byte) 0x25;
+    private static final byte INS_FACTORY_RESET = (byte) 0x26;
+
+    // ── Applet version (reported by GET_VERSION) ───────────────────────
+    // Bump MINOR for backward-compatible additions, MAJOR when the APDU
+    // semantics change. Keep in sync with the desktop's known versions.
+    private static final byte VERSION_MAJOR    = (byte) 1;
+    private static final byte VERSION_MINOR    = (byte) 5;
+
+    // ── FCI proprietary data (returned on SELECT) ──────────────────────
+    private static final short CAPABILITIES    = (short) 0x00FF;
+    private static final byte LIFECYCLE_FRESH  = (byte) 0x01;
+    private static final byte LIFECYCLE_IN_USE = (byte) 0x02;
+    private static final byte LIFECYCLE_PIN_BLOCKED = (byte) 0x03;
 
     // ── Constants ──────────────────────────────────────────────────────
     private static final byte CLA_PROPRIETARY  = (byte) 0x80;
@@ -49,7 +93,9 @@ public class SeQRetsApplet extends Applet {
     private static final byte MAX_PIN_SIZE     = (byte) 16;
     private static final byte MIN_PIN_SIZE     = (byte) 8;
     private static final byte MAX_PIN_RETRIES  = (byte) 5;
+    private static final byte MAX_PUK_RETRIES  = (byte) 10;
     private static final short CHUNK_SIZE      = (short) 240;
+    private static final byte READ_SEQUENCED   = (byte) 0x01; // READ_DATA P2
 
     // ── Data type constants ────────────────────────────────────────────
     private static final byte TYPE_EMPTY       = (byte) 0x00;
@@ -67,9 +113,22 @@ public class SeQRetsApplet extends Applet {
     private byte   pinRetries;
     private boolean pinSet;
     private boolean wipeProtected;
+    private byte[] puk;
+    private byte   pukLength;
+    private byte   pukRetries;
+    private boolean pukSet;
+    // Completed writes and erases of the data area, big-endian. Never reset
+    // (not even by FACTORY_RESET): it tracks EEPROM wear, not contents.
+    private byte[] writeCount;
+    // Bumped by every STORE_DATA chunk 0. Each 240-byte read chunk records
+    // the generation that last wrote to it, so a read spanning two writes
+    // (one of them interrupted) can be told apart from a clean one.
+    private byte   writeGeneration;
+    private byte[] chunkGeneration;
 
     // ── Transient storage (RAM — clears on deselect) ───────────────────
     private boolean[] pinVerified;
+    private short[] writeChunkSize; // set by STORE_DATA chunk 0
 
     /**
      * Private constructor — called from install().
@@ -85,10 +144,18 @@ public class SeQRetsApplet extends Applet {
         pinRetries  = MAX_PIN_RETRIES;
         pinSet      = false;
         wipeProtected = false;
+        puk         = new byte[MAX_PIN_SIZE];
+        pukLength   = (byte) 0;
+        pukRetries  = MAX_PUK_RETRIES;
+        pukSet      = false;
+        writeCount  = new byte[4];
+        writeGeneration = (byte) 0;
+        chunkGeneration = new byte[(short) (MAX_DATA_SIZE / CHUNK_SIZE + 1)];
 
         // Transient array — clears when applet is deselected (card removed)
         pinVerified = JCSystem.makeTransientBooleanArray((short) 1, JCSystem.CLEAR_ON_DESELECT);
         pinVerified[0] = false;
+        writeChunkSize = JCSystem.makeTransientShortArray((short) 1, JCSystem.CLEAR_ON_DESELECT);
 
         register();
     }
@@ -123,6 +190,7 @@ public class SeQRetsApplet extends Applet {
 
         // Handle standard SELECT APDU
         if (selectingApplet()) {
+            sendFci(apdu);
             return;
         }
 
@@ -154,6 +222,12 @@ public class SeQRetsApplet extends Applet {
                 }
                 processEraseData(apdu);
                 break;
+            case INS_GET_VERSION:
+                processGetVersion(apdu);
+                break;
+            case INS_GET_WRITE_COUNT:
+                processGetWriteCount(apdu);
+                break;
             case INS_SET_TYPE:
                 checkPinIfRequired();
                 processSetType(apdu);
@@ -174,11 +248,66 @@ public class SeQRetsApplet extends Applet {
             case INS_SET_WIPE_PROTECT:
                 processSetWipeProtect(apdu);
                 break;
+            case INS_SET_PUK:
+                processSetPuk(apdu);
+                break;
+            case INS_UNBLOCK_PIN:
+                processUnblockPin(apdu);
+                break;
+            case INS_FACTORY_RESET:
+                processFactoryReset(apdu);
+                break;
             default:
                 ISOException.throwIt(ISO7816.SW_INS_NOT_SUPPORTED);
         }
     }
 
+    // ── SELECT response ────────────────────────────────────────────────
+
+    /**
+     * Send the FCI template described in the class comment.
+     */
+    private void sendFci(APDU apdu) {
+        byte[] buffer = apdu.getBuffer();
+        short offset = 0;
+
+        buffer[offset++] = (byte) 0x6F;
+        short fciLengthOffset = offset++;
+
+        // DF name: this instance's AID
+        buffer[offset++] = (byte) 0x84;
+        byte aidLength = JCSystem.getAID().getBytes(buffer, (short) (offset + 1));
+        buffer[offset++] = aidLength;
+        offset += aidLength;
+
+        // Proprietary data
+        buffer[offset++] = (byte) 0xA5;
+        buffer[offset++] = (byte) 11;
+        buffer[offset++] = (byte) 0x80;
+        buffer[offset++] = (byte) 2;
+        buffer[offset++] = VERSION_MAJOR;
+        buffer[offset++] = VERSION_MINOR;
+        buffer[offset++] = (byte) 0x81;
+        buffer[offset++] = (byte) 2;
+        offset = Util.setShort(buffer, offset, CAPABILITIES);
+        buffer[offset++] = (byte) 0x82;
+        buffer[offset++] = (byte) 1;
+        buffer[offset++] = lifecycleState();
+
+        buffer[fciLengthOffset] = (byte) (offset - fciLengthOffset - 1);
+        apdu.setOutgoingAndSend((short) 0, offset);
+    }
+
+    private byte lifecycleState() {
+        if (pinSet && pinRetries == (byte) 0) {
+            return LIFECYCLE_PIN_BLOCKED;
+        }
+        if (dataLength == (short) 0 && !pinSet && !pukSet) {
+            return LIFECYCLE_FRESH;
+        }
+        return LIFECYCLE_IN_USE;
+    }
+
     // ── PIN check helper ───────────────────────────────────────────────
 
     /**
@@ -196,7 +325,8 @@ public class SeQRetsApplet extends Applet {
      * Write a chunk of data.
      * P1 = chunk index (0-based)
      * P2 = 0x00 (more chunks follow) or 0x01 (last chunk)
-     * Data = up to 240 bytes of payload
+     * Data = up to 255 bytes of payload. Every chunk but the last must be
+     * as long as chunk 0 (240 bytes for hosts older than applet 1.3).
      */
     private void processStoreData(APDU apdu) {
         byte[] buffer = apdu.getBuffer();
@@ -206,13 +336,17 @@ public class SeQRetsApplet extends Applet {
         short bytesRead = apdu.setIncomingAndReceive();
         short dataOffset = ISO7816.OFFSET_CDATA;
 
-        // If chunk 0, we're starting fresh — clear existing data
+        // If chunk 0, we're starting fresh — clear existing data and take
+        // the chunk size from this chunk
         if (p1 == (byte) 0x00) {
             dataLength = (short) 0;
+            writeChunkSize[0] = bytesRead;
+            writeGeneration++;
         }
+        short chunkSize = writeChunkSize[0] > 0 ? writeChunkSize[0] : CHUNK_SIZE;
 
-        // Calculate write offset from chunk index
-        short writeOffset = (short) (p1 * CHUNK_SIZE);
+        // Calculate write offset from chunk index (P1 is unsigned)
+        short writeOffset = (short) ((short) (p1 & 0xFF) * chunkSize);
 
         // Bounds check
         if ((short) (writeOffset + bytesRead) > MAX_DATA_SIZE) {
@@ -222,11 +356,20 @@ public class SeQRetsApplet extends Applet {
         // Copy data to EEPROM
         Util.arrayCopy(buffer, dataOffset, storedData, writeOffset, bytesRead);
 
-        // Update total length
+        // Tag the read chunks this one overlaps with the current write
         short newEnd = (short) (writeOffset + bytesRead);
+        for (short c = (short) (writeOffset / CHUNK_SIZE); c <= (short) ((short) (newEnd - 1) / CHUNK_SIZE); c++) {
+            chunkGeneration[c] = writeGeneration;
+        }
+
+        // Update total length
         if (newEnd > dataLength) {
             dataLength = newEnd;
         }
+
+        if (p2 == (byte) 0x01) {
+            bumpWriteCount();
+        }
     }
 
     // ── READ_DATA (INS 0x02) ───────────────────────────────────────────
@@ -234,17 +377,30 @@ public class SeQRetsApplet extends Applet {
     /**
      * Read a chunk of data.
      * P1 = chunk index (0-based)
+     * P2 = 0x00, or 0x01 to prefix the data with the chunk index and the
+     *      generation of the write that stored the chunk, so the host can
+     *      detect chunks returned out of order and a read that spans an
+     *      interrupted write and an older one (since 1.4)
      * Returns up to 240 bytes. SW=0x6100 if more data available.
      */
     private void processReadData(APDU apdu) {
         byte[] buffer = apdu.getBuffer();
         byte p1 = buffer[ISO7816.OFFSET_P1]; // chunk index
+        byte p2 = buffer[ISO7816.OFFSET_P2];
 
         if (dataLength == (short) 0) {
             ISOException.throwIt(ISO7816.SW_CONDITIONS_NOT_SATISFIED);
         }
+        if (p2 != (byte) 0x00 && p2 != READ_SEQUENCED) {
+            ISOException.throwIt(ISO7816.SW_WRONG_P1P2);
+        }
 
-        short readOffset = (short) (p1 * CHUNK_SIZE);
+        // P1 is unsigned; past the last possible chunk the offset would overflow
+        short chunkIndex = (short) (p1 & 0xFF);
+        if (chunkIndex > (short) (MAX_DATA_SIZE / CHUNK_SIZE)) {
+            ISOException.throwIt(ISO7816.SW_WRONG_P1P2);
+        }
+        short readOffset = (short) (chunkIndex * CHUNK_SIZE);
 
         if (readOffset >= dataLength) {
             ISOException.throwIt(ISO7816.SW_WRONG_P1P2);
@@ -253,9 +409,57 @@ public class SeQRetsApplet extends Applet {
         short remaining = (short) (dataLength - readOffset);
         short sendLen = (remaining > CHUNK_SIZE) ? CHUNK_SIZE : remaining;
 
-        Util.arrayCopy(storedData, readOffset, buffer, (short) 0, sendLen);
+        if (p2 == READ_SEQUENCED) {
+            buffer[0] = (byte) chunkIndex;
+            buffer[1] = chunkGeneration[chunkIndex];
+            Util.arrayCopy(storedData, readOffset, buffer, (short) 2, sendLen);
+            apdu.setOutgoingAndSend((short) 0, (short) (sendLen + 2));
+        } else {
+            Util.arrayCopy(storedData, readOffset, buffer, (short) 0, sendLen);
+            apdu.setOutgoingAndSend((short) 0, sendLen);
+        }
+    }
+
+    // ── GET_VERSION (INS 0x05) ─────────────────────────────────────────
+
+    /**
+     * Returns the applet version: [0] major, [1] minor.
+     * Applets before 1.1 answer SW_INS_NOT_SUPPORTED.
+     */
+    private void processGetVersion(APDU apdu) {
+        byte[] buffer = apdu.getBuffer();
+        buffer[0] = VERSION_MAJOR;
+        buffer[1] = VERSION_MINOR;
+        apdu.setOutgoingAndSend((short) 0, (short) 2);
+    }
+
+    // ── GET_WRITE_COUNT (INS 0x06) ─────────────────────────────────────
+
+    /**
+     * Returns the write counter (4 bytes, big-endian). Needs no PIN: it says
+     * how worn the card is, nothing about what it holds.
+     */
+    private void processGetWriteCount(APDU apdu) {
+        byte[] buffer = apdu.getBuffer();
+        Util.arrayCopyNonAtomic(writeCount, (short) 0, buffer, (short) 0, (short) 4);
+        apdu.setOutgoingAndSend((short) 0, (short) 4);
+    }
 
-        apdu.setOutgoingAndSend((short) 0, sendLen);
+    /**
+     * Add one to the write counter in a transaction, saturating at 0xFFFFFFFF.
+     */
+    private void bumpWriteCount() {
+        JCSystem.beginTransaction();
+        for (short i = (short) 3; i >= (short) 0; i--) {
+            if (writeCount[i] != (byte) 0xFF) {
+                writeCount[i]++;
+                for (short j = (short) (i + 1); j < (short) 4; j++) {
+                    writeCount[j] = (byte) 0x00;
+                }
+                break;
+            }
+        }
+        JCSystem.commitTransaction();
     }
 
     // ── GET_STATUS (INS 0x03) ──────────────────────────────────────────
@@ -271,6 +475,8 @@ public class SeQRetsApplet extends Applet {
      *   [7..]  label bytes (up to 64)
      *   [7+labelLen .. 7+labelLen+1]  total capacity (2 bytes, big-endian)
      *   [7+labelLen+2]  wipe protected flag (0x00=no, 0x01=yes)
+     *   [7+labelLen+3]  puk set flag (0x00=no, 0x01=yes)
+     *   [7+labelLen+4]  puk retries remaining (0-10)
      */
     private void processGetStatus(APDU apdu) {
         byte[] buffer = apdu.getBuffer();
@@ -310,14 +516,19 @@ public class SeQRetsApplet extends Applet {
         // Wipe protection flag
         buffer[offset++] = wipeProtected ? (byte) 0x01 : (byte) 0x00;
 
+        // PUK state
+        buffer[offset++] = pukSet ? (byte) 0x01 : (byte) 0x00;
+        buffer[offset++] = pukRetries;
+
         apdu.setOutgoingAndSend((short) 0, offset);
     }
 
     // ── ERASE_DATA (INS 0x04) ──────────────────────────────────────────
 
     /**
-     * Factory reset — clear all stored data, metadata, AND PIN.
-     * After erase, the card is fully clean with no PIN protection.
+     * Factory reset — clear all stored data, metadata, PIN AND PUK.
+     * After erase, the card is fully clean with no PIN protection and no
+     * PUK (see the erase rule in the class comment).
      */
     private void processEraseData(APDU apdu) {
         // Clear stored data
@@ -334,6 +545,12 @@ public class SeQRetsApplet extends Applet {
         pinRetries = MAX_PIN_RETRIES;
         pinVerified[0] = false;
         wipeProtected = false;
+        // Clear PUK: it unblocks the PIN just cleared, nothing else
+        Util.arrayFillNonAtomic(puk, (short) 0, MAX_PIN_SIZE, (byte) 0x00);
+        pukLength = (byte) 0;
+        pukSet = false;
+        pukRetries = MAX_PUK_RETRIES;
+        bumpWriteCount();
     }
 
     // ── SET_TYPE (INS 0x10) ────────────────────────────────────────────
@@ -492,4 +709,113 @@ public class SeQRetsApplet extends Applet {
 
         wipeProtected = (p1 == (byte) 0x01);
     }
+
+    // ── SET_PUK (INS 0x24) ─────────────────────────────────────────────
+
+    /**
+     * Initial PUK setup. Only works if no PUK is currently set.
+     * If a PIN is set it must be verified first, so only the card owner
+     * can install the unblock key.
+     * Data = PUK bytes (8-16 bytes).
+     */
+    private void processSetPuk(APDU apdu) {
+        if (pukSet) {
+            ISOException.throwIt(ISO7816.SW_CONDITIONS_NOT_SATISFIED);
+        }
+        checkPinIfRequired();
+
+        byte[] buffer = apdu.getBuffer();
+        short bytesRead = apdu.setIncomingAndReceive();
+
+        if (bytesRead < MIN_PIN_SIZE || bytesRead > MAX_PIN_SIZE) {
+            ISOException.throwIt(ISO7816.SW_WRONG_LENGTH);
+        }
+
+        Util.arrayCopy(buffer, ISO7816.OFFSET_CDATA, puk, (short) 0, bytesRead);
+        pukLength = (byte) bytesRead;
+        pukSet = true;
+        pukRetries = MAX_PUK_RETRIES;
+    }
+
+    // ── UNBLOCK_PIN (INS 0x25) ─────────────────────────────────────────
+
+    /**
+     * Reset the PIN using the PUK. Works even when the PIN is locked.
+     * P1 = PUK length
+     * Data = PUK bytes + new PIN bytes
+     * Wrong PUK returns SW 0x63Cx (x = PUK retries remaining).
+     */
+    private void processUnblockPin(APDU apdu) {
+        if (!pukSet) {
+            ISOException.throwIt(ISO7816.SW_CONDITIONS_NOT_SATISFIED);
+        }
+        if (pukRetries == (byte) 0) {
+            ISOException.throwIt(ISO7816.SW_FILE_INVALID); // PUK locked out
+        }
+
+        byte[] buffer = apdu.getBuffer();
+        short bytesRead = apdu.setIncomingAndReceive();
+        short givenPukLen = (short) (buffer[ISO7816.OFFSET_P1] & 0xFF);
+        // P1 can't claim more PUK bytes than were sent
+        if (givenPukLen > bytesRead) {
+            ISOException.throwIt(ISO7816.SW_WRONG_LENGTH);
+        }
+        short newPinLen = (short) (bytesRead - givenPukLen);
+
+        // Validate PUK. The try is counted before comparing, so cutting the
+        // power mid-compare can't make a guess free; a match gives it back.
+        pukRetries--;
+        if (givenPukLen != (short) pukLength ||
+            Util.arrayCompare(buffer, ISO7816.OFFSET_CDATA, puk, (short) 0, (short) pukLength) != 0) {
+            ISOException.throwIt((short) (0x63C0 | pukRetries));
+        }
+        pukRetries = MAX_PUK_RETRIES;
+
+        // Validate new PIN length
+        if (newPinLen < MIN_PIN_SIZE || newPinLen > MAX_PIN_SIZE) {
+            ISOException.throwIt(ISO7816.SW_WRONG_LENGTH);
+        }
+
+        // Store new PIN and reset both try counters
+        Util.arrayFillNonAtomic(pin, (short) 0, MAX_PIN_SIZE, (byte) 0x00);
+        Util.arrayCopy(buffer, (short) (ISO7816.OFFSET_CDATA + givenPukLen), pin, (short) 0, newPinLen);
+        pinLength = (byte) newPinLen;
+        pinSet = true;
+        pinRetries = MAX_PIN_RETRIES;
+        pukRetries = MAX_PUK_RETRIES;
+        pinVerified[0] = true;
+    }
+
+    // ── FACTORY_RESET (INS 0x26) ───────────────────────────────────────
+
+    /**
+     * Return the applet to its just-installed state: clears stored data,
+     * type, label, PIN, PUK and wipe protection, exactly as ERASE_DATA does.
+     * Irreversible. If a PUK is set, Data = PUK bytes and must match (wrong
+     * PUK returns SW 0x63Cx like UNBLOCK_PIN), and the PIN isn't needed even
+     * with wipe protection on. Without a PUK the same rules as ERASE_DATA
+     * apply (PIN required only when wipe protection is on).
+     */
+    private void processFactoryReset(APDU apdu) {
+        byte[] buffer = apdu.getBuffer();
+
+        if (pukSet) {
+            if (pukRetries == (byte) 0) {
+                ISOException.throwIt(ISO7816.SW_FILE_INVALID); // PUK locked out
+            }
+            short bytesRead = apdu.setIncomingAndReceive();
+            // Counted before comparing, as in UNBLOCK_PIN
+            pukRetries--;
+            if (bytesRead != (short) pukLength ||
+                Util.arrayCompare(buffer, ISO7816.OFFSET_CDATA, puk, (short) 0, (short) pukLength) != 0) {
+                ISOException.throwIt((short) (0x63C0 | pukRetries));
+            }
+            pukRetries = MAX_PUK_RETRIES;
+        } else if (wipeProtected) {
+            checkPinIfRequired();
+        }
+
+        // Data, label, type, PIN and PUK
+        processEraseData(apdu);
+    }
 }
@@ -0,0 +1,91 @@
+/**
+ * Applet tests, run in the jcardsim simulator with `ant test`.
+ *
+ * They cover the rules that matter when a card passes from one owner to the
+ * next: an erase or factory reset must leave nothing of the previous owner's
+ * PIN or PUK behind.
+ */
+package com.seqrets.card;
+
+import static org.junit.Assert.assertEquals;
+
+import com.licel.jcardsim.smartcardio.CardSimulator;
+import com.licel.jcardsim.utils.AIDUtil;
+import java.nio.charset.StandardCharsets;
+import javacard.framework.AID;
+import javax.smartcardio.CommandAPDU;
+import org.junit.Before;
+import org.junit.Test;
+
+public class SeQRetsAppletTest {
+
+    private static final int INS_ERASE_DATA    = 0x04;
+    private static final int INS_SET_PIN       = 0x22;
+    private static final int INS_SET_PUK       = 0x24;
+    private static final int INS_UNBLOCK_PIN   = 0x25;
+    private static final int INS_FACTORY_RESET = 0x26;
+
+    private static final int SW_OK                 = 0x9000;
+    private static final int SW_CONDITIONS_NOT_MET = 0x6985;
+
+    private static final byte[] OLD_PIN = ascii("old-owner-pin");
+    private static final byte[] OLD_PUK = ascii("old-owner-puk");
+    private static final byte[] NEW_PIN = ascii("new-owner-pin");
+    private static final byte[] NEW_PUK = ascii("new-owner-puk");
+
+    private CardSimulator card;
+
+    @Before
+    public void installApplet() {
+        card = new CardSimulator();
+        AID aid = AIDUtil.create("F05351525453010000");
+        card.installApplet(aid, SeQRetsApplet.class);
+        card.selectApplet(aid);
+    }
+
+    @Test
+    public void eraseDataClearsThePuk() {
+        givenAnOwnedCard();
+        // Wipe protection is off, so no PIN is needed
+        assertEquals(SW_OK, send(INS_ERASE_DATA, 0, null));
+
+        // The previous owner's PUK no longer unblocks anything...
+        assertEquals(SW_CONDITIONS_NOT_MET, send(INS_UNBLOCK_PIN, OLD_PUK.length, concat(OLD_PUK, NEW_PIN)));
+        // ...and the new owner can install their own and reset with it
+        assertEquals(SW_OK, send(INS_SET_PUK, 0, NEW_PUK));
+        assertEquals(SW_OK, send(INS_FACTORY_RESET, 0, NEW_PUK));
+    }
+
+    @Test
+    public void factoryResetClearsThePuk() {
+        givenAnOwnedCard();
+        assertEquals(SW_OK, send(INS_FACTORY_RESET, 0, OLD_PUK));
+
+        assertEquals(SW_CONDITIONS_NOT_MET, send(INS_UNBLOCK_PIN, OLD_PUK.length, concat(OLD_PUK, NEW_PIN)));
+        assertEquals(SW_OK, send(INS_SET_PUK, 0, NEW_PUK));
+    }
+
+    /** A card with the previous owner's PIN and PUK on it. */
+    private void givenAnOwnedCard() {
+        assertEquals(SW_OK, send(INS_SET_PIN, 0, OLD_PIN));
+        assertEquals(SW_OK, send(INS_SET_PUK, 0, OLD_PUK));
+    }
+
+    private int send(int ins, int p1, byte[] data) {
+        CommandAPDU command = data == null
+            ? new CommandAPDU(0x80, ins, p1, 0x00)
+            : new CommandAPDU(0x80, ins, p1, 0x00, data);
+        return card.transmitCommand(command).getSW();
+    }
+
+    private static byte[] ascii(String s) {
+        return s.getBytes(StandardCharsets.US_ASCII);
+    }
+
+    private static byte[] concat(byte[] a, byte[] b) {
+        byte[] out = new byte[a.length + b.length];
+        System.arraycopy(a, 0, out, 0, a.length);
+        System.arraycopy(b, 0, out, a.length, b.length);
+        return out;
+    }
+}